@@ -0,0 +1,82 @@
+use crate::{Direction, Snek, SnekHaus};
+
+/// Something that can steer a snek, one tick at a time.
+pub trait Policy {
+    fn name(&self) -> &str;
+
+    /// Pick the direction for `snek`, one of the sneks in `haus`, to move in this tick.
+    fn decide(&mut self, haus: &SnekHaus, snek: &Snek) -> Direction;
+}
+
+/// Heads straight for the nearest morsel, refusing any move that would be
+/// immediately fatal.
+#[derive(Debug, Default)]
+pub struct Greedy;
+
+impl Policy for Greedy {
+    fn name(&self) -> &str {
+        "greedy"
+    }
+
+    fn decide(&mut self, haus: &SnekHaus, snek: &Snek) -> Direction {
+        let target = haus
+            .moresels
+            .iter()
+            .map(|m| m.pos)
+            .min_by_key(|&pos| snek.head.wrapped_distance(pos, haus.size));
+
+        haus.directions()
+            .iter()
+            .copied()
+            .filter(|&dir| dir != snek.direction.opposite())
+            .map(|dir| (dir, haus.next_head(snek, dir)))
+            .filter(|&(_, pos)| !haus.is_lethal(pos))
+            // Prefer getting closer to the target, then carrying on straight
+            .min_by_key(|&(dir, pos)| {
+                let dist = target.map_or(0, |t| pos.wrapped_distance(t, haus.size));
+                (dist, dir != snek.direction)
+            })
+            .map_or(snek.direction, |(dir, _)| dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Morsel, MorselKind, Pos, Rules, Size};
+
+    #[test]
+    fn test_greedy_heads_for_morsel() {
+        let size = Size {
+            width: 10,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.moresels.push(Morsel {
+            pos: Pos { x: 6, y: 1, floor: 0 },
+            growth_value: 1,
+            kind: MorselKind::Normal,
+            age: 0,
+        });
+
+        assert_eq!(Greedy.decide(&haus, &haus.snek), Direction::North, "Bot should turn towards the morsel");
+    }
+
+    #[test]
+    fn test_greedy_avoids_body() {
+        let size = Size {
+            width: 10,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.moresels.push(Morsel {
+            pos: Pos { x: 6, y: 1, floor: 0 },
+            growth_value: 1,
+            kind: MorselKind::Normal,
+            age: 0,
+        });
+        haus.snek.body.push_back(Pos { x: 6, y: 4, floor: 0 });
+
+        assert_ne!(Greedy.decide(&haus, &haus.snek), Direction::North, "Bot should not turn into its own body");
+    }
+}
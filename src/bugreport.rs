@@ -0,0 +1,102 @@
+//! Bug report bundles: everything needed to reproduce a problem, gathered into
+//! one text file that can be attached to an issue.
+
+use crate::daily;
+use ratatui::buffer::Buffer;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const BUG_REPORT_DIR: &str = "bug_reports";
+/// Lines from the end of the log included in a report
+const LOG_LINES: usize = 100;
+
+/// Write `sections` to a new, timestamped report in `dir`, returning its path.
+pub fn write(dir: &Path, sections: &[(&str, String)]) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = unique_path(dir, "snekrs-bug");
+    let mut text = String::new();
+    for (title, body) in sections {
+        text.push_str(&format!("== {} ==\n{}\n\n", title, body.trim_end()));
+    }
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// A path in `dir` for a text file named `prefix` and the time, that isn't
+/// taken yet.
+pub fn unique_path(dir: &Path, prefix: &str) -> PathBuf {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let seconds = now % (24 * 60 * 60);
+    let stamp = format!(
+        "{}-{:02}{:02}{:02}",
+        daily::date(now / (24 * 60 * 60)),
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+
+    // Don't overwrite a file written in the same second
+    let mut path = dir.join(format!("{}-{}.txt", prefix, stamp));
+    let mut copy = 1;
+    while path.exists() {
+        copy += 1;
+        path = dir.join(format!("{}-{}-{}.txt", prefix, stamp, copy));
+    }
+    path
+}
+
+/// What's on screen, as plain text.
+pub fn frame_text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    (area.top()..area.bottom())
+        .map(|y| {
+            let row: String = (area.left()..area.right())
+                .map(|x| buffer[(x, y)].symbol())
+                .collect();
+            row.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The last lines of the log file at `path`.
+pub fn log_tail(path: &Path) -> String {
+    match fs::read_to_string(path) {
+        Ok(log) => {
+            let lines: Vec<_> = log.lines().collect();
+            lines[lines.len().saturating_sub(LOG_LINES)..].join("\n")
+        }
+        Err(e) => format!("Couldn't read {}: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{layout::Rect, style::Style};
+
+    #[test]
+    fn test_write_report() {
+        let dir = std::env::temp_dir().join(format!("snekrs_bugs_{}", std::process::id()));
+        let sections = [("Seed", "42".to_string()), ("State", "dizzy\n".to_string())];
+        let first = write(&dir, &sections).expect("Report should be written");
+        let second = write(&dir, &sections).expect("Second report should be written");
+        assert_ne!(first, second, "Reports shouldn't overwrite each other");
+
+        let text = fs::read_to_string(&first).expect("Report should be readable");
+        assert_eq!(text, "== Seed ==\n42\n\n== State ==\ndizzy\n\n", "Report should have a heading per section");
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_frame_text() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 2));
+        buffer.set_string(0, 0, "ab", Style::default());
+        buffer.set_string(1, 1, "c", Style::default());
+        assert_eq!(frame_text(&buffer), "ab\n c", "Rows should be joined, without trailing blanks");
+    }
+}
@@ -0,0 +1,147 @@
+//! The campaign: a run of stages that get faster and busier, each unlocked by
+//! reaching the target score on the one before.
+
+use crate::{GameMode, Rules, VictoryTarget};
+use log::error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Where the number of stages cleared is kept between sessions.
+pub const PROGRESS_FILE: &str = ".snekrs_campaign.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stage {
+    pub name: &'static str,
+    /// File name of the level in the levels directory, or `None` for the open arena
+    pub level: Option<&'static str>,
+    /// Tick length at a score of zero
+    pub tick_millis: u64,
+    pub obstacles: u8,
+    /// Score needed to clear the stage
+    pub target: u16,
+}
+
+pub const STAGES: [Stage; 5] = [
+    Stage {
+        name: "Warming Up",
+        level: None,
+        tick_millis: 150,
+        obstacles: 0,
+        target: 20,
+    },
+    Stage {
+        name: "The Box",
+        level: Some("01_box.txt"),
+        tick_millis: 135,
+        obstacles: 0,
+        target: 30,
+    },
+    Stage {
+        name: "Crossroads",
+        level: Some("02_crossroads.txt"),
+        tick_millis: 120,
+        obstacles: 1,
+        target: 40,
+    },
+    Stage {
+        name: "The Tower",
+        level: Some("03_tower.txt"),
+        tick_millis: 105,
+        obstacles: 2,
+        target: 50,
+    },
+    Stage {
+        name: "Rush Hour",
+        level: None,
+        tick_millis: 90,
+        obstacles: 4,
+        target: 60,
+    },
+];
+
+impl Stage {
+    pub fn rules(&self) -> Rules {
+        let mut rules = GameMode::Campaign.rules();
+        rules.speed.base_millis = self.tick_millis;
+        rules.obstacles = self.obstacles;
+        rules.target = Some(VictoryTarget::Score(self.target));
+        rules
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Progress {
+    /// Number of stages cleared, which are always the first ones
+    pub cleared: usize,
+}
+
+impl Progress {
+    /// Load progress from `path`, starting from scratch if there isn't any.
+    pub fn load(path: &Path) -> Progress {
+        match fs::read_to_string(path) {
+            Ok(text) => Progress {
+                cleared: text.trim().parse().unwrap_or(0),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Progress::default(),
+            Err(e) => {
+                error!("Error loading campaign progress: {}", e);
+                Progress::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        if let Err(e) = fs::write(path, self.cleared.to_string()) {
+            error!("Error saving campaign progress: {}", e);
+        }
+    }
+
+    /// Whether stage `index` can be played yet.
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        index <= self.cleared
+    }
+
+    /// Record stage `index` as cleared, unlocking the next one.
+    pub fn clear(&mut self, index: usize) {
+        self.cleared = self.cleared.max(index + 1).min(STAGES.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level;
+
+    #[test]
+    fn test_unlocking() {
+        let mut progress = Progress::default();
+        assert!(progress.is_unlocked(0), "First stage should always be unlocked");
+        assert!(!progress.is_unlocked(1), "Second stage should start locked");
+
+        progress.clear(0);
+        assert!(progress.is_unlocked(1), "Clearing a stage should unlock the next");
+        progress.clear(0);
+        assert_eq!(progress.cleared, 1, "Replaying a stage shouldn't unlock more");
+        progress.clear(STAGES.len() - 1);
+        assert_eq!(progress.cleared, STAGES.len(), "Progress shouldn't run past the last stage");
+    }
+
+    #[test]
+    fn test_progress_persists() {
+        let path = std::env::temp_dir().join(format!("snekrs_campaign_{}.txt", std::process::id()));
+        Progress { cleared: 3 }.save(&path);
+        assert_eq!(Progress::load(&path), Progress { cleared: 3 }, "Progress should survive a reload");
+        fs::remove_file(&path).ok();
+        assert_eq!(Progress::load(&path), Progress::default(), "Missing progress should start from scratch");
+    }
+
+    #[test]
+    fn test_stage_levels_load() {
+        let levels = level::load_dir(Path::new(level::LEVELS_DIR), 3);
+        for file in STAGES.iter().filter_map(|stage| stage.level) {
+            let found = levels.iter().find(|(path, _)| path.file_name().is_some_and(|name| name == file));
+            assert!(matches!(found, Some((_, Ok(_)))), "Stage level {} should load", file);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Sharing levels and run codes through the system clipboard, so passing them
+//! around doesn't mean digging through files.
+//!
+//! A run code names the mode, arena size, speed, starting length, growth,
+//! morsel weights, modifiers and seed a game was played with, such as
+//! `SNEK-classic-40x20-normal-3-1-mode-0000-00c0ffee00c0ffee`. Starting from a
+//! pasted run code lays out the same arena and morsels again.
+
+use crate::Size;
+use arboard::Clipboard;
+use std::fmt;
+
+/// Longest text accepted from the clipboard
+pub const MAX_PASTE_BYTES: usize = 16 * 1024;
+const RUN_CODE_PREFIX: &str = "SNEK";
+
+/// The system clipboard, opened the first time it's used and then kept open.
+/// On X11 and Wayland copied text only lasts as long as the clipboard that
+/// copied it, unless a clipboard manager takes it over.
+#[derive(Default)]
+pub struct SystemClipboard(Option<Clipboard>);
+
+impl SystemClipboard {
+    fn open(&mut self) -> Result<&mut Clipboard, arboard::Error> {
+        if self.0.is_none() {
+            self.0 = Some(Clipboard::new()?);
+        }
+        Ok(self.0.as_mut().expect("Clipboard was just opened"))
+    }
+
+    pub fn copy(&mut self, text: &str) -> Result<(), String> {
+        self.open()
+            .and_then(|clipboard| clipboard.set_text(text))
+            .map_err(|e| format!("Couldn't copy to the clipboard: {}", e))
+    }
+
+    /// The text on the clipboard, if there's not too much of it.
+    pub fn paste(&mut self) -> Result<String, String> {
+        let text = self
+            .open()
+            .and_then(|clipboard| clipboard.get_text())
+            .map_err(|e| format!("Couldn't paste from the clipboard: {}", e))?;
+        check_size(&text)?;
+        Ok(text)
+    }
+}
+
+fn check_size(text: &str) -> Result<(), String> {
+    if text.len() > MAX_PASTE_BYTES {
+        return Err(format!(
+            "The clipboard holds {} KiB, more than the {} KiB that can be pasted",
+            text.len().div_ceil(1024),
+            MAX_PASTE_BYTES / 1024
+        ));
+    }
+    Ok(())
+}
+
+/// Everything needed to play a game again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunCode {
+    /// Slug of the mode
+    pub mode: String,
+    pub size: Size,
+    /// Slug of the speed setting
+    pub speed: String,
+    pub start_length: u16,
+    pub growth: u16,
+    /// The morsel weights setting, as written in the settings file
+    pub morsel_weights: String,
+    /// The modifiers, packed by `Mutators::bits`
+    pub mutators: u16,
+    pub seed: u64,
+}
+
+impl RunCode {
+    pub fn parse(text: &str) -> Result<RunCode, String> {
+        let invalid = || {
+            format!(
+                "Not a run code, expected something like {}",
+                RunCode {
+                    mode: "classic".to_string(),
+                    size: Size {
+                        width: 40,
+                        height: 20,
+                    },
+                    speed: "normal".to_string(),
+                    start_length: 3,
+                    growth: 1,
+                    morsel_weights: "mode".to_string(),
+                    mutators: 0,
+                    seed: 0xc0ffee,
+                }
+            )
+        };
+        let text = text.trim().to_lowercase();
+        let parts: Vec<&str> = text.split('-').collect();
+        let [prefix, mode, size, speed, start_length, growth, morsel_weights, mutators, seed] =
+            parts[..]
+        else {
+            return Err(invalid());
+        };
+        if !prefix.eq_ignore_ascii_case(RUN_CODE_PREFIX) {
+            return Err(invalid());
+        }
+        let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+        Ok(RunCode {
+            mode: mode.to_string(),
+            size: Size {
+                width: width.parse().map_err(|_| invalid())?,
+                height: height.parse().map_err(|_| invalid())?,
+            },
+            speed: speed.to_string(),
+            start_length: start_length.parse().map_err(|_| invalid())?,
+            growth: growth.parse().map_err(|_| invalid())?,
+            morsel_weights: morsel_weights.to_string(),
+            mutators: u16::from_str_radix(mutators, 16).map_err(|_| invalid())?,
+            seed: u64::from_str_radix(seed, 16).map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl fmt::Display for RunCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}-{}x{}-{}-{}-{}-{}-{:04x}-{:016x}",
+            RUN_CODE_PREFIX,
+            self.mode,
+            self.size.width,
+            self.size.height,
+            self.speed,
+            self.start_length,
+            self.growth,
+            self.morsel_weights,
+            self.mutators,
+            self.seed
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_code() {
+        let code = RunCode {
+            mode: "time_attack".to_string(),
+            size: Size {
+                width: 60,
+                height: 25,
+            },
+            speed: "fast".to_string(),
+            start_length: 5,
+            growth: 2,
+            morsel_weights: "4,2,1".to_string(),
+            mutators: 0x0124,
+            seed: 0xdead_beef,
+        };
+        assert_eq!(
+            code.to_string(),
+            "SNEK-time_attack-60x25-fast-5-2-4,2,1-0124-00000000deadbeef",
+            "Run codes should spell out everything the game was played with"
+        );
+        assert_eq!(
+            RunCode::parse(&code.to_string()),
+            Ok(code.clone()),
+            "Run codes should parse back the same"
+        );
+        assert_eq!(
+            RunCode::parse("  snek-TIME_ATTACK-60X25-Fast-5-2-4,2,1-124-deadbeef\n"),
+            Ok(code),
+            "Pasting should forgive case and whitespace"
+        );
+        assert!(
+            RunCode::parse("SNEK-classic-40x20-normal-3-1-mode-0000").is_err(),
+            "A run code needs a seed"
+        );
+        assert!(
+            RunCode::parse("SNEK-classic-xyz").is_err(),
+            "A mode and seed alone aren't enough"
+        );
+        assert!(
+            RunCode::parse("SNEK-classic-40by20-normal-3-1-mode-0000-1").is_err(),
+            "Sizes should be written WxH"
+        );
+        assert!(
+            RunCode::parse("SNEK-classic-40x20-normal-3-1-mode-0000-xyz").is_err(),
+            "Seeds should be hex"
+        );
+        assert!(
+            RunCode::parse("WORM-classic-40x20-normal-3-1-mode-0000-1").is_err(),
+            "Run codes should start with SNEK"
+        );
+    }
+
+    #[test]
+    fn test_paste_size() {
+        assert!(check_size("#S.#").is_ok(), "Small pastes should be fine");
+        assert!(check_size(&"#".repeat(MAX_PASTE_BYTES + 1)).is_err(), "Huge pastes should be refused");
+    }
+}
@@ -0,0 +1,67 @@
+//! The retro CRT look: every other row dimmed like a scanline, and every
+//! colour pulled a little towards a phosphor green. It's drawn over the
+//! finished frame, so nothing else has to know about it.
+
+use crate::skin;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier};
+
+/// The glow colours are pulled towards
+const PHOSPHOR: (u8, u8, u8) = (96, 255, 160);
+/// How far colours are pulled towards the phosphor, from 0 to 1
+const TINT: f32 = 0.15;
+/// How bright the scanlines are, from 0 to 1
+const SCANLINE: f32 = 0.7;
+
+/// Tint `area` of `buf`, and dim every other row of it.
+pub fn apply(buf: &mut Buffer, area: Rect) {
+    let area = area.intersection(buf.area);
+    for y in area.top()..area.bottom() {
+        let scanline = !(y - area.top()).is_multiple_of(2);
+        for x in area.left()..area.right() {
+            let cell = &mut buf[(x, y)];
+            let fg = tint(cell.fg, scanline);
+            let bg = tint(cell.bg, scanline);
+            // The terminal's own colours can only be dimmed by the terminal
+            if scanline && fg == Color::Reset {
+                cell.modifier.insert(Modifier::DIM);
+            }
+            cell.set_fg(fg).set_bg(bg);
+        }
+    }
+}
+
+/// `colour` pulled towards the phosphor, and darkened on a scanline. True
+/// colours stay true colours, and the rest go through the standard 256.
+fn tint(colour: Color, scanline: bool) -> Color {
+    let Some((r, g, b)) = skin::rgb(colour) else {
+        return colour;
+    };
+    let brightness = if scanline { SCANLINE } else { 1.0 };
+    let mix =
+        |v: u8, p: u8| ((v as f32 * (1.0 - TINT) + p as f32 * TINT) * brightness).round() as u8;
+    let tinted = Color::Rgb(mix(r, PHOSPHOR.0), mix(g, PHOSPHOR.1), mix(b, PHOSPHOR.2));
+    match colour {
+        Color::Rgb(..) => tinted,
+        _ => skin::indexed(tinted),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crt() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 2));
+        for y in 0..2 {
+            buf[(0, y)].set_fg(Color::Rgb(200, 0, 0));
+        }
+        apply(&mut buf, Rect::new(0, 0, 2, 2));
+        let (Color::Rgb(r, g, _), Color::Rgb(dim, ..)) = (buf[(0, 0)].fg, buf[(0, 1)].fg) else { panic!("True colours should stay true colours") };
+        assert!(g > 0 && r < 200, "Colours should be tinted towards the phosphor");
+        assert!(dim < r, "Every other row should be dimmer");
+        assert!(buf[(1, 1)].modifier.contains(Modifier::DIM) && !buf[(1, 0)].modifier.contains(Modifier::DIM), "The terminal's own colours should be dimmed on the scanlines");
+    }
+}
@@ -0,0 +1,51 @@
+//! The daily challenge, where everyone playing on the same day gets the same
+//! arena and the same run of random numbers.
+
+use crate::Size;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The daily challenge is always played at this size, whatever the terminal,
+/// so that the layout is the same for everyone.
+pub const ARENA: Size = Size {
+    width: 40,
+    height: 20,
+};
+
+/// Today, counted in days since 1970-01-01 UTC.
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / (24 * 60 * 60))
+}
+
+/// `day`, counted in days since 1970-01-01, as a `YYYY-MM-DD` date.
+pub fn date(day: u64) -> String {
+    // Shift the epoch to 0000-03-01 so leap days fall at the end of the year
+    let days = day + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = era * 400 + year_of_era + (month <= 2) as u64;
+    format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date() {
+        assert_eq!(date(0), "1970-01-01", "Day zero should be the epoch");
+        assert_eq!(date(11_016), "2000-02-29", "Leap days should be counted");
+        assert_eq!(date(20_742), "2026-10-16", "Days should count up from the epoch");
+    }
+}
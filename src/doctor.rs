@@ -0,0 +1,303 @@
+//! `snekrs doctor`: checks what the terminal can do, to help track down
+//! rendering problems.
+
+use crate::settings::{self, Settings};
+use crate::{daily, glyphs};
+use crossterm::{
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    style::{available_color_count, Print},
+    terminal::{self, disable_raw_mode, enable_raw_mode, Clear, ClearType},
+};
+use ratatui::text::Span;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
+
+/// Rows taken up by the header and the arena's border.
+const CHROME_HEIGHT: u16 = 5;
+const CHROME_WIDTH: u16 = 2;
+
+/// How wide a symbol is meant to be, and how wide the terminal drew it.
+#[derive(Clone, Debug, PartialEq)]
+struct SymbolWidth {
+    symbol: &'static str,
+    expected: usize,
+    /// `None` when the cursor position couldn't be read back
+    measured: Option<u16>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Report {
+    size: Option<(u16, u16)>,
+    colours: u16,
+    widths: Vec<SymbolWidth>,
+    mouse: Option<String>,
+    keyboard_enhancement: Option<bool>,
+    /// The multiplexer the game is running in, and the terminal outside it
+    multiplexer: Option<(&'static str, String)>,
+    /// Whether the game holds back for multiplexers
+    compat: bool,
+}
+
+impl Report {
+    /// Anything that will stop the game looking the way it should.
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let needed = (
+            daily::ARENA.width + CHROME_WIDTH,
+            daily::ARENA.height + CHROME_HEIGHT,
+        );
+        match self.size {
+            Some((width, height)) if width < needed.0 || height < needed.1 => {
+                problems.push(format!(
+                    "Terminal is {}x{}, the daily challenge needs at least {}x{}",
+                    width, height, needed.0, needed.1
+                ))
+            }
+            Some(_) => {}
+            None => problems.push("Couldn't read the terminal size".to_string()),
+        }
+        if self.colours < 16 {
+            problems.push(format!(
+                "Only {} colours, morsels and hazards may be hard to tell apart",
+                self.colours
+            ));
+        }
+        for width in &self.widths {
+            if let Some(measured) = width.measured {
+                if measured as usize != width.expected {
+                    problems.push(format!(
+                        "{} is drawn {} cells wide but should be {}, the arena will be misaligned",
+                        width.symbol, measured, width.expected
+                    ));
+                }
+            }
+        }
+        problems
+    }
+
+    /// Changes that would help the game run better here.
+    fn recommendations(&self) -> Vec<String> {
+        let mut recommendations = Vec::new();
+        if let Some((name, _)) = &self.multiplexer {
+            if !self.compat {
+                recommendations.push(format!(
+                    "Running inside {}, set 'multiplexer: auto' in {} to avoid features it passes on badly",
+                    name,
+                    settings::SETTINGS_FILE
+                ));
+            }
+            if *name == "tmux" && self.colours < 256 {
+                recommendations.push(
+                    "Add 'set -g default-terminal \"tmux-256color\"' to ~/.tmux.conf for 256 colours"
+                        .to_string(),
+                );
+            }
+        }
+        let misaligned = self
+            .widths
+            .iter()
+            .any(|w| w.measured.is_some_and(|m| m as usize != w.expected));
+        if misaligned {
+            recommendations.push(format!(
+                "Set 'symbols: ascii' in {} to draw the arena without the symbols that are the wrong width",
+                settings::SETTINGS_FILE
+            ));
+        }
+        if self.compat {
+            recommendations.push(
+                "Multiplexer compatibility is on: colours are limited to 256, and the mouse and \
+                 keyboard protocol aren't used"
+                    .to_string(),
+            );
+        }
+        recommendations
+    }
+
+    fn print(&self) {
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+        let size = self
+            .size
+            .map_or("unknown".to_string(), |(w, h)| format!("{}x{}", w, h));
+        println!("{:<24}{}", "Terminal size", size);
+        println!("{:<24}{}", "Colours", self.colours);
+        if let Some((name, outer)) = &self.multiplexer {
+            println!("{:<24}{} (in {})", "Multiplexer", name, outer);
+        }
+        println!(
+            "{:<24}{}",
+            "Mouse",
+            self.mouse.as_deref().unwrap_or("not checked")
+        );
+        println!(
+            "{:<24}{}",
+            "Kitty keyboard protocol",
+            self.keyboard_enhancement.map_or("unknown", yes_no)
+        );
+        println!("Symbol widths");
+        for width in &self.widths {
+            let measured = width
+                .measured
+                .map_or("unknown".to_string(), |m| m.to_string());
+            println!(
+                "  {:<22}expected {}, drawn {}",
+                width.symbol, width.expected, measured
+            );
+        }
+
+        let problems = self.problems();
+        println!();
+        if problems.is_empty() {
+            println!("No problems found");
+        }
+        for problem in problems {
+            println!("Problem: {}", problem);
+        }
+        for recommendation in self.recommendations() {
+            println!("Recommendation: {}", recommendation);
+        }
+    }
+}
+
+/// Draw `symbol` at the start of the current line and see how far the cursor moves.
+fn measure(stdout: &mut io::Stdout, symbol: &str) -> io::Result<u16> {
+    execute!(
+        stdout,
+        cursor::MoveToColumn(0),
+        Clear(ClearType::CurrentLine),
+        Print(symbol)
+    )?;
+    let (column, _) = cursor::position()?;
+    execute!(
+        stdout,
+        cursor::MoveToColumn(0),
+        Clear(ClearType::CurrentLine)
+    )?;
+    Ok(column)
+}
+
+/// The multiplexer the game is running in, if any, and the terminal it's running in.
+fn multiplexer() -> Option<(&'static str, String)> {
+    let term = env::var("TERM").unwrap_or_default();
+    if env::var_os("TMUX").is_some() {
+        // TERM is tmux's own, so ask tmux about the terminal it's attached to
+        let outer = Command::new("tmux")
+            .args(["display-message", "-p", "#{client_termname}"])
+            .output()
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|outer| !outer.is_empty())
+            .unwrap_or(term);
+        Some(("tmux", outer))
+    } else if env::var_os("STY").is_some() {
+        Some(("screen", term))
+    } else {
+        None
+    }
+}
+
+fn probe() -> io::Result<Report> {
+    let mut stdout = io::stdout();
+    let symbols = glyphs::UNICODE.all().into_iter();
+    let compat = Settings::load(Path::new(settings::SETTINGS_FILE)).multiplexer_compat();
+    let mut report = Report {
+        size: terminal::size().ok(),
+        colours: available_color_count(),
+        multiplexer: multiplexer(),
+        compat,
+        ..Report::default()
+    };
+    if !stdout.is_terminal() {
+        report.widths = symbols
+            .map(|symbol| SymbolWidth {
+                symbol,
+                expected: Span::raw(symbol).width(),
+                measured: None,
+            })
+            .collect();
+        return Ok(report);
+    }
+
+    enable_raw_mode()?;
+    report.widths = symbols
+        .map(|symbol| SymbolWidth {
+            symbol,
+            expected: Span::raw(symbol).width(),
+            measured: measure(&mut stdout, symbol).ok(),
+        })
+        .collect();
+    if compat {
+        // These are the queries that can hang or leak escape codes under a multiplexer
+        report.mouse = Some("not checked, multiplexer compatibility is on".to_string());
+        disable_raw_mode()?;
+        return Ok(report);
+    }
+    report.keyboard_enhancement = terminal::supports_keyboard_enhancement().ok();
+    report.mouse = Some(
+        match execute!(stdout, EnableMouseCapture)
+            .and_then(|_| execute!(stdout, DisableMouseCapture))
+        {
+            Ok(()) => "capture can be switched on".to_string(),
+            Err(e) => format!("capture failed: {}", e),
+        },
+    );
+    disable_raw_mode()?;
+    stdout.flush()?;
+    Ok(report)
+}
+
+/// `snekrs doctor`
+///
+/// Prints what the terminal supports, and anything likely to spoil the game.
+pub fn doctor_command(_args: &[String]) -> io::Result<()> {
+    probe()?.print();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problems() {
+        let healthy = Report {
+            size: Some((80, 30)),
+            colours: 256,
+            widths: vec![SymbolWidth { symbol: "♣", expected: 1, measured: Some(1) }],
+            ..Report::default()
+        };
+        assert!(healthy.problems().is_empty(), "A roomy, colourful terminal should be fine");
+
+        let cramped = Report { size: Some((30, 10)), ..healthy.clone() };
+        assert_eq!(cramped.problems().len(), 1, "A small terminal should be a problem");
+
+        let misaligned = Report {
+            widths: vec![SymbolWidth { symbol: "😀", expected: 2, measured: Some(1) }],
+            ..healthy.clone()
+        };
+        assert!(misaligned.problems()[0].contains("😀"), "Symbols drawn the wrong width should be a problem");
+
+        let unmeasured = Report {
+            widths: vec![SymbolWidth { symbol: "😀", expected: 2, measured: None }],
+            ..healthy
+        };
+        assert!(unmeasured.problems().is_empty(), "Symbols that couldn't be measured shouldn't be a problem");
+    }
+
+    #[test]
+    fn test_recommendations() {
+        let plain = Report { colours: 256, ..Report::default() };
+        assert!(plain.recommendations().is_empty(), "Nothing to recommend outside a multiplexer");
+
+        let tmux = Report { colours: 8, multiplexer: Some(("tmux", "xterm-256color".to_string())), ..Report::default() };
+        let advice = tmux.recommendations();
+        assert!(advice[0].contains("multiplexer: auto"), "Compatibility should be recommended inside tmux");
+        assert!(advice[1].contains("tmux-256color"), "Low colour tmux should be pointed at a better TERM");
+
+        let compat = Report { compat: true, ..tmux };
+        assert!(!compat.recommendations()[0].contains("multiplexer: auto"), "Compatibility shouldn't be recommended once it's on");
+    }
+}
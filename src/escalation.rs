@@ -0,0 +1,159 @@
+//! Escalation tables: what gets harder as the score climbs, mode by mode.
+//!
+//! Each mode's table is a plain text file in `escalation/` named after the
+//! mode, like `escalation/classic.txt`, with a score threshold on each line:
+//!
+//! ```text
+//! ; Lines starting with ';' are comments
+//! 50: obstacles 2
+//! 100: predator
+//! 150: fog 6
+//! ```
+//!
+//! - `obstacles N` adds N moving obstacles
+//! - `predator` lets loose a predator that hunts the snek's head
+//! - `fog R` hides everything further than R cells from the head
+//!
+//! Tables are read at the start of every game, so they can be tuned between
+//! games without rebuilding. A mode with no table doesn't escalate.
+
+use log::error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const ESCALATION_DIR: &str = "escalation";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Escalation {
+    Obstacles(u8),
+    Predator,
+    /// Vision shrinks to this many cells from the head
+    Fog(u16),
+}
+
+impl Escalation {
+    fn parse(text: &str) -> Result<Escalation, String> {
+        let mut words = text.split_whitespace();
+        let name = words.next().ok_or("expected an escalation")?;
+        let mut number = |what: &str| -> Result<u16, String> {
+            let word = words
+                .next()
+                .ok_or_else(|| format!("'{}' needs {}", name, what))?;
+            word.parse()
+                .map_err(|_| format!("'{}' isn't {} for '{}'", word, what, name))
+        };
+        let escalation = match name {
+            "obstacles" => {
+                let count = number("a number of obstacles")?;
+                Escalation::Obstacles(u8::try_from(count).map_err(|_| "too many obstacles")?)
+            }
+            "predator" => Escalation::Predator,
+            "fog" => Escalation::Fog(number("a distance")?),
+            _ => return Err(format!("unknown escalation '{}'", name)),
+        };
+        match words.next() {
+            Some(extra) => Err(format!("unexpected '{}' after '{}'", extra, name)),
+            None => Ok(escalation),
+        }
+    }
+
+    /// What the player is told when it happens.
+    pub fn describe(&self) -> String {
+        match self {
+            Escalation::Obstacles(1) => "A moving obstacle appears!".to_string(),
+            Escalation::Obstacles(count) => format!("{} moving obstacles appear!", count),
+            Escalation::Predator => "A predator is on the prowl!".to_string(),
+            Escalation::Fog(_) => "Fog rolls in!".to_string(),
+        }
+    }
+}
+
+/// Something that happens once the score reaches `score`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Step {
+    pub score: u16,
+    pub escalation: Escalation,
+}
+
+/// Parse an escalation table, lowest threshold first.
+pub fn parse(text: &str) -> Result<Vec<Step>, String> {
+    let mut steps = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with(';') || line.is_empty() {
+            continue;
+        }
+        let in_line = |e: String| format!("line {}: {}", index + 1, e);
+        let (score, escalation) = line
+            .split_once(':')
+            .ok_or_else(|| in_line("expected 'score: escalation'".to_string()))?;
+        let score = score
+            .trim()
+            .parse()
+            .map_err(|_| in_line(format!("'{}' isn't a score", score.trim())))?;
+        let escalation = Escalation::parse(escalation).map_err(in_line)?;
+        steps.push(Step { score, escalation });
+    }
+    steps.sort_by_key(|step| step.score);
+    Ok(steps)
+}
+
+/// Load the table for the mode `slug` from `dir`. A missing table means no
+/// escalation, and a broken one is logged and ignored rather than stopping
+/// the game.
+pub fn load(dir: &Path, slug: &str) -> Vec<Step> {
+    let path = dir.join(format!("{}.txt", slug));
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            error!("Error reading {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+    parse(&text).unwrap_or_else(|e| {
+        error!("Error in {}: {}", path.display(), e);
+        Vec::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let table = parse("; comment\n150: fog 6\n\n50: obstacles 2\n100: predator\n").expect("Table should parse");
+        assert_eq!(
+            table,
+            vec![
+                Step { score: 50, escalation: Escalation::Obstacles(2) },
+                Step { score: 100, escalation: Escalation::Predator },
+                Step { score: 150, escalation: Escalation::Fog(6) },
+            ],
+            "Steps should be in score order"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("50 obstacles 2").unwrap_err().starts_with("line 1"), "Errors should say which line");
+        assert!(parse("lots: predator").is_err(), "Thresholds should be numbers");
+        assert!(parse("50: dragons").is_err(), "Unknown escalations should be refused");
+        assert!(parse("50: fog").is_err(), "Fog needs a distance");
+        assert!(parse("50: predator 3").is_err(), "Extra words should be refused");
+        assert!(parse("50: obstacles 300").is_err(), "Obstacle counts should fit");
+    }
+
+    #[test]
+    fn test_bundled_tables_load() {
+        let dir = Path::new(ESCALATION_DIR);
+        let entries = fs::read_dir(dir).expect("Bundled tables should be found");
+        for entry in entries {
+            let path = entry.expect("Table should be listed").path();
+            let text = fs::read_to_string(&path).expect("Table should be readable");
+            assert!(parse(&text).is_ok(), "{} should parse: {:?}", path.display(), parse(&text));
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! A menu of settings, each row a name and a value changed with left and
+//! right, shared by every screen that lets the player pick options, a menu of
+//! actions to pick one of, and a box to type a line of text into.
+
+use crossterm::event::KeyCode;
+use ratatui::{prelude::*, widgets::*};
+
+pub struct Form<'a> {
+    pub title: &'a str,
+    /// How to use the form, shown above the rows
+    pub help: &'a str,
+    /// Each setting's name and current value
+    pub rows: Vec<(&'a str, String)>,
+    /// Index into `rows` of the row being changed
+    pub selected: usize,
+    /// Indexes into `rows` of the rows whose value is locked, drawn greyed out
+    pub locked: Vec<usize>,
+}
+
+impl Widget for Form<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut lines = vec![Line::from(self.help), Line::from("")];
+        for (row, (name, value)) in self.rows.iter().enumerate() {
+            let mut line = if row == self.selected {
+                Line::from(format!("{}: < {} >", name, value)).bold()
+            } else {
+                Line::from(format!("{}: {}", name, value))
+            };
+            if self.locked.contains(&row) {
+                line = line.dark_gray();
+            }
+            lines.push(line);
+        }
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default().title(self.title).borders(Borders::ALL))
+            .render(area, buf);
+    }
+}
+
+/// A list of actions, picked with up and down.
+pub struct Menu<'a> {
+    pub title: &'a str,
+    /// How to use the menu, shown above the actions
+    pub help: &'a str,
+    pub actions: Vec<&'a str>,
+    /// Index into `actions` of the one picked
+    pub selected: usize,
+    /// Indexes into `actions` of the ones that can't be picked, drawn greyed out
+    pub disabled: Vec<usize>,
+}
+
+impl Widget for Menu<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut lines = vec![Line::from(self.help), Line::from("")];
+        for (row, action) in self.actions.iter().enumerate() {
+            let mut line = if row == self.selected {
+                Line::from(format!("> {} <", action)).bold()
+            } else {
+                Line::from(*action)
+            };
+            if self.disabled.contains(&row) {
+                line = line.dark_gray();
+            }
+            lines.push(line);
+        }
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default().title(self.title).borders(Borders::ALL))
+            .render(area, buf);
+    }
+}
+
+/// A line of text being typed, with a cursor at the end.
+pub struct TextInput<'a> {
+    pub title: &'a str,
+    /// What to type, shown above the text
+    pub prompt: &'a str,
+    pub text: &'a str,
+    /// How to finish, shown below the text
+    pub help: &'a str,
+}
+
+impl Widget for TextInput<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines = vec![
+            Line::from(self.prompt),
+            Line::from(""),
+            Line::from(format!("{}_", self.text)).bold(),
+            Line::from(""),
+            Line::from(self.help).dark_gray(),
+        ];
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default().title(self.title).borders(Borders::ALL))
+            .render(area, buf);
+    }
+}
+
+/// Type `code` into `text`, which holds at most `max` characters. Returns
+/// whether it was a key for typing.
+pub fn edit(text: &mut String, code: KeyCode, max: usize) -> bool {
+    match code {
+        KeyCode::Backspace => {
+            text.pop();
+            true
+        }
+        KeyCode::Char(c) if !c.is_control() => {
+            if text.chars().count() < max {
+                text.push(c);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// `selected` moved `step` rows through a form of `rows` rows, stopping at either end.
+pub fn step(selected: usize, step: isize, rows: usize) -> usize {
+    selected
+        .saturating_add_signed(step)
+        .min(rows.saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selected_row() {
+        let form = Form { title: "Settings", help: "Pick one", rows: vec![("Speed", "Fast".to_string()), ("Symbols", "ASCII".to_string())], selected: 1, locked: vec![0] };
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 30, 6));
+        form.render(buffer.area, &mut buffer);
+        let row = |y| (0..30).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        assert!(row(3).contains("Speed: Fast") && !row(3).contains('<'), "Unselected rows should just show the value");
+        assert!(row(4).contains("Symbols: < ASCII >"), "The selected row should show arrows");
+        assert!(buffer[(9, 4)].modifier.contains(Modifier::BOLD), "The selected row should be bold");
+        assert_eq!(buffer[(11, 3)].fg, Color::DarkGray, "Locked rows should be greyed out");
+    }
+
+    #[test]
+    fn test_menu() {
+        let menu = Menu { title: "Paused", help: "Pick one", actions: vec!["Resume", "Restart"], selected: 0, disabled: vec![1] };
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 20, 6));
+        menu.render(buffer.area, &mut buffer);
+        let row = |y| (0..20).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        assert!(row(3).contains("> Resume <"), "The picked action should be marked");
+        assert!(row(4).contains("Restart") && !row(4).contains('>'), "Other actions should be plain");
+        assert_eq!(buffer[(8, 4)].fg, Color::DarkGray, "Actions that can't be picked should be greyed out");
+    }
+
+    #[test]
+    fn test_text_input() {
+        let mut text = "Bo".to_string();
+        assert!(edit(&mut text, KeyCode::Char('b'), 3), "Letters should be typed");
+        assert!(edit(&mut text, KeyCode::Char('x'), 3), "Typing past the limit should still count as typing");
+        assert_eq!(text, "Bob", "Text should stop at the limit");
+        edit(&mut text, KeyCode::Backspace, 3);
+        assert_eq!(text, "Bo", "Backspace should take the last letter off");
+        assert!(!edit(&mut text, KeyCode::Enter, 3), "Enter isn't for typing");
+
+        let input = TextInput { title: "Name", prompt: "Who are you?", text: &text, help: "ENTER to save" };
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 20, 7));
+        input.render(buffer.area, &mut buffer);
+        let row = |y| (0..20).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        assert!(row(3).contains("Bo_"), "The text should be shown with a cursor");
+    }
+
+    #[test]
+    fn test_step() {
+        assert_eq!(step(0, -1, 4), 0, "Moving up from the top should stay put");
+        assert_eq!(step(3, 1, 4), 3, "Moving down from the bottom should stay put");
+        assert_eq!(step(1, 1, 4), 2, "Moving down should go to the next row");
+    }
+}
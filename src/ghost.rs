@@ -0,0 +1,143 @@
+//! Ghost races: the head's path through the best run so far, kept next to the
+//! high score, so later runs can race against it.
+//!
+//! A ghost file gives the arena size, then where the head was after each
+//! tick, starting with where it set off:
+//!
+//! ```text
+//! size: 40x20
+//! 20,10,0
+//! 21,10,0
+//! ```
+
+use crate::{Pos, Size};
+use log::error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ghost {
+    /// The arena the run was played in, the only one the ghost makes sense in
+    size: Size,
+    /// The head's position after each tick
+    heads: Vec<Pos>,
+}
+
+impl Ghost {
+    /// A run in an arena of `size`, yet to set off.
+    pub fn new(size: Size) -> Ghost {
+        Ghost {
+            size,
+            heads: Vec::new(),
+        }
+    }
+
+    fn parse(text: &str) -> Result<Ghost, String> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        let size = lines
+            .next()
+            .and_then(|line| line.strip_prefix("size:"))
+            .and_then(|size| size.trim().split_once('x'))
+            .and_then(|(width, height)| {
+                Some(Size {
+                    width: width.parse().ok()?,
+                    height: height.parse().ok()?,
+                })
+            })
+            .ok_or("expected the arena size first, like 'size: 40x20'")?;
+        let heads = lines
+            .enumerate()
+            .map(|(tick, line)| {
+                parse_pos(line).ok_or(format!("tick {}: '{}' isn't a position", tick, line))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Ghost { size, heads })
+    }
+
+    /// Load the ghost from `path`, `None` if there isn't one yet or it can't
+    /// be read.
+    pub fn load(path: &Path) -> Option<Ghost> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ghost::parse(&text)
+                .map_err(|e| error!("Error in {}: {}", path.display(), e))
+                .ok(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                error!("Error loading ghost: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        let mut text = format!("size: {}x{}\n", self.size.width, self.size.height);
+        for head in &self.heads {
+            text.push_str(&format!("{},{},{}\n", head.x, head.y, head.floor));
+        }
+        if let Err(e) = fs::write(path, text) {
+            error!("Error saving ghost: {}", e);
+        }
+    }
+
+    /// Note where the head is after `tick` ticks, forgetting anything later
+    /// left over from before a rewind.
+    pub fn record(&mut self, tick: u32, head: Pos) {
+        self.heads.truncate(tick as usize);
+        self.heads.push(head);
+    }
+
+    /// Where the head was after `tick` ticks, if the run got that far in an
+    /// arena of `size`.
+    pub fn head(&self, tick: u32, size: Size) -> Option<Pos> {
+        if size != self.size {
+            return None;
+        }
+        self.heads.get(tick as usize).copied()
+    }
+}
+
+/// A position written as `x,y,floor`.
+fn parse_pos(text: &str) -> Option<Pos> {
+    let mut numbers = text.split(',').map(str::trim);
+    Some(Pos {
+        x: numbers.next()?.parse().ok()?,
+        y: numbers.next()?.parse().ok()?,
+        floor: numbers.next()?.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ghost_path() {
+        let size = Size { width: 10, height: 5 };
+        let mut ghost = Ghost::new(size);
+        for (tick, x) in [1, 2, 3, 4].into_iter().enumerate() {
+            ghost.record(tick as u32, Pos { x, y: 2, floor: 0 });
+        }
+        assert_eq!(ghost.head(2, size), Some(Pos { x: 3, y: 2, floor: 0 }), "The ghost should be where the head was");
+        assert_eq!(ghost.head(4, size), None, "The ghost should vanish once its run is over");
+        assert_eq!(ghost.head(0, Size { width: 20, height: 5 }), None, "The ghost only races in its own arena");
+
+        ghost.record(1, Pos { x: 2, y: 3, floor: 0 });
+        assert_eq!(ghost.head(1, size), Some(Pos { x: 2, y: 3, floor: 0 }), "Recording again should replace what came after");
+        assert_eq!(ghost.head(2, size), None, "Ticks after a rewind should be forgotten");
+    }
+
+    #[test]
+    fn test_ghost_persists() {
+        let path = std::env::temp_dir().join(format!("snekrs_ghost_{}.txt", std::process::id()));
+        let mut ghost = Ghost::new(Size { width: 10, height: 5 });
+        ghost.record(0, Pos { x: 4, y: 2, floor: 0 });
+        ghost.record(1, Pos { x: 5, y: 2, floor: 1 });
+        ghost.save(&path);
+        assert_eq!(Ghost::load(&path), Some(ghost), "A ghost should load back");
+        fs::write(&path, "5,2,0\n").unwrap();
+        assert_eq!(Ghost::load(&path), None, "A ghost without a size should be ignored");
+        fs::remove_file(&path).ok();
+        assert_eq!(Ghost::load(&path), None, "A missing file should mean no ghost");
+    }
+}
@@ -0,0 +1,278 @@
+//! The symbols the arena is drawn with, one table per symbol set, so that
+//! drawing never has to know which set is in use.
+
+use crate::Direction;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::Span;
+use std::env;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glyphs {
+    /// The head pointing north, east, south and west, then north-east,
+    /// north-west, south-east and south-west. Nothing else in the table looks
+    /// like a head
+    pub heads: [&'static str; 8],
+    /// The body is mostly its skin's colour, drawn behind this
+    pub body: &'static str,
+    /// Lines joining up a body drawn in segments: across, down, then the
+    /// corners joining north and east, south and east, south and west, and
+    /// north and west
+    pub joints: [&'static str; 6],
+    /// Cells filled an eighth at a time from the left, then from the bottom,
+    /// for a head sliding between cells. Empty where there's nothing to fill
+    /// them with
+    pub fills: [[&'static str; 8]; 2],
+    /// Morsels from the least to the most they're worth
+    pub morsels: [&'static str; 5],
+    /// The hollow twins of the morsels, told apart by shape rather than colour
+    pub traps: [&'static str; 5],
+    pub poison: &'static str,
+    pub scythe: &'static str,
+    pub rewind: &'static str,
+    pub shield: &'static str,
+    pub phase: &'static str,
+    pub ghost: &'static str,
+    pub rival: &'static str,
+    pub wall: &'static str,
+    pub hazard: &'static str,
+    pub weed: &'static str,
+    pub ice: &'static str,
+    pub portal: &'static str,
+    pub wormhole: &'static str,
+    /// Tunnels on the left and right edges, then the top and bottom
+    pub tunnels: [&'static str; 2],
+    /// Speed pads pushing north, east, south and west
+    pub pads: [&'static str; 4],
+    /// Stairs up and down
+    pub stairs: [&'static str; 2],
+    pub obstacle: &'static str,
+    pub predator: &'static str,
+    /// Lives left, in the header
+    pub life: &'static str,
+    /// The dots of the grid background
+    pub dot: &'static str,
+}
+
+pub const UNICODE: Glyphs = Glyphs {
+    heads: ["▴", "▸", "▾", "◂", "◥", "◤", "◢", "◣"],
+    body: " ",
+    joints: ["─", "│", "└", "┌", "┐", "┘"],
+    fills: [
+        ["▏", "▎", "▍", "▌", "▋", "▊", "▉", "█"],
+        ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"],
+    ],
+    morsels: ["♣", "♦", "♥", "♠", "★"],
+    traps: ["♧", "♢", "♡", "♤", "☆"],
+    poison: "☠",
+    scythe: "✂",
+    rewind: "↺",
+    shield: "◈",
+    phase: "◌",
+    ghost: "○",
+    rival: "●",
+    wall: "█",
+    hazard: "▒",
+    weed: "ψ",
+    ice: "░",
+    portal: "◎",
+    wormhole: "¤",
+    tunnels: ["⇄", "⇅"],
+    pads: ["↑", "→", "↓", "←"],
+    stairs: ["▲", "▼"],
+    obstacle: "■",
+    predator: "☻",
+    life: "♥",
+    dot: "·",
+};
+
+/// For fonts without the card suits and emoji. Morsels show what they're
+/// worth, and every trap looks the same.
+pub const ASCII: Glyphs = Glyphs {
+    heads: ["A", "}", "V", "{", "/", "\\", "\\", "/"],
+    body: " ",
+    joints: ["-", "|", "+", "+", "+", "+"],
+    fills: [[""; 8]; 2],
+    morsels: ["1", "2", "3", "4", "5"],
+    traps: ["-"; 5],
+    poison: "!",
+    scythe: "x",
+    rewind: "<",
+    shield: "+",
+    phase: "&",
+    ghost: "o",
+    rival: "o",
+    wall: "#",
+    hazard: "%",
+    weed: "w",
+    ice: ":",
+    portal: "O",
+    wormhole: "*",
+    tunnels: ["=", "|"],
+    pads: ["^", ">", "v", "<"],
+    stairs: ["^", "v"],
+    obstacle: "X",
+    predator: "P",
+    life: "*",
+    dot: ".",
+};
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        UNICODE
+    }
+}
+
+impl Glyphs {
+    /// The head, pointing the way the snek is going.
+    pub fn head(&self, direction: Direction) -> &'static str {
+        self.heads[direction as usize]
+    }
+
+    /// Every symbol in the table, each once.
+    pub fn all(&self) -> Vec<&'static str> {
+        let mut all: Vec<&'static str> = [
+            self.poison,
+            self.scythe,
+            self.rewind,
+            self.shield,
+            self.phase,
+            self.ghost,
+            self.rival,
+            self.wall,
+            self.hazard,
+            self.weed,
+            self.ice,
+            self.portal,
+            self.wormhole,
+            self.obstacle,
+            self.predator,
+            self.life,
+            self.dot,
+        ]
+        .into_iter()
+        .chain(self.heads)
+        .chain(self.joints)
+        .chain(
+            self.fills
+                .into_iter()
+                .flatten()
+                .filter(|fill| !fill.is_empty()),
+        )
+        .chain(self.morsels)
+        .chain(self.traps)
+        .chain(self.tunnels)
+        .chain(self.pads)
+        .chain(self.stairs)
+        .collect();
+        all.sort_unstable();
+        all.dedup();
+        all
+    }
+}
+
+/// Whether `symbol` takes up more than one cell, like most emoji.
+pub fn is_wide(symbol: &str) -> bool {
+    Span::raw(symbol).width() > 1
+}
+
+/// Clear the cells that wide symbols in `area` of `buf` spill into, so
+/// nothing drawn there fights with the spill. A wide symbol at the right of
+/// `area`, with nowhere to spill, is left to whoever drew it.
+pub fn clear_spill(buf: &mut Buffer, area: Rect) {
+    let area = area.intersection(buf.area);
+    for y in area.top()..area.bottom() {
+        let mut x = area.left();
+        while x < area.right() {
+            let width = Span::raw(buf[(x, y)].symbol()).width().max(1) as u16;
+            for spill in (x + 1..x + width).filter(|&spill| spill < area.right()) {
+                buf[(spill, y)].reset();
+            }
+            x += width;
+        }
+    }
+}
+
+/// Whether the terminal takes UTF-8, going by the first of `LC_ALL`,
+/// `LC_CTYPE` and `LANG` that's set, as the C library does. With none of
+/// them set it's taken to, as most terminals do these days.
+pub fn locale_is_utf8() -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty());
+    takes_utf8(locale.as_deref())
+}
+
+/// Whether `locale`, if one is named at all, is a UTF-8 one.
+fn takes_utf8(locale: Option<&str>) -> bool {
+    locale.is_none_or(names_utf8)
+}
+
+fn names_utf8(locale: &str) -> bool {
+    let locale = locale.to_ascii_lowercase();
+    locale.contains("utf-8") || locale.contains("utf8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glyph_tables() {
+        assert!(
+            ASCII.all().iter().all(|glyph| glyph.is_ascii()),
+            "The ASCII table should be nothing but ASCII"
+        );
+        assert!(
+            UNICODE.all().len() > ASCII.all().len(),
+            "Unicode should tell more things apart than ASCII"
+        );
+        assert_eq!(
+            (UNICODE.head(Direction::West), ASCII.head(Direction::South)),
+            ("◂", "V"),
+            "The head should point the way the snek is going"
+        );
+        assert!(
+            names_utf8("en_GB.UTF-8") && names_utf8("C.utf8"),
+            "UTF-8 locales should be spotted"
+        );
+        assert!(
+            !names_utf8("C") && !names_utf8("POSIX") && !names_utf8("en_US.ISO-8859-1"),
+            "Other locales shouldn't be taken for UTF-8"
+        );
+        assert!(
+            takes_utf8(None) && !takes_utf8(Some("C")),
+            "Only a locale that's named and isn't UTF-8 should mean ASCII"
+        );
+    }
+
+    #[test]
+    fn test_heads_stand_out() {
+        for glyphs in [UNICODE, ASCII] {
+            let others = Glyphs {
+                heads: [""; 8],
+                ..glyphs
+            }
+            .all();
+            assert!(
+                glyphs.heads.iter().all(|head| !others.contains(head)),
+                "Heads shouldn't look like anything else in the arena: {:?}",
+                glyphs.heads
+            );
+        }
+    }
+
+    #[test]
+    fn test_clear_spill() {
+        assert!(is_wide("😀") && !is_wide("▶") && !is_wide("@"), "Emoji should be wide and the usual heads not");
+        let area = Rect::new(0, 0, 4, 1);
+        let mut buf = Buffer::empty(area);
+        for (x, symbol) in (0..).zip(["😀", "♣", "♣", "😀"]) {
+            buf[(x, 0)].set_symbol(symbol);
+        }
+        clear_spill(&mut buf, area);
+        let row: Vec<_> = (0..4).map(|x| buf[(x, 0)].symbol()).collect();
+        assert_eq!(row, ["😀", " ", "♣", "😀"], "The cell a wide symbol spills into should be cleared, and nothing else");
+    }
+}
@@ -0,0 +1,158 @@
+//! User commands run when things happen in the game.
+//!
+//! Hooks are read from a plain text file, one per line as `event: command`:
+//!
+//! ```text
+//! ; Lines starting with ';' are comments
+//! game_over: notify-send "Snek" "Game over with $SNEKRS_SCORE"
+//! high_score: curl -s -X POST http://lights.local/party
+//! ```
+//!
+//! Commands are run by the shell in the background, with the details of the
+//! event in `SNEKRS_*` environment variables, so a slow command never holds up
+//! the game.
+
+use log::{error, info};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+pub const HOOKS_FILE: &str = ".snekrs_hooks.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookEvent {
+    GameOver,
+    Victory,
+    HighScore,
+}
+
+impl HookEvent {
+    const ALL: [HookEvent; 3] = [
+        HookEvent::GameOver,
+        HookEvent::Victory,
+        HookEvent::HighScore,
+    ];
+
+    pub fn slug(&self) -> &'static str {
+        match self {
+            HookEvent::GameOver => "game_over",
+            HookEvent::Victory => "victory",
+            HookEvent::HighScore => "high_score",
+        }
+    }
+
+    fn from_slug(slug: &str) -> Option<HookEvent> {
+        Self::ALL.into_iter().find(|event| event.slug() == slug)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hooks {
+    commands: Vec<(HookEvent, String)>,
+}
+
+impl Hooks {
+    pub fn parse(text: &str) -> Result<Hooks, String> {
+        let mut commands = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with(';') || line.is_empty() {
+                continue;
+            }
+            let (event, command) = line
+                .split_once(':')
+                .ok_or_else(|| format!("line {}: expected 'event: command'", index + 1))?;
+            let event = HookEvent::from_slug(event.trim())
+                .ok_or_else(|| format!("line {}: unknown event '{}'", index + 1, event.trim()))?;
+            commands.push((event, command.trim().to_string()));
+        }
+        Ok(Hooks { commands })
+    }
+
+    /// Load hooks from `path`. A missing file means no hooks, and a broken
+    /// one is logged and ignored rather than stopping the game.
+    pub fn load(path: &Path) -> Hooks {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Hooks::default(),
+            Err(e) => {
+                error!("Error reading hooks: {}", e);
+                return Hooks::default();
+            }
+        };
+        Hooks::parse(&text).unwrap_or_else(|e| {
+            error!("Error in {}: {}", path.display(), e);
+            Hooks::default()
+        })
+    }
+
+    /// Start every command hooked to `event`, without waiting for them to finish.
+    pub fn run(&self, event: HookEvent, env: &[(&str, String)]) {
+        for (_, command) in self.commands.iter().filter(|(e, _)| *e == event) {
+            info!("Running {} hook: {}", event.slug(), command);
+            match shell(command, event, env).spawn() {
+                // Reap the child off the game thread so it doesn't linger as a zombie
+                Ok(mut child) => {
+                    thread::spawn(move || child.wait());
+                }
+                Err(e) => error!("Error running {} hook: {}", event.slug(), e),
+            }
+        }
+    }
+}
+
+/// A shell command for `command`, kept away from the terminal the game is drawing on.
+fn shell(command: &str, event: HookEvent, env: &[(&str, String)]) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .env("SNEKRS_EVENT", event.slug())
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hooks() {
+        let text = "; hooks\ngame_over: echo bye\n\nhigh_score:  notify-send \"yay: $SNEKRS_SCORE\"\n";
+        let hooks = Hooks::parse(text).expect("Hooks should parse");
+        assert_eq!(
+            hooks.commands,
+            vec![
+                (HookEvent::GameOver, "echo bye".to_string()),
+                (HookEvent::HighScore, "notify-send \"yay: $SNEKRS_SCORE\"".to_string()),
+            ],
+            "Each line should hook a command to an event"
+        );
+
+        assert!(Hooks::parse("party: echo").is_err(), "Unknown events should be rejected");
+        assert!(Hooks::parse("game_over").is_err(), "Lines need a command");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hook_environment() {
+        let env = [("SNEKRS_SCORE", "42".to_string())];
+        let output = shell("echo $SNEKRS_EVENT $SNEKRS_SCORE", HookEvent::HighScore, &env)
+            .stdout(Stdio::piped())
+            .output()
+            .expect("Shell should run");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "high_score 42\n", "Event details should be in the environment");
+    }
+}
@@ -0,0 +1,118 @@
+//! Kiosk mode, for a public demo terminal: started with `snekrs --kiosk`, it
+//! only quits on a secret key combination, shows a bot playing between
+//! games, and puts every choice back to how it started for the next player.
+
+use crate::settings::Settings;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// How long the game can sit untouched, outside of a game in progress,
+/// before going back to the bot playing
+pub const IDLE: Duration = Duration::from_secs(20);
+
+/// The key combination that quits kiosk mode, like `ctrl+alt+q`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unlock {
+    modifiers: KeyModifiers,
+    key: char,
+}
+
+impl Default for Unlock {
+    fn default() -> Self {
+        Unlock {
+            modifiers: KeyModifiers::CONTROL | KeyModifiers::ALT,
+            key: 'q',
+        }
+    }
+}
+
+impl Unlock {
+    pub fn parse(text: &str) -> Result<Unlock, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = None;
+        for part in text.split('+').map(str::trim) {
+            let mut chars = part.chars();
+            match (part, chars.next(), chars.next()) {
+                ("ctrl", ..) => modifiers |= KeyModifiers::CONTROL,
+                ("alt", ..) => modifiers |= KeyModifiers::ALT,
+                ("shift", ..) => modifiers |= KeyModifiers::SHIFT,
+                (_, Some(c), None) if key.is_none() && c.is_ascii_alphanumeric() => {
+                    key = Some(c.to_ascii_lowercase())
+                }
+                _ => return Err(format!("'{}' isn't a key combination", text)),
+            }
+        }
+        // A single key could be hit by accident while playing
+        match key {
+            Some(key) if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                Ok(Unlock { modifiers, key })
+            }
+            _ => Err(format!("'{}' needs ctrl or alt and a key", text)),
+        }
+    }
+
+    pub fn matches(&self, event: KeyEvent) -> bool {
+        let KeyCode::Char(c) = event.code else {
+            return false;
+        };
+        c.to_ascii_lowercase() == self.key && event.modifiers.contains(self.modifiers)
+    }
+}
+
+impl fmt::Display for Unlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl"),
+            (KeyModifiers::ALT, "alt"),
+            (KeyModifiers::SHIFT, "shift"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+/// A kiosk's state while it runs.
+#[derive(Clone, Debug)]
+pub struct Kiosk {
+    pub unlock: Unlock,
+    /// The settings every player starts with
+    pub settings: Settings,
+    /// When a player last pressed anything
+    pub last_input: Instant,
+}
+
+impl Kiosk {
+    pub fn new(unlock: Unlock, settings: Settings) -> Kiosk {
+        Kiosk {
+            unlock,
+            settings,
+            last_input: Instant::now(),
+        }
+    }
+
+    pub fn idle(&self) -> bool {
+        self.last_input.elapsed() >= IDLE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlock() {
+        let unlock = Unlock::parse("ctrl + alt + X").expect("Combination should parse");
+        assert_eq!(unlock.to_string(), "ctrl+alt+x", "Combination should print the way it's written");
+        assert_eq!(Unlock::parse(&Unlock::default().to_string()), Ok(Unlock::default()), "The default should round trip");
+        assert!(unlock.matches(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL | KeyModifiers::ALT)), "The combination should unlock");
+        assert!(!unlock.matches(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL)), "Every modifier should be needed");
+        assert!(!unlock.matches(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL | KeyModifiers::ALT)), "Other keys shouldn't unlock");
+        assert!(Unlock::parse("q").is_err(), "A lone key is too easy to hit");
+        assert!(Unlock::parse("ctrl+hyper+q").is_err(), "Unknown modifiers should be refused");
+        assert!(Unlock::parse("ctrl+a+b").is_err(), "Only one key should be allowed");
+    }
+}
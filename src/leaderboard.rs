@@ -0,0 +1,229 @@
+//! The high score table: the best scores in each high score file, best first,
+//! one per line as the score, the mode's slug, the date and the player's name:
+//!
+//! ```text
+//! 120 classic 2026-10-16 Ann
+//! 95 wrap 2026-10-14 Bo Peep
+//! ```
+//!
+//! The name goes last, so it can have spaces in. A file holding nothing but a
+//! number is a high score from before there was a table, and loads as a table
+//! of one unnamed score.
+
+use log::error;
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Scores kept in each table
+pub const TABLE_SIZE: usize = 10;
+/// Shortest name a player can enter
+pub const MIN_NAME_LENGTH: usize = 3;
+/// Longest name a player can enter
+pub const MAX_NAME_LENGTH: usize = 12;
+/// The name and date given to a score from before the table
+const UNKNOWN: &str = "???";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub score: u16,
+    /// The slug of the mode the score was set in
+    pub mode: String,
+    /// When the score was set, as `YYYY-MM-DD`
+    pub date: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Leaderboard {
+    /// Best first
+    entries: Vec<Entry>,
+}
+
+impl Leaderboard {
+    /// Parse a table, where a lone number is an old high score set in `mode`.
+    fn parse(text: &str, mode: &str) -> Result<Leaderboard, String> {
+        if let Ok(score) = text.trim().parse::<u16>() {
+            let mut board = Leaderboard::default();
+            board.record(Entry {
+                score,
+                mode: mode.to_string(),
+                date: UNKNOWN.to_string(),
+                name: UNKNOWN.to_string(),
+            });
+            return Ok(board);
+        }
+        let mut entries = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut fields = line.splitn(4, ' ');
+            let (Some(score), Some(mode), Some(date), Some(name)) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(format!(
+                    "line {}: expected 'score mode date name'",
+                    index + 1
+                ));
+            };
+            let score = score
+                .parse()
+                .map_err(|_| format!("line {}: invalid score '{}'", index + 1, score))?;
+            entries.push(Entry {
+                score,
+                mode: mode.to_string(),
+                date: date.to_string(),
+                name: name.trim().to_string(),
+            });
+        }
+        // Edited by hand, perhaps
+        entries.sort_by_key(|entry| Reverse(entry.score));
+        entries.truncate(TABLE_SIZE);
+        Ok(Leaderboard { entries })
+    }
+
+    /// Load the table from `path`, for scores set in `mode`. It's empty if
+    /// there isn't one yet or it can't be read.
+    pub fn load(path: &Path, mode: &str) -> Leaderboard {
+        match fs::read_to_string(path) {
+            Ok(text) => Leaderboard::parse(&text, mode).unwrap_or_else(|e| {
+                error!("Error in {}: {}", path.display(), e);
+                Leaderboard::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Leaderboard::default(),
+            Err(e) => {
+                error!("Error loading high scores: {}", e);
+                Leaderboard::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        let text: String = self
+            .entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{} {} {} {}\n",
+                    entry.score, entry.mode, entry.date, entry.name
+                )
+            })
+            .collect();
+        if let Err(e) = fs::write(path, text) {
+            error!("Error saving high scores: {}", e);
+        }
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// The best score in the table, 0 if it's empty.
+    pub fn best(&self) -> u16 {
+        self.entries.first().map_or(0, |entry| entry.score)
+    }
+
+    /// Add `entry` if it's good enough to make the table, returning where it
+    /// went. Ties go below the scores already there, which got there first.
+    pub fn record(&mut self, entry: Entry) -> Option<usize> {
+        if entry.score == 0 {
+            return None;
+        }
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(self.entries.len());
+        if rank >= TABLE_SIZE {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(TABLE_SIZE);
+        Some(rank)
+    }
+
+    /// Put `name` to the score at `rank`.
+    pub fn rename(&mut self, rank: usize, name: &str) {
+        if let Some(entry) = self.entries.get_mut(rank) {
+            entry.name = name.to_string();
+        }
+    }
+
+    /// The table, one line per score with a header, where `mode_name` gives
+    /// the name of a mode from its slug.
+    pub fn table(&self, mode_name: impl Fn(&str) -> String) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{:<4}{:<width$}{:>6}  {:<16}{}",
+            "",
+            "Name",
+            "Score",
+            "Mode",
+            "Date",
+            width = MAX_NAME_LENGTH + 2
+        )];
+        for (rank, entry) in self.entries.iter().enumerate() {
+            lines.push(format!(
+                "{:<4}{:<width$}{:>6}  {:<16}{}",
+                format!("{}.", rank + 1),
+                entry.name,
+                entry.score,
+                mode_name(&entry.mode),
+                entry.date,
+                width = MAX_NAME_LENGTH + 2
+            ));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u16, name: &str) -> Entry {
+        Entry { score, mode: "classic".to_string(), date: "2026-10-16".to_string(), name: name.to_string() }
+    }
+
+    #[test]
+    fn test_record_scores() {
+        let mut board = Leaderboard::default();
+        assert_eq!(board.record(entry(0, "Nil")), None, "Nothing scored shouldn't make the table");
+        assert_eq!(board.record(entry(50, "Ann")), Some(0), "The first score should top the table");
+        assert_eq!(board.record(entry(80, "Bo")), Some(0), "A better score should go above");
+        assert_eq!(board.record(entry(50, "Cy")), Some(2), "Ties should go below the score already there");
+        for score in 60..67 {
+            board.record(entry(score, "Dee"));
+        }
+        assert_eq!(board.entries().len(), TABLE_SIZE, "The table should stop at ten");
+        assert_eq!(board.entries().last().map(|e| e.name.as_str()), Some("Cy"), "The worst score should be at the bottom");
+        assert_eq!(board.record(entry(50, "Eve")), None, "A score no better than the bottom shouldn't make a full table");
+        assert_eq!(board.record(entry(51, "Fay")), Some(8), "A score better than the bottom should push it off");
+        assert_eq!(board.entries().last().map(|e| e.name.as_str()), Some("Ann"), "The old bottom score should be gone");
+        assert_eq!(board.best(), 80, "The best score should be at the top");
+        board.rename(0, "Bob");
+        assert_eq!(board.entries()[0].name, "Bob", "Scores should take a new name");
+    }
+
+    #[test]
+    fn test_high_scores_persist() {
+        let path = std::env::temp_dir().join(format!("snekrs_leaderboard_{}.txt", std::process::id()));
+        let mut board = Leaderboard::default();
+        board.record(entry(120, "Ann"));
+        board.record(entry(95, "Bo Peep"));
+        board.save(&path);
+        assert_eq!(Leaderboard::load(&path, "classic"), board, "The table should load back, names with spaces and all");
+
+        fs::write(&path, "42\n").unwrap();
+        let old = Leaderboard::load(&path, "wrap");
+        assert_eq!(old.best(), 42, "An old high score should carry over");
+        assert_eq!(old.entries()[0].mode, "wrap", "An old high score should be from the mode it was kept for");
+
+        fs::write(&path, "many classic 2026-10-16 Ann\n").unwrap();
+        assert_eq!(Leaderboard::load(&path, "classic"), Leaderboard::default(), "A broken table should be ignored");
+        fs::remove_file(&path).ok();
+        assert_eq!(Leaderboard::load(&path, "classic"), Leaderboard::default(), "A missing file should mean no scores");
+    }
+}
@@ -0,0 +1,547 @@
+//! Maze levels loaded from plain text files.
+//!
+//! A level file is a picture of the arena, one line per row:
+//!
+//! ```text
+//! ; Lines starting with ';' are comments
+//! name: The Box
+//! ##########
+//! #S.......#
+//! #..1..1..#
+//! ##########
+//! ```
+//!
+//! - `#` is a wall, `.` or a space is open floor
+//! - `S` is where the snek's head starts, heading east with its body trailing west
+//! - A digit marks a portal; each digit must appear exactly twice to form a pair
+//! - `^` is a staircase up to the floor above, which needs a `v` in the same cell
+//! - `=` is a tunnel on the edge of the arena, still open when the edges are walled.
+//!   It needs another `=` on the opposite edge to come out of
+//! - `~` is ice, where the snek can't turn until it slides off the other side
+//! - `n`, `e`, `s` and `w` are speed pads, which push the snek a cell further
+//!   north, east, south or west as it crosses them
+//! - `*` is a morsel worth one point, there from the start
+//!
+//! The arena is exactly as big as the picture, and every row must be the same width.
+//!
+//! A line of `---` starts another floor, stacked above the previous one. Every
+//! floor must be the same size, and portals only link cells on the same floor.
+
+use crate::{Direction, PortalPair, Pos, Size};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const LEVELS_DIR: &str = "levels";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    pub name: String,
+    pub size: Size,
+    pub walls: HashSet<Pos>,
+    pub portals: Vec<PortalPair>,
+    /// Number of floors, at least one
+    pub floors: u8,
+    pub stairs: Vec<PortalPair>,
+    pub tunnels: HashSet<Pos>,
+    pub ice: HashSet<Pos>,
+    /// Speed pads, and which way they push
+    pub pads: HashMap<Pos, Direction>,
+    /// Morsels there from the start, in the order they appear in the file
+    pub morsels: Vec<Pos>,
+    pub spawn: Pos,
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    Empty,
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    UnknownTile {
+        line: usize,
+        column: usize,
+        tile: char,
+    },
+    MissingSpawn,
+    MultipleSpawns,
+    UnpairedPortal(char),
+    /// A floor with a different number of rows to the first
+    RaggedFloor {
+        floor: u8,
+        expected: usize,
+        found: usize,
+    },
+    /// A `^` without a `v` directly above it, or the other way round
+    UnmatchedStairs(Pos),
+    /// A `=` away from the edge, or without a `=` opposite it
+    UnmatchedTunnel(Pos),
+    TooManyFloors,
+    /// The starting body would run into a wall or off the edge
+    SpawnBlocked,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Io(e) => write!(f, "couldn't read level: {}", e),
+            LevelError::Empty => write!(f, "level has no rows"),
+            LevelError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: row is {} cells wide, expected {}",
+                line, found, expected
+            ),
+            LevelError::UnknownTile { line, column, tile } => {
+                write!(
+                    f,
+                    "line {}, column {}: unknown tile '{}'",
+                    line, column, tile
+                )
+            }
+            LevelError::MissingSpawn => write!(f, "no spawn point 'S'"),
+            LevelError::MultipleSpawns => write!(f, "more than one spawn point 'S'"),
+            LevelError::UnpairedPortal(tile) => {
+                write!(f, "portal '{}' must appear exactly twice", tile)
+            }
+            LevelError::RaggedFloor {
+                floor,
+                expected,
+                found,
+            } => write!(
+                f,
+                "floor {}: {} rows tall, expected {}",
+                floor + 1,
+                found,
+                expected
+            ),
+            LevelError::UnmatchedStairs(pos) => write!(
+                f,
+                "floor {}, row {}, column {}: stairs don't meet a matching '^' or 'v'",
+                pos.floor + 1,
+                pos.y + 1,
+                pos.x + 1
+            ),
+            LevelError::UnmatchedTunnel(pos) => write!(
+                f,
+                "floor {}, row {}, column {}: tunnels must be on the edge, opposite another '='",
+                pos.floor + 1,
+                pos.y + 1,
+                pos.x + 1
+            ),
+            LevelError::TooManyFloors => write!(f, "level has more than {} floors", u8::MAX),
+            LevelError::SpawnBlocked => {
+                write!(f, "no room for the snek's body to the west of 'S'")
+            }
+        }
+    }
+}
+
+impl From<io::Error> for LevelError {
+    fn from(e: io::Error) -> Self {
+        LevelError::Io(e)
+    }
+}
+
+/// Whether a snek of `length` fits to the west of `spawn` without touching a wall.
+fn body_fits(spawn: Pos, walls: &HashSet<Pos>, length: u16) -> bool {
+    (1..=length).all(|i| {
+        spawn.x >= i
+            && !walls.contains(&Pos {
+                x: spawn.x - i,
+                y: spawn.y,
+                floor: spawn.floor,
+            })
+    })
+}
+
+impl Level {
+    /// Whether a snek of `length` has room behind the spawn point.
+    pub fn fits_snek(&self, length: u16) -> bool {
+        body_fits(self.spawn, &self.walls, length)
+    }
+
+    /// Parse a level, using `default_name` if the file doesn't name itself.
+    pub fn parse(text: &str, default_name: &str, initial_length: u16) -> Result<Level, LevelError> {
+        let mut name = default_name.to_string();
+        let mut floors = vec![Vec::new()];
+        for (index, line) in text.lines().enumerate() {
+            let line_no = index + 1;
+            if line.starts_with(';') || line.trim().is_empty() {
+                continue;
+            }
+            if let Some(value) = line.strip_prefix("name:") {
+                name = value.trim().to_string();
+                continue;
+            }
+            if line.trim_end() == "---" {
+                floors.push(Vec::new());
+                continue;
+            }
+            floors
+                .last_mut()
+                .expect("There is always a floor")
+                .push((line_no, line.trim_end_matches('\r')));
+        }
+        if floors.len() > u8::MAX as usize {
+            return Err(LevelError::TooManyFloors);
+        }
+
+        let first = floors.first().and_then(|rows| rows.first());
+        let width = first.ok_or(LevelError::Empty)?.1.chars().count();
+        let height = floors[0].len();
+        let mut walls = HashSet::new();
+        let mut portal_ends: Vec<(u8, char, Vec<Pos>)> = Vec::new();
+        let mut ups = HashSet::new();
+        let mut downs = HashSet::new();
+        let mut tunnels = HashSet::new();
+        let mut ice = HashSet::new();
+        let mut pads = HashMap::new();
+        let mut morsels = Vec::new();
+        let mut spawn = None;
+
+        for (floor, rows) in floors.iter().enumerate() {
+            let floor = floor as u8;
+            if rows.len() != height {
+                return Err(LevelError::RaggedFloor {
+                    floor,
+                    expected: height,
+                    found: rows.len(),
+                });
+            }
+            for (y, &(line, row)) in rows.iter().enumerate() {
+                let found = row.chars().count();
+                if found != width {
+                    return Err(LevelError::RaggedRow {
+                        line,
+                        expected: width,
+                        found,
+                    });
+                }
+                for (x, tile) in row.chars().enumerate() {
+                    let pos = Pos {
+                        x: x as u16,
+                        y: y as u16,
+                        floor,
+                    };
+                    match tile {
+                        '.' | ' ' => {}
+                        '#' => {
+                            walls.insert(pos);
+                        }
+                        'S' => {
+                            if spawn.replace(pos).is_some() {
+                                return Err(LevelError::MultipleSpawns);
+                            }
+                        }
+                        '^' => {
+                            ups.insert(pos);
+                        }
+                        'v' => {
+                            downs.insert(pos);
+                        }
+                        '=' => {
+                            tunnels.insert(pos);
+                        }
+                        '~' => {
+                            ice.insert(pos);
+                        }
+                        '*' => morsels.push(pos),
+                        'n' | 'e' | 's' | 'w' => {
+                            let direction = match tile {
+                                'n' => Direction::North,
+                                'e' => Direction::East,
+                                's' => Direction::South,
+                                _ => Direction::West,
+                            };
+                            pads.insert(pos, direction);
+                        }
+                        '0'..='9' => {
+                            match portal_ends
+                                .iter_mut()
+                                .find(|(f, t, _)| *f == floor && *t == tile)
+                            {
+                                Some((_, _, ends)) => ends.push(pos),
+                                None => portal_ends.push((floor, tile, vec![pos])),
+                            }
+                        }
+                        _ => {
+                            return Err(LevelError::UnknownTile {
+                                line,
+                                column: x + 1,
+                                tile,
+                            })
+                        }
+                    }
+                }
+            }
+        }
+
+        let spawn = spawn.ok_or(LevelError::MissingSpawn)?;
+        if !body_fits(spawn, &walls, initial_length) {
+            return Err(LevelError::SpawnBlocked);
+        }
+
+        let portals = portal_ends
+            .into_iter()
+            .map(|(_, tile, ends)| match ends[..] {
+                [a, b] => Ok(PortalPair { a, b }),
+                _ => Err(LevelError::UnpairedPortal(tile)),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let mut stairs = Vec::new();
+        for &a in &ups {
+            let b = Pos {
+                floor: a.floor + 1,
+                ..a
+            };
+            if !downs.remove(&b) {
+                return Err(LevelError::UnmatchedStairs(a));
+            }
+            stairs.push(PortalPair { a, b });
+        }
+        if let Some(&b) = downs.iter().next() {
+            return Err(LevelError::UnmatchedStairs(b));
+        }
+        // HashSet order isn't stable, so keep levels comparable
+        stairs.sort_by_key(|pair| (pair.a.floor, pair.a.y, pair.a.x));
+
+        let (width, height) = (width as u16, height as u16);
+        let opposite = |v: u16, len: u16| match v {
+            0 => Some(len - 1),
+            v if v + 1 == len => Some(0),
+            _ => None,
+        };
+        for &pos in &tunnels {
+            let across = opposite(pos.x, width).map(|x| Pos { x, ..pos });
+            let down = opposite(pos.y, height).map(|y| Pos { y, ..pos });
+            let matched = (across.is_some() || down.is_some())
+                && [across, down]
+                    .into_iter()
+                    .flatten()
+                    .all(|other| tunnels.contains(&other));
+            if !matched {
+                return Err(LevelError::UnmatchedTunnel(pos));
+            }
+        }
+
+        Ok(Level {
+            name,
+            size: Size { width, height },
+            walls,
+            portals,
+            floors: floors.len() as u8,
+            stairs,
+            tunnels,
+            ice,
+            pads,
+            morsels,
+            spawn,
+        })
+    }
+
+    pub fn load(path: &Path, initial_length: u16) -> Result<Level, LevelError> {
+        let text = fs::read_to_string(path)?;
+        let default_name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Level::parse(&text, &default_name, initial_length)
+    }
+}
+
+/// Save `text`, the file `level` was parsed from, as a new file in `dir` named
+/// after the level, numbered to keep clear of any file already there.
+pub fn save_new(dir: &Path, level: &Level, text: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let stem: String = level
+        .name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let stem = match stem.trim_matches('_') {
+        "" => "level",
+        stem => stem,
+    };
+    let path = std::iter::once(dir.join(format!("{}.txt", stem)))
+        .chain((2..).map(|n| dir.join(format!("{}_{}.txt", stem, n))))
+        .find(|path| !path.exists())
+        .expect("Some file name should be free");
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// Load every level in `dir`, sorted by file name. Levels that fail to load
+/// are kept alongside the error so it can be shown to the player.
+pub fn load_dir(dir: &Path, initial_length: u16) -> Vec<(PathBuf, Result<Level, LevelError>)> {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file())
+            .collect(),
+        // No levels directory just means no levels
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let level = Level::load(&path, initial_length);
+            (path, level)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_level() {
+        let text = "; a test level\nname: Tiny\n#######\n#.S.1.#\n#...1.#\n#######\n";
+        let level = Level::parse(text, "fallback", 1).expect("Level should parse");
+
+        assert_eq!(level.name, "Tiny", "Name should come from the file");
+        assert_eq!(level.size, Size { width: 7, height: 4 }, "Size should come from the picture");
+        assert_eq!(level.spawn, Pos { x: 2, y: 1, floor: 0 }, "Spawn should be where the S is");
+        assert_eq!(level.walls.len(), 18, "Every # should be a wall");
+        assert!(level.walls.contains(&Pos { x: 0, y: 0, floor: 0 }), "Corner should be a wall");
+        assert_eq!(
+            level.portals,
+            vec![PortalPair {
+                a: Pos { x: 4, y: 1, floor: 0 },
+                b: Pos { x: 4, y: 2, floor: 0 }
+            }],
+            "Matching digits should make a portal pair"
+        );
+    }
+
+    #[test]
+    fn test_bundled_levels_load() {
+        let levels = load_dir(Path::new(LEVELS_DIR), 3);
+        assert!(!levels.is_empty(), "Bundled levels should be found");
+        for (path, level) in levels {
+            assert!(level.is_ok(), "{} should load: {}", path.display(), level.unwrap_err());
+        }
+    }
+
+    #[test]
+    fn test_default_name() {
+        let level = Level::parse("..S.", "from_file", 2).expect("Level should parse");
+        assert_eq!(level.name, "from_file", "Unnamed level should use the default name");
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        assert!(matches!(Level::parse("", "x", 1), Err(LevelError::Empty)), "Empty file should be rejected");
+        assert!(
+            matches!(Level::parse("..S.\n...", "x", 1), Err(LevelError::RaggedRow { line: 2, expected: 4, found: 3 })),
+            "Ragged rows should be rejected with their line"
+        );
+        assert!(
+            matches!(Level::parse("..S?", "x", 1), Err(LevelError::UnknownTile { line: 1, column: 4, tile: '?' })),
+            "Unknown tiles should be rejected with their position"
+        );
+        assert!(matches!(Level::parse("....", "x", 1), Err(LevelError::MissingSpawn)), "Spawn is required");
+        assert!(matches!(Level::parse(".S.S", "x", 1), Err(LevelError::MultipleSpawns)), "Only one spawn is allowed");
+        assert!(matches!(Level::parse(".S1.", "x", 1), Err(LevelError::UnpairedPortal('1'))), "Portals must be paired");
+        assert!(matches!(Level::parse("#.S.", "x", 3), Err(LevelError::SpawnBlocked)), "Body must fit behind the spawn");
+        assert!(
+            matches!(Level::parse(".S..\n---\n....\n....", "x", 1), Err(LevelError::RaggedFloor { floor: 1, expected: 1, found: 2 })),
+            "Floors must all be the same size"
+        );
+        assert!(
+            matches!(Level::parse(".S^.\n---\n...v", "x", 1), Err(LevelError::UnmatchedStairs(Pos { x: 2, y: 0, floor: 0 }))),
+            "Stairs up must land on stairs down"
+        );
+        assert!(matches!(Level::parse(".S.v", "x", 1), Err(LevelError::UnmatchedStairs(_))), "Stairs down need stairs up below");
+        assert!(
+            matches!(Level::parse("=S..\n....\n....", "x", 1), Err(LevelError::UnmatchedTunnel(Pos { x: 0, y: 0, floor: 0 }))),
+            "Tunnels need a partner on the opposite edge"
+        );
+        assert!(
+            matches!(Level::parse("....\n.S=.\n....", "x", 1), Err(LevelError::UnmatchedTunnel(_))),
+            "Tunnels must be on the edge"
+        );
+    }
+
+    #[test]
+    fn test_parse_tunnels() {
+        let level = Level::parse("##=##\n=.S.=\n##=##", "x", 1).expect("Level should parse");
+        assert_eq!(level.tunnels.len(), 4, "Every = should be a tunnel");
+        assert!(level.tunnels.contains(&Pos { x: 4, y: 1, floor: 0 }), "East tunnel should be on the edge");
+        assert!(!level.walls.contains(&Pos { x: 2, y: 0, floor: 0 }), "Tunnels shouldn't be walls");
+    }
+
+    #[test]
+    fn test_parse_ice() {
+        let level = Level::parse("######\n#.S~~#\n######", "x", 1).expect("Level should parse");
+        assert_eq!(level.ice.len(), 2, "Every ~ should be ice");
+        assert!(level.ice.contains(&Pos { x: 3, y: 1, floor: 0 }), "Ice should be where it's drawn");
+        assert!(!level.walls.contains(&Pos { x: 4, y: 1, floor: 0 }), "Ice should be open floor");
+    }
+
+    #[test]
+    fn test_parse_pads() {
+        let level = Level::parse("#..S.n#\n#.e.sw#", "x", 2).expect("Level should parse");
+        assert_eq!(level.pads.len(), 4, "Every pad letter should be a pad");
+        assert_eq!(level.pads.get(&Pos { x: 5, y: 0, floor: 0 }), Some(&Direction::North), "n should push north");
+        assert_eq!(level.pads.get(&Pos { x: 2, y: 1, floor: 0 }), Some(&Direction::East), "e should push east");
+        assert_eq!(level.pads.get(&Pos { x: 5, y: 1, floor: 0 }), Some(&Direction::West), "w should push west");
+        assert_eq!(level.spawn, Pos { x: 3, y: 0, floor: 0 }, "S should still be the spawn, not a pad");
+    }
+
+    #[test]
+    fn test_parse_morsels() {
+        let level = Level::parse("#*.S.*#\n#..*..#", "x", 2).expect("Level should parse");
+        assert_eq!(level.morsels, vec![Pos { x: 1, y: 0, floor: 0 }, Pos { x: 5, y: 0, floor: 0 }, Pos { x: 3, y: 1, floor: 0 }], "Morsels should be where they're drawn, in reading order");
+        assert!(!level.walls.contains(&Pos { x: 1, y: 0, floor: 0 }), "Morsels should be on open floor");
+    }
+
+    #[test]
+    fn test_parse_floors() {
+        let text = "#.S^1\n#...1\n---\n#..v.\n#1..1\n";
+        let level = Level::parse(text, "x", 1).expect("Level should parse");
+
+        assert_eq!(level.floors, 2, "Each --- should start a floor");
+        assert_eq!(level.size, Size { width: 5, height: 2 }, "Size should be that of a single floor");
+        assert!(level.walls.contains(&Pos { x: 0, y: 1, floor: 1 }), "Walls should be on their own floor");
+        assert_eq!(
+            level.stairs,
+            vec![PortalPair {
+                a: Pos { x: 3, y: 0, floor: 0 },
+                b: Pos { x: 3, y: 0, floor: 1 }
+            }],
+            "Stairs should link the same cell on neighbouring floors"
+        );
+        assert_eq!(level.portals.len(), 2, "Same digit on different floors should make separate pairs");
+    }
+
+    #[test]
+    fn test_save_new() {
+        let dir = std::env::temp_dir().join(format!("snekrs_levels_{}", std::process::id()));
+        let text = "name: The Big Box!\n#####\n#..S#\n#####\n";
+        let level = Level::parse(text, "x", 2).expect("Level should parse");
+        let first = save_new(&dir, &level, text).expect("Level should be saved");
+        let second = save_new(&dir, &level, text).expect("Second copy should be saved");
+        assert_eq!(first.file_name().unwrap(), "the_big_box.txt", "File should be named after the level");
+        assert_eq!(second.file_name().unwrap(), "the_big_box_2.txt", "Copies shouldn't overwrite each other");
+        assert_eq!(Level::load(&second, 2).map(|l| l.name).ok(), Some("The Big Box!".to_string()), "Saved levels should load back");
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,80 +1,331 @@
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind,
+        KeyboardEnhancementFlags, MouseButton, MouseEventKind, PopKeyboardEnhancementFlags,
+        PushKeyboardEnhancementFlags,
+    },
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
 };
 use log::{error, info, warn};
-use rand::Rng;
-use ratatui::{
-    prelude::*,
-    style::{Style, Stylize},
-    widgets::*,
-};
+use rand::{rngs::StdRng, seq::IteratorRandom, Rng, SeedableRng};
+use ratatui::{prelude::*, widgets::*};
 use simplelog::{Config, LevelFilter, WriteLogger};
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::fs::{self, File};
-use std::io::{self, Read, Write};
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-const MORSEL_SYMBOLS: [&str; 5] = ["♣", "♦", "♥", "♠", "★"];
+mod bot;
+mod bugreport;
+mod campaign;
+mod clipboard;
+mod crt;
+mod daily;
+mod doctor;
+mod escalation;
+mod form;
+mod ghost;
+mod glyphs;
+mod hooks;
+mod kiosk;
+mod leaderboard;
+mod level;
+mod puzzle;
+mod replay;
+mod sandbox;
+mod scoring;
+mod session;
+mod settings;
+mod sim;
+mod skin;
+mod spectate;
+mod split;
+mod theme;
+mod title;
+mod toast;
+mod tournament;
+mod trail;
+mod unlocks;
+
+use bot::{Greedy, Policy};
+use campaign::{Progress, STAGES};
+use clipboard::{RunCode, SystemClipboard};
+use escalation::Escalation;
+use form::{Form, Menu, TextInput};
+use ghost::Ghost;
+use glyphs::Glyphs;
+use hooks::{HookEvent, Hooks};
+use kiosk::Kiosk;
+use leaderboard::{Entry, Leaderboard};
+use level::{Level, LevelError};
+use puzzle::{Puzzle, Solved};
+use replay::{History, Input, Playback, Recording, Rewound, SavedRun, Viewer};
+use sandbox::Brush;
+use session::Session;
+use settings::{Background, Choice, Field, Head, Settings};
+use skin::Skin;
+use spectate::{Bot, Feed};
+use split::{ArenaPreset, GameView, SplitLayout};
+use theme::Theme;
+use title::Title;
+use toast::Toasts;
+use tournament::Tournament;
+use trail::Trail;
+use unlocks::{Goal, Item, Unlocks};
+
+/// The most a morsel can ever be worth, one for each of the morsel symbols
+const MAX_MORSEL_VALUE: usize = 5;
+/// Morsels are worth anything from 1 to 5, all as likely as each other,
+/// unless the rules say otherwise
+const EVEN_MORSEL_WEIGHTS: MorselWeights = MorselWeights::new(&[1; MAX_MORSEL_VALUE]);
+/// Cells between the dots of the grid background, across and down
+const GRID_SPACING: (u16, u16) = (4, 2);
+/// A dark red behind the cells the danger cell assist warns of
+const DANGER_TINT: Color = Color::Indexed(52);
+/// Arena shown in the welcome wizard's preview pane.
+const PREVIEW_ARENA: Size = Size {
+    width: 24,
+    height: 10,
+};
+/// Room for a skin's name in the skins menu, and the snek beside it
+const SKIN_LABEL_WIDTH: u16 = 40;
+const SKIN_SAMPLE_LENGTH: u16 = 12;
 const HIGH_SCORE_FILE: &str = ".snekrs_high_score.txt";
+const LOG_FILE: &str = "snekrs.log";
+/// Time between frames drawn between ticks, for smooth motion
+const FRAME_TIME: Duration = Duration::from_millis(16);
+/// Time to get ready before the snek starts moving, or moves again after a pause
+const COUNTDOWN: Duration = Duration::from_secs(3);
+const MIN_SAFE_ZONE: u16 = 5;
+const GAUGE_WIDTH: u16 = 24;
+/// Ticks a replay jumps when scrubbing back and forth
+const SCRUB_TICKS: i32 = 20;
+/// Width of the speed gauge, next to the score
+const SPEED_GAUGE_WIDTH: u16 = 16;
+/// The tick that fills the speed gauge, Hardcore's fastest
+const FASTEST_TICK: Duration = Duration::from_millis(25);
+/// Ticks allowed between noms to keep a combo going
+const COMBO_WINDOW: u32 = 15;
+/// Combo bonus stops growing after this many points per nom
+const MAX_COMBO_BONUS: u16 = 5;
+/// Tail segments dropped as hazards after eating a brittle morsel
+const TAIL_DROP: usize = 3;
+/// A full boost meter
+const BOOST_CAPACITY: u16 = 100;
+/// Boost gained for each point a morsel is worth
+const BOOST_PER_POINT: u16 = 10;
+/// Boost spent on each boosted tick
+const BOOST_DRAIN: u16 = 4;
+/// How long a press of the boost key counts as holding it, on terminals that
+/// don't report key releases. Long enough to bridge the pause before a held
+/// key starts repeating.
+const BOOST_HOLD: Duration = Duration::from_millis(550);
+/// What to announce at each combo milestone, from smallest to biggest
+const COMBO_ANNOUNCEMENTS: [(u16, &str); 3] = [(3, "Tasty!"), (5, "Feast!"), (8, "SNEKTACULAR!")];
+/// Time between bonus rounds
+const BONUS_ROUND_INTERVAL: Duration = Duration::from_secs(180);
+const BONUS_ROUND_LENGTH: Duration = Duration::from_secs(20);
+/// Most morsels scattered for a bonus round, fewer in a small arena
+const BONUS_MORSELS: usize = 40;
+/// Ticks an announcement stays on screen
+const ANNOUNCEMENT_TICKS: u32 = 12;
+const ANNOUNCEMENT_COLOURS: [Color; 4] = [
+    Color::LightRed,
+    Color::LightYellow,
+    Color::LightGreen,
+    Color::LightMagenta,
+];
+/// Ticks between mirrored controls flipping on or off
+const MIRROR_TICKS: RangeInclusive<u32> = 60..=180;
+/// Weeds planted at the start of a game with weeds
+const WEED_SEEDS: usize = 3;
+/// How many steps from the snek's head weeds keep clear of when they spread
+const WEED_CLEARANCE: u16 = 2;
+/// One in this many morsels is a scythe, when there are weeds
+const SCYTHE_ODDS: u32 = 6;
+/// How far from the head a scythe clears weeds
+const SCYTHE_RADIUS: u16 = 4;
+/// One in this many morsels is a trap, in every mode
+const TRAP_ODDS: u32 = 40;
+/// One in this many morsels is a rewind pickup, in every mode
+const REWIND_ODDS: u32 = 30;
+/// Most rewind charges a snek can save up
+const MAX_REWINDS: u8 = 3;
+/// One in this many morsels is a shield, in every mode
+const SHIELD_ODDS: u32 = 25;
+/// One in this many morsels lets the snek phase through walls, when there
+/// are walls it could phase through
+const PHASE_ODDS: u32 = 20;
+/// Ticks a snek can phase through walls for
+const PHASE_TICKS: u32 = 30;
+/// Ticks between a dizzy snek's turns of its own
+const DIZZY_TICKS: RangeInclusive<u32> = 15..=25;
+/// Ticks a rotting morsel takes to lose a point of value
+const ROT_TICKS: u32 = 40;
+/// Turns kept waiting for later ticks when the player turns more than once
+/// in one
+const MAX_QUEUED_TURNS: usize = 3;
+/// Ticks the head and score flash for after a nom, which is how long an
+/// invisible body shows too
+const FLASH_TICKS: u8 = 3;
+/// Wormholes put out by the wormholes modifier
+const WORMHOLES: usize = 3;
+/// Computer-controlled sneks in a battle royale
+const ROYALE_RIVALS: RangeInclusive<u8> = 5..=10;
+/// Width of the scoreboard beside a battle royale's arena
+const SCOREBOARD_WIDTH: u16 = 20;
+/// Width of the stats sidebar beside the arena
+const STATS_WIDTH: u16 = 20;
+/// Width of the run's stats, shown under the summary screens
+const RUN_STATS_WIDTH: u16 = 32;
+/// Size of the break reminder, shown over the summary screens
+const BREAK_POPUP: Size = Size {
+    width: 46,
+    height: 9,
+};
+/// Size of the pause menu, shown over the paused game
+const PAUSE_POPUP: Size = Size {
+    width: 36,
+    height: 8,
+};
+/// Size of the prompt for a high score's name, shown over the summary screens
+const NAME_POPUP: Size = Size {
+    width: 36,
+    height: 7,
+};
+/// Predators move every this many ticks, so a snek can outrun them
+const PREDATOR_TICKS: u32 = 2;
+/// Time between rule changes in chaos mode
+const CHAOS_INTERVAL: Duration = Duration::from_secs(30);
+/// Ticks the banner announcing a rule change stays up
+const CHAOS_BANNER_TICKS: u32 = 25;
+/// Chaos keeps the tick length in this range, in milliseconds
+const CHAOS_SPEEDS: RangeInclusive<u64> = 60..=240;
+const PORTAL_COLOURS: [Color; 4] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::LightBlue,
+    Color::LightYellow,
+];
 
 fn main() -> Result<(), io::Error> {
     // Set up logging before anything else
     WriteLogger::init(
         LevelFilter::Info,
         Config::default(),
-        File::create("snekrs.log")?,
+        File::create(LOG_FILE)?,
     )
     .expect("Failed to initialize logger");
 
     info!("Starting Snekrs");
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let kiosk = args.first().is_some_and(|arg| arg == "--kiosk");
+    if let Some(command) = args.first().filter(|_| !kiosk) {
+        return match command.as_str() {
+            "compare" => sim::compare_command(&args[1..]),
+            "doctor" => doctor::doctor_command(&args[1..]),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown command: {}", command),
+            )),
+        };
+    }
+
+    let settings = Settings::load(Path::new(settings::SETTINGS_FILE));
+    // Mouse reporting is one of the things multiplexers pass on unreliably
+    let capture_mouse = !settings.multiplexer_compat();
+    // As are terminal queries. Key releases tell when a held key is let go
+    let key_releases =
+        !settings.multiplexer_compat() && supports_keyboard_enhancement().unwrap_or(false);
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
+    if capture_mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    if key_releases {
+        execute!(
+            stdout,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
-    let mut game = Game::new();
+    let first_run = !Path::new(settings::SETTINGS_FILE).exists();
+    let mut game = Game::new(settings);
+    game.key_releases = key_releases;
+    if kiosk {
+        game.start_kiosk();
+    } else if first_run {
+        game.welcome();
+    }
 
     // Run game loop
-    let tick_rate = Duration::from_millis(150);
     let mut last_tick = Instant::now();
 
-    let mut ignore_input = false;
     loop {
+        let tick_rate = game.tick_rate();
+        game.set_motion(last_tick.elapsed().as_secs_f32() / tick_rate.as_secs_f32());
         terminal.draw(|f| game.render(f))?;
 
-        // Handle input
-        if !ignore_input && event::poll(tick_rate)? {
-            if let Event::Key(key) = event::read()? {
-                game.handle_input(key);
-                ignore_input = true;
+        // Moving smoothly needs drawing between ticks
+        let wait = if game.settings.smooth_motion {
+            FRAME_TIME.min(tick_rate)
+        } else {
+            tick_rate
+        };
+
+        // Handle every event that's come in, not just the first, so no key
+        // is lost. Turns still only take one a tick, through the snek's queue
+        let mut ready = event::poll(wait)?;
+        while ready {
+            match event::read()? {
+                Event::Key(key) => match key.kind {
+                    KeyEventKind::Press => game.handle_input(key),
+                    KeyEventKind::Release => game.handle_release(key),
+                    // A held key counts from when it was pressed
+                    KeyEventKind::Repeat => {}
+                },
+                Event::Mouse(mouse) => game.handle_mouse(mouse),
+                _ => {}
             }
+            ready = event::poll(Duration::ZERO)?;
         }
 
         if last_tick.elapsed() >= tick_rate {
-            game.update();
+            // Nothing moves until the countdown's done
+            if !game.counting_down() {
+                game.update();
+            }
             last_tick = Instant::now();
-            ignore_input = false;
         }
 
-        match game.state {
-            GameState::Exit => break,
-            _ => {}
+        if let GameState::Exit = game.state {
+            break;
         }
     }
 
     // Cleanup terminal
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    if capture_mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
+    if key_releases {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     terminal.show_cursor()?;
 
     Ok(())
@@ -92,23 +343,65 @@ enum Direction {
     East,
     South,
     West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
 }
 
 impl Direction {
+    /// The four ways a snek can go without the diagonal mutator
+    const CARDINAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+    /// Every way a snek can go, clockwise from north
+    const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
     fn opposite(&self) -> Direction {
         match self {
             Direction::North => Direction::South,
             Direction::South => Direction::North,
             Direction::East => Direction::West,
             Direction::West => Direction::East,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::SouthWest => Direction::NorthEast,
+            Direction::NorthWest => Direction::SouthEast,
+            Direction::SouthEast => Direction::NorthWest,
+        }
+    }
+
+    /// Where a press of this direction takes the snek while controls are mirrored.
+    fn mirrored(&self, mirror: Mirror) -> Direction {
+        match (mirror, self) {
+            (Mirror::Off, _) => *self,
+            (Mirror::LeftRight, Direction::North | Direction::South) => *self,
+            (Mirror::LeftRight, Direction::NorthEast) => Direction::NorthWest,
+            (Mirror::LeftRight, Direction::NorthWest) => Direction::NorthEast,
+            (Mirror::LeftRight, Direction::SouthEast) => Direction::SouthWest,
+            (Mirror::LeftRight, Direction::SouthWest) => Direction::SouthEast,
+            _ => self.opposite(),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 struct Pos {
     x: u16,
     y: u16,
+    /// Which floor of a multi-floor level the cell is on
+    floor: u8,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -117,6 +410,12 @@ struct PosDelta {
     y: i32,
 }
 
+impl From<Size> for Rect {
+    fn from(size: Size) -> Self {
+        Rect::new(0, 0, size.width, size.height)
+    }
+}
+
 impl From<Direction> for PosDelta {
     fn from(dir: Direction) -> Self {
         match dir {
@@ -124,6 +423,10 @@ impl From<Direction> for PosDelta {
             Direction::South => PosDelta { x: 0, y: 1 },
             Direction::East => PosDelta { x: 1, y: 0 },
             Direction::West => PosDelta { x: -1, y: 0 },
+            Direction::NorthEast => PosDelta { x: 1, y: -1 },
+            Direction::NorthWest => PosDelta { x: -1, y: -1 },
+            Direction::SouthEast => PosDelta { x: 1, y: 1 },
+            Direction::SouthWest => PosDelta { x: -1, y: 1 },
         }
     }
 }
@@ -132,11 +435,36 @@ impl Pos {
     fn wrapped_add(&self, delta: PosDelta, size: Size) -> Pos {
         let new_x = (self.x as i32 + delta.x).rem_euclid(size.width as i32) as u16;
         let new_y = (self.y as i32 + delta.y).rem_euclid(size.height as i32) as u16;
-        Pos { x: new_x, y: new_y }
+        Pos {
+            x: new_x,
+            y: new_y,
+            floor: self.floor,
+        }
+    }
+
+    fn is_on_edge(&self, size: Size) -> bool {
+        self.x == 0 || self.y == 0 || self.x + 1 == size.width || self.y + 1 == size.height
+    }
+
+    /// Which way `other` is, if it's a step away on the same floor, allowing
+    /// for the arena wrapping.
+    fn direction_to(&self, other: Pos, size: Size) -> Option<Direction> {
+        Direction::ALL
+            .into_iter()
+            .find(|&direction| self.wrapped_add(direction.into(), size) == other)
+    }
+
+    /// Manhattan distance to `other`, allowing for the arena wrapping.
+    fn wrapped_distance(&self, other: Pos, size: Size) -> u16 {
+        let axis = |a: u16, b: u16, len: u16| {
+            let d = a.abs_diff(b);
+            d.min(len - d)
+        };
+        axis(self.x, other.x, size.width) + axis(self.y, other.y, size.height)
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct Snek {
     head: Pos,
     body: VecDeque<Pos>,
@@ -152,22 +480,28 @@ impl Default for Snek {
 
 impl Snek {
     fn new(size: Size, initial_length: u16) -> Self {
-        let mid_x = size.width / 2;
-        let mid_y = size.height / 2;
-        let half_length = initial_length / 2;
-        let length_rounding = initial_length % 2;
+        Self::spawn_at(Self::centre_spawn(size, initial_length), initial_length)
+    }
 
+    /// Where the head starts so that a fresh snek sits centred in the arena.
+    fn centre_spawn(size: Size, initial_length: u16) -> Pos {
+        Pos {
+            x: size.width / 2 + initial_length / 2,
+            y: size.height / 2,
+            floor: 0,
+        }
+    }
+
+    /// A snek heading east from `head`, with its body trailing to the west.
+    fn spawn_at(head: Pos, initial_length: u16) -> Self {
         let mut body = VecDeque::new();
         for i in 0..(initial_length) {
             body.push_back(Pos {
-                x: mid_x - half_length - length_rounding + i,
-                y: mid_y,
+                x: head.x - initial_length + i,
+                y: head.y,
+                floor: head.floor,
             });
         }
-        let head = Pos {
-            x: mid_x + half_length,
-            y: mid_y,
-        };
 
         Snek {
             head,
@@ -209,8 +543,47 @@ impl Snek {
         self.head == pos.into()
     }
 
-    fn snack(&mut self, morsel: Morsel) {
-        self.pending_growth += morsel.growth_value;
+    /// Eat `morsel`, growing `per_point` segments for each point it's worth.
+    fn snack(&mut self, morsel: Morsel, per_point: u16) {
+        self.pending_growth += morsel.growth_value * per_point;
+    }
+
+    /// Drop up to `count` segments off the end of the tail, always keeping
+    /// at least one, and return where they were.
+    fn shed_tail(&mut self, count: usize) -> Vec<Pos> {
+        let count = count.min(self.body.len().saturating_sub(1));
+        self.body.drain(..count).collect()
+    }
+
+    /// Lose a segment off the tail, or growth still to come, returning false
+    /// if there's nothing left but the head to lose.
+    fn waste_away(&mut self) -> bool {
+        if self.pending_growth > 0 {
+            self.pending_growth -= 1;
+            true
+        } else {
+            self.body.pop_front().is_some()
+        }
+    }
+
+    /// Number of cells the snek covers, head included.
+    fn length(&self) -> u16 {
+        self.body.len() as u16 + 1
+    }
+
+    /// The snek's mirror image across the middle of an arena of `size`,
+    /// heading the opposite way across it.
+    fn mirrored(&self, size: Size) -> Snek {
+        let flip = |pos: Pos| Pos {
+            x: size.width - 1 - pos.x,
+            ..pos
+        };
+        Snek {
+            head: flip(self.head),
+            body: self.body.iter().map(|&pos| flip(pos)).collect(),
+            direction: self.direction.mirrored(Mirror::LeftRight),
+            pending_growth: self.pending_growth,
+        }
     }
 }
 
@@ -218,6 +591,31 @@ impl Snek {
 struct Morsel {
     pos: Pos,
     growth_value: u16,
+    kind: MorselKind,
+    /// Ticks since it was put out, for rotting
+    age: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum MorselKind {
+    #[default]
+    Normal,
+    /// Eating it makes the snek drop the end of its tail as a hazard
+    Brittle,
+    /// Eating it is fatal
+    Poison,
+    /// Eating it clears the weeds around the snek's head
+    Scythe,
+    /// Eating it takes its value off the score, and the snek doesn't grow
+    Trap,
+    /// Eating it gives a charge to rewind the game with
+    Rewind,
+    /// Eating it shields the snek from its next crash
+    Shield,
+    /// Eating it lets the snek pass through walls and obstacles for a while
+    Phase,
+    /// Scattered for a bonus round, and cleared away when it ends
+    Bonus,
 }
 
 impl From<Morsel> for Pos {
@@ -226,675 +624,9246 @@ impl From<Morsel> for Pos {
     }
 }
 
-#[derive(Debug)]
-enum StepResult {
-    Ongoing,     // Normal movement, no special events
-    Nommed(u16), // Ate food, with score increment
-    Collision,   // Hit self, game over
-}
-
-#[derive(Debug, Default)]
-struct SnekHaus {
-    size: Size,
+/// A computer-controlled snek competing for the same morsels.
+#[derive(Clone, Debug)]
+struct Rival {
     snek: Snek,
-    moresels: Vec<Morsel>,
     score: u16,
+    alive: bool,
 }
 
-impl SnekHaus {
-    fn new(size: Size, initial_length: u16) -> Self {
-        SnekHaus {
-            size,
-            snek: Snek::new(size, initial_length),
-            moresels: Vec::new(),
-            score: 0,
-        }
-    }
+/// Two linked teleporters: entering either one sends the head out of the other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PortalPair {
+    a: Pos,
+    b: Pos,
+}
 
-    fn check_nomming(&mut self) -> Option<u16> {
-        // Find any morsel at the head position and remove it
-        if let Some(index) = self.moresels.iter().position(|m| m.pos == self.snek.head) {
-            let morsel = self.moresels.swap_remove(index);
-            let score_increase = morsel.growth_value;
-            self.score += score_increase; // assuming score increases by growth value
-            self.snek.snack(morsel);
-            Some(score_increase)
+impl PortalPair {
+    fn partner(&self, pos: Pos) -> Option<Pos> {
+        if pos == self.a {
+            Some(self.b)
+        } else if pos == self.b {
+            Some(self.a)
         } else {
             None
         }
     }
+}
 
-    fn check_snek_hit_itself(&self) -> bool {
-        self.snek.would_collide_with_body(self.snek.head)
-    }
-
-    fn move_snek(&mut self) {
-        self.snek.slither(self.size);
-    }
+/// A tile that sends the head somewhere random, but safe.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Wormhole {
+    pos: Pos,
+    /// Where it sends the head this tick, picked afresh every tick, or `None`
+    /// if there's nowhere safe
+    exit: Option<Pos>,
+}
 
-    fn slither_on(&mut self) -> StepResult {
-        self.move_snek();
+/// How an obstacle works its way along its route.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Patrol {
+    /// Out to the end of the route and back again
+    BackAndForth,
+    /// Round and round, from the last cell straight back to the first
+    Loop,
+}
 
-        if self.check_snek_hit_itself() {
-            return StepResult::Collision;
-        }
+/// A lethal block that moves one cell along its route every tick.
+#[derive(Clone, Debug, PartialEq)]
+struct Obstacle {
+    route: Vec<Pos>,
+    patrol: Patrol,
+    index: usize,
+    /// Whether a back-and-forth patrol is on its way back to the start
+    returning: bool,
+}
 
-        if let Some(score_increase) = self.check_nomming() {
-            return StepResult::Nommed(score_increase);
+impl Obstacle {
+    fn new(route: Vec<Pos>, patrol: Patrol) -> Self {
+        assert!(!route.is_empty(), "Obstacle needs somewhere to patrol");
+        Obstacle {
+            route,
+            patrol,
+            index: 0,
+            returning: false,
         }
-
-        StepResult::Ongoing
     }
 
-    fn change_direction(&mut self, new_direction: Direction) {
-        self.snek.change_direction(new_direction);
-    }
-
-    fn place_morsel(&mut self, morsel: Morsel) {
-        assert!(
-            !self.snek.would_collide_with_body(morsel)
-                && !self.snek.would_collide_with_head(morsel),
-            "Attempted to place morsel at invalid position"
-        );
-        self.moresels.push(morsel);
+    fn pos(&self) -> Pos {
+        self.route[self.index]
     }
 
-    fn new_morsel(&self, rng: &mut impl Rng) -> Morsel {
-        loop {
-            let pos = Pos {
-                x: rng.gen_range(0..self.size.width),
-                y: rng.gen_range(0..self.size.height),
-            };
-
-            if !self.snek.would_collide_with_body(pos) && pos != self.snek.head {
-                return Morsel {
-                    pos,
-                    growth_value: rng.gen_range(1..=5),
+    /// Where along the route it goes next, and whether it's then heading back.
+    fn next(&self) -> (usize, bool) {
+        let last = self.route.len() - 1;
+        match self.patrol {
+            Patrol::Loop => ((self.index + 1) % self.route.len(), false),
+            Patrol::BackAndForth => {
+                let returning = match self.index {
+                    0 => false,
+                    i if i == last => true,
+                    _ => self.returning,
                 };
+                let index = if returning {
+                    self.index.saturating_sub(1)
+                } else {
+                    (self.index + 1).min(last)
+                };
+                (index, returning)
             }
         }
     }
+
+    fn next_pos(&self) -> Pos {
+        self.route[self.next().0]
+    }
+
+    fn advance(&mut self) {
+        (self.index, self.returning) = self.next();
+    }
 }
 
 #[derive(Debug)]
-enum GameState {
-    ReadyToStart,
-    Playing(SnekHaus),
-    Paused(SnekHaus),
-    GameOver { haus: SnekHaus, final_score: u16 },
-    Exit,
+enum StepResult {
+    Ongoing,          // Normal movement, no special events
+    Nommed(u16),      // Ate food, with score increment
+    LostLife,         // Died, but respawned with a life to spare
+    Died(DeathCause), // Out of lives, game over
+    Victory,          // Reached the target, game won
 }
 
-struct Game {
-    state: GameState,
-    high_score: u16,
-    arena_size: Option<Size>,
+/// What finally ended a run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DeathCause {
+    BitSelf,
+    HitWall,
+    HitRival,
+    LeftSafeZone,
+    Starved,
+    WastedAway,
+    HitObstacle,
+    HitHazard,
+    TimeUp,
+    Poisoned,
+    HitWeed,
+    Caught,
+    HitTwin,
+    OutOfMoves,
 }
 
-impl Game {
-    fn new() -> Self {
-        Game {
-            state: GameState::ReadyToStart,
-            high_score: Self::load_high_score(),
-            arena_size: None,
+impl DeathCause {
+    fn describe(&self) -> &'static str {
+        match self {
+            DeathCause::BitSelf => "You bit yourself",
+            DeathCause::HitWall => "You hit a wall",
+            DeathCause::HitRival => "You ran into the rival",
+            DeathCause::LeftSafeZone => "You were caught outside the safe zone",
+            DeathCause::Starved => "You starved",
+            DeathCause::WastedAway => "You wasted away to nothing",
+            DeathCause::HitObstacle => "You ran into a moving obstacle",
+            DeathCause::HitHazard => "You ran into a dropped tail",
+            DeathCause::TimeUp => "Time's up!",
+            DeathCause::Poisoned => "You ate a poison morsel",
+            DeathCause::HitWeed => "You got tangled in the weeds",
+            DeathCause::Caught => "You were caught by a predator",
+            DeathCause::HitTwin => "Your sneks ran into each other",
+            DeathCause::OutOfMoves => "You ran out of moves",
         }
     }
 
-    fn load_high_score() -> u16 {
-        match fs::read_to_string(HIGH_SCORE_FILE).map(|s| s.trim().parse().unwrap_or(0)) {
-            Ok(score) => score,
-            Err(e) => {
-                error!("Error loading high score: {}", e);
-                0
-            }
-        }
+    /// Whether a shield saves the snek from this, running into itself or
+    /// something solid.
+    fn shieldable(&self) -> bool {
+        matches!(
+            self,
+            DeathCause::BitSelf | DeathCause::HitWall | DeathCause::HitObstacle
+        )
     }
+}
 
-    fn save_high_score(&self) {
-        if let Err(e) = fs::write(HIGH_SCORE_FILE, self.high_score.to_string()) {
-            error!("Error saving high score: {}", e);
-        }
+/// A combo milestone being shown to the player.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Announcement {
+    /// Index into `COMBO_ANNOUNCEMENTS`, bigger milestones getting flashier text
+    tier: usize,
+    ticks_left: u32,
+}
+
+impl Announcement {
+    fn text(&self) -> &'static str {
+        COMBO_ANNOUNCEMENTS[self.tier].1
     }
 
-    fn update_high_score(&mut self, score: u16) {
-        if score > self.high_score {
-            self.high_score = score;
-            self.save_high_score();
+    fn style(&self) -> Style {
+        match self.tier {
+            0 => Style::default().fg(Color::Yellow),
+            1 => Style::default()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::BOLD),
+            // The top milestone cycles through colours as it fades
+            _ => Style::default()
+                .fg(ANNOUNCEMENT_COLOURS[self.ticks_left as usize % ANNOUNCEMENT_COLOURS.len()])
+                .add_modifier(Modifier::BOLD | Modifier::ITALIC),
         }
     }
+}
 
-    fn render(&mut self, frame: &mut Frame) {
-        let score_text = match &self.state {
-            GameState::Playing(haus) | GameState::Paused(haus) => {
-                format!(
-                    "SNEK    High Score: {}    Score: {}",
-                    self.high_score, haus.score
-                )
-            }
-            _ => {
-                format!("SNEK    High Score: {}", self.high_score)
-            }
-        };
+/// A rule changed partway through a game of chaos.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mutation {
+    Edges(EdgePolicy),
+    Speed { faster: bool },
+    Mirror(bool),
+    DoubleValue(bool),
+}
 
-        let size = frame.area();
-        let layout = Layout::default()
-            .direction(layout::Direction::Vertical)
-            .constraints([
-                Constraint::Length(3), // Title + High Score
-                Constraint::Min(0),    // Game area
-            ])
-            .split(size);
+impl Mutation {
+    fn text(&self) -> &'static str {
+        match self {
+            Mutation::Edges(EdgePolicy::Wrap) => "The edges wrap round again",
+            Mutation::Edges(_) => "The edges are walls!",
+            Mutation::Speed { faster: true } => "Faster!",
+            Mutation::Speed { faster: false } => "Slower...",
+            Mutation::Mirror(true) => "Controls mirrored!",
+            Mutation::Mirror(false) => "Controls back to normal",
+            Mutation::DoubleValue(true) => "Morsels worth double!",
+            Mutation::DoubleValue(false) => "Morsels back to normal value",
+        }
+    }
+}
 
-        // Render title area with high score
-        frame.render_widget(
-            Paragraph::new(score_text)
-                .alignment(Alignment::Left)
-                .block(Block::default().borders(Borders::ALL)),
-            layout[0],
-        );
+/// The rules a `SnekHaus` is played under.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Rules {
+    /// Number of lives, or `None` for a single life
+    lives: Option<u8>,
+    /// Ticks between each ring of the arena becoming lethal, or `None` for a fixed arena
+    shrink_interval: Option<u32>,
+    speed: SpeedCurve,
+    /// Number of linked teleporter pairs to scatter around the arena
+    portal_pairs: u8,
+    /// Reaching this ends the game in victory, otherwise play goes on until collision
+    target: Option<VictoryTarget>,
+    /// Number of computer-controlled sneks competing for morsels
+    rivals: u8,
+    /// Whether it's a battle royale: crashed rivals turn into morsels, and
+    /// the last snek standing wins
+    royale: bool,
+    /// Ticks the snek can go without eating before it starves, or `None` to never starve
+    hunger: Option<u32>,
+    /// Ticks the snek can go without eating before losing a tail segment, and
+    /// then another each time as many ticks pass, or `None` to never waste away
+    famine: Option<u32>,
+    /// Ticks allowed between noms for a combo bonus, or `None` for no combos
+    combo_window: Option<u32>,
+    edges: EdgePolicy,
+    /// Number of moving obstacles patrolling the arena
+    obstacles: u8,
+    /// Whether play breaks every so often for a bonus round
+    bonus_rounds: bool,
+    /// How long the game lasts, or `None` to play until the snek dies
+    time_limit: Option<Duration>,
+    /// One in this many morsels is brittle, or `None` for no brittle morsels
+    brittle_odds: Option<u32>,
+    /// One in this many morsels is a rewind pickup, or `None` for none
+    rewind_odds: Option<u32>,
+    /// One in this many morsels is a shield, or `None` for none
+    shield_odds: Option<u32>,
+    /// Whether the player steers a second snek too, a mirror image of the
+    /// first
+    twin: bool,
+    /// Moves allowed to eat every morsel, with the snek only moving when the
+    /// player does, or `None` for a snek that never stops
+    moves: Option<u16>,
+    scoring: Scoring,
+    /// Time between new hazards appearing, or `None` for an arena that stays the same
+    hazard_interval: Option<Duration>,
+    /// Ticks between weeds spreading, or `None` for no weeds
+    weed_interval: Option<u32>,
+    /// How likely a morsel is to be worth each value, or `None` for
+    /// `EVEN_MORSEL_WEIGHTS`
+    morsel_weights: Option<MorselWeights>,
+    /// Segments grown for each point a morsel is worth, or `None` for one
+    growth: Option<u16>,
+    /// Whether the game carries on regardless when space is pressed
+    no_pause: bool,
+    /// Whether eating fills a boost meter, spent on bursts of double speed
+    boost: bool,
+    /// Time between one of these rules changing at random, or `None` for rules that stay put
+    chaos_interval: Option<Duration>,
+    /// Whether morsels score double their value
+    double_value: bool,
+    /// Whether the snek leaves a trail behind it, never shrinking so every
+    /// cell it's been through stays lethal, with no morsels to eat
+    trail: bool,
+    mutators: Mutators,
+}
 
-        // Game area - different for each state
-        match &self.state {
-            GameState::ReadyToStart => {
-                let block = Block::default().borders(Borders::ALL);
-                let inner_area = block.inner(layout[1]);
-                self.arena_size = Some(Size {
-                    width: inner_area.width,
-                    height: inner_area.height,
-                });
-                frame.render_widget(
-                    Paragraph::new("Press SPACE to start")
-                        .alignment(Alignment::Center)
-                        .block(block),
-                    layout[1],
-                );
-            }
-            GameState::Playing(haus) => {
-                let block = Block::default().title("Playing").borders(Borders::ALL);
-                let inner_area = block.inner(layout[1]);
+impl Rules {
+    fn morsel_weights(&self) -> &[u32] {
+        self.morsel_weights
+            .as_ref()
+            .unwrap_or(&EVEN_MORSEL_WEIGHTS)
+            .as_slice()
+    }
 
-                frame.render_widget(block, layout[1]);
-                frame.render_widget(haus, inner_area);
-            }
-            GameState::Paused(haus) => {
-                let block = Block::default()
-                    .title("Paused. Press SPACE to continue")
-                    .borders(Borders::ALL);
-                let inner_area = block.inner(layout[1]);
+    /// The most a morsel can be worth.
+    fn max_morsel_value(&self) -> u16 {
+        self.morsel_weights().len().max(1) as u16
+    }
 
-                frame.render_widget(block, layout[1]);
-                frame.render_widget(haus, inner_area);
-            }
-            GameState::GameOver { final_score, haus } => {
-                let block = Block::default().borders(Borders::ALL);
-                let inner_area = block.inner(layout[1]);
+    /// What a morsel is worth on average.
+    fn mean_morsel_value(&self) -> f64 {
+        let weights = self.morsel_weights();
+        let total: u32 = weights.iter().sum();
+        let sum: u32 = (1..)
+            .zip(weights)
+            .map(|(value, weight)| value * weight)
+            .sum();
+        sum as f64 / total.max(1) as f64
+    }
 
-                frame.render_widget(block, layout[1]);
-                frame.render_widget(haus, inner_area);
-                frame.render_widget(
-                    Paragraph::new(format!(
-                        "GAME OVER\nFinal Score: {}\nPress SPACE to play again",
-                        final_score
-                    ))
-                    .alignment(Alignment::Center),
-                    inner_area,
-                );
+    /// A morsel value picked by the weights.
+    fn roll_morsel_value(&self, rng: &mut impl Rng) -> u16 {
+        let weights = self.morsel_weights();
+        let total: u32 = weights.iter().sum();
+        if total == 0 {
+            return 1;
+        }
+        let mut roll = rng.gen_range(0..total);
+        for (value, &weight) in (1..).zip(weights) {
+            if roll < weight {
+                return value;
             }
-            GameState::Exit => {}
+            roll -= weight;
         }
+        weights.len() as u16
     }
 
-    fn handle_input(&mut self, key: event::KeyEvent) {
-        use event::KeyCode;
+    /// These rules with `mutators` added, edges and all.
+    fn with_mutators(self, mutators: Mutators) -> Rules {
+        Rules {
+            mutators,
+            edges: mutators.edges.unwrap_or(self.edges),
+            ..self
+        }
+    }
 
-        let new_state = match &mut self.state {
-            GameState::ReadyToStart => match key.code {
-                KeyCode::Char('q') | KeyCode::Esc => Some(GameState::Exit),
-                KeyCode::Char(' ') => {
-                    let size = self.arena_size.expect("Arena size not initialized");
-                    let mut haus = SnekHaus::new(size, 3);
+    /// These rules played at `speed`, growing `growth` segments a point, and
+    /// with `morsel_weights` in place of the mode's own, if given.
+    fn tuned(
+        mut self,
+        speed: settings::Speed,
+        growth: u16,
+        morsel_weights: Option<MorselWeights>,
+    ) -> Rules {
+        self.speed.base_millis = speed.scale(self.speed.base_millis);
+        self.speed.min_millis = speed.scale(self.speed.min_millis);
+        self.growth = Some(growth);
+        self.morsel_weights = morsel_weights.or(self.morsel_weights);
+        self
+    }
+}
 
-                    let mut rng = rand::thread_rng();
-                    let morsel = haus.new_morsel(&mut rng);
-                    haus.place_morsel(morsel);
+/// How likely a morsel is to be worth 1, 2, 3 and so on, up to the most it
+/// can be worth. Written as the weights separated by commas, like `4,2,1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MorselWeights {
+    weights: [u32; MAX_MORSEL_VALUE],
+    len: usize,
+}
 
-                    Some(GameState::Playing(haus))
-                }
-                _ => None,
-            },
-            GameState::Playing(haus) => match key.code {
-                KeyCode::Char('q') => {
-                    let final_score = haus.score;
-                    Some(GameState::GameOver {
-                        haus: std::mem::take(haus),
-                        final_score,
-                    })
-                }
-                KeyCode::Esc => Some(GameState::Exit),
-                KeyCode::Char(' ') => Some(GameState::Paused(std::mem::take(haus))),
-                KeyCode::Up | KeyCode::Char('w') => {
-                    haus.change_direction(Direction::North);
-                    None
-                }
-                KeyCode::Down | KeyCode::Char('s') => {
-                    haus.change_direction(Direction::South);
-                    None
-                }
-                KeyCode::Left | KeyCode::Char('a') => {
-                    haus.change_direction(Direction::West);
-                    None
-                }
-                KeyCode::Right | KeyCode::Char('d') => {
-                    haus.change_direction(Direction::East);
-                    None
-                }
-                _ => None,
-            },
-            GameState::Paused(haus) => match key.code {
-                KeyCode::Char('q') => {
-                    let final_score = haus.score;
-                    Some(GameState::GameOver {
-                        haus: std::mem::take(haus),
-                        final_score,
-                    })
-                }
-                KeyCode::Esc => Some(GameState::Exit),
-                KeyCode::Char(' ') => Some(GameState::Playing(std::mem::take(haus))),
-                _ => None,
-            },
-            GameState::GameOver { .. } => match key.code {
-                KeyCode::Esc => Some(GameState::Exit),
-                KeyCode::Char(' ') | KeyCode::Char('q') => Some(GameState::ReadyToStart),
-                _ => None,
-            },
-            _ => None,
-        };
+impl MorselWeights {
+    /// Weights for values from 1 up to `weights.len()`, at most
+    /// `MAX_MORSEL_VALUE` of them.
+    const fn new(weights: &[u32]) -> MorselWeights {
+        assert!(!weights.is_empty() && weights.len() <= MAX_MORSEL_VALUE);
+        let mut padded = [0; MAX_MORSEL_VALUE];
+        let mut i = 0;
+        while i < weights.len() {
+            padded[i] = weights[i];
+            i += 1;
+        }
+        MorselWeights {
+            weights: padded,
+            len: weights.len(),
+        }
+    }
 
-        if let Some(new_state) = new_state {
-            self.state = new_state;
+    pub fn parse(text: &str) -> Result<MorselWeights, String> {
+        let weights = text
+            .split(',')
+            .map(|weight| {
+                weight
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("'{}' isn't a weight", weight.trim()))
+            })
+            .collect::<Result<Vec<u32>, String>>()?;
+        if weights.len() > MAX_MORSEL_VALUE {
+            return Err(format!(
+                "expected at most {} weights, not {}",
+                MAX_MORSEL_VALUE,
+                weights.len()
+            ));
         }
+        if weights.iter().all(|&weight| weight == 0) {
+            return Err("at least one weight should be more than 0".to_string());
+        }
+        Ok(MorselWeights::new(&weights))
     }
 
-    fn update(&mut self) {
-        match &mut self.state {
-            GameState::Playing(haus) => {
-                match haus.slither_on() {
-                    StepResult::Collision => {
-                        // Game over - save the haus and score
-                        let final_score = haus.score;
-                        let haus = std::mem::take(haus);
-                        self.update_high_score(final_score);
-                        self.state = GameState::GameOver { haus, final_score };
-                    }
-                    StepResult::Nommed(_score) => {
-                        let mut rng = rand::thread_rng();
-                        let morsel = haus.new_morsel(&mut rng);
-                        haus.place_morsel(morsel);
+    fn as_slice(&self) -> &[u32] {
+        &self.weights[..self.len]
+    }
+}
+
+impl fmt::Display for MorselWeights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let weights: Vec<String> = self.as_slice().iter().map(u32::to_string).collect();
+        write!(f, "{}", weights.join(","))
+    }
+}
+
+/// Optional twists that can be added to any mode from the modifiers menu.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Mutators {
+    mirror: Mirror,
+    /// The snek turns on its own every so often
+    dizzy: bool,
+    /// The snek can move diagonally too
+    diagonal: bool,
+    /// Morsels lose value the longer they're left uneaten
+    rotting: bool,
+    /// Only the snek's head is drawn, apart from a flash after each nom
+    invisible: bool,
+    /// A few wormholes send the head to a random safe cell
+    wormholes: bool,
+    /// The odd morsel is a trap, taking its value off the score
+    traps: bool,
+    /// Edges to play with instead of the mode's own
+    edges: Option<EdgePolicy>,
+}
+
+impl Mutators {
+    /// The name and current setting of `mutator`, for the modifiers menu.
+    fn describe(&self, mutator: Mutator) -> (&'static str, &'static str) {
+        match mutator {
+            Mutator::Mirror => ("Mirrored controls", self.mirror.name()),
+            Mutator::Dizzy => ("Dizzy snek", if self.dizzy { "On" } else { "Off" }),
+            Mutator::Diagonal => ("Diagonal moves", if self.diagonal { "On" } else { "Off" }),
+            Mutator::Rotting => ("Rotting morsels", if self.rotting { "On" } else { "Off" }),
+            Mutator::Invisible => ("Invisible body", if self.invisible { "On" } else { "Off" }),
+            Mutator::Wormholes => ("Wormholes", if self.wormholes { "On" } else { "Off" }),
+            Mutator::Traps => ("Trap morsels", if self.traps { "On" } else { "Off" }),
+            Mutator::Edges => ("Edges", self.edges.map_or("Off", |edges| edges.name())),
+        }
+    }
+
+    /// Step the setting of `mutator` through its choices.
+    fn cycle(&mut self, mutator: Mutator, step: isize) {
+        match mutator {
+            Mutator::Mirror => self.mirror = cycle(&Mirror::ALL, self.mirror, step),
+            Mutator::Dizzy => self.dizzy = !self.dizzy,
+            Mutator::Diagonal => self.diagonal = !self.diagonal,
+            Mutator::Rotting => self.rotting = !self.rotting,
+            Mutator::Invisible => self.invisible = !self.invisible,
+            Mutator::Wormholes => self.wormholes = !self.wormholes,
+            Mutator::Traps => self.traps = !self.traps,
+            Mutator::Edges => self.edges = cycle(&EdgePolicy::CHOICES, self.edges, step),
+        }
+    }
+
+    /// The mutators packed into a number, for run codes: a bit for each of
+    /// the switches, then the mirror and edges settings as indexes into
+    /// their choices.
+    fn bits(&self) -> u16 {
+        let switches = [
+            self.dizzy,
+            self.diagonal,
+            self.rotting,
+            self.invisible,
+            self.wormholes,
+            self.traps,
+        ];
+        let mirror = Mirror::ALL
+            .iter()
+            .position(|&m| m == self.mirror)
+            .unwrap_or(0);
+        let edges = EdgePolicy::CHOICES
+            .iter()
+            .position(|&e| e == self.edges)
+            .unwrap_or(0);
+        let bits = (0..)
+            .zip(switches)
+            .fold(0, |bits, (bit, on)| bits | (on as u16) << bit);
+        bits | (mirror as u16) << 6 | (edges as u16) << 8
+    }
+
+    /// The mutators packed by `bits`, or `None` if `bits` isn't something it
+    /// could have packed.
+    fn from_bits(bits: u16) -> Option<Mutators> {
+        let on = |bit: u16| bits & (1 << bit) != 0;
+        if bits >> 11 != 0 {
+            return None;
+        }
+        Some(Mutators {
+            dizzy: on(0),
+            diagonal: on(1),
+            rotting: on(2),
+            invisible: on(3),
+            wormholes: on(4),
+            traps: on(5),
+            mirror: *Mirror::ALL.get((bits >> 6 & 0b11) as usize)?,
+            edges: *EdgePolicy::CHOICES.get((bits >> 8 & 0b111) as usize)?,
+        })
+    }
+
+    /// The names of the mutators switched on, for the start screen.
+    fn summary(&self) -> String {
+        let active: Vec<_> = Mutator::ALL
+            .into_iter()
+            .map(|mutator| self.describe(mutator))
+            .filter(|(_, value)| *value != "Off")
+            .map(|(name, _)| name)
+            .collect();
+        if active.is_empty() {
+            "None".to_string()
+        } else {
+            active.join(", ")
+        }
+    }
+}
+
+/// A row of the modifiers menu.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mutator {
+    Mirror,
+    Dizzy,
+    Diagonal,
+    Rotting,
+    Invisible,
+    Wormholes,
+    Traps,
+    Edges,
+}
+
+impl Mutator {
+    const ALL: [Mutator; 8] = [
+        Mutator::Mirror,
+        Mutator::Dizzy,
+        Mutator::Diagonal,
+        Mutator::Rotting,
+        Mutator::Invisible,
+        Mutator::Wormholes,
+        Mutator::Traps,
+        Mutator::Edges,
+    ];
+}
+
+/// Which controls swap over while they're mirrored.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Mirror {
+    #[default]
+    Off,
+    LeftRight,
+    All,
+}
+
+impl Mirror {
+    const ALL: [Mirror; 3] = [Mirror::Off, Mirror::LeftRight, Mirror::All];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Mirror::Off => "Off",
+            Mirror::LeftRight => "Left/Right",
+            Mirror::All => "All directions",
+        }
+    }
+}
+
+/// The choice `step` places along from `current` in `choices`, wrapping round.
+fn cycle<T: Copy + PartialEq>(choices: &[T], current: T, step: isize) -> T {
+    let index = choices.iter().position(|c| *c == current).unwrap_or(0) as isize;
+    choices[(index + step).rem_euclid(choices.len() as isize) as usize]
+}
+
+/// What the score counts.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Scoring {
+    /// Points for every morsel eaten
+    #[default]
+    Morsels,
+    /// Seconds survived
+    Survival,
+    /// Cells of the arena the snek has covered
+    Coverage,
+}
+
+/// What happens at the edges of the arena.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum EdgePolicy {
+    /// Slithering off one edge brings the snek back on the opposite one
+    #[default]
+    Wrap,
+    /// The edges are walls, apart from tunnel cells that still wrap
+    Walled,
+    /// The edges are walls all the way round
+    Solid,
+    /// Only the left and right edges wrap, the top and bottom are walls
+    WrapAcross,
+    /// Only the top and bottom edges wrap, the left and right are walls
+    WrapDown,
+}
+
+impl EdgePolicy {
+    /// Edges offered in the modifiers menu, `None` keeping the mode's own.
+    const CHOICES: [Option<EdgePolicy>; 6] = [
+        None,
+        Some(EdgePolicy::Wrap),
+        Some(EdgePolicy::WrapAcross),
+        Some(EdgePolicy::WrapDown),
+        Some(EdgePolicy::Walled),
+        Some(EdgePolicy::Solid),
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            EdgePolicy::Wrap => "Wrap all round",
+            EdgePolicy::Walled => "Walls with tunnels",
+            EdgePolicy::Solid => "Solid walls",
+            EdgePolicy::WrapAcross => "Wrap left/right",
+            EdgePolicy::WrapDown => "Wrap up/down",
+        }
+    }
+
+    /// Whether `pos` is on an edge that's walled off.
+    fn is_walled(&self, pos: Pos, size: Size) -> bool {
+        match self {
+            EdgePolicy::Wrap => false,
+            EdgePolicy::Walled | EdgePolicy::Solid => pos.is_on_edge(size),
+            EdgePolicy::WrapAcross => pos.y == 0 || pos.y + 1 == size.height,
+            EdgePolicy::WrapDown => pos.x == 0 || pos.x + 1 == size.width,
+        }
+    }
+}
+
+/// A goal that ends the game in victory once reached, checked every tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum VictoryTarget {
+    Score(u16),
+    Length(u16),
+    /// Seconds of play to survive
+    Survive(u16),
+}
+
+impl VictoryTarget {
+    /// Targets offered on the start screen, `None` meaning endless play.
+    const CHOICES: [Option<VictoryTarget>; 7] = [
+        None,
+        Some(VictoryTarget::Score(50)),
+        Some(VictoryTarget::Score(100)),
+        Some(VictoryTarget::Length(25)),
+        Some(VictoryTarget::Length(50)),
+        Some(VictoryTarget::Survive(60)),
+        Some(VictoryTarget::Survive(180)),
+    ];
+
+    fn cycle(target: Option<VictoryTarget>, step: isize) -> Option<VictoryTarget> {
+        cycle(&Self::CHOICES, target, step)
+    }
+
+    fn describe(target: Option<VictoryTarget>) -> String {
+        match target {
+            None => "Endless".to_string(),
+            Some(VictoryTarget::Score(goal)) => format!("Score {}", goal),
+            Some(VictoryTarget::Length(goal)) => format!("Length {}", goal),
+            Some(VictoryTarget::Survive(goal)) => format!("Survive {}s", goal),
+        }
+    }
+
+    /// How far along the target is, as `(current, goal)`.
+    fn progress(&self, haus: &SnekHaus) -> (u16, u16) {
+        match *self {
+            VictoryTarget::Score(goal) => (haus.score.min(goal), goal),
+            VictoryTarget::Length(goal) => (haus.snek.length().min(goal), goal),
+            VictoryTarget::Survive(goal) => {
+                let seconds = haus.clock.as_secs().min(goal as u64) as u16;
+                (seconds, goal)
+            }
+        }
+    }
+
+    fn reached(&self, haus: &SnekHaus) -> bool {
+        let (current, goal) = self.progress(haus);
+        current >= goal
+    }
+}
+
+/// How the tick rate changes as the score climbs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct SpeedCurve {
+    /// Tick length at a score of zero
+    base_millis: u64,
+    /// Milliseconds shaved off the tick for every 10 points scored
+    step_millis: u64,
+    /// The tick never gets shorter than this
+    min_millis: u64,
+}
+
+impl Default for SpeedCurve {
+    fn default() -> Self {
+        SpeedCurve {
+            base_millis: 150,
+            step_millis: 0,
+            min_millis: 50,
+        }
+    }
+}
+
+impl SpeedCurve {
+    fn tick_rate(&self, score: u16) -> Duration {
+        let speedup = self.step_millis * (score / 10) as u64;
+        let millis = self
+            .base_millis
+            .saturating_sub(speedup)
+            .max(self.min_millis);
+        Duration::from_millis(millis)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum GameMode {
+    #[default]
+    Classic,
+    Lives,
+    Shrinking,
+    Portals,
+    Rival,
+    Starvation,
+    Famine,
+    Tunnels,
+    Obstacles,
+    Brittle,
+    TimeAttack,
+    Survival,
+    Weeds,
+    Chaos,
+    Hardcore,
+    Daily,
+    Campaign,
+    Sandbox,
+    Tron,
+    Twin,
+    Puzzle,
+    Royale,
+}
+
+impl GameMode {
+    const ALL: [GameMode; 22] = [
+        GameMode::Classic,
+        GameMode::Lives,
+        GameMode::Shrinking,
+        GameMode::Portals,
+        GameMode::Rival,
+        GameMode::Starvation,
+        GameMode::Famine,
+        GameMode::Tunnels,
+        GameMode::Obstacles,
+        GameMode::Brittle,
+        GameMode::TimeAttack,
+        GameMode::Survival,
+        GameMode::Weeds,
+        GameMode::Chaos,
+        GameMode::Hardcore,
+        GameMode::Daily,
+        GameMode::Campaign,
+        GameMode::Sandbox,
+        GameMode::Tron,
+        GameMode::Twin,
+        GameMode::Puzzle,
+        GameMode::Royale,
+    ];
+
+    fn slug(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "classic",
+            GameMode::Lives => "lives",
+            GameMode::Shrinking => "shrinking",
+            GameMode::Portals => "portals",
+            GameMode::Rival => "rival",
+            GameMode::Starvation => "starvation",
+            GameMode::Famine => "famine",
+            GameMode::Tunnels => "tunnels",
+            GameMode::Obstacles => "obstacles",
+            GameMode::Brittle => "brittle",
+            GameMode::TimeAttack => "time_attack",
+            GameMode::Survival => "survival",
+            GameMode::Weeds => "weeds",
+            GameMode::Chaos => "chaos",
+            GameMode::Hardcore => "hardcore",
+            GameMode::Daily => "daily",
+            GameMode::Campaign => "campaign",
+            GameMode::Sandbox => "sandbox",
+            GameMode::Tron => "tron",
+            GameMode::Twin => "twin",
+            GameMode::Puzzle => "puzzle",
+            GameMode::Royale => "royale",
+        }
+    }
+
+    fn from_slug(slug: &str) -> Option<GameMode> {
+        Self::ALL.into_iter().find(|mode| mode.slug() == slug)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Lives => "Three Lives",
+            GameMode::Shrinking => "Shrinking Arena",
+            GameMode::Portals => "Portals",
+            GameMode::Rival => "Rival Snek",
+            GameMode::Starvation => "Starvation",
+            GameMode::Famine => "Famine",
+            GameMode::Tunnels => "Tunnels",
+            GameMode::Obstacles => "Moving Obstacles",
+            GameMode::Brittle => "Brittle Tail",
+            GameMode::TimeAttack => "Time Attack",
+            GameMode::Survival => "Survival",
+            GameMode::Weeds => "Weeds",
+            GameMode::Chaos => "Chaos",
+            GameMode::Hardcore => "Hardcore",
+            GameMode::Daily => "Daily Challenge",
+            GameMode::Campaign => "Campaign",
+            GameMode::Sandbox => "Practice Sandbox",
+            GameMode::Tron => "Tron Trail",
+            GameMode::Twin => "Twin Sneks",
+            GameMode::Puzzle => "Puzzles",
+            GameMode::Royale => "Battle Royale",
+        }
+    }
+
+    /// Where the high score is kept. Modes that play too differently to
+    /// compare with the rest keep their own.
+    fn high_score_file(&self) -> String {
+        match self {
+            GameMode::TimeAttack
+            | GameMode::Survival
+            | GameMode::Chaos
+            | GameMode::Hardcore
+            | GameMode::Campaign
+            | GameMode::Tron
+            | GameMode::Twin
+            | GameMode::Puzzle
+            | GameMode::Royale => {
+                format!(".snekrs_high_score_{}.txt", self.slug())
+            }
+            // A fresh daily best every day
+            GameMode::Daily => {
+                format!(
+                    ".snekrs_high_score_daily_{}.txt",
+                    daily::date(daily::today())
+                )
+            }
+            _ => HIGH_SCORE_FILE.to_string(),
+        }
+    }
+
+    /// Where the best run's path is kept, next to its high score.
+    fn ghost_file(&self) -> String {
+        self.high_score_file().replacen("high_score", "ghost", 1)
+    }
+
+    fn cycle(&self, step: isize) -> GameMode {
+        cycle(&Self::ALL, *self, step)
+    }
+
+    fn rules(&self) -> Rules {
+        let rules = match self {
+            GameMode::Classic => Rules::default(),
+            GameMode::Lives => Rules {
+                lives: Some(3),
+                ..Rules::default()
+            },
+            GameMode::Shrinking => Rules {
+                shrink_interval: Some(50),
+                ..Rules::default()
+            },
+            GameMode::Portals => Rules {
+                portal_pairs: 2,
+                ..Rules::default()
+            },
+            GameMode::Rival => Rules {
+                rivals: 1,
+                ..Rules::default()
+            },
+            GameMode::Starvation => Rules {
+                hunger: Some(60),
+                ..Rules::default()
+            },
+            GameMode::Famine => Rules {
+                famine: Some(25),
+                ..Rules::default()
+            },
+            GameMode::Tunnels => Rules {
+                edges: EdgePolicy::Walled,
+                ..Rules::default()
+            },
+            GameMode::Obstacles => Rules {
+                obstacles: 3,
+                ..Rules::default()
+            },
+            GameMode::Brittle => Rules {
+                brittle_odds: Some(4),
+                ..Rules::default()
+            },
+            GameMode::TimeAttack => Rules {
+                time_limit: Some(Duration::from_secs(120)),
+                // Big morsels keep the scores up against the clock
+                morsel_weights: Some(MorselWeights::new(&[1, 1, 2, 3, 3])),
+                ..Rules::default()
+            },
+            GameMode::Survival => Rules {
+                scoring: Scoring::Survival,
+                hazard_interval: Some(Duration::from_secs(30)),
+                ..Rules::default()
+            },
+            GameMode::Weeds => Rules {
+                weed_interval: Some(10),
+                ..Rules::default()
+            },
+            GameMode::Chaos => Rules {
+                chaos_interval: Some(CHAOS_INTERVAL),
+                ..Rules::default()
+            },
+            GameMode::Hardcore => Rules {
+                speed: SpeedCurve {
+                    base_millis: 75,
+                    min_millis: 25,
+                    ..SpeedCurve::default()
+                },
+                edges: EdgePolicy::Solid,
+                morsel_weights: Some(MorselWeights::new(&[1])),
+                no_pause: true,
+                ..Rules::default()
+            },
+            GameMode::Daily => Rules {
+                portal_pairs: 2,
+                obstacles: 2,
+                ..Rules::default()
+            },
+            // Each stage sets its own speed, obstacles and target on top
+            GameMode::Campaign => Rules::default(),
+            // The player sets the scene themselves
+            GameMode::Sandbox => Rules::default(),
+            GameMode::Tron => Rules {
+                scoring: Scoring::Coverage,
+                trail: true,
+                ..Rules::default()
+            },
+            GameMode::Twin => Rules {
+                twin: true,
+                ..Rules::default()
+            },
+            // Each puzzle sets its own board and move limit
+            GameMode::Puzzle => Rules::default(),
+            GameMode::Royale => Rules {
+                royale: true,
+                ..Rules::default()
+            },
+        };
+        if !self.has_extras() {
+            return rules;
+        }
+        // The rest reward quick chains of noms, have bonus rounds and a boost
+        // meter, and offer the odd rewind and shield
+        Rules {
+            combo_window: Some(COMBO_WINDOW),
+            // Bonus rounds would let the snek pass through its own trail
+            bonus_rounds: !rules.trail,
+            rewind_odds: Some(REWIND_ODDS),
+            shield_odds: Some(SHIELD_ODDS),
+            boost: true,
+            ..rules
+        }
+    }
+
+    /// Whether combos, bonus rounds, boost, rewinds and shields are added to the
+    /// mode's own rules. Hardcore gives no second chances, the daily challenge
+    /// is the same for everyone, and puzzles and the sandbox are played by
+    /// their own rules.
+    fn has_extras(&self) -> bool {
+        !matches!(
+            self,
+            GameMode::Hardcore | GameMode::Daily | GameMode::Puzzle | GameMode::Sandbox
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct SnekHaus {
+    size: Size,
+    snek: Snek,
+    moresels: Vec<Morsel>,
+    score: u16,
+    rules: Rules,
+    initial_length: u16,
+    lives: u8,
+    ticks: u32,
+    /// Number of outer rings that have become lethal
+    shrunk_by: u16,
+    portals: Vec<PortalPair>,
+    wormholes: Vec<Wormhole>,
+    walls: HashSet<Pos>,
+    /// Number of stacked floors, only ever more than one in levels with stairs
+    floors: u8,
+    /// Stairs linking the same cell on adjacent floors
+    stairs: Vec<PortalPair>,
+    /// Edge cells left open when the edges are walled
+    tunnels: HashSet<Pos>,
+    /// Slippery cells where sneks can't turn
+    ice: HashSet<Pos>,
+    /// Speed pads, and which way they push the snek
+    pads: HashMap<Pos, Direction>,
+    /// Where the snek's head starts, and restarts after losing a life
+    spawn: Pos,
+    rivals: Vec<Rival>,
+    obstacles: Vec<Obstacle>,
+    /// Tail segments dropped by eating brittle morsels, lethal for good
+    hazards: HashSet<Pos>,
+    /// Lethal weeds, spreading every weed interval
+    weeds: HashSet<Pos>,
+    /// Collisions are switched off, as they are in a bonus round
+    ghost: bool,
+    /// How the player's body is coloured
+    skin: Skin,
+    glyphs: Glyphs,
+    background: Background,
+    theme: Theme,
+    /// Tint the head when something lethal is coming up
+    danger_warning: bool,
+    /// Tint the cells the head could move into next that are lethal
+    danger_cells: bool,
+    /// Play time so far, counted in ticks at the speed they were played
+    clock: Duration,
+    /// Number of hazards added so far by the rules' hazard interval
+    escalations: u32,
+    /// The mode's escalation table, applied as the score climbs
+    escalation: Vec<escalation::Step>,
+    /// Number of steps of the escalation table reached so far
+    escalated: usize,
+    /// Predators hunting the snek's head
+    predators: Vec<Pos>,
+    /// How far from the head can be seen, once fog has rolled in
+    fog: Option<u16>,
+    /// Ticks left before starving, when the rules have hunger
+    hunger: u32,
+    /// Ticks left before losing a tail segment, when the rules have famine
+    famine: u32,
+    /// What ended the run, once it's over
+    death: Option<DeathCause>,
+    /// Noms in the current chain, each within the combo window of the last
+    combo: u16,
+    /// Ticks left to nom again before the combo resets
+    combo_timer: u32,
+    announcement: Option<Announcement>,
+    /// Whether the controls are mirrored right now, with the mirror mutator on
+    mirrored: bool,
+    /// Ticks until the controls next flip between mirrored and normal
+    mirror_timer: u32,
+    /// Ticks until a dizzy snek next turns of its own accord
+    dizzy_timer: u32,
+    /// The turn a dizzy snek is about to make
+    dizzy_turn: Option<Direction>,
+    /// Whether the player has steered since the last tick
+    steered: bool,
+    /// Turns pressed after the one this tick, taken a tick at a time
+    turns: VecDeque<Direction>,
+    /// Number of rule changes made so far by the rules' chaos interval
+    mutations: u32,
+    /// The last rule change, and ticks left to announce it
+    mutation: Option<(Mutation, u32)>,
+    /// Edge walls put up by chaos, to take down again
+    chaos_walls: HashSet<Pos>,
+    /// Boost saved up, out of `BOOST_CAPACITY`
+    boost: u16,
+    /// Whether the snek is boosting, playing ticks at double speed
+    boosting: bool,
+    /// Rewind charges picked up and not yet used
+    rewinds: u8,
+    /// Whether the snek's next crash into itself, a wall or an obstacle is
+    /// forgiven
+    shield: bool,
+    /// The player's second snek, which turns the mirror image of the way the
+    /// first one does
+    twin: Option<Snek>,
+    /// Ticks left of flashing the head and score, and showing an invisible
+    /// body, after a nom
+    flash: u8,
+    /// How far through the tick to draw the head, from 0 to 1, when it
+    /// slides smoothly between cells
+    motion: Option<f32>,
+    /// Ticks left of passing through walls and obstacles, which never run
+    /// out with a head inside a wall
+    phasing: u32,
+    /// Where the best run's head was by this tick, to race against
+    ghost_head: Option<Pos>,
+    /// Morsels eaten so far, traps and all
+    eaten: u32,
+    /// Morsels eaten so far, traps aside, counted by what they were worth
+    noms: BTreeMap<u16, u32>,
+    /// The longest the snek has been
+    longest: u16,
+}
+
+impl SnekHaus {
+    fn new(size: Size, initial_length: u16, rules: Rules) -> Self {
+        let tunnels = match rules.edges {
+            EdgePolicy::Walled => Self::default_tunnels(size),
+            _ => HashSet::new(),
+        };
+        SnekHaus {
+            size,
+            snek: Snek::new(size, initial_length),
+            moresels: Vec::new(),
+            score: 0,
+            rules,
+            initial_length,
+            lives: rules.lives.unwrap_or(1),
+            ticks: 0,
+            shrunk_by: 0,
+            portals: Vec::new(),
+            wormholes: Vec::new(),
+            walls: Self::edge_walls(size, 1, rules.edges, &tunnels),
+            floors: 1,
+            stairs: Vec::new(),
+            tunnels,
+            ice: HashSet::new(),
+            pads: HashMap::new(),
+            spawn: Snek::centre_spawn(size, initial_length),
+            rivals: Vec::new(),
+            obstacles: Vec::new(),
+            hazards: HashSet::new(),
+            weeds: HashSet::new(),
+            ghost: false,
+            skin: Skin::default(),
+            glyphs: Glyphs::default(),
+            background: Background::default(),
+            theme: Theme::default(),
+            danger_warning: false,
+            danger_cells: false,
+            clock: Duration::ZERO,
+            escalations: 0,
+            escalation: Vec::new(),
+            escalated: 0,
+            predators: Vec::new(),
+            fog: None,
+            hunger: rules.hunger.unwrap_or(0),
+            famine: rules.famine.unwrap_or(0),
+            death: None,
+            combo: 0,
+            combo_timer: 0,
+            announcement: None,
+            mirrored: false,
+            mirror_timer: *MIRROR_TICKS.start(),
+            dizzy_timer: *DIZZY_TICKS.start(),
+            dizzy_turn: None,
+            steered: false,
+            turns: VecDeque::new(),
+            mutations: 0,
+            mutation: None,
+            chaos_walls: HashSet::new(),
+            boost: 0,
+            boosting: false,
+            rewinds: 0,
+            shield: false,
+            twin: None,
+            flash: 0,
+            motion: None,
+            phasing: 0,
+            ghost_head: None,
+            eaten: 0,
+            noms: BTreeMap::new(),
+            longest: initial_length,
+        }
+    }
+
+    fn from_level(level: &Level, initial_length: u16, rules: Rules) -> Self {
+        let tunnels = match rules.edges {
+            EdgePolicy::Solid => HashSet::new(),
+            _ => level.tunnels.clone(),
+        };
+        let mut walls = Self::edge_walls(level.size, level.floors, rules.edges, &tunnels);
+        walls.extend(&level.walls);
+        let snek = Snek::spawn_at(level.spawn, initial_length);
+        // A longer snek than the level expects can start on top of a morsel
+        let moresels = level
+            .morsels
+            .iter()
+            .filter(|&&pos| !snek.would_collide_with_body(pos))
+            .map(|&pos| Morsel {
+                pos,
+                growth_value: 1,
+                kind: MorselKind::Normal,
+                age: 0,
+            })
+            .collect();
+        SnekHaus {
+            snek,
+            moresels,
+            portals: level.portals.clone(),
+            walls,
+            floors: level.floors,
+            stairs: level.stairs.clone(),
+            tunnels,
+            ice: level.ice.clone(),
+            pads: level.pads.clone(),
+            spawn: level.spawn,
+            ..Self::new(level.size, initial_length, rules)
+        }
+    }
+
+    /// A tunnel through the middle of each edge.
+    fn default_tunnels(size: Size) -> HashSet<Pos> {
+        let (mid_x, mid_y) = (size.width / 2, size.height / 2);
+        [
+            (0, mid_y),
+            (size.width.saturating_sub(1), mid_y),
+            (mid_x, 0),
+            (mid_x, size.height.saturating_sub(1)),
+        ]
+        .into_iter()
+        .map(|(x, y)| Pos { x, y, floor: 0 })
+        .collect()
+    }
+
+    /// The walls around the edge of every floor, leaving gaps for `tunnels`.
+    fn edge_walls(
+        size: Size,
+        floors: u8,
+        edges: EdgePolicy,
+        tunnels: &HashSet<Pos>,
+    ) -> HashSet<Pos> {
+        (0..floors)
+            .flat_map(|floor| {
+                (0..size.height)
+                    .flat_map(move |y| (0..size.width).map(move |x| Pos { x, y, floor }))
+            })
+            .filter(|&pos| edges.is_walled(pos, size) && !tunnels.contains(&pos))
+            .collect()
+    }
+
+    fn respawn(&mut self) {
+        self.snek = Snek::spawn_at(self.spawn, self.initial_length);
+        self.hunger = self.rules.hunger.unwrap_or(0);
+        self.famine = self.rules.famine.unwrap_or(0);
+        self.combo = 0;
+        self.combo_timer = 0;
+        self.announcement = None;
+        // Drop any morsels the fresh snek landed on
+        let snek = &self.snek;
+        self.moresels
+            .retain(|&m| !snek.would_collide_with_body(m) && !snek.would_collide_with_head(m));
+    }
+
+    /// What `morsel` scores, or costs if it's a trap.
+    fn value(&self, morsel: Morsel) -> u16 {
+        if self.rules.double_value {
+            morsel.growth_value * 2
+        } else {
+            morsel.growth_value
+        }
+    }
+
+    /// The symbol from `symbols` for a morsel worth `value`, spread over the
+    /// table so the most a morsel can be worth gets the last symbol.
+    fn morsel_symbol(&self, symbols: &[&'static str], value: u16) -> &'static str {
+        let last = symbols.len() - 1;
+        let max = self.rules.max_morsel_value() as usize;
+        let index = match max {
+            0 | 1 => 0,
+            _ => (value.max(1) as usize - 1) * last / (max - 1),
+        };
+        symbols[index.min(last)]
+    }
+
+    fn check_nomming(&mut self) -> Option<u16> {
+        // Find any morsel at the head position and remove it, leaving
+        // poison for the collision checks
+        let head = self.snek.head;
+        if let Some(index) = self
+            .moresels
+            .iter()
+            .position(|m| m.pos == head && m.kind != MorselKind::Poison)
+        {
+            let morsel = self.moresels.swap_remove(index);
+            self.eaten += 1;
+            if morsel.kind == MorselKind::Trap {
+                // A trap breaks the chain as well as costing points
+                self.combo = 0;
+                self.combo_timer = 0;
+                if self.rules.scoring == Scoring::Morsels {
+                    self.score = self.score.saturating_sub(self.value(morsel));
+                }
+                return Some(0);
+            }
+            *self.noms.entry(morsel.growth_value).or_default() += 1;
+            let mut score_increase = self.value(morsel) + self.extend_combo();
+            // The risk of going fast pays off double
+            if self.boosting {
+                score_increase *= 2;
+            }
+            if self.rules.boost {
+                self.boost =
+                    (self.boost + morsel.growth_value * BOOST_PER_POINT).min(BOOST_CAPACITY);
+            }
+            if self.rules.scoring == Scoring::Morsels {
+                self.score += score_increase;
+            }
+            self.snek.snack(morsel, self.rules.growth.unwrap_or(1));
+            match morsel.kind {
+                MorselKind::Brittle => {
+                    let dropped = self.snek.shed_tail(TAIL_DROP);
+                    self.hazards.extend(dropped);
+                }
+                MorselKind::Scythe => {
+                    let size = self.size;
+                    self.weeds.retain(|weed| {
+                        weed.floor != head.floor
+                            || weed.wrapped_distance(head, size) > SCYTHE_RADIUS
+                    });
+                }
+                MorselKind::Rewind => self.rewinds = (self.rewinds + 1).min(MAX_REWINDS),
+                MorselKind::Shield => self.shield = true,
+                MorselKind::Phase => self.phasing = PHASE_TICKS,
+                MorselKind::Normal | MorselKind::Poison | MorselKind::Trap | MorselKind::Bonus => {}
+            }
+            Some(score_increase)
+        } else {
+            None
+        }
+    }
+
+    /// Count a nom towards the combo, returning the bonus points it earns.
+    fn extend_combo(&mut self) -> u16 {
+        let Some(window) = self.rules.combo_window else {
+            return 0;
+        };
+        self.combo = if self.combo_timer > 0 {
+            self.combo + 1
+        } else {
+            1
+        };
+        self.combo_timer = window;
+        if let Some(tier) = COMBO_ANNOUNCEMENTS
+            .iter()
+            .position(|&(at, _)| at == self.combo)
+        {
+            self.announcement = Some(Announcement {
+                tier,
+                ticks_left: ANNOUNCEMENT_TICKS,
+            });
+        }
+        (self.combo - 1).min(MAX_COMBO_BONUS)
+    }
+
+    /// Run down the combo timer, and any announcement on screen.
+    fn decay_combo(&mut self) {
+        self.combo_timer = self.combo_timer.saturating_sub(1);
+        if self.combo_timer == 0 {
+            self.combo = 0;
+        }
+        if let Some(announcement) = &mut self.announcement {
+            announcement.ticks_left = announcement.ticks_left.saturating_sub(1);
+            if announcement.ticks_left == 0 {
+                self.announcement = None;
+            }
+        }
+    }
+
+    fn check_snek_hit_itself(&self) -> bool {
+        self.snek.would_collide_with_body(self.snek.head)
+    }
+
+    fn in_safe_zone(&self, pos: Pos) -> bool {
+        pos.x >= self.shrunk_by
+            && pos.y >= self.shrunk_by
+            && pos.x < self.size.width.saturating_sub(self.shrunk_by)
+            && pos.y < self.size.height.saturating_sub(self.shrunk_by)
+    }
+
+    /// Whether moving the head onto `pos` would end in a collision.
+    fn is_lethal(&self, pos: Pos) -> bool {
+        self.is_occupied_by_snek(pos)
+            || self.walls.contains(&pos)
+            || self.hazards.contains(&pos)
+            || self.weeds.contains(&pos)
+            || self.is_obstacle(pos)
+            || self.predators.contains(&pos)
+            || self.is_poison(pos)
+            || !self.in_safe_zone(pos)
+    }
+
+    fn is_poison(&self, pos: Pos) -> bool {
+        self.moresels
+            .iter()
+            .any(|m| m.pos == pos && m.kind == MorselKind::Poison)
+    }
+
+    fn is_obstacle(&self, pos: Pos) -> bool {
+        self.obstacles.iter().any(|obstacle| obstacle.pos() == pos)
+    }
+
+    /// Whether any obstacle will pass through `pos` at some point.
+    fn is_on_patrol_route(&self, pos: Pos) -> bool {
+        self.obstacles
+            .iter()
+            .any(|obstacle| obstacle.route.contains(&pos))
+    }
+
+    /// Whether any snek, player or rival, is covering `pos`.
+    fn is_occupied_by_snek(&self, pos: Pos) -> bool {
+        self.sneks()
+            .any(|snek| snek.would_collide_with_body(pos) || snek.would_collide_with_head(pos))
+    }
+
+    /// Draw the head of `snek`, one of the player's, into `cell`. Moving
+    /// smoothly, it's a block sliding into the cell from the one behind.
+    fn draw_head(&self, cell: &mut buffer::Cell, snek: &Snek) {
+        let entered = snek
+            .body
+            .back()
+            .and_then(|&neck| neck.direction_to(snek.head, self.size));
+        let [across, up] = &self.glyphs.fills;
+        // Eighths of the cell filled, at least one so the head never vanishes
+        let filled = self
+            .motion
+            .map(|progress| 1 + (progress.clamp(0.0, 1.0) * 7.0) as usize);
+        // Cells only fill from the left or the bottom, so filling from the
+        // other side draws the empty part reversed
+        let slide = match (filled, entered) {
+            (Some(8), Some(_)) => Some((across[7], false)),
+            (Some(filled), Some(Direction::East)) => Some((across[filled - 1], false)),
+            (Some(filled), Some(Direction::West)) => Some((across[7 - filled], true)),
+            (Some(filled), Some(Direction::North)) => Some((up[filled - 1], false)),
+            (Some(filled), Some(Direction::South)) => Some((up[7 - filled], true)),
+            _ => None,
+        };
+        match slide.filter(|(symbol, _)| !symbol.is_empty()) {
+            Some((symbol, reversed)) => {
+                cell.set_symbol(symbol).set_fg(self.theme.head);
+                if reversed {
+                    cell.modifier.insert(Modifier::REVERSED);
+                }
+            }
+            None => {
+                let mut head = self.skin.head(&self.glyphs, snek.direction);
+                // A wide head has nowhere to spill at the right of the arena
+                if glyphs::is_wide(head) && snek.head.x + 1 >= self.size.width {
+                    head = self.glyphs.head(snek.direction);
+                }
+                cell.set_symbol(head).set_fg(self.theme.head);
+            }
+        }
+        if self.shield {
+            cell.set_bg(Color::Cyan);
+        }
+        // A nom lights the head up, unless there's danger to warn of
+        if self.flash > 0 {
+            cell.set_fg(Color::Black).set_bg(self.theme.head);
+        }
+    }
+
+    /// Whether the player's sneks' bodies are drawn, which they aren't while
+    /// invisible unless flashing up after a nom.
+    fn body_visible(&self) -> bool {
+        !self.rules.mutators.invisible || self.flash > 0
+    }
+
+    /// The player's snek and its twin, if it has one.
+    fn player_sneks(&self) -> impl Iterator<Item = &Snek> {
+        std::iter::once(&self.snek).chain(&self.twin)
+    }
+
+    /// The player's sneks followed by every rival still in play.
+    fn sneks(&self) -> impl Iterator<Item = &Snek> {
+        self.player_sneks()
+            .chain(self.live_rivals().map(|rival| &rival.snek))
+    }
+
+    /// Run `f` with the twin swapped in for the snek, so everything that
+    /// happens to the snek can happen to the twin. `None` without a twin.
+    fn as_twin<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> Option<T> {
+        let twin = self.twin.take()?;
+        let snek = std::mem::replace(&mut self.snek, twin);
+        self.twin = Some(snek);
+        let result = f(self);
+        let twin = std::mem::replace(&mut self.snek, self.twin.take()?);
+        self.twin = Some(twin);
+        Some(result)
+    }
+
+    /// Move the snek into the left half of the arena and put its twin, its
+    /// mirror image, in the right half.
+    fn spawn_twin(&mut self) {
+        let length = self.initial_length;
+        if length > self.size.width / 4 {
+            warn!("No room for twin sneks of length {}", length);
+            return;
+        }
+        let spawn = Pos {
+            x: self.size.width / 4 + length / 2,
+            ..self.spawn
+        };
+        let snek = Snek::spawn_at(spawn, length);
+        let twin = snek.mirrored(self.size);
+        let blocked = [&snek, &twin].into_iter().any(|snek| {
+            std::iter::once(&snek.head)
+                .chain(&snek.body)
+                .any(|pos| self.walls.contains(pos))
+        });
+        if blocked {
+            warn!("No room for twin sneks in this arena");
+            return;
+        }
+        self.spawn = spawn;
+        self.snek = snek;
+        self.twin = Some(twin);
+        let sneks: Vec<Snek> = self.player_sneks().cloned().collect();
+        self.moresels.retain(|&m| {
+            !sneks
+                .iter()
+                .any(|snek| snek.would_collide_with_body(m) || snek.would_collide_with_head(m))
+        });
+    }
+
+    fn live_rivals(&self) -> impl Iterator<Item = &Rival> {
+        self.rivals.iter().filter(|rival| rival.alive)
+    }
+
+    fn check_snek_hit_rival(&self) -> bool {
+        let head = self.snek.head;
+        self.live_rivals().any(|rival| {
+            rival.snek.would_collide_with_body(head) || rival.snek.would_collide_with_head(head)
+        })
+    }
+
+    fn check_snek_hit_twin(&self) -> bool {
+        let head = self.snek.head;
+        self.twin.as_ref().is_some_and(|twin| {
+            twin.would_collide_with_body(head) || twin.would_collide_with_head(head)
+        })
+    }
+
+    fn check_snek_hit_wall(&self) -> bool {
+        let head = self.snek.head;
+        self.walls.contains(&head) && (self.phasing == 0 || !self.can_phase(head))
+    }
+
+    /// Whether a phasing snek can pass through the wall at `pos`, which is
+    /// any wall but the edge of an arena with solid edges.
+    fn can_phase(&self, pos: Pos) -> bool {
+        self.rules.edges != EdgePolicy::Solid || !pos.is_on_edge(self.size)
+    }
+
+    fn check_snek_hit_hazard(&self) -> bool {
+        self.hazards.contains(&self.snek.head)
+    }
+
+    fn check_snek_hit_obstacle(&self) -> bool {
+        self.phasing == 0 && self.is_obstacle(self.snek.head)
+    }
+
+    fn check_snek_left_safe_zone(&self) -> bool {
+        !self.in_safe_zone(self.snek.head)
+    }
+
+    fn shrink_arena(&mut self) {
+        // Always leave a playable area in the middle
+        let next = self.shrunk_by + 1;
+        if self.size.width.saturating_sub(2 * next) < MIN_SAFE_ZONE
+            || self.size.height.saturating_sub(2 * next) < MIN_SAFE_ZONE
+        {
+            return;
+        }
+        self.shrunk_by = next;
+        info!("Arena shrunk by {} rings", self.shrunk_by);
+
+        let moresels = std::mem::take(&mut self.moresels);
+        self.moresels = moresels
+            .into_iter()
+            .filter(|m| self.in_safe_zone(m.pos))
+            .collect();
+    }
+
+    /// Where the head comes out after stepping onto `pos` while heading in
+    /// `direction`, if `pos` is a portal or a staircase.
+    fn portal_exit(&self, pos: Pos, direction: Direction) -> Option<Pos> {
+        self.portals
+            .iter()
+            .chain(&self.stairs)
+            .find_map(|pair| pair.partner(pos))
+            .map(|partner| partner.wrapped_add(direction.into(), self.size))
+    }
+
+    fn wormhole_exit(&self, pos: Pos) -> Option<Pos> {
+        self.wormholes
+            .iter()
+            .find(|wormhole| wormhole.pos == pos)
+            .and_then(|wormhole| wormhole.exit)
+    }
+
+    /// Pick a fresh exit for every wormhole: an empty cell with nothing
+    /// lethal straight ahead of it, so the snek has a moment to get its
+    /// bearings.
+    fn aim_wormholes(&mut self, rng: &mut impl Rng) {
+        const ATTEMPTS: usize = 100;
+        let direction = self.snek.direction;
+        for index in 0..self.wormholes.len() {
+            self.wormholes[index].exit = (0..ATTEMPTS)
+                .filter_map(|_| self.random_empty_cell(rng))
+                .find(|&pos| !self.is_lethal(pos.wrapped_add(direction.into(), self.size)));
+        }
+    }
+
+    fn is_portal(&self, pos: Pos) -> bool {
+        self.portals
+            .iter()
+            .chain(&self.stairs)
+            .any(|pair| pair.partner(pos).is_some())
+    }
+
+    /// Play one tick of a game in progress: everything random first, then the
+    /// snek's move, then fresh morsels if the game goes on.
+    fn tick(&mut self, round: &mut Round, rng: &mut impl Rng) -> StepResult {
+        if self.rules.bonus_rounds {
+            let elapsed = self.tick_rate();
+            round.advance(elapsed, self, rng);
+        }
+        self.escalate(rng);
+        self.escalate_by_score(rng);
+        self.mutate(rng);
+        self.flip_mirror(rng);
+        self.wobble(rng);
+        self.grow_weeds(rng);
+        self.rot();
+        // Aimed last thing, so nothing turns up where they lead before the
+        // snek gets there
+        self.aim_wormholes(rng);
+
+        let result = self.slither_on();
+        if !matches!(result, StepResult::Died(_) | StepResult::Victory) {
+            self.top_up_morsels(rng);
+        }
+        result
+    }
+
+    /// How many of the cells the head could move into next are lethal.
+    fn lethal_neighbours(&self) -> u8 {
+        self.lethal_moves().len() as u8
+    }
+
+    /// The cells the player's head could move into next tick that would end
+    /// in a collision.
+    fn lethal_moves(&self) -> Vec<Pos> {
+        self.directions()
+            .iter()
+            .copied()
+            .filter(|&direction| direction != self.snek.direction.opposite())
+            .map(|direction| self.next_head(&self.snek, direction))
+            .filter(|&pos| self.is_lethal(pos))
+            .collect()
+    }
+
+    /// The cells the player's head will move through over the next `steps`
+    /// ticks if it carries straight on, nearest first.
+    fn look_ahead(&self, steps: usize) -> Vec<Pos> {
+        let mut pos = self.snek.head;
+        (0..steps)
+            .map(|_| {
+                let next = pos.wrapped_add(self.snek.direction.into(), self.size);
+                pos = self.portal_exit(next, self.snek.direction).unwrap_or(next);
+                pos
+            })
+            .collect()
+    }
+
+    /// How many steps ahead, up to `steps`, carrying straight on runs into
+    /// something lethal.
+    fn danger_ahead(&self, steps: usize) -> Option<usize> {
+        self.look_ahead(steps)
+            .into_iter()
+            .position(|pos| self.is_lethal(pos))
+            .map(|index| index + 1)
+    }
+
+    /// The ways a snek can go under these rules.
+    fn directions(&self) -> &'static [Direction] {
+        if self.rules.mutators.diagonal {
+            &Direction::ALL
+        } else {
+            &Direction::CARDINAL
+        }
+    }
+
+    /// Where `snek`'s head would end up if it moved one step in `direction`.
+    fn next_head(&self, snek: &Snek, direction: Direction) -> Pos {
+        let pos = snek.head.wrapped_add(direction.into(), self.size);
+        self.portal_exit(pos, direction).unwrap_or(pos)
+    }
+
+    fn move_snek(&mut self) {
+        if self.rules.trail {
+            self.snek.pending_growth = 1;
+        }
+        self.snek.slither(self.size);
+        if let Some(exit) = self.portal_exit(self.snek.head, self.snek.direction) {
+            self.snek.head = exit;
+        }
+        if let Some(exit) = self.wormhole_exit(self.snek.head) {
+            self.snek.head = exit;
+        }
+    }
+
+    /// Steer, move and feed each rival, retiring any that crash.
+    fn move_rivals(&mut self) {
+        let directions: Vec<Direction> = self
+            .rivals
+            .iter()
+            .map(|rival| Greedy.decide(self, &rival.snek))
+            .collect();
+
+        for (index, direction) in directions.into_iter().enumerate() {
+            if !self.rivals[index].alive {
+                continue;
+            }
+            let snek = &self.rivals[index].snek;
+            let direction = if self.ice.contains(&snek.head) {
+                snek.direction
+            } else {
+                direction
+            };
+            let head = self.next_head(snek, direction);
+            let rival = &mut self.rivals[index];
+            rival.snek.change_direction(direction);
+            rival.snek.slither(self.size);
+            rival.snek.head = head;
+
+            let rival = &self.rivals[index];
+            let crashed = rival.snek.would_collide_with_body(head)
+                || self.snek.would_collide_with_body(head)
+                || self.snek.would_collide_with_head(head)
+                || self.walls.contains(&head)
+                || self.hazards.contains(&head)
+                || self.weeds.contains(&head)
+                || self.is_obstacle(head)
+                || self.is_poison(head)
+                || !self.in_safe_zone(head)
+                || self.rivals.iter().enumerate().any(|(other, r)| {
+                    other != index
+                        && r.alive
+                        && (r.snek.would_collide_with_body(head)
+                            || r.snek.would_collide_with_head(head))
+                });
+            if crashed {
+                info!("Rival {} crashed with a score of {}", index, rival.score);
+                self.rivals[index].alive = false;
+                if self.rules.royale {
+                    self.leave_remains(index);
+                }
+                continue;
+            }
+
+            if let Some(morsel_index) = self.moresels.iter().position(|m| m.pos == head) {
+                let morsel = self.moresels.swap_remove(morsel_index);
+                let rival = &mut self.rivals[index];
+                if morsel.kind == MorselKind::Trap {
+                    rival.score = rival.score.saturating_sub(morsel.growth_value);
+                } else {
+                    rival.score += morsel.growth_value;
+                    rival.snek.snack(morsel, self.rules.growth.unwrap_or(1));
+                }
+            }
+        }
+    }
+
+    /// Turn the body of the crashed rival at `index` into single-point
+    /// morsels, wherever there's room for one.
+    fn leave_remains(&mut self, index: usize) {
+        let remains: Vec<Pos> = self.rivals[index]
+            .snek
+            .body
+            .iter()
+            .copied()
+            .filter(|&pos| self.is_empty(pos))
+            .collect();
+        for pos in remains {
+            self.place_morsel(Morsel {
+                pos,
+                growth_value: 1,
+                kind: MorselKind::Normal,
+                age: 0,
+            });
+        }
+    }
+
+    /// Time left to play, when the rules have a time limit.
+    fn time_left(&self) -> Option<Duration> {
+        self.rules
+            .time_limit
+            .map(|limit| limit.saturating_sub(self.clock))
+    }
+
+    /// Boost on the ticks from now on while `held`, for as long as the meter lasts.
+    fn set_boost(&mut self, held: bool) {
+        self.boosting = held && self.rules.boost && self.boost >= BOOST_DRAIN;
+    }
+
+    /// How long a tick lasts right now, halved while boosting.
+    fn tick_rate(&self) -> Duration {
+        let tick_rate = self.rules.speed.tick_rate(self.score);
+        if self.boosting {
+            tick_rate / 2
+        } else {
+            tick_rate
+        }
+    }
+
+    fn slither_on(&mut self) -> StepResult {
+        self.take_turn();
+        self.apply_mutators();
+        self.ticks += 1;
+        if self.boosting {
+            self.boost -= BOOST_DRAIN;
+        }
+        self.clock += self.tick_rate();
+        if self.rules.scoring == Scoring::Survival {
+            self.score = self.clock.as_secs().min(u16::MAX as u64) as u16;
+        }
+        if self.time_left().is_some_and(|left| left.is_zero()) {
+            // However many lives are left, the game is over
+            self.death = Some(DeathCause::TimeUp);
+            return StepResult::Died(DeathCause::TimeUp);
+        }
+        if let Some(interval) = self.rules.shrink_interval {
+            if self.ticks.is_multiple_of(interval) {
+                self.shrink_arena();
+            }
+        }
+
+        let before = self.snek.clone();
+        self.move_snek();
+        self.longest = self.longest.max(self.snek.length());
+        // The twin moves at the same time, so neither crashes into a cell the
+        // other's tail is just leaving
+        let twin_before = self.as_twin(|haus| {
+            let before = haus.snek.clone();
+            haus.move_snek();
+            before
+        });
+        if let Some(result) = self.crash(before) {
+            return result;
+        }
+        if let Some(Some(result)) =
+            twin_before.and_then(|before| self.as_twin(|haus| haus.crash(before)))
+        {
+            return result;
+        }
+        if let Some(result) = self.ride_pads() {
+            return result;
+        }
+        if let Some(Some(result)) = self.as_twin(|haus| haus.ride_pads()) {
+            return result;
+        }
+        if self.phasing > 0
+            && !self
+                .player_sneks()
+                .any(|snek| self.walls.contains(&snek.head))
+        {
+            self.phasing -= 1;
+        }
+
+        if self.rules.scoring == Scoring::Coverage {
+            self.score = self.snek.length();
+        }
+
+        self.decay_combo();
+        let nommed = match (
+            self.check_nomming(),
+            self.as_twin(|haus| haus.check_nomming()).flatten(),
+        ) {
+            (Some(snek), Some(twin)) => Some(snek + twin),
+            (snek, twin) => snek.or(twin),
+        };
+        self.flash = if nommed.is_some() {
+            FLASH_TICKS
+        } else {
+            self.flash.saturating_sub(1)
+        };
+        if let Some(max_hunger) = self.rules.hunger {
+            if nommed.is_some() {
+                self.hunger = max_hunger;
+            } else {
+                self.hunger = self.hunger.saturating_sub(1);
+                if self.hunger == 0 {
+                    return self.die(DeathCause::Starved);
+                }
+            }
+        }
+        if let Some(interval) = self.rules.famine {
+            if nommed.is_some() {
+                self.famine = interval;
+            } else {
+                self.famine = self.famine.saturating_sub(1);
+                if self.famine == 0 {
+                    self.famine = interval;
+                    if !self.snek.waste_away() {
+                        return self.die(DeathCause::WastedAway);
+                    }
+                }
+            }
+        }
+        self.move_rivals();
+        if self.rules.royale && self.live_rivals().next().is_none() {
+            return StepResult::Victory;
+        }
+        self.move_obstacles();
+        if self.move_predators() && !self.ghost {
+            return self.die(DeathCause::Caught);
+        }
+        if let Some(moves) = self.rules.moves {
+            if self.moresels.is_empty() {
+                return StepResult::Victory;
+            }
+            if self.ticks >= moves as u32 {
+                return self.die(DeathCause::OutOfMoves);
+            }
+        }
+
+        if self.rules.target.is_some_and(|target| target.reached(self)) {
+            return StepResult::Victory;
+        }
+
+        if let Some(score_increase) = nommed {
+            return StepResult::Nommed(score_increase);
+        }
+
+        StepResult::Ongoing
+    }
+
+    /// What the snek's head has run into, if anything.
+    fn collision(&self) -> Option<DeathCause> {
+        if self.check_snek_hit_itself() {
+            Some(DeathCause::BitSelf)
+        } else if self.check_snek_hit_wall() {
+            Some(DeathCause::HitWall)
+        } else if self.check_snek_hit_rival() {
+            Some(DeathCause::HitRival)
+        } else if self.check_snek_hit_twin() {
+            Some(DeathCause::HitTwin)
+        } else if self.check_snek_hit_hazard() {
+            Some(DeathCause::HitHazard)
+        } else if self.weeds.contains(&self.snek.head) {
+            Some(DeathCause::HitWeed)
+        } else if self.check_snek_hit_obstacle() {
+            Some(DeathCause::HitObstacle)
+        } else if self.predators.contains(&self.snek.head) {
+            Some(DeathCause::Caught)
+        } else if self.is_poison(self.snek.head) {
+            Some(DeathCause::Poisoned)
+        } else if self.check_snek_left_safe_zone() {
+            Some(DeathCause::LeftSafeZone)
+        } else {
+            None
+        }
+    }
+
+    /// A speed pad carries the snek on a cell, checked for a crash again so it
+    /// can't jump over anything.
+    fn ride_pads(&mut self) -> Option<StepResult> {
+        let before = self.snek.clone();
+        if self.ride_pad() {
+            self.crash(before)
+        } else {
+            None
+        }
+    }
+
+    /// Push the snek on a cell if its head is on a speed pad, turning it the
+    /// way the pad points unless that would double it back. Returns whether it
+    /// moved.
+    fn ride_pad(&mut self) -> bool {
+        let Some(&pad) = self.pads.get(&self.snek.head) else {
+            return false;
+        };
+        if pad == self.snek.direction.opposite() {
+            return false;
+        }
+        self.snek.direction = pad;
+        self.move_snek();
+        true
+    }
+
+    /// Step each obstacle along its route, holding back any that would land on
+    /// a snek's head.
+    fn move_obstacles(&mut self) {
+        for index in 0..self.obstacles.len() {
+            let next = self.obstacles[index].next_pos();
+            if !self.sneks().any(|snek| snek.head == next) {
+                self.obstacles[index].advance();
+            }
+        }
+    }
+
+    /// Every `PREDATOR_TICKS`, step each predator towards the snek's head
+    /// by whichever open cell brings it closest, waiting if the way is
+    /// blocked. Returns whether one caught the head.
+    fn move_predators(&mut self) -> bool {
+        if !self.ticks.is_multiple_of(PREDATOR_TICKS) {
+            return false;
+        }
+        let head = self.snek.head;
+        for index in 0..self.predators.len() {
+            let pos = self.predators[index];
+            let mut steps: Vec<Pos> = Direction::CARDINAL
+                .iter()
+                .map(|&direction| pos.wrapped_add(direction.into(), self.size))
+                .filter(|&next| {
+                    next == head
+                        || (!self.is_lethal(next)
+                            && !self.is_portal(next)
+                            && !self.live_rivals().any(|rival| rival.snek.head == next))
+                })
+                .collect();
+            steps.sort_by_key(|next| next.wrapped_distance(head, self.size));
+            match steps.first() {
+                Some(&next)
+                    if next.wrapped_distance(head, self.size)
+                        < pos.wrapped_distance(head, self.size) =>
+                {
+                    self.predators[index] = next;
+                }
+                _ => {}
+            }
+            if self.predators[index] == head {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Check whether the snek's move crashed it. A shield takes the crash
+    /// instead, putting the snek back `before` the move.
+    fn crash(&mut self, before: Snek) -> Option<StepResult> {
+        let cause = self.collision().filter(|_| !self.ghost)?;
+        if self.shield && cause.shieldable() {
+            info!("The shield saved the snek: {}", cause.describe());
+            self.shield = false;
+            self.snek = before;
+            return None;
+        }
+        Some(self.die(cause))
+    }
+
+    /// Switch collisions back on after a bonus round, unless the snek is still
+    /// inside something, in which case it stays a ghost until it's clear.
+    fn solidify(&mut self) {
+        if self.collision().is_none() {
+            self.ghost = false;
+        }
+    }
+
+    /// Use up a life, respawning if there are any left.
+    fn die(&mut self, cause: DeathCause) -> StepResult {
+        if self.lives > 1 {
+            self.lives -= 1;
+            info!("{}, {} lives remaining", cause.describe(), self.lives);
+            self.respawn();
+            return StepResult::LostLife;
+        }
+        self.lives = 0;
+        self.death = Some(cause);
+        StepResult::Died(cause)
+    }
+
+    /// Turn the snek, unless it's sliding across ice.
+    fn change_direction(&mut self, new_direction: Direction) {
+        if let Some(twin) = &mut self.twin {
+            if !self.ice.contains(&twin.head) {
+                twin.change_direction(new_direction.mirrored(Mirror::LeftRight));
+            }
+        }
+        if self.ice.contains(&self.snek.head) {
+            return;
+        }
+        self.snek.change_direction(new_direction);
+    }
+
+    /// Turn the way the player asked, unless the controls are mirrored.
+    fn steer(&mut self, pressed: Direction) {
+        if !self.directions().contains(&pressed) {
+            return;
+        }
+        let direction = if self.mirrored {
+            pressed.mirrored(self.rules.mutators.mirror)
+        } else {
+            pressed
+        };
+        // One turn a tick, or two quick turns could double the snek back on
+        // itself before it's moved. The rest wait their turn
+        if self.steered || !self.turns.is_empty() {
+            if self.turns.len() < MAX_QUEUED_TURNS {
+                self.turns.push_back(direction);
+            }
+            return;
+        }
+        self.change_direction(direction);
+        self.steered = true;
+    }
+
+    /// Make the next turn held over from an earlier tick, unless there's
+    /// been one this tick already.
+    fn take_turn(&mut self) {
+        if self.steered {
+            return;
+        }
+        if let Some(direction) = self.turns.pop_front() {
+            self.change_direction(direction);
+            self.steered = true;
+        }
+    }
+
+    /// Count down to the controls flipping, with the mirror mutator on.
+    fn flip_mirror(&mut self, rng: &mut impl Rng) {
+        // Under chaos the controls only flip when the rules change
+        if self.rules.mutators.mirror == Mirror::Off || self.rules.chaos_interval.is_some() {
+            return;
+        }
+        if self.mirror_timer > 0 {
+            self.mirror_timer -= 1;
+            return;
+        }
+        self.mirrored = !self.mirrored;
+        self.mirror_timer = rng.gen_range(MIRROR_TICKS);
+        info!("Controls mirrored: {}", self.mirrored);
+    }
+
+    /// Age the morsels, knocking a point off any that have been left long
+    /// enough, down to one, when they rot.
+    fn rot(&mut self) {
+        if !self.rules.mutators.rotting {
+            return;
+        }
+        for morsel in &mut self.moresels {
+            if !matches!(morsel.kind, MorselKind::Normal | MorselKind::Brittle) {
+                continue;
+            }
+            morsel.age += 1;
+            if morsel.age.is_multiple_of(ROT_TICKS) && morsel.growth_value > 1 {
+                morsel.growth_value -= 1;
+            }
+        }
+    }
+
+    /// Count down to a dizzy snek's next turn, and pick which way it'll go.
+    fn wobble(&mut self, rng: &mut impl Rng) {
+        if !self.rules.mutators.dizzy {
+            return;
+        }
+        if self.dizzy_timer > 0 {
+            self.dizzy_timer -= 1;
+            return;
+        }
+        let ahead = self.snek.direction;
+        let turns = self
+            .directions()
+            .iter()
+            .copied()
+            .filter(|&d| d != ahead && d != ahead.opposite());
+        self.dizzy_turn = turns.choose(rng);
+        self.dizzy_timer = rng.gen_range(DIZZY_TICKS);
+    }
+
+    /// Changes mutators make to the snek before it moves each tick.
+    fn apply_mutators(&mut self) {
+        if let Some(turn) = self.dizzy_turn.take() {
+            // Steering this tick keeps the snek on the player's course
+            if !self.steered {
+                self.change_direction(turn);
+            }
+        }
+        self.steered = false;
+    }
+
+    fn place_morsel(&mut self, morsel: Morsel) {
+        assert!(
+            !self.snek.would_collide_with_body(morsel)
+                && !self.snek.would_collide_with_head(morsel),
+            "Attempted to place morsel at invalid position"
+        );
+        self.moresels.push(morsel);
+    }
+
+    /// Lay out the arena features for a fresh game.
+    fn setup(&mut self, rng: &mut impl Rng) {
+        if self.rules.twin {
+            self.spawn_twin();
+        }
+        for _ in 0..self.rules.portal_pairs {
+            let Some(a) = self.random_portal_cell(rng) else {
+                warn!("No room for a portal");
+                break;
+            };
+            // Mark the first end as taken before choosing its partner
+            self.portals.push(PortalPair { a, b: a });
+            match self.random_portal_cell(rng) {
+                Some(b) => self.portals.last_mut().unwrap().b = b,
+                None => {
+                    warn!("No room for a portal's partner");
+                    self.portals.pop();
+                    break;
+                }
+            }
+        }
+        if self.rules.mutators.wormholes {
+            for _ in 0..WORMHOLES {
+                match self.random_portal_cell(rng) {
+                    Some(pos) => self.wormholes.push(Wormhole { pos, exit: None }),
+                    None => warn!("No room for a wormhole"),
+                }
+            }
+        }
+        for _ in 0..self.rules.obstacles {
+            match self.random_obstacle(rng) {
+                Some(obstacle) => self.obstacles.push(obstacle),
+                None => warn!("No room to place an obstacle"),
+            }
+        }
+        let rivals = if self.rules.royale {
+            rng.gen_range(ROYALE_RIVALS)
+        } else {
+            self.rules.rivals
+        };
+        for _ in 0..rivals {
+            match self.random_spawn(rng) {
+                Some(head) => self.rivals.push(Rival {
+                    snek: Snek::spawn_at(head, self.initial_length),
+                    score: 0,
+                    alive: true,
+                }),
+                None => warn!("No room to spawn a rival"),
+            }
+        }
+        if self.rules.weed_interval.is_some() {
+            for _ in 0..WEED_SEEDS {
+                self.plant_weed(rng);
+            }
+        }
+        self.top_up_morsels(rng);
+    }
+
+    /// Plant a weed on an empty cell away from the player's row, so it isn't
+    /// right in front of the snek.
+    fn plant_weed(&mut self, rng: &mut impl Rng) {
+        const ATTEMPTS: usize = 100;
+        let row = self.snek.head.y;
+        match (0..ATTEMPTS)
+            .filter_map(|_| self.random_empty_cell(rng))
+            .find(|pos| pos.y != row)
+        {
+            Some(pos) => {
+                self.weeds.insert(pos);
+            }
+            None => warn!("No room to plant a weed"),
+        }
+    }
+
+    /// Spread the weeds into one empty neighbouring cell every weed interval,
+    /// or plant a fresh one once they've all been cut down. Like hazards, they
+    /// never spread right under the snek's nose.
+    fn grow_weeds(&mut self, rng: &mut impl Rng) {
+        let Some(interval) = self.rules.weed_interval else {
+            return;
+        };
+        if self.ticks == 0 || !self.ticks.is_multiple_of(interval) {
+            return;
+        }
+        if self.weeds.is_empty() {
+            self.plant_weed(rng);
+            return;
+        }
+        // Sorted so the same seed always spreads the same way
+        let mut weeds: Vec<Pos> = self.weeds.iter().copied().collect();
+        weeds.sort_by_key(|pos| (pos.floor, pos.y, pos.x));
+        let spread = weeds
+            .into_iter()
+            .flat_map(|weed| {
+                Direction::CARDINAL.map(|direction| weed.wrapped_add(direction.into(), self.size))
+            })
+            .filter(|&pos| {
+                self.is_empty(pos)
+                    && self
+                        .player_sneks()
+                        .all(|snek| pos.wrapped_distance(snek.head, self.size) > WEED_CLEARANCE)
+            })
+            .choose(rng);
+        if let Some(pos) = spread {
+            self.weeds.insert(pos);
+        }
+    }
+
+    /// A head position for a new snek whose whole starting body lands on
+    /// empty cells, away from the player's row.
+    fn random_spawn(&self, rng: &mut impl Rng) -> Option<Pos> {
+        const ATTEMPTS: usize = 1000;
+        (0..ATTEMPTS)
+            .filter_map(|_| self.random_empty_cell(rng))
+            .find(|head| {
+                head.y != self.snek.head.y
+                    && head.x >= self.initial_length
+                    && (1..=self.initial_length).all(|i| {
+                        let pos = Pos {
+                            x: head.x - i,
+                            y: head.y,
+                            floor: head.floor,
+                        };
+                        !self.is_lethal(pos) && !self.is_portal(pos)
+                    })
+            })
+    }
+
+    /// A patrol route over open cells, either a line walked back and forth or
+    /// a small rectangle walked round in a loop. Like portals, routes keep off
+    /// the snek's starting row.
+    fn random_obstacle(&self, rng: &mut impl Rng) -> Option<Obstacle> {
+        const ATTEMPTS: usize = 1000;
+        const LINE_LENGTH: usize = 8;
+        (0..ATTEMPTS).find_map(|_| {
+            let start = self.random_empty_cell(rng)?;
+            let (legs, patrol) = if rng.gen_bool(0.5) {
+                let direction = if rng.gen_bool(0.5) {
+                    Direction::East
+                } else {
+                    Direction::South
+                };
+                (vec![(direction, LINE_LENGTH - 1)], Patrol::BackAndForth)
+            } else {
+                let legs = vec![
+                    (Direction::East, 4),
+                    (Direction::South, 2),
+                    (Direction::West, 4),
+                    (Direction::North, 2),
+                ];
+                (legs, Patrol::Loop)
+            };
+
+            let mut route = vec![start];
+            for (direction, steps) in legs {
+                for _ in 0..steps {
+                    let last = route[route.len() - 1];
+                    route.push(last.wrapped_add(direction.into(), self.size));
+                }
+            }
+            if patrol == Patrol::Loop {
+                // The last leg arrives back at the start
+                route.pop();
+            }
+
+            let clear = route.iter().all(|&pos| {
+                pos.y != self.snek.head.y
+                    && !self.is_lethal(pos)
+                    && !self.is_portal(pos)
+                    && !self.is_on_patrol_route(pos)
+                    && !self.tunnels.contains(&pos)
+                    && !self.moresels.iter().any(|m| m.pos == pos)
+            });
+            clear.then(|| Obstacle::new(route, patrol))
+        })
+    }
+
+    /// A free cell for a portal, kept off the snek's starting row so it
+    /// doesn't teleport before the player has a chance to react. `None` if
+    /// the arena is too crowded to find one.
+    fn random_portal_cell(&self, rng: &mut impl Rng) -> Option<Pos> {
+        const ATTEMPTS: usize = 1000;
+        (0..ATTEMPTS)
+            .filter_map(|_| self.random_empty_cell(rng))
+            .find(|&pos| {
+                let crowded = self.portals.iter().any(|pair| {
+                    pair.a.wrapped_distance(pos, self.size) < 3
+                        || pair.b.wrapped_distance(pos, self.size) < 3
+                });
+                pos.y != self.snek.head.y && !crowded
+            })
+    }
+
+    /// A random free cell on the floor the player is currently on, or `None`
+    /// if there isn't one left.
+    fn random_empty_cell(&self, rng: &mut impl Rng) -> Option<Pos> {
+        const ATTEMPTS: usize = 100;
+        let floor = self.snek.head.floor;
+        let xs = self.shrunk_by..self.size.width - self.shrunk_by;
+        let ys = self.shrunk_by..self.size.height - self.shrunk_by;
+        (0..ATTEMPTS)
+            .map(|_| Pos {
+                x: rng.gen_range(xs.clone()),
+                y: rng.gen_range(ys.clone()),
+                floor,
+            })
+            .find(|&pos| self.is_empty(pos))
+            .or_else(|| {
+                // Nearly full, so stop guessing and pick from what's left
+                ys.clone()
+                    .flat_map(|y| xs.clone().map(move |x| Pos { x, y, floor }))
+                    .filter(|&pos| self.is_empty(pos))
+                    .choose(rng)
+            })
+    }
+
+    /// The score so far, normalized to compare with games at other speeds.
+    fn normalized_score(&self) -> Option<f64> {
+        scoring::normalized(self.score, self.ticks, &self.rules)
+    }
+
+    /// The normalized score as a line for the summary screens, if there is one.
+    fn normalized_summary(&self) -> String {
+        self.normalized_score().map_or(String::new(), |normalized| {
+            format!("\nNormalized: {:.1} per minute", normalized)
+        })
+    }
+
+    /// How the game was won as a line for the victory screen, if there's
+    /// more to say than that it was.
+    fn victory_summary(&self) -> String {
+        if self.rules.royale && self.live_rivals().next().is_none() {
+            return "\nLast snek standing!".to_string();
+        }
+        if self.rules.moves.is_some() {
+            return format!("\nPuzzle solved in {} moves", self.ticks);
+        }
+        self.rules.target.map_or(String::new(), |target| {
+            format!("\nTarget {} reached", VictoryTarget::describe(Some(target)))
+        })
+    }
+
+    /// Whether nothing at all is on `pos`, so something new can go there.
+    fn is_empty(&self, pos: Pos) -> bool {
+        !self.is_occupied_by_snek(pos)
+            && !self.is_portal(pos)
+            && !self.wormholes.iter().any(|wormhole| wormhole.pos == pos)
+            && !self.walls.contains(&pos)
+            && !self.hazards.contains(&pos)
+            && !self.weeds.contains(&pos)
+            && !self.tunnels.contains(&pos)
+            && !self.pads.contains_key(&pos)
+            && !self.is_on_patrol_route(pos)
+            && !self.predators.contains(&pos)
+            && !self.moresels.iter().any(|m| m.pos == pos)
+    }
+
+    /// Scatter up to `count` single-point bonus morsels, leaving plenty of room
+    /// to move.
+    fn scatter_morsels(&mut self, count: usize, rng: &mut impl Rng) {
+        let room = (self.size.width as usize * self.size.height as usize) / 8;
+        for _ in 0..count.min(room) {
+            let Some(pos) = self.random_empty_cell(rng) else {
+                return;
+            };
+            let morsel = Morsel {
+                pos,
+                growth_value: 1,
+                kind: MorselKind::Bonus,
+                age: 0,
+            };
+            self.place_morsel(morsel);
+        }
+    }
+
+    /// Make sure there's always something to eat, since eating, respawning
+    /// and shrinking can all clear the only morsel.
+    fn top_up_morsels(&mut self, rng: &mut impl Rng) {
+        // A puzzle only has the morsels it was made with
+        if self.rules.trail || self.rules.moves.is_some() {
+            return;
+        }
+        if self.moresels.iter().all(|m| m.kind == MorselKind::Poison) {
+            match self.new_morsel(rng) {
+                Some(morsel) => self.place_morsel(morsel),
+                None => warn!("No room for a morsel"),
+            }
+        }
+    }
+
+    /// Change a rule at random for every chaos interval that's passed, and run
+    /// down the banner announcing the last change.
+    fn mutate(&mut self, rng: &mut impl Rng) {
+        if let Some((_, ticks_left)) = &mut self.mutation {
+            *ticks_left = ticks_left.saturating_sub(1);
+            if *ticks_left == 0 {
+                self.mutation = None;
+            }
+        }
+        let Some(interval) = self.rules.chaos_interval else {
+            return;
+        };
+        let due = (self.clock.as_millis() / interval.as_millis().max(1)) as u32;
+        while self.mutations < due {
+            self.mutations += 1;
+            let millis = self.rules.speed.base_millis;
+            let faster = if millis <= *CHAOS_SPEEDS.start() {
+                false
+            } else if millis >= *CHAOS_SPEEDS.end() {
+                true
+            } else {
+                rng.gen_bool(0.5)
+            };
+            let mut choices = vec![
+                Mutation::Speed { faster },
+                Mutation::Mirror(!self.mirrored),
+                Mutation::DoubleValue(!self.rules.double_value),
+            ];
+            // Walls springing up right in front of the snek would be too cruel
+            let head = self.snek.head;
+            let near_edge = head.x < 3
+                || head.y < 3
+                || head.x + 3 >= self.size.width
+                || head.y + 3 >= self.size.height;
+            match self.rules.edges {
+                EdgePolicy::Wrap if !near_edge => choices.push(Mutation::Edges(EdgePolicy::Walled)),
+                EdgePolicy::Walled if !self.chaos_walls.is_empty() => {
+                    choices.push(Mutation::Edges(EdgePolicy::Wrap))
+                }
+                _ => {}
+            }
+            let mutation = choices
+                .into_iter()
+                .choose(rng)
+                .expect("There's always a rule to change");
+            info!("Chaos: {}", mutation.text());
+            self.apply_mutation(mutation);
+            self.mutation = Some((mutation, CHAOS_BANNER_TICKS));
+        }
+    }
+
+    fn apply_mutation(&mut self, mutation: Mutation) {
+        match mutation {
+            Mutation::Edges(EdgePolicy::Wrap) => {
+                let chaos_walls = std::mem::take(&mut self.chaos_walls);
+                self.walls.retain(|pos| !chaos_walls.contains(pos));
+                self.rules.edges = EdgePolicy::Wrap;
+            }
+            Mutation::Edges(edges) => {
+                if self.tunnels.is_empty() {
+                    self.tunnels = Self::default_tunnels(self.size);
+                }
+                let new_walls: Vec<Pos> =
+                    Self::edge_walls(self.size, self.floors, EdgePolicy::Walled, &self.tunnels)
+                        .difference(&self.walls)
+                        .copied()
+                        .collect();
+                self.walls.extend(&new_walls);
+                self.chaos_walls.extend(new_walls);
+                let walls = &self.walls;
+                self.moresels.retain(|morsel| !walls.contains(&morsel.pos));
+                self.rules.edges = edges;
+            }
+            Mutation::Speed { faster } => {
+                let speed = &mut self.rules.speed;
+                let scale = |millis: u64| {
+                    let scaled = if faster {
+                        millis * 3 / 4
+                    } else {
+                        millis * 4 / 3
+                    };
+                    scaled.clamp(*CHAOS_SPEEDS.start(), *CHAOS_SPEEDS.end())
+                };
+                speed.base_millis = scale(speed.base_millis);
+                speed.min_millis = scale(speed.min_millis).min(speed.base_millis);
+            }
+            Mutation::Mirror(on) => {
+                self.rules.mutators.mirror = if on { Mirror::All } else { Mirror::Off };
+                self.mirrored = on;
+            }
+            Mutation::DoubleValue(on) => self.rules.double_value = on,
+        }
+    }
+
+    /// Apply each step of the escalation table the score has reached.
+    fn escalate_by_score(&mut self, rng: &mut impl Rng) {
+        while let Some(&step) = self.escalation.get(self.escalated) {
+            if self.score < step.score {
+                return;
+            }
+            self.escalated += 1;
+            info!("{}", step.escalation.describe());
+            match step.escalation {
+                Escalation::Obstacles(count) => {
+                    for _ in 0..count {
+                        match self.random_obstacle(rng) {
+                            Some(obstacle) => self.obstacles.push(obstacle),
+                            None => warn!("No room for another obstacle"),
+                        }
+                    }
+                }
+                Escalation::Predator => {
+                    // Far enough away to see it coming
+                    match (0..1000)
+                        .filter_map(|_| self.random_empty_cell(rng))
+                        .find(|pos| pos.wrapped_distance(self.snek.head, self.size) >= 10)
+                    {
+                        Some(pos) => self.predators.push(pos),
+                        None => warn!("No room for a predator"),
+                    }
+                }
+                Escalation::Fog(radius) => self.fog = Some(radius),
+            }
+        }
+    }
+
+    /// Add a hazard for every hazard interval that's passed, working through
+    /// a wall block, a poison morsel and a moving obstacle in turn.
+    fn escalate(&mut self, rng: &mut impl Rng) {
+        let Some(interval) = self.rules.hazard_interval else {
+            return;
+        };
+        let due = (self.clock.as_millis() / interval.as_millis().max(1)) as u32;
+        while self.escalations < due {
+            self.escalations += 1;
+            // Keep new hazards from landing right under the snek's nose
+            let Some(pos) = (0..1000)
+                .filter_map(|_| self.random_empty_cell(rng))
+                .find(|pos| pos.wrapped_distance(self.snek.head, self.size) >= 5)
+            else {
+                warn!("No room for another hazard");
+                return;
+            };
+            match self.escalations % 3 {
+                1 => {
+                    info!("A wall appears");
+                    self.walls.insert(pos);
+                }
+                2 => {
+                    info!("A poison morsel appears");
+                    self.place_morsel(Morsel {
+                        pos,
+                        growth_value: 1,
+                        kind: MorselKind::Poison,
+                        age: 0,
+                    });
+                }
+                _ => match self.random_obstacle(rng) {
+                    Some(obstacle) => {
+                        info!("A moving obstacle appears");
+                        self.obstacles.push(obstacle);
+                    }
+                    None => warn!("No room for another obstacle"),
+                },
+            }
+        }
+    }
+
+    /// A fresh morsel on a random free cell, if there's one left.
+    fn new_morsel(&self, rng: &mut impl Rng) -> Option<Morsel> {
+        let pos = self.random_empty_cell(rng)?;
+        Some(Morsel {
+            pos,
+            growth_value: self.rules.roll_morsel_value(rng),
+            kind: match self.rules.brittle_odds {
+                Some(odds) if rng.gen_ratio(1, odds) => MorselKind::Brittle,
+                _ if self.rules.mutators.traps && rng.gen_ratio(1, TRAP_ODDS) => MorselKind::Trap,
+                _ if self.rules.weed_interval.is_some() && rng.gen_ratio(1, SCYTHE_ODDS) => {
+                    MorselKind::Scythe
+                }
+                _ if self
+                    .rules
+                    .rewind_odds
+                    .is_some_and(|odds| rng.gen_ratio(1, odds)) =>
+                {
+                    MorselKind::Rewind
+                }
+                _ if self
+                    .rules
+                    .shield_odds
+                    .is_some_and(|odds| rng.gen_ratio(1, odds)) =>
+                {
+                    MorselKind::Shield
+                }
+                _ if self.walls.iter().any(|&pos| self.can_phase(pos))
+                    && rng.gen_ratio(1, PHASE_ODDS) =>
+                {
+                    MorselKind::Phase
+                }
+                _ => MorselKind::Normal,
+            },
+            age: 0,
+        })
+    }
+}
+
+/// Which part of a game is being played.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Round {
+    /// Normal play, counting down to the next bonus round
+    Normal { until_bonus: Duration },
+    /// The arena is full of snacks and nothing can kill the snek
+    Bonus { remaining: Duration },
+}
+
+impl Default for Round {
+    fn default() -> Self {
+        Round::Normal {
+            until_bonus: BONUS_ROUND_INTERVAL,
+        }
+    }
+}
+
+impl Round {
+    /// Count down by one tick's worth of `elapsed` time, starting or ending a
+    /// bonus round in `haus` when the time comes.
+    fn advance(&mut self, elapsed: Duration, haus: &mut SnekHaus, rng: &mut impl Rng) {
+        match self {
+            Round::Normal { until_bonus } => {
+                if haus.ghost {
+                    haus.solidify();
+                }
+                *until_bonus = until_bonus.saturating_sub(elapsed);
+                if until_bonus.is_zero() {
+                    info!("Bonus round!");
+                    haus.ghost = true;
+                    haus.scatter_morsels(BONUS_MORSELS, rng);
+                    *self = Round::Bonus {
+                        remaining: BONUS_ROUND_LENGTH,
+                    };
+                }
+            }
+            Round::Bonus { remaining } => {
+                *remaining = remaining.saturating_sub(elapsed);
+                if remaining.is_zero() {
+                    info!("Bonus round over");
+                    haus.moresels.retain(|m| m.kind != MorselKind::Bonus);
+                    haus.solidify();
+                    *self = Round::default();
+                }
+            }
+        }
+    }
+}
+
+/// What can be done from the pause menu.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PauseAction {
+    Resume,
+    Restart,
+    Settings,
+    Quit,
+}
+
+impl PauseAction {
+    const ALL: [PauseAction; 4] = [
+        PauseAction::Resume,
+        PauseAction::Restart,
+        PauseAction::Settings,
+        PauseAction::Quit,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            PauseAction::Resume => "Resume",
+            PauseAction::Restart => "Restart",
+            PauseAction::Settings => "Settings",
+            PauseAction::Quit => "Quit to menu",
+        }
+    }
+}
+
+#[derive(Debug)]
+enum GameState {
+    ReadyToStart,
+    Playing(SnekHaus, Round),
+    Paused(SnekHaus, Round),
+    GameOver {
+        haus: SnekHaus,
+        final_score: u16,
+    },
+    Victory {
+        haus: SnekHaus,
+        final_score: u16,
+    },
+    /// Choosing a level, where 0 is the open arena and the rest index `Game::levels`
+    LevelSelect {
+        selected: usize,
+    },
+    /// Choosing a campaign stage, indexing `campaign::STAGES`
+    StageSelect {
+        selected: usize,
+    },
+    /// Choosing a puzzle, indexing `Game::puzzles`
+    PuzzleSelect {
+        selected: usize,
+    },
+    /// Choosing a saved run to watch, from `runs`
+    ReplaySelect {
+        runs: Vec<PathBuf>,
+        selected: usize,
+    },
+    /// Choosing mutators, where `selected` indexes `Mutator::ALL`
+    Modifiers {
+        selected: usize,
+    },
+    /// Choosing a skin, indexing `Game::skins`
+    Skins {
+        selected: usize,
+    },
+    /// Watching the best moment of the game that just ended, before going back to `then`
+    Highlight {
+        playback: Box<Playback>,
+        title: String,
+        then: Box<GameState>,
+    },
+    /// Watching the whole of the game that just ended, at the viewer's own
+    /// pace, before going back to `then`
+    Replay {
+        viewer: Box<Viewer>,
+        then: Box<GameState>,
+    },
+    /// Time stopped in the practice sandbox, painting the arena with `brush`,
+    /// and morsels worth `value`
+    Sandbox {
+        haus: SnekHaus,
+        round: Round,
+        brush: Brush,
+        value: u16,
+    },
+    /// Looking at the trail art of a game, the one that just ended or a saved
+    /// one, described by `title`, before going back to `then`
+    TrailArt {
+        trail: Trail,
+        title: String,
+        then: Box<GameState>,
+    },
+    /// Looking at the high score table, before going back to `then`
+    HighScores {
+        then: Box<GameState>,
+    },
+    /// Picking settings on first launch, where `selected` indexes `Field::ALL`
+    /// and a bot plays in `preview` to show them off
+    Wizard {
+        selected: usize,
+        preview: Bot,
+    },
+    /// Changing settings from the menu or the pause overlay, where `selected`
+    /// indexes `Field::ALL`, before going back to `then`
+    Settings {
+        selected: usize,
+        then: Box<GameState>,
+    },
+    /// Entering names for a hotseat tournament, with the one being typed in `typing`
+    TournamentSetup {
+        names: Vec<String>,
+        typing: String,
+        rounds: usize,
+    },
+    /// The tournament table between turns
+    Standings,
+    /// A bot playing `demo` to draw players in, in kiosk mode
+    Attract {
+        demo: Bot,
+    },
+    /// Watching a game from `feed`, with no say in it. `live` goes false
+    /// once the feed has ended, leaving its last tick on screen.
+    Spectating {
+        feed: Box<dyn Feed>,
+        live: bool,
+    },
+    /// Picking how to use a very wide terminal
+    ArenaPrompt {
+        selected: usize,
+        /// Whether to start the game once it's picked
+        starting: bool,
+    },
+    Exit,
+}
+
+struct Game {
+    state: GameState,
+    /// The logo and its snek, on the start screen
+    title: Title,
+    /// The high score table of the mode being played
+    scores: Leaderboard,
+    /// Whether the game just finished beat the best score in the table
+    personal_best: bool,
+    /// The name last put to a high score, offered for the next one
+    player_name: String,
+    /// Where the game just finished went in the high score table, and the
+    /// name being typed for it
+    naming: Option<(usize, String)>,
+    /// When the countdown to the snek moving runs out
+    countdown: Option<Instant>,
+    /// Index into `PauseAction::ALL` of the pause menu's pick
+    pause_selected: usize,
+    arena_size: Option<Size>,
+    /// Where the sandbox's arena was last drawn, to find the cell under the mouse
+    sandbox_arena: Rect,
+    /// How to use a very wide terminal, once the player has picked
+    arena_preset: Option<ArenaPreset>,
+    mode: GameMode,
+    target: Option<VictoryTarget>,
+    levels: Vec<(PathBuf, Result<Level, LevelError>)>,
+    /// Index into `levels` of the chosen level, or `None` for the open arena
+    level: Option<usize>,
+    /// Why the last attempt to start a game failed
+    start_error: Option<String>,
+    hooks: Hooks,
+    skins: Vec<Skin>,
+    /// Index into `skins` of the player's skin
+    skin: usize,
+    /// Source of everything random in the game, seeded afresh for each one
+    rng: StdRng,
+    /// What `rng` was last seeded with, so a game can be reproduced
+    seed: u64,
+    campaign: Progress,
+    /// Skins, backgrounds and modifiers earned so far
+    unlocks: Unlocks,
+    /// Index into `campaign::STAGES` of the stage being played, if any
+    stage: Option<usize>,
+    puzzles: Vec<(PathBuf, Result<Puzzle, String>)>,
+    solved: Solved,
+    /// Index into `puzzles` of the puzzle being played, if any
+    puzzle: Option<usize>,
+    /// The puzzle as it was before each move so far, to undo back to
+    undo: Vec<Rewound>,
+    /// Whether the player has made a move in a puzzle that's still to be played
+    step: bool,
+    settings: Settings,
+    /// Mutators to add to the next game
+    mutators: Mutators,
+    /// The whole terminal, as of the last frame
+    screen: Rect,
+    /// Short messages waiting to show over the game, and the one showing
+    toasts: Toasts,
+    /// Whether the game being played has beaten the best score yet, and said so
+    paced: bool,
+    /// The game being played, or the last one
+    recording: Recording,
+    /// The last few ticks of the game being played, to rewind to
+    history: History,
+    /// The head's path through the game being played, kept if it's a new best
+    run: Ghost,
+    /// The best run in this mode, raced against as a ghost
+    best: Option<Ghost>,
+    /// The hotseat tournament being played, if any
+    tournament: Option<Tournament>,
+    /// How the last game in the open arena was started, to share as a run code
+    last_run: Option<RunCode>,
+    /// Seed for the next game, from a pasted run code
+    next_seed: Option<u64>,
+    /// Arena size for the next game, from a pasted run code
+    next_arena: Option<Size>,
+    system_clipboard: SystemClipboard,
+    /// What the last copy or paste did, or why it failed, shown on the screen it
+    /// was done from
+    clipboard: Option<Result<String, String>>,
+    /// When the boost key was pressed, while it's held
+    boost_pressed: Option<Instant>,
+    /// Whether the terminal reports key releases, so a held key is known to
+    /// be held until it's let go
+    key_releases: bool,
+    /// Play time since the player's last break
+    session: Session,
+    /// Running on a public demo terminal, if it's in kiosk mode
+    kiosk: Option<Kiosk>,
+    /// Where the player's settings, scores, ghosts and progress are kept
+    data_dir: PathBuf,
+}
+
+impl Game {
+    fn new(settings: Settings) -> Self {
+        Self::in_dir(settings, PathBuf::from("."))
+    }
+
+    /// A game keeping the player's files in `data_dir`.
+    fn in_dir(settings: Settings, data_dir: PathBuf) -> Self {
+        let mut skins = skin::load_dir(Path::new(skin::SKINS_DIR));
+        if settings.multiplexer_compat() {
+            skins = skins.into_iter().map(Skin::limited_to_256).collect();
+        }
+        let skin = Self::chosen_skin(&skins, &settings);
+        let seed = rand::random();
+        let mutators = Mutators {
+            edges: settings.edges,
+            ..Mutators::default()
+        };
+        Game {
+            state: GameState::ReadyToStart,
+            title: Title::default(),
+            scores: Self::load_high_scores(&data_dir, GameMode::default()),
+            personal_best: false,
+            player_name: Self::default_player_name(),
+            naming: None,
+            countdown: None,
+            pause_selected: 0,
+            arena_size: None,
+            sandbox_arena: Rect::default(),
+            arena_preset: settings.arena,
+            mode: GameMode::default(),
+            target: None,
+            levels: level::load_dir(Path::new(level::LEVELS_DIR), 3),
+            level: None,
+            start_error: None,
+            hooks: Hooks::load(Path::new(hooks::HOOKS_FILE)),
+            skins,
+            skin,
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            campaign: Progress::load(&data_dir.join(campaign::PROGRESS_FILE)),
+            unlocks: Unlocks::load(&data_dir.join(unlocks::UNLOCKS_FILE)),
+            stage: None,
+            puzzles: Vec::new(),
+            solved: Solved::load(&data_dir.join(puzzle::SOLVED_FILE)),
+            puzzle: None,
+            undo: Vec::new(),
+            step: false,
+            settings,
+            mutators,
+            screen: Rect::default(),
+            toasts: Toasts::default(),
+            paced: false,
+            recording: Recording::default(),
+            history: History::default(),
+            run: Ghost::default(),
+            best: None,
+            tournament: None,
+            last_run: None,
+            next_seed: None,
+            next_arena: None,
+            system_clipboard: SystemClipboard::default(),
+            clipboard: None,
+            boost_pressed: None,
+            key_releases: false,
+            session: Session::load(&data_dir.join(session::SESSION_FILE), session::now()),
+            kiosk: None,
+            data_dir,
+        }
+    }
+
+    /// Where the player's file called `name` is kept.
+    fn data_file(&self, name: &str) -> PathBuf {
+        self.data_dir.join(name)
+    }
+
+    /// Index into `skins` of the skin `settings` ask for.
+    fn chosen_skin(skins: &[Skin], settings: &Settings) -> usize {
+        settings
+            .skin
+            .as_ref()
+            .and_then(|name| skins.iter().position(|skin| skin.name == *name))
+            .unwrap_or(0)
+    }
+
+    /// Run as a kiosk until the unlock combination is pressed.
+    fn start_kiosk(&mut self) {
+        info!(
+            "Starting kiosk mode, {} to quit",
+            self.settings.kiosk_unlock
+        );
+        self.kiosk = Some(Kiosk::new(
+            self.settings.kiosk_unlock,
+            self.settings.clone(),
+        ));
+        self.attract();
+    }
+
+    /// Put every choice back to how the kiosk started, and let the bot play
+    /// until the next player comes along.
+    fn attract(&mut self) {
+        let Some(kiosk) = &self.kiosk else {
+            return;
+        };
+        self.settings = kiosk.settings.clone();
+        // Whoever walked away keeps their score, under the name offered
+        if let Some((_, typing)) = &mut self.naming {
+            typing.clear();
+            self.finish_naming();
+        }
+        self.skin = Self::chosen_skin(&self.skins, &self.settings);
+        self.mode = GameMode::default();
+        self.scores = Self::load_high_scores(&self.data_dir, self.mode);
+        self.target = None;
+        self.level = None;
+        self.mutators = Mutators {
+            edges: self.settings.edges,
+            ..Mutators::default()
+        };
+        self.arena_preset = self.settings.arena;
+        self.campaign = Progress::default();
+        self.stage = None;
+        self.tournament = None;
+        self.next_seed = None;
+        self.next_arena = None;
+        self.start_error = None;
+        self.clipboard = None;
+        let size = self.arena_size.unwrap_or(PREVIEW_ARENA);
+        self.state = GameState::Attract {
+            demo: self.new_bot(size),
+        };
+    }
+
+    /// Watch a computer snek play a game of its own in an arena the size
+    /// of the start screen's.
+    fn spectate(&mut self) -> GameState {
+        let size = self.arena_size.unwrap_or(PREVIEW_ARENA);
+        GameState::Spectating {
+            feed: Box::new(self.new_bot(size)),
+            live: true,
+        }
+    }
+
+    /// Open the welcome wizard, for a first launch.
+    fn welcome(&mut self) {
+        self.state = GameState::Wizard {
+            selected: 0,
+            preview: self.new_bot(PREVIEW_ARENA),
+        };
+    }
+
+    /// A computer snek playing a fresh game of `size`.
+    fn new_bot(&mut self, size: Size) -> Bot {
+        let mut haus = SnekHaus::new(size, 4, Rules::default());
+        haus.setup(&mut self.rng);
+        self.dress(&mut haus);
+        Bot::new(haus, StdRng::seed_from_u64(self.rng.gen()))
+    }
+
+    /// Draw `haus` the way the player's settings ask for.
+    fn dress(&self, haus: &mut SnekHaus) {
+        haus.skin = self.worn_skin();
+        haus.theme = self.theme();
+        haus.glyphs = self.settings.symbols.glyphs();
+        haus.background = match self.setting_goal(Field::Background) {
+            None => self.settings.background,
+            Some(_) => Background::None,
+        };
+        haus.danger_warning = self.settings.danger_warning;
+        haus.danger_cells = self.settings.danger_cells;
+    }
+
+    /// The player's colour theme, made readable on the terminal's background.
+    fn theme(&self) -> Theme {
+        self.settings.backdrop.adapt(self.settings.theme)
+    }
+
+    /// The skin the player's snek wears, as the settings have it.
+    fn worn_skin(&self) -> Skin {
+        // Anything still locked is swapped for the default until it's earned
+        let mut skin = match self.setting_goal(Field::Skin) {
+            None => self.skins[self.skin].clone(),
+            Some(_) => Skin::default(),
+        };
+        // The built in skin is whatever colour the theme says
+        if skin == Skin::default() {
+            skin.colours = vec![self.theme().snek];
+        }
+        match self.settings.head {
+            Head::Skin => {}
+            Head::Arrows => skin.head = None,
+            head => skin.head = head.symbol().map(str::to_string),
+        }
+        skin.tail_fade = self.settings.tail_fade;
+        skin
+    }
+
+    /// The menu's name for the value of `field`.
+    fn describe_setting(&self, field: Field) -> String {
+        let value = match field {
+            Field::Skin => self.skins[self.skin].name.clone(),
+            field => self.settings.describe(field),
+        };
+        match self.setting_goal(field) {
+            Some(goal) => format!("{} (locked: {})", value, goal.describe()),
+            None => value,
+        }
+    }
+
+    /// What it takes to unlock the value `field` is set to, if it's locked.
+    fn setting_goal(&self, field: Field) -> Option<Goal> {
+        match field {
+            Field::Skin => self.unlocks.skin_goal(&self.skins[self.skin].name),
+            Field::Background => self
+                .unlocks
+                .goal(Item::Background(self.settings.background)),
+            _ => None,
+        }
+    }
+
+    /// Unlock whatever the game just finished has earned, with a toast to
+    /// say so.
+    fn record_unlocks(&mut self, haus: &SnekHaus) {
+        // Practice doesn't count
+        if self.mode == GameMode::Sandbox {
+            return;
+        }
+        let earned = self
+            .unlocks
+            .record(self.mode, haus.score, haus.snek.length());
+        if earned.is_empty() {
+            return;
+        }
+        let names: Vec<String> = earned.iter().map(Item::name).collect();
+        info!("Unlocked {}", names.join(", "));
+        self.toasts
+            .push(format!("Unlocked {}!", names.join(" and ")));
+        if self.kiosk.is_none() {
+            self.unlocks.save(&self.data_file(unlocks::UNLOCKS_FILE));
+        }
+    }
+
+    fn cycle_setting(&mut self, field: Field, step: isize) {
+        match field {
+            Field::Skin => {
+                self.skin =
+                    (self.skin as isize + step).rem_euclid(self.skins.len() as isize) as usize;
+                self.settings.skin = Some(self.skins[self.skin].name.clone());
+            }
+            field => self.settings.cycle(field, step),
+        }
+        // The arena and edges are picked on the start screen too, so keep
+        // those in step
+        match field {
+            Field::Arena => self.arena_preset = self.settings.arena,
+            Field::Edges => self.mutators.edges = self.settings.edges,
+            _ => {}
+        }
+    }
+
+    /// Save the settings, with a toast if they couldn't be. A kiosk's
+    /// changes only last until the next player.
+    fn save_settings(&mut self) {
+        if self.kiosk.is_some() {
+            return;
+        }
+        if let Err(e) = self.settings.save(&self.data_file(settings::SETTINGS_FILE)) {
+            error!("Error saving settings: {}", e);
+            self.toasts.push(format!("Couldn't save settings: {}", e));
+        }
+    }
+
+    /// The skins menu: each skin's name beside a snek wearing it.
+    fn render_skins(&self, frame: &mut Frame, area: Rect, selected: usize) {
+        let block = self
+            .theme()
+            .block()
+            .title("Skins  (SPACE to wear, ESC to go back)");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let glyphs = self.settings.symbols.glyphs();
+        // Every other row, leaving a gap between the samples
+        let rows = inner.rows().skip(1).step_by(2);
+        // Scroll just far enough to keep the selected skin in view
+        let first = (selected + 1).saturating_sub(usize::from(inner.height / 2));
+        let skins = self.skins.iter().enumerate().skip(first);
+        for ((index, skin), row) in skins.zip(rows) {
+            let [label, sample] = Layout::horizontal([
+                Constraint::Length(SKIN_LABEL_WIDTH),
+                Constraint::Length(SKIN_SAMPLE_LENGTH),
+            ])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(row);
+            let name = match self.unlocks.skin_goal(&skin.name) {
+                Some(goal) => format!("{} (locked: {})", skin.name, goal.describe()),
+                None => skin.name.clone(),
+            };
+            let line = if index == selected {
+                Line::from(format!("> {}", name)).bold()
+            } else {
+                Line::from(format!("  {}", name))
+            };
+            frame.render_widget(line, label);
+            let skin = Skin {
+                tail_fade: self.settings.tail_fade,
+                ..skin.clone()
+            };
+            skin.draw_sample(sample, frame.buffer_mut(), &glyphs, self.theme().head);
+        }
+    }
+
+    fn chosen_level(&self) -> Option<&Level> {
+        self.level
+            .and_then(|index| self.levels.get(index))
+            .and_then(|(_, level)| level.as_ref().ok())
+    }
+
+    /// Start a game from the start screen, once there's nothing left to pick.
+    fn begin(&mut self) -> Option<GameState> {
+        if self.arena_preset.is_none()
+            && self.settings.arena_size.is_none()
+            && self.arena_size.is_some_and(ArenaPreset::wanted)
+        {
+            return Some(GameState::ArenaPrompt {
+                selected: 0,
+                starting: true,
+            });
+        }
+        if self.mode == GameMode::Campaign {
+            return Some(GameState::StageSelect {
+                selected: self.campaign.cleared.min(STAGES.len() - 1),
+            });
+        }
+        if self.mode == GameMode::Puzzle {
+            self.puzzles = puzzle::load_dir(Path::new(puzzle::PUZZLES_DIR));
+            if self.puzzles.is_empty() {
+                self.start_error = Some(format!(
+                    "There are no puzzles in the {} directory",
+                    puzzle::PUZZLES_DIR
+                ));
+                return None;
+            }
+            return Some(GameState::PuzzleSelect {
+                selected: self.first_unsolved(),
+            });
+        }
+        self.start_game()
+    }
+
+    /// Index into `puzzles` of the first puzzle still to solve, or the first
+    /// puzzle if they're all solved.
+    fn first_unsolved(&self) -> usize {
+        self.puzzles
+            .iter()
+            .position(|(_, puzzle)| {
+                puzzle
+                    .as_ref()
+                    .is_ok_and(|puzzle| !self.solved.is_solved(&puzzle.level.name))
+            })
+            .unwrap_or(0)
+    }
+
+    fn start_game(&mut self) -> Option<GameState> {
+        let rules = Rules {
+            target: self.target,
+            ..self.mode.rules()
+        };
+        self.stage = None;
+        self.puzzle = None;
+        self.launch(rules, self.chosen_level().cloned())
+    }
+
+    /// Start stage `index` of the campaign, on its own level.
+    fn start_stage(&mut self, index: usize) -> Option<GameState> {
+        let stage = STAGES[index];
+        let level = match stage.level {
+            Some(file) => {
+                let found = self.levels.iter().position(|(path, level)| {
+                    path.file_name().is_some_and(|name| name == file) && level.is_ok()
+                });
+                if found.is_none() {
+                    self.start_error = Some(format!(
+                        "{} needs {} from the {} directory",
+                        stage.name,
+                        file,
+                        level::LEVELS_DIR
+                    ));
+                    return None;
+                }
+                found.and_then(|index| self.levels[index].1.as_ref().ok().cloned())
+            }
+            None => None,
+        };
+        self.stage = Some(index);
+        self.puzzle = None;
+        self.launch(stage.rules(), level)
+    }
+
+    /// Start puzzle `index` of `puzzles`.
+    fn start_puzzle(&mut self, index: usize) -> Option<GameState> {
+        let puzzle = match &self.puzzles[index].1 {
+            Ok(puzzle) => puzzle.clone(),
+            Err(e) => {
+                self.start_error = Some(format!(
+                    "{} can't be played: {}",
+                    self.puzzles[index].0.display(),
+                    e
+                ));
+                return None;
+            }
+        };
+        self.stage = None;
+        self.puzzle = Some(index);
+        self.undo.clear();
+        self.step = false;
+        self.launch(puzzle.rules(), Some(puzzle.level))
+    }
+
+    /// Start playing by `rules`, on `level` or the open arena.
+    fn launch(&mut self, rules: Rules, level: Option<Level>) -> Option<GameState> {
+        let mut available = self.arena_size.expect("Arena size not initialized");
+        if rules.royale {
+            available.width = available.width.saturating_sub(SCOREBOARD_WIDTH);
+        }
+        if self.settings.stats {
+            available.width = available.width.saturating_sub(STATS_WIDTH);
+        }
+        let size = self
+            .arena_preset
+            .map_or(available, |preset| preset.size(available));
+        // A fixed size keeps scores comparable between terminals, and a run
+        // code brings its own
+        let open = self
+            .next_arena
+            .take()
+            .or(self.settings.arena_size)
+            .unwrap_or(size);
+        // Puzzles are played as they were made, without modifiers
+        let mutators = if rules.moves.is_some() {
+            Mutators::default()
+        } else {
+            self.mutators
+        };
+        let mut rules = rules.with_mutators(mutators);
+        let daily = self.mode == GameMode::Daily;
+        // The daily challenge is played at the same speed and length by everyone
+        let mut length = 3;
+        if rules.moves.is_some() {
+            length = puzzle::LENGTH;
+        } else if !daily {
+            rules = rules.tuned(
+                self.settings.speed,
+                self.settings.growth,
+                self.settings.morsel_weights,
+            );
+            length = self.settings.start_length;
+        }
+        let mut haus = match &level {
+            // Levels are ignored, so the daily layout is the same for everyone
+            _ if daily && !split::fits(size.into(), daily::ARENA) => {
+                self.start_error = Some(format!(
+                    "The daily challenge needs a {}x{} arena but only {}x{} fits, try a bigger terminal",
+                    daily::ARENA.width, daily::ARENA.height, size.width, size.height
+                ));
+                return None;
+            }
+            _ if daily => SnekHaus::new(daily::ARENA, 3, rules),
+            Some(level) if !split::fits(size.into(), level.size) => {
+                self.start_error = Some(format!(
+                    "{} needs a {}x{} arena but only {}x{} fits, try a bigger terminal",
+                    level.name, level.size.width, level.size.height, size.width, size.height
+                ));
+                return None;
+            }
+            Some(level) if !level.fits_snek(length) => {
+                self.start_error = Some(format!(
+                    "{} has no room for a snek of length {}, try a shorter starting length",
+                    level.name, length
+                ));
+                return None;
+            }
+            Some(level) => SnekHaus::from_level(level, length, rules),
+            None if !split::fits(size.into(), open) => {
+                self.start_error = Some(format!(
+                    "The {}x{} arena from your settings doesn't fit, only {}x{} does, try a bigger terminal or a smaller arena",
+                    open.width, open.height, size.width, size.height
+                ));
+                return None;
+            }
+            None if length >= open.width => {
+                self.start_error = Some(format!(
+                    "A snek of length {} doesn't fit in a {}x{} arena, try a shorter starting length",
+                    length, open.width, open.height
+                ));
+                return None;
+            }
+            None => SnekHaus::new(open, length, rules),
+        };
+        self.dress(&mut haus);
+        self.seed = if daily {
+            daily::today()
+        } else if let Some(seed) = self.tournament.as_ref().and_then(Tournament::next_seed) {
+            seed
+        } else if let Some(seed) = self.next_seed.take() {
+            seed
+        } else {
+            rand::random()
+        };
+        // Levels and the daily challenge lay out their own arena
+        self.last_run = (level.is_none() && !daily).then(|| RunCode {
+            mode: self.mode.slug().to_string(),
+            size: haus.size,
+            speed: self.settings.speed.slug().to_string(),
+            start_length: length,
+            growth: haus.rules.growth.unwrap_or(1),
+            morsel_weights: settings::morsel_weights_slug(self.settings.morsel_weights),
+            mutators: haus.rules.mutators.bits(),
+            seed: self.seed,
+        });
+        self.rng = StdRng::seed_from_u64(self.seed);
+        if !daily {
+            haus.escalation =
+                escalation::load(Path::new(escalation::ESCALATION_DIR), self.mode.slug());
+        }
+        haus.setup(&mut self.rng);
+        self.start_error = None;
+        self.boost_pressed = None;
+        self.paced = false;
+        self.personal_best = false;
+        self.recording = Recording::start(&haus, Round::default(), &self.rng);
+        self.history = History::default();
+        self.history.push(0, &haus, Round::default(), &self.rng);
+        // Practice runs are neither raced against nor kept
+        self.best = Some(self.data_file(&self.mode.ghost_file()))
+            .filter(|_| self.mode != GameMode::Sandbox)
+            .and_then(|path| Ghost::load(&path));
+        self.run = Ghost::new(haus.size);
+        self.run.record(0, haus.snek.head);
+        haus.ghost_head = self.best.as_ref().and_then(|best| best.head(0, haus.size));
+        // A puzzle waits for the first move anyway
+        if haus.rules.moves.is_none() {
+            self.countdown = Some(Instant::now() + COUNTDOWN);
+        }
+
+        Some(GameState::Playing(haus, Round::default()))
+    }
+
+    /// Time left before the snek moves, if it's still being counted down.
+    fn countdown_left(&self) -> Option<Duration> {
+        if !matches!(self.state, GameState::Playing(..)) {
+            return None;
+        }
+        self.countdown?.checked_duration_since(Instant::now())
+    }
+
+    fn counting_down(&self) -> bool {
+        self.countdown_left().is_some()
+    }
+
+    fn load_high_scores(data_dir: &Path, mode: GameMode) -> Leaderboard {
+        Leaderboard::load(&data_dir.join(mode.high_score_file()), mode.slug())
+    }
+
+    /// The best score in the table.
+    fn high_score(&self) -> u16 {
+        self.scores.best()
+    }
+
+    /// The name offered for a first high score: the player's login, if
+    /// there's one to find.
+    fn default_player_name() -> String {
+        std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok()
+            .map(|name| {
+                name.trim()
+                    .chars()
+                    .take(leaderboard::MAX_NAME_LENGTH)
+                    .collect()
+            })
+            .filter(|name: &String| name.chars().count() >= leaderboard::MIN_NAME_LENGTH)
+            .unwrap_or_else(|| "Player".to_string())
+    }
+
+    /// Slide the snek's head `progress` of the way through the tick, if the
+    /// settings ask for smooth motion.
+    fn set_motion(&mut self, progress: f32) {
+        if let GameState::Playing(haus, _) = &mut self.state {
+            haus.motion = self.settings.smooth_motion.then_some(progress);
+        }
+    }
+
+    fn tick_rate(&self) -> Duration {
+        match &self.state {
+            GameState::Playing(haus, _) | GameState::Paused(haus, _) => haus.tick_rate(),
+            GameState::Highlight { playback, .. } => playback.haus.tick_rate(),
+            GameState::Replay { viewer, .. } => viewer.playback.haus.tick_rate(),
+            GameState::Attract { demo } => demo.haus().tick_rate(),
+            GameState::Spectating { feed, .. } => feed.haus().tick_rate(),
+            GameState::Wizard { .. } => {
+                Duration::from_millis(self.settings.speed.scale(SpeedCurve::default().base_millis))
+            }
+            _ => SpeedCurve::default().tick_rate(0),
+        }
+    }
+
+    /// Put the game just finished in the high score table, if it's good
+    /// enough, returning where it went.
+    fn update_high_score(&mut self, haus: &SnekHaus) -> Option<usize> {
+        // Practice doesn't count
+        if self.mode == GameMode::Sandbox {
+            self.personal_best = false;
+            return None;
+        }
+        let best = self.high_score();
+        self.personal_best = haus.score > best;
+        let name = self
+            .tournament_player()
+            .map_or_else(|| self.player_name.clone(), str::to_string);
+        let rank = self.scores.record(Entry {
+            score: haus.score,
+            mode: self.mode.slug().to_string(),
+            date: daily::date(daily::today()),
+            name,
+        });
+        if haus.score > best {
+            self.run.save(&self.data_file(&self.mode.ghost_file()));
+            self.hooks.run(HookEvent::HighScore, &self.hook_env(haus));
+        }
+        rank
+    }
+
+    /// Ask for a name for the score that made the table at `rank`, if it
+    /// did, offering the one it was given.
+    fn ask_name(&mut self, rank: Option<usize>) {
+        self.naming = rank.map(|rank| (rank, self.scores.entries()[rank].name.clone()));
+    }
+
+    /// Save the high score table with the name typed so far, or the one
+    /// offered if there's too little of it.
+    fn finish_naming(&mut self) {
+        let Some((rank, typing)) = self.naming.take() else {
+            return;
+        };
+        let name = typing.trim();
+        if name.chars().count() >= leaderboard::MIN_NAME_LENGTH {
+            self.scores.rename(rank, name);
+            if self.tournament.is_none() {
+                self.player_name = name.to_string();
+            }
+        }
+        self.scores
+            .save(&self.data_file(&self.mode.high_score_file()));
+    }
+
+    /// The high score table.
+    fn high_score_lines(&self) -> Vec<Line<'_>> {
+        if self.scores.entries().is_empty() {
+            return vec![Line::from("No high scores yet")];
+        }
+        let mode_name = |slug: &str| {
+            GameMode::from_slug(slug).map_or(slug.to_string(), |mode| mode.name().to_string())
+        };
+        self.scores
+            .table(mode_name)
+            .into_iter()
+            .map(Line::from)
+            .collect()
+    }
+
+    /// Details of the game just finished, for hook commands.
+    fn hook_env(&self, haus: &SnekHaus) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("SNEKRS_SCORE", haus.score.to_string()),
+            ("SNEKRS_HIGH_SCORE", self.high_score().to_string()),
+            ("SNEKRS_MODE", self.mode.slug().to_string()),
+        ];
+        if let Some(normalized) = haus.normalized_score() {
+            env.push(("SNEKRS_NORMALIZED_SCORE", format!("{:.1}", normalized)));
+        }
+        env
+    }
+
+    /// Paint or clear the cell under the mouse in the sandbox.
+    fn handle_mouse(&mut self, mouse: event::MouseEvent) {
+        let GameState::Sandbox {
+            haus, brush, value, ..
+        } = &mut self.state
+        else {
+            return;
+        };
+        let arena = self.sandbox_arena;
+        if !arena.contains(Position::new(mouse.column, mouse.row)) {
+            return;
+        }
+        let pos = Pos {
+            x: mouse.column - arena.x,
+            y: mouse.row - arena.y,
+            floor: haus.snek.head.floor,
+        };
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left) => {
+                sandbox::paint(haus, pos, *brush, *value);
+            }
+            MouseEventKind::Down(MouseButton::Right) | MouseEventKind::Drag(MouseButton::Right) => {
+                sandbox::clear(haus, pos);
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether the boost key is being held down.
+    fn boost_held(&self) -> bool {
+        self.boost_pressed
+            .is_some_and(|pressed| self.key_releases || pressed.elapsed() < BOOST_HOLD)
+    }
+
+    /// Handle a key being let go, on terminals that report it.
+    fn handle_release(&mut self, key: event::KeyEvent) {
+        if key.code == event::KeyCode::Char('b') {
+            self.boost_pressed = None;
+        }
+    }
+
+    /// Copy the last game's run code to the clipboard.
+    fn copy_run_code(&mut self) {
+        self.clipboard = Some(match &self.last_run {
+            Some(code) => self
+                .system_clipboard
+                .copy(&code.to_string())
+                .map(|_| format!("Copied run code {}", code)),
+            None => Err("Play a game in the open arena first to get a run code".to_string()),
+        });
+    }
+
+    /// Set up the next game from a run code on the clipboard.
+    fn paste_run_code(&mut self) {
+        let pasted = self
+            .system_clipboard
+            .paste()
+            .and_then(|text| RunCode::parse(&text))
+            .and_then(|code| self.use_run_code(code));
+        self.clipboard = Some(pasted);
+    }
+
+    /// Set up the next game from `code`. The mode, modifiers and arena come
+    /// from the code, but the speed, starting length and growth are settings,
+    /// so they have to match it already.
+    fn use_run_code(&mut self, code: RunCode) -> Result<String, String> {
+        let (mode, mutators, speed, morsel_weights) = Self::run_setup(&code)?;
+        if (speed, code.start_length, code.growth, morsel_weights)
+            != (
+                self.settings.speed,
+                self.settings.start_length,
+                self.settings.growth,
+                self.settings.morsel_weights,
+            )
+        {
+            return Err(format!(
+                "That run was played at {} speed, starting length {}, growth {} and morsel weights {}, change your settings to match",
+                speed.name(),
+                code.start_length,
+                code.growth,
+                code.morsel_weights
+            ));
+        }
+        self.mode = mode;
+        self.scores = Self::load_high_scores(&self.data_dir, mode);
+        self.mutators = mutators;
+        self.level = None;
+        self.next_arena = Some(code.size);
+        self.next_seed = Some(code.seed);
+        Ok(format!("The next game replays {}", code))
+    }
+
+    /// The mode, modifiers, speed and morsel weights `code` was played with.
+    fn run_setup(
+        code: &RunCode,
+    ) -> Result<(GameMode, Mutators, settings::Speed, Option<MorselWeights>), String> {
+        let mode = match GameMode::from_slug(&code.mode) {
+            Some(GameMode::Daily) => {
+                return Err("The daily challenge is already the same for everyone".to_string())
+            }
+            Some(mode) => mode,
+            None => return Err(format!("Unknown mode '{}' in run code", code.mode)),
+        };
+        let mutators = Mutators::from_bits(code.mutators)
+            .ok_or_else(|| format!("Unknown modifiers '{:04x}' in run code", code.mutators))?;
+        let speed = settings::Speed::parse(&code.speed)
+            .map_err(|e| format!("Unknown speed in run code: {}", e))?;
+        let morsel_weights = settings::parse_morsel_weights(&code.morsel_weights)
+            .map_err(|e| format!("Unknown morsel weights in run code: {}", e))?;
+        Ok((mode, mutators, speed, morsel_weights))
+    }
+
+    /// Whether the game just played can be saved to watch again, which needs
+    /// a run code to set it up from. The campaign's stages and the sandbox's
+    /// painting aren't in the code.
+    fn run_saveable(&self) -> bool {
+        self.last_run.is_some() && !matches!(self.mode, GameMode::Campaign | GameMode::Sandbox)
+    }
+
+    /// Save the game just played to the replays directory.
+    fn save_run(&mut self) {
+        let message = match &self.last_run {
+            _ if self.kiosk.is_some() => "Saving is switched off on this kiosk".to_string(),
+            Some(run) if self.run_saveable() => {
+                match self
+                    .recording
+                    .saved(run.clone())
+                    .save(Path::new(replay::REPLAYS_DIR))
+                {
+                    Ok(path) => format!("Run saved to {}", path.display()),
+                    Err(e) => {
+                        error!("Error saving the run: {}", e);
+                        format!("Couldn't save the run: {}", e)
+                    }
+                }
+            }
+            _ => "Only games with a run code can be saved".to_string(),
+        };
+        self.toasts.push(message);
+    }
+
+    /// Load a saved run and play it all out again, set up the way its run
+    /// code says.
+    fn load_run(&self, path: &Path) -> Result<Recording, String> {
+        let saved = SavedRun::load(path)?;
+        let code = &saved.run;
+        let (mode, mutators, speed, morsel_weights) = Self::run_setup(code)?;
+        if code.start_length >= code.size.width {
+            return Err(format!(
+                "A snek of length {} doesn't fit in a {}x{} arena",
+                code.start_length, code.size.width, code.size.height
+            ));
+        }
+        let rules = mode
+            .rules()
+            .with_mutators(mutators)
+            .tuned(speed, code.growth, morsel_weights);
+        let mut haus = SnekHaus::new(code.size, code.start_length, rules);
+        self.dress(&mut haus);
+        haus.escalation = escalation::load(Path::new(escalation::ESCALATION_DIR), mode.slug());
+        let mut rng = StdRng::seed_from_u64(code.seed);
+        haus.setup(&mut rng);
+        Ok(Recording::replay(&saved, haus, rng))
+    }
+
+    /// Copy the level file behind entry `selected` of the level list.
+    fn copy_level(&mut self, selected: usize) {
+        self.clipboard = Some(match selected.checked_sub(1) {
+            Some(index) => fs::read_to_string(&self.levels[index].0)
+                .map_err(|e| format!("Couldn't read the level: {}", e))
+                .and_then(|text| self.system_clipboard.copy(&text))
+                .map(|_| "Copied the level".to_string()),
+            None => Err("The open arena has no level file to copy".to_string()),
+        });
+    }
+
+    /// Save a level from the clipboard into the levels directory, returning
+    /// its entry in the level list.
+    fn paste_level(&mut self) -> Option<usize> {
+        if self.kiosk.is_some() {
+            self.clipboard = Some(Err("Levels can't be saved on this kiosk".to_string()));
+            return None;
+        }
+        let pasted = self.system_clipboard.paste().and_then(|text| {
+            let level = Level::parse(&text, "Pasted level", 3)
+                .map_err(|e| format!("Not a valid level: {}", e))?;
+            level::save_new(Path::new(level::LEVELS_DIR), &level, &text)
+                .map_err(|e| format!("Couldn't save the level: {}", e))
+        });
+        match pasted {
+            Ok(path) => {
+                self.levels = level::load_dir(Path::new(level::LEVELS_DIR), 3);
+                self.clipboard = Some(Ok(format!("Saved as {}", path.display())));
+                self.levels
+                    .iter()
+                    .position(|(saved, _)| *saved == path)
+                    .map(|index| index + 1)
+            }
+            Err(e) => {
+                self.clipboard = Some(Err(e));
+                None
+            }
+        }
+    }
+
+    /// The outcome of the last copy or paste, for the screen it was done from.
+    fn clipboard_line(&self) -> Option<Line<'_>> {
+        match &self.clipboard {
+            Some(Ok(message)) => Some(Line::from(message.as_str()).green()),
+            Some(Err(error)) => Some(Line::from(error.as_str()).red()),
+            None => None,
+        }
+    }
+
+    /// Name of whoever's turn it is in the tournament, if one is on.
+    fn tournament_player(&self) -> Option<&str> {
+        let tournament = self.tournament.as_ref()?;
+        let (player, _) = tournament.next_turn()?;
+        Some(&tournament.players[player].name)
+    }
+
+    /// Score a finished game for the tournament, if one is on.
+    fn record_turn(&mut self, final_score: u16) {
+        if let Some(tournament) = &mut self.tournament {
+            tournament.record(final_score);
+        }
+    }
+
+    /// End the game at the player's asking, counting it like any other.
+    fn quit(&mut self, haus: SnekHaus) -> GameState {
+        let final_score = haus.score;
+        self.record_turn(final_score);
+        self.record_session(&haus);
+        GameState::GameOver { haus, final_score }
+    }
+
+    /// Count the game just played towards the time since the last break.
+    fn record_session(&mut self, haus: &SnekHaus) {
+        self.session.record_game(haus.clock, session::now());
+        self.session.save(&self.data_file(session::SESSION_FILE));
+    }
+
+    /// Where to go once a game's over: the standings in a tournament, or
+    /// back to the stages or puzzles to retry or move on, or otherwise the
+    /// start screen.
+    fn menu(&self) -> GameState {
+        if self.tournament.is_some() {
+            GameState::Standings
+        } else if self.stage.is_some() {
+            GameState::StageSelect {
+                selected: self.campaign.cleared.min(STAGES.len() - 1),
+            }
+        } else if self.puzzle.is_some() {
+            GameState::PuzzleSelect {
+                selected: self.first_unsolved(),
+            }
+        } else {
+            GameState::ReadyToStart
+        }
+    }
+
+    /// Start the game being played over again: the same stage, puzzle or mode.
+    fn restart(&mut self) -> Option<GameState> {
+        match (self.stage, self.puzzle) {
+            (Some(stage), _) => self.start_stage(stage),
+            (_, Some(puzzle)) => self.start_puzzle(puzzle),
+            _ => self.start_game(),
+        }
+    }
+
+    /// What SPACE does from the summary screens.
+    fn play_again_prompt(&self) -> &'static str {
+        if self.tournament.is_some() {
+            "Press SPACE for the standings"
+        } else {
+            "Press SPACE to play again"
+        }
+    }
+
+    /// The panel beside a dual view's arena, with the details of the run.
+    fn run_details(&self, haus: &SnekHaus) -> Paragraph<'_> {
+        let mut lines = vec![
+            Line::from(format!("Mode: {}", self.mode.name())),
+            Line::from(format!("Score: {}", haus.score)),
+            Line::from(format!("High score: {}", self.high_score())),
+            Line::from(format!("Length: {}", haus.snek.length())),
+            Line::from(format!("Time: {}s", haus.clock.as_secs())),
+            Line::from(format!("Modifiers: {}", haus.rules.mutators.summary())),
+        ];
+        if let Some(code) = &self.last_run {
+            lines.push(Line::from(format!("Run code: {}", code)));
+        }
+        Paragraph::new(lines).block(Block::default().title("Run").borders(Borders::ALL))
+    }
+
+    /// The panel beside a battle royale's arena, with every snek's score.
+    fn scoreboard(&self, haus: &SnekHaus) -> Paragraph<'_> {
+        let left = haus.live_rivals().count();
+        let mut lines = vec![
+            Line::from(format!("Rivals left: {}", left)),
+            Line::from(""),
+            Line::from(format!("You: {}", haus.score)).bold(),
+        ];
+        for (index, rival) in haus.rivals.iter().enumerate() {
+            let line = Line::from(format!("Snek {}: {}", index + 1, rival.score));
+            if rival.alive {
+                lines.push(line.light_blue());
+            } else {
+                lines.push(line.dark_gray().crossed_out());
+            }
+        }
+        Paragraph::new(lines).block(Block::default().title("Sneks").borders(Borders::ALL))
+    }
+
+    /// The sidebar beside the arena, with how the run is going.
+    fn stats(&self, haus: &SnekHaus) -> Paragraph<'_> {
+        let lines = vec![
+            Line::from(format!("Length: {}", haus.snek.length())),
+            Line::from(format!(
+                "Speed: {:.1}/s",
+                1.0 / haus.tick_rate().as_secs_f64()
+            )),
+            Line::from(format!("Time: {}s", haus.clock.as_secs())),
+            Line::from(format!("Eaten: {}", haus.eaten)),
+            Line::from(format!("Growing: {}", haus.snek.pending_growth)),
+        ];
+        Paragraph::new(lines).block(Block::default().title("Stats").borders(Borders::ALL))
+    }
+
+    /// The panel under the summary screens, with how the run went.
+    fn run_stats(&self, haus: &SnekHaus) -> Paragraph<'_> {
+        let mut lines = vec![Line::from(format!(
+            "Morsels eaten: {}",
+            haus.noms.values().sum::<u32>()
+        ))];
+        for (worth, count) in &haus.noms {
+            lines.push(Line::from(format!("  worth {}: {}", worth, count)));
+        }
+        let secs = haus.clock.as_secs_f64();
+        let speed = if secs > 0.0 {
+            haus.ticks as f64 / secs
+        } else {
+            0.0
+        };
+        lines.extend([
+            Line::from(format!("Longest: {}", haus.longest)),
+            Line::from(format!("Time: {}s", haus.clock.as_secs())),
+            Line::from(format!("Average speed: {:.1}/s", speed)),
+        ]);
+        // Practice doesn't count towards a best
+        if self.mode != GameMode::Sandbox {
+            lines.push(if self.personal_best {
+                Line::from("New personal best!").green().bold()
+            } else {
+                Line::from(format!("Personal best: {}", self.high_score()))
+            });
+        }
+        Paragraph::new(lines).block(Block::default().title("Run stats").borders(Borders::ALL))
+    }
+
+    /// Draw the run's stats along the bottom of `area`, under the summary.
+    fn render_run_stats(&self, frame: &mut Frame, area: Rect, haus: &SnekHaus) {
+        // Borders, the total, one line per worth, then three more and the best
+        // outside practice
+        let best = u16::from(self.mode != GameMode::Sandbox);
+        let height = haus.noms.len() as u16 + 6 + best;
+        let [_, bottom] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(height)]).areas(area);
+        let [panel] = Layout::horizontal([Constraint::Length(RUN_STATS_WIDTH)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(bottom);
+        frame.render_widget(Clear, panel);
+        frame.render_widget(self.run_stats(haus), panel);
+    }
+
+    /// Whether to suggest a break, which happens between games once the
+    /// player has played as long as their settings allow.
+    fn break_due(&self) -> bool {
+        matches!(
+            self.state,
+            GameState::GameOver { .. } | GameState::Victory { .. }
+        ) && self.naming.is_none()
+            && self.settings.break_reminder.is_some_and(|minutes| {
+                self.session
+                    .break_due(Duration::from_secs(minutes as u64 * 60))
+            })
+    }
+
+    /// A line offering the last game's replay, to watch or save, and its
+    /// highlight if it had one.
+    fn replay_prompt(&self) -> String {
+        let save = if self.run_saveable() {
+            ", E to save it"
+        } else {
+            ""
+        };
+        self.recording.highlight().map_or(
+            format!("Press W to watch the replay{}\n", save),
+            |highlight| {
+                format!(
+                    "Press W to watch the replay{}, R for the highlight: {}\n",
+                    save,
+                    highlight.describe()
+                )
+            },
+        )
+    }
+
+    /// Save a bug report bundle to `dir`, returning where it went.
+    fn save_bug_report(&mut self, dir: &Path) -> io::Result<PathBuf> {
+        // Draw the screen again off to the side, to see what the player sees
+        let mut terminal = Terminal::new(backend::TestBackend::new(
+            self.screen.width,
+            self.screen.height,
+        ))?;
+        let frame = bugreport::frame_text(terminal.draw(|f| self.render(f))?.buffer);
+        let config = format!(
+            "Version: {}\nMode: {:?}\nTarget: {:?}\nLevel: {}\nSkin: {}\nMutators: {:?}\nSettings: {:?}",
+            env!("CARGO_PKG_VERSION"),
+            self.mode,
+            self.target,
+            self.chosen_level().map_or("Open arena", |l| l.name.as_str()),
+            self.skins[self.skin].name,
+            self.mutators,
+            self.settings,
+        );
+        let sections = [
+            ("Frame", frame),
+            ("Seed", self.seed.to_string()),
+            ("Config", config),
+            ("State", format!("{:#?}", self.state)),
+            ("Log", bugreport::log_tail(Path::new(LOG_FILE))),
+        ];
+        bugreport::write(dir, &sections)
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        self.screen = frame.area();
+        // Text is in the theme's colour unless something says otherwise
+        let theme = self.theme();
+        frame
+            .buffer_mut()
+            .set_style(self.screen, Style::default().fg(theme.text));
+        let score_text = match &self.state {
+            GameState::Playing(haus, _)
+            | GameState::Paused(haus, _)
+            | GameState::Sandbox { haus, .. } => {
+                let mut text = format!(
+                    "SNEK    High Score: {}    Score: {}",
+                    self.high_score(),
+                    haus.score
+                );
+                if let Some(player) = self.tournament_player() {
+                    text.push_str(&format!("    Player: {}", player));
+                }
+                if haus.rules.lives.is_some() {
+                    text.push_str(&format!(
+                        "    Lives: {}",
+                        haus.glyphs.life.repeat(haus.lives as usize)
+                    ));
+                }
+                if haus.rewinds > 0 {
+                    text.push_str(&format!(
+                        "    Rewind (Z): {}",
+                        haus.glyphs.rewind.repeat(haus.rewinds as usize)
+                    ));
+                }
+                if haus.shield {
+                    text.push_str(&format!("    Shield: {}", haus.glyphs.shield));
+                }
+                if haus.phasing > 0 {
+                    text.push_str(&format!("    Phasing: {}", haus.phasing));
+                }
+                if let Some(moves) = haus.rules.moves {
+                    text.push_str(&format!("    Moves: {}/{} (U to undo)", haus.ticks, moves));
+                }
+                // A battle royale has a scoreboard of its own
+                for rival in haus.rivals.iter().filter(|_| !haus.rules.royale) {
+                    let status = if rival.alive { "" } else { " (crashed)" };
+                    text.push_str(&format!("    Rival: {}{}", rival.score, status));
+                }
+                text
+            }
+            GameState::Spectating { feed, .. } => {
+                let haus = feed.haus();
+                format!(
+                    "SPECTATING {}    Score: {}    Length: {}",
+                    feed.name(),
+                    haus.score,
+                    haus.snek.length()
+                )
+            }
+            _ => {
+                format!("SNEK    High Score: {}", self.high_score())
+            }
+        };
+
+        // Keep clear of the edge cells that some terminals clip
+        let size = frame.area().inner(Margin {
+            horizontal: self.settings.margin,
+            vertical: self.settings.margin,
+        });
+        let layout = Layout::default()
+            .direction(layout::Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // Title + High Score
+                Constraint::Min(0),    // Game area
+            ])
+            .split(size);
+
+        // Render title area with high score
+        let header = theme.block();
+        let header_inner = header.inner(layout[0]);
+        let score_at = score_text.find("    Score: ").map(|at| at + 4);
+        frame.render_widget(
+            Paragraph::new(score_text)
+                .alignment(Alignment::Left)
+                .block(header),
+            layout[0],
+        );
+        // The score pulses after a nom
+        if let (GameState::Playing(haus, _), Some(at)) = (&self.state, score_at) {
+            if haus.flash > 0 {
+                let width = format!("Score: {}", haus.score).len() as u16;
+                let pulse = Rect::new(header_inner.x + at as u16, header_inner.y, width, 1)
+                    .intersection(header_inner);
+                frame.buffer_mut().set_style(
+                    pulse,
+                    Style::default().fg(Color::Black).bg(theme.morsel).bold(),
+                );
+            }
+        }
+
+        // Speed, hunger and progress towards the target, on the right of the header
+        if let GameState::Playing(haus, round) | GameState::Paused(haus, round) = &self.state {
+            let mut gauges = Vec::new();
+            // Puzzles go at the player's pace
+            if haus.rules.moves.is_none() {
+                let tick = haus.tick_rate();
+                let ratio = (FASTEST_TICK.as_secs_f64() / tick.as_secs_f64()).min(1.0);
+                let colour = match ratio {
+                    r if r < 0.4 => Color::Green,
+                    r if r < 0.7 => Color::Yellow,
+                    _ => Color::LightRed,
+                };
+                gauges.push((
+                    LineGauge::default()
+                        .filled_style(Style::default().fg(colour))
+                        .label(format!("Speed {:.1}/s", 1.0 / tick.as_secs_f64()))
+                        .ratio(ratio),
+                    SPEED_GAUGE_WIDTH,
+                ));
+            }
+            if let (Some(limit), Some(left)) = (haus.rules.time_limit, haus.time_left()) {
+                let secs = left.as_secs_f64().ceil() as u64;
+                let colour = if secs > 15 {
+                    Color::LightCyan
+                } else {
+                    Color::LightRed
+                };
+                gauges.push((
+                    LineGauge::default()
+                        .filled_style(Style::default().fg(colour))
+                        .label(Span::styled(
+                            format!("Time {}:{:02}", secs / 60, secs % 60),
+                            Style::default().fg(colour).bold(),
+                        ))
+                        .ratio(left.as_secs_f64() / limit.as_secs_f64()),
+                    GAUGE_WIDTH,
+                ));
+            }
+            if let Round::Bonus { remaining } = round {
+                gauges.push((
+                    LineGauge::default()
+                        .filled_style(Style::default().fg(Color::LightYellow))
+                        .label(format!("Bonus {}s", remaining.as_secs_f64().ceil()))
+                        .ratio(remaining.as_secs_f64() / BONUS_ROUND_LENGTH.as_secs_f64()),
+                    GAUGE_WIDTH,
+                ));
+            }
+            if let Some(max_hunger) = haus.rules.hunger {
+                let ratio = haus.hunger as f64 / max_hunger.max(1) as f64;
+                let colour = match ratio {
+                    r if r > 0.5 => Color::Green,
+                    r if r > 0.25 => Color::Yellow,
+                    _ => Color::Red,
+                };
+                gauges.push((
+                    LineGauge::default()
+                        .filled_style(Style::default().fg(colour))
+                        .label("Hunger")
+                        .ratio(ratio),
+                    GAUGE_WIDTH,
+                ));
+            }
+            if let Some(interval) = haus.rules.famine {
+                let ratio = haus.famine as f64 / interval.max(1) as f64;
+                let colour = if ratio > 0.3 {
+                    Color::LightGreen
+                } else {
+                    Color::LightRed
+                };
+                gauges.push((
+                    LineGauge::default()
+                        .filled_style(Style::default().fg(colour))
+                        .label("Hunger")
+                        .ratio(ratio),
+                    GAUGE_WIDTH,
+                ));
+            }
+            if let (Some(window), 2..) = (haus.rules.combo_window, haus.combo) {
+                gauges.push((
+                    LineGauge::default()
+                        .filled_style(Style::default().fg(Color::Magenta))
+                        .label(format!("Combo x{}", haus.combo))
+                        .ratio(haus.combo_timer as f64 / window.max(1) as f64),
+                    GAUGE_WIDTH,
+                ));
+            }
+            if haus.rules.boost {
+                let colour = if haus.boosting {
+                    Color::LightMagenta
+                } else {
+                    Color::Blue
+                };
+                gauges.push((
+                    LineGauge::default()
+                        .filled_style(Style::default().fg(colour))
+                        .label(Span::styled("Boost (B)", Style::default().fg(colour)))
+                        .ratio(haus.boost as f64 / BOOST_CAPACITY as f64),
+                    GAUGE_WIDTH,
+                ));
+            }
+            if let Some(target) = haus.rules.target {
+                let (current, goal) = target.progress(haus);
+                gauges.push((
+                    LineGauge::default()
+                        .filled_style(Style::default().fg(Color::Green))
+                        .label(format!("{} / {}", current, goal))
+                        .ratio(current as f64 / goal.max(1) as f64),
+                    GAUGE_WIDTH,
+                ));
+            }
+
+            let constraints = std::iter::once(Constraint::Min(0))
+                .chain(gauges.iter().map(|&(_, width)| Constraint::Length(width)));
+            let areas = Layout::horizontal(constraints)
+                .spacing(2)
+                .split(header_inner);
+            for ((gauge, _), area) in gauges.into_iter().zip(areas.iter().skip(1)) {
+                frame.render_widget(gauge, *area);
+            }
+        }
+
+        // A dual view plays on the left and keeps the run's details on the right
+        let mut game_area = layout[1];
+        if self.arena_preset == Some(ArenaPreset::Dual) {
+            if let GameState::Playing(haus, _)
+            | GameState::Paused(haus, _)
+            | GameState::GameOver { haus, .. }
+            | GameState::Victory { haus, .. } = &self.state
+            {
+                let panes = SplitLayout::Dual.areas(layout[1]);
+                game_area = panes[0];
+                frame.render_widget(self.run_details(haus), panes[1]);
+            }
+        }
+        if let GameState::Playing(haus, _)
+        | GameState::Paused(haus, _)
+        | GameState::GameOver { haus, .. }
+        | GameState::Victory { haus, .. } = &self.state
+        {
+            if haus.rules.royale {
+                let [arena, board] =
+                    Layout::horizontal([Constraint::Fill(1), Constraint::Length(SCOREBOARD_WIDTH)])
+                        .areas(game_area);
+                game_area = arena;
+                frame.render_widget(self.scoreboard(haus), board);
+            }
+            // Only once there's room beside the arena, which a game started
+            // without the sidebar might not have left
+            if self.settings.stats && game_area.width >= haus.size.width + 2 + STATS_WIDTH {
+                let [arena, sidebar] =
+                    Layout::horizontal([Constraint::Fill(1), Constraint::Length(STATS_WIDTH)])
+                        .areas(game_area);
+                game_area = arena;
+                frame.render_widget(self.stats(haus), sidebar);
+            }
+        }
+
+        // Game area - different for each state
+        match &self.state {
+            GameState::ReadyToStart => {
+                let block = theme.block();
+                let inner_area = block.inner(layout[1]);
+                self.arena_size = Some(Size {
+                    width: inner_area.width,
+                    height: inner_area.height,
+                });
+                let mut lines = vec![
+                    Line::from("Press SPACE to start"),
+                    Line::from(""),
+                    Line::from(format!(
+                        "Mode: < {} >  (Up/Down to change)",
+                        self.mode.name()
+                    ))
+                    .bold(),
+                    Line::from(""),
+                ];
+                lines.push(Line::from(format!(
+                    "Target: < {} >",
+                    VictoryTarget::describe(self.target)
+                )));
+                let level_name = self
+                    .chosen_level()
+                    .map_or("Open arena", |l| l.name.as_str());
+                lines.push(Line::from(format!(
+                    "Level: {}  (TAB to choose)",
+                    level_name
+                )));
+                lines.push(Line::from(format!(
+                    "Skin: {}  (C to change)",
+                    self.skins[self.skin].name
+                )));
+                lines.push(Line::from(format!(
+                    "Modifiers: {}  (M to change)",
+                    self.mutators.summary()
+                )));
+                lines.push(Line::from(format!(
+                    "Background: {}  (B to change)",
+                    self.settings.background.name()
+                )));
+                if let Some(size) = self.settings.arena_size {
+                    lines.push(Line::from(format!(
+                        "Arena: fixed at {}x{}  (O to change)",
+                        size.width, size.height
+                    )));
+                } else if let Some(preset) = self.arena_preset {
+                    lines.push(Line::from(format!(
+                        "Arena: {}  (R to change)",
+                        preset.name()
+                    )));
+                } else if self.arena_size.is_some_and(ArenaPreset::wanted) {
+                    lines.push(Line::from(
+                        "Arena: picked when you start on a terminal this wide  (R to pick now)",
+                    ));
+                }
+                lines.push(Line::from("Settings: O to change"));
+                lines.push(Line::from("High scores: H to see the table"));
+                lines.push(Line::from("Hotseat tournament: T to set up"));
+                lines.push(Line::from("Spectate: V to watch a computer snek play"));
+                lines.push(Line::from("Replays: L to watch a saved run"));
+                lines.push(Line::from(
+                    "Run codes: Y to copy the last game's, P to play one from the clipboard",
+                ));
+                if let Some(error) = &self.start_error {
+                    lines.push(Line::from(""));
+                    lines.push(Line::from(error.as_str()).red());
+                }
+                if let Some(line) = self.clipboard_line() {
+                    lines.push(Line::from(""));
+                    lines.push(line);
+                }
+                let broken: Vec<_> = self
+                    .levels
+                    .iter()
+                    .filter_map(|(path, level)| level.as_ref().err().map(|e| (path, e)))
+                    .collect();
+                if !broken.is_empty() {
+                    lines.push(Line::from(""));
+                    for (path, error) in broken {
+                        lines.push(Line::from(format!("{}: {}", path.display(), error)).red());
+                    }
+                }
+                frame.render_widget(block, layout[1]);
+                let mut text_area = inner_area;
+                // The title only shows when it leaves room for everything else
+                if inner_area.height >= lines.len() as u16 + title::HEIGHT {
+                    let [top, rest] =
+                        Layout::vertical([Constraint::Length(title::HEIGHT), Constraint::Fill(1)])
+                            .areas(inner_area);
+                    self.title.draw(
+                        top,
+                        frame.buffer_mut(),
+                        &self.worn_skin(),
+                        &self.settings.symbols.glyphs(),
+                        self.theme().head,
+                    );
+                    text_area = rest;
+                }
+                frame.render_widget(
+                    Paragraph::new(lines).alignment(Alignment::Center),
+                    text_area,
+                );
+            }
+            GameState::Playing(haus, round) => {
+                let title = match round {
+                    Round::Normal { .. } => "Playing",
+                    Round::Bonus { .. } => "BONUS ROUND! Eat all you can",
+                };
+                let inner_areas =
+                    split::render_views(frame, game_area, &[GameView::new(title, haus)]);
+                if let Some(left) = self.countdown_left() {
+                    let arena = inner_areas[0];
+                    let line = Rect {
+                        y: arena.y + arena.height / 3,
+                        height: 1.min(arena.height),
+                        ..arena
+                    };
+                    // Rounded up, so it's 1 rather than 0 in the last second
+                    let seconds = left.as_millis().div_ceil(1000);
+                    frame.render_widget(
+                        Paragraph::new(format!("Get ready... {}", seconds))
+                            .alignment(Alignment::Center)
+                            .bold()
+                            .yellow(),
+                        line,
+                    );
+                }
+                if let Some(announcement) = haus.announcement {
+                    let arena = inner_areas[0];
+                    let line = Rect {
+                        y: arena.y + arena.height / 3,
+                        height: 1.min(arena.height),
+                        ..arena
+                    };
+                    frame.render_widget(
+                        Paragraph::new(Span::styled(announcement.text(), announcement.style()))
+                            .alignment(Alignment::Center),
+                        line,
+                    );
+                }
+                if let Some((mutation, _)) = haus.mutation {
+                    let arena = inner_areas[0];
+                    let banner = Rect {
+                        y: arena.y + arena.height / 2 - 1.min(arena.height / 2),
+                        height: 3.min(arena.height),
+                        ..arena
+                    };
+                    frame.render_widget(Clear, banner);
+                    frame.render_widget(
+                        Paragraph::new(format!(
+                            "\n⚡ CHAOS: {} ⚡",
+                            mutation.text().to_uppercase()
+                        ))
+                        .alignment(Alignment::Center)
+                        .black()
+                        .on_light_magenta()
+                        .bold(),
+                        banner,
+                    );
+                }
+                if haus.mirrored {
+                    let arena = inner_areas[0];
+                    let banner = Rect {
+                        height: 1.min(arena.height),
+                        ..arena
+                    };
+                    frame.render_widget(
+                        Paragraph::new("⚠ CONTROLS MIRRORED ⚠")
+                            .alignment(Alignment::Center)
+                            .black()
+                            .on_yellow()
+                            .bold(),
+                        banner,
+                    );
+                }
+            }
+            GameState::Paused(haus, _) => {
+                split::render_views(frame, game_area, &[GameView::new("Paused", haus)]);
+                frame
+                    .buffer_mut()
+                    .set_style(game_area, Style::default().add_modifier(Modifier::DIM));
+                let popup = split::centred(game_area, PAUSE_POPUP);
+                let disabled = PauseAction::ALL
+                    .iter()
+                    .position(|&action| action == PauseAction::Restart)
+                    .filter(|_| self.tournament.is_some());
+                frame.render_widget(Clear, popup);
+                frame.render_widget(
+                    Menu {
+                        title: "Paused",
+                        help: "ENTER to pick, SPACE to resume",
+                        actions: PauseAction::ALL.iter().map(PauseAction::name).collect(),
+                        selected: self.pause_selected,
+                        disabled: disabled.into_iter().collect(),
+                    },
+                    popup,
+                );
+            }
+            GameState::Sandbox {
+                haus, brush, value, ..
+            } => {
+                let mouse = if self.settings.multiplexer_compat() {
+                    "the mouse is off in multiplexer compatibility mode"
+                } else {
+                    "left click to paint, right click to clear"
+                };
+                let title = format!(
+                    "Sandbox: {} (TAB), morsels worth {} (1-5), {}, arrows to turn, SPACE to play",
+                    brush.name(),
+                    value,
+                    mouse
+                );
+                self.sandbox_arena =
+                    split::render_views(frame, game_area, &[GameView::new(title, haus)])[0];
+            }
+            GameState::GameOver { final_score, haus } => {
+                let inner_areas = split::render_views(frame, game_area, &[GameView::new("", haus)]);
+                let cause = haus.death.map_or("", |cause| cause.describe());
+                let mut text = Text::from(format!(
+                    "GAME OVER\n{}\nFinal Score: {}{}\n{}{}\nPress Y to copy the run code, A to draw its trail, H for high scores",
+                    cause,
+                    final_score,
+                    haus.normalized_summary(),
+                    self.replay_prompt(),
+                    self.play_again_prompt()
+                ));
+                text.extend(self.clipboard_line());
+                frame.render_widget(
+                    Paragraph::new(text).alignment(Alignment::Center),
+                    inner_areas[0],
+                );
+                self.render_run_stats(frame, inner_areas[0], haus);
+            }
+            GameState::Victory { final_score, haus } => {
+                let inner_areas = split::render_views(frame, game_area, &[GameView::new("", haus)]);
+                let mut text = Text::from(format!(
+                    "VICTORY!{}\nFinal Score: {}{}\n{}{}\nPress Y to copy the run code, A to draw its trail, H for high scores",
+                    haus.victory_summary(),
+                    final_score,
+                    haus.normalized_summary(),
+                    self.replay_prompt(),
+                    self.play_again_prompt()
+                ));
+                text.extend(self.clipboard_line());
+                frame.render_widget(
+                    Paragraph::new(text).alignment(Alignment::Center).green(),
+                    inner_areas[0],
+                );
+                self.render_run_stats(frame, inner_areas[0], haus);
+            }
+            GameState::TournamentSetup {
+                names,
+                typing,
+                rounds,
+            } => {
+                let mut lines = vec![
+                    Line::from("Type a name and press ENTER to add a player"),
+                    Line::from(format!(
+                        "ENTER with no name to start, once there are {} to {} players",
+                        tournament::MIN_PLAYERS,
+                        tournament::MAX_PLAYERS
+                    )),
+                    Line::from("ESC to go back"),
+                    Line::from(""),
+                    Line::from(format!("Rounds: < {} >", rounds)),
+                    Line::from(""),
+                ];
+                for (index, name) in names.iter().enumerate() {
+                    lines.push(Line::from(format!("{}. {}", index + 1, name)));
+                }
+                if names.len() < tournament::MAX_PLAYERS {
+                    lines.push(Line::from(format!("{}. {}_", names.len() + 1, typing)).bold());
+                }
+                frame.render_widget(
+                    Paragraph::new(lines)
+                        .alignment(Alignment::Center)
+                        .block(Block::default().title("Tournament").borders(Borders::ALL)),
+                    layout[1],
+                );
+            }
+            GameState::ArenaPrompt { selected, .. } => {
+                let available = self.arena_size.unwrap_or_default();
+                let rows = ArenaPreset::ALL
+                    .into_iter()
+                    .map(|preset| {
+                        let size = preset.size(available);
+                        (preset.name(), format!("{}x{}", size.width, size.height))
+                    })
+                    .collect();
+                let form = Form {
+                    title: "Arena",
+                    help: "This terminal is very wide, so a stretched arena plays much easier than usual. UP/DOWN to pick, SPACE to choose",
+                    rows,
+                    selected: *selected,
+                    locked: Vec::new(),
+                };
+                frame.render_widget(form, layout[1]);
+            }
+            GameState::Standings => {
+                let Some(tournament) = &self.tournament else {
+                    return;
+                };
+                let mut lines = match (tournament.winner(), tournament.next_turn()) {
+                    (Some(winner), _) => {
+                        vec![Line::from(format!("{} wins!", winner.name)).green().bold()]
+                    }
+                    (None, Some((_, round))) => {
+                        vec![Line::from(format!(
+                            "Round {} of {}",
+                            round + 1,
+                            tournament.rounds
+                        ))]
+                    }
+                    (None, None) => vec![],
+                };
+                lines.push(Line::from(""));
+                lines.extend(tournament.table().into_iter().map(Line::from));
+                lines.push(Line::from(""));
+                lines.push(match self.tournament_player() {
+                    Some(player) => {
+                        Line::from(format!("Next up: {}, press SPACE when ready", player)).bold()
+                    }
+                    None => Line::from("Press SPACE to finish"),
+                });
+                lines.push(Line::from("ESC to abandon the tournament"));
+                frame.render_widget(
+                    Paragraph::new(lines)
+                        .alignment(Alignment::Center)
+                        .block(Block::default().title("Standings").borders(Borders::ALL)),
+                    layout[1],
+                );
+            }
+            GameState::LevelSelect { selected } => {
+                let mut lines = vec![
+                    Line::from("Choose a level, SPACE to select, ESC to go back"),
+                    Line::from("Y to copy the level to the clipboard, P to paste one in"),
+                    Line::from(""),
+                ];
+                let entries = std::iter::once(Ok("Open arena".to_string())).chain(
+                    self.levels.iter().map(|(path, level)| match level {
+                        Ok(level) => Ok(format!(
+                            "{} ({}x{})",
+                            level.name, level.size.width, level.size.height
+                        )),
+                        Err(e) => Err(format!("{}: {}", path.display(), e)),
+                    }),
+                );
+                for (index, entry) in entries.enumerate() {
+                    let line = match entry {
+                        Ok(name) if index == *selected => {
+                            Line::from(format!("> {} <", name)).bold()
+                        }
+                        Ok(name) => Line::from(name),
+                        Err(error) if index == *selected => {
+                            Line::from(format!("> {} <", error)).red().bold()
+                        }
+                        Err(error) => Line::from(error).red(),
+                    };
+                    lines.push(line);
+                }
+                if let Some(line) = self.clipboard_line() {
+                    lines.push(Line::from(""));
+                    lines.push(line);
+                }
+                frame.render_widget(
+                    Paragraph::new(lines)
+                        .alignment(Alignment::Center)
+                        .block(Block::default().title("Levels").borders(Borders::ALL)),
+                    layout[1],
+                );
+            }
+            GameState::StageSelect { selected } => {
+                let mut lines = vec![
+                    Line::from("Choose a stage, SPACE to play, ESC to go back"),
+                    Line::from(""),
+                ];
+                for (index, stage) in STAGES.iter().enumerate() {
+                    let status = if index < self.campaign.cleared {
+                        "cleared"
+                    } else if self.campaign.is_unlocked(index) {
+                        "unlocked"
+                    } else {
+                        "locked"
+                    };
+                    let text = format!(
+                        "{}. {}  (score {}, {})",
+                        index + 1,
+                        stage.name,
+                        stage.target,
+                        status
+                    );
+                    let line = if index == *selected {
+                        Line::from(format!("> {} <", text)).bold()
+                    } else {
+                        Line::from(text)
+                    };
+                    if self.campaign.is_unlocked(index) {
+                        lines.push(line);
+                    } else {
+                        lines.push(line.dark_gray());
+                    }
+                }
+                frame.render_widget(
+                    Paragraph::new(lines)
+                        .alignment(Alignment::Center)
+                        .block(Block::default().title("Campaign").borders(Borders::ALL)),
+                    layout[1],
+                );
+            }
+            GameState::PuzzleSelect { selected } => {
+                let mut lines = vec![
+                    Line::from("Choose a puzzle, SPACE to play, ESC to go back"),
+                    Line::from(""),
+                ];
+                for (index, (path, puzzle)) in self.puzzles.iter().enumerate() {
+                    let text = match puzzle {
+                        Ok(puzzle) => {
+                            let status = if self.solved.is_solved(&puzzle.level.name) {
+                                ", solved"
+                            } else {
+                                ""
+                            };
+                            format!(
+                                "{}. {}  ({} moves{})",
+                                index + 1,
+                                puzzle.level.name,
+                                puzzle.moves,
+                                status
+                            )
+                        }
+                        Err(e) => format!("{}. {}: {}", index + 1, path.display(), e),
+                    };
+                    let line = if index == *selected {
+                        Line::from(format!("> {} <", text)).bold()
+                    } else {
+                        Line::from(text)
+                    };
+                    if puzzle.is_ok() {
+                        lines.push(line);
+                    } else {
+                        lines.push(line.red());
+                    }
+                }
+                frame.render_widget(
+                    Paragraph::new(lines)
+                        .alignment(Alignment::Center)
+                        .block(Block::default().title("Puzzles").borders(Borders::ALL)),
+                    layout[1],
+                );
+            }
+            GameState::ReplaySelect { runs, selected } => {
+                let mut lines = vec![
+                    Line::from("Choose a saved run, SPACE to watch, V to spectate, ESC to go back"),
+                    Line::from(""),
+                ];
+                if runs.is_empty() {
+                    lines.push(Line::from(format!(
+                        "No saved runs in {} yet, press E after a game to save one",
+                        replay::REPLAYS_DIR
+                    )));
+                }
+                for (index, path) in runs.iter().enumerate() {
+                    let name = path
+                        .file_stem()
+                        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+                    lines.push(if index == *selected {
+                        Line::from(format!("> {} <", name)).bold()
+                    } else {
+                        Line::from(name)
+                    });
+                }
+                frame.render_widget(
+                    Paragraph::new(lines)
+                        .alignment(Alignment::Center)
+                        .block(Block::default().title("Replays").borders(Borders::ALL)),
+                    layout[1],
+                );
+            }
+            GameState::Modifiers { selected } => {
+                let rows = Mutator::ALL
+                    .into_iter()
+                    .map(|mutator| {
+                        let (name, value) = self.mutators.describe(mutator);
+                        match self.unlocks.goal(Item::Mutator(mutator)) {
+                            Some(goal) => (name, format!("Locked: {}", goal.describe())),
+                            None => (name, value.to_string()),
+                        }
+                    })
+                    .collect();
+                let locked = (0..Mutator::ALL.len())
+                    .filter(|&row| !self.unlocks.is_unlocked(Item::Mutator(Mutator::ALL[row])))
+                    .collect();
+                let form = Form {
+                    title: "Modifiers",
+                    help: "Modifiers, LEFT/RIGHT to change, ESC to go back",
+                    rows,
+                    selected: *selected,
+                    locked,
+                };
+                frame.render_widget(form, layout[1]);
+            }
+            GameState::Highlight {
+                playback, title, ..
+            } => {
+                let title = format!("Highlight: {}  (SPACE to skip)", title);
+                split::render_views(frame, layout[1], &[GameView::new(title, &playback.haus)]);
+            }
+            GameState::Replay { viewer, .. } => {
+                let [arena, bar, help] = Layout::vertical([
+                    Constraint::Fill(1),
+                    Constraint::Length(1),
+                    Constraint::Length(1),
+                ])
+                .areas(layout[1]);
+                split::render_views(
+                    frame,
+                    arena,
+                    &[GameView::new("Replay", &viewer.playback.haus)],
+                );
+                let state = if viewer.paused {
+                    "Paused".to_string()
+                } else {
+                    format!("{}x", viewer.speed)
+                };
+                frame.render_widget(
+                    LineGauge::default()
+                        .filled_style(Style::default().fg(theme.snek))
+                        .label(format!(
+                            "Tick {}/{}  {}",
+                            viewer.tick(),
+                            viewer.ticks(),
+                            state
+                        ))
+                        .ratio(viewer.progress()),
+                    bar,
+                );
+                frame.render_widget(
+                    Paragraph::new(
+                        "SPACE pause, S step, F speed, LEFT/RIGHT scrub, HOME restart, A trail art, ESC back",
+                    )
+                    .alignment(Alignment::Center),
+                    help,
+                );
+            }
+            GameState::TrailArt { trail, .. } => {
+                let block = theme
+                    .block()
+                    .title("Trail art  (S to save, SPACE to go back)");
+                let inner = block.inner(layout[1]);
+                frame.render_widget(block, layout[1]);
+                frame.render_widget(trail.picture(), split::centred(inner, trail.size()));
+            }
+            GameState::Skins { selected } => self.render_skins(frame, layout[1], *selected),
+            GameState::HighScores { .. } => {
+                let mut lines = vec![Line::from(self.mode.name()).bold(), Line::from("")];
+                lines.extend(self.high_score_lines());
+                lines.push(Line::from(""));
+                lines.push(Line::from("Press SPACE to go back"));
+                frame.render_widget(
+                    Paragraph::new(lines)
+                        .alignment(Alignment::Center)
+                        .block(Block::default().title("High scores").borders(Borders::ALL)),
+                    layout[1],
+                );
+            }
+            GameState::Wizard { selected, preview } => {
+                let [left, right] =
+                    Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(layout[1]);
+                let form = Form {
+                    title: "Welcome to snekrs",
+                    help: "UP/DOWN to pick, LEFT/RIGHT to change, SPACE when you're done",
+                    rows: Field::ALL
+                        .into_iter()
+                        .map(|field| (field.label(), self.describe_setting(field)))
+                        .collect(),
+                    selected: *selected,
+                    locked: (0..Field::ALL.len())
+                        .filter(|&row| self.setting_goal(Field::ALL[row]).is_some())
+                        .collect(),
+                };
+                frame.render_widget(form, left);
+                split::render_views(frame, right, &[GameView::new("Preview", preview.haus())]);
+            }
+            GameState::Settings { selected, then } => {
+                let form = Form {
+                    title: "Settings",
+                    help: "UP/DOWN to pick, LEFT/RIGHT to change, SPACE when you're done. \
+                           Speed, length, growth, arena and edges apply from the next game",
+                    rows: Field::ALL
+                        .into_iter()
+                        .map(|field| (field.label(), self.describe_setting(field)))
+                        .collect(),
+                    selected: *selected,
+                    locked: (0..Field::ALL.len())
+                        .filter(|&row| self.setting_goal(Field::ALL[row]).is_some())
+                        .collect(),
+                };
+                if let GameState::Paused(haus, _) = then.as_ref() {
+                    let [left, right] =
+                        Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)])
+                            .areas(layout[1]);
+                    frame.render_widget(form, left);
+                    split::render_views(frame, right, &[GameView::new("Paused", haus)]);
+                } else {
+                    frame.render_widget(form, layout[1]);
+                }
+            }
+            GameState::Spectating { feed, live } => {
+                let title = if *live {
+                    "Spectating  (ESC to leave)"
+                } else {
+                    "Feed ended  (ESC to leave)"
+                };
+                split::render_views(frame, layout[1], &[GameView::new(title, feed.haus())]);
+            }
+            GameState::Attract { demo } => {
+                self.arena_size = Some(Size {
+                    width: layout[1].width.saturating_sub(2),
+                    height: layout[1].height.saturating_sub(2),
+                });
+                let inner_areas = split::render_views(
+                    frame,
+                    layout[1],
+                    &[GameView::new("Press any key to play", demo.haus())],
+                );
+                let line = Rect {
+                    y: inner_areas[0].y + inner_areas[0].height / 2,
+                    height: 1.min(inner_areas[0].height),
+                    ..inner_areas[0]
+                };
+                frame.render_widget(
+                    Paragraph::new("S N E K R S")
+                        .alignment(Alignment::Center)
+                        .bold()
+                        .yellow(),
+                    line,
+                );
+            }
+            GameState::Exit => {}
+        }
+
+        // Prompts go over the top afterwards, so they stay easy to read
+        if self.settings.crt {
+            crt::apply(frame.buffer_mut(), layout[1]);
+        }
+
+        if self.break_due() {
+            let area = layout[1];
+            let popup = Rect {
+                x: area.x + area.width.saturating_sub(BREAK_POPUP.width) / 2,
+                y: area.y + area.height.saturating_sub(BREAK_POPUP.height) / 2,
+                width: BREAK_POPUP.width.min(area.width),
+                height: BREAK_POPUP.height.min(area.height),
+            };
+            frame.render_widget(Clear, popup);
+            frame.render_widget(
+                Paragraph::new(format!(
+                    "You've been playing for {} minutes.\n\nHow about a break? Stretch, have a drink\nand give your eyes a rest.\n\nPress any key to carry on",
+                    self.session.played.as_secs() / 60
+                ))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true })
+                .block(
+                    Block::default()
+                        .title("Time for a break?")
+                        .borders(Borders::ALL)
+                        .cyan(),
+                ),
+                popup,
+            );
+        }
+
+        if let Some((_, typing)) = &self.naming {
+            let popup = split::centred(layout[1], NAME_POPUP);
+            frame.render_widget(Clear, popup);
+            frame.render_widget(
+                TextInput {
+                    title: "New high score!",
+                    prompt: "Type your name",
+                    text: typing,
+                    help: &format!(
+                        "{} to {} characters, ENTER to save",
+                        leaderboard::MIN_NAME_LENGTH,
+                        leaderboard::MAX_NAME_LENGTH
+                    ),
+                },
+                popup,
+            );
+        }
+
+        if let Some(message) = self.toasts.current(Instant::now()).map(str::to_string) {
+            let line = Rect {
+                y: layout[1].bottom().saturating_sub(1),
+                height: 1.min(layout[1].height),
+                ..layout[1]
+            };
+            // Say there's more to come, so a queue of them isn't a surprise
+            let message = match self.toasts.waiting() {
+                0 => message,
+                more => format!("{}  (+{} more)", message, more),
+            };
+            frame.render_widget(
+                Paragraph::new(message)
+                    .alignment(Alignment::Center)
+                    .black()
+                    .on_yellow(),
+                line,
+            );
+        }
+    }
+
+    fn handle_input(&mut self, key: event::KeyEvent) {
+        use event::KeyCode;
+
+        if let Some(kiosk) = &mut self.kiosk {
+            kiosk.last_input = Instant::now();
+            if kiosk.unlock.matches(key) {
+                info!("Kiosk unlocked");
+                self.state = GameState::Exit;
+                return;
+            }
+            if key.code == KeyCode::F(12) {
+                // Kiosks are read-only, so players can't leave files behind
+                return;
+            }
+        }
+        if key.code == KeyCode::F(12) {
+            let message = match self.save_bug_report(Path::new(bugreport::BUG_REPORT_DIR)) {
+                Ok(path) => format!("Bug report saved to {}", path.display()),
+                Err(e) => {
+                    error!("Error saving bug report: {}", e);
+                    format!("Couldn't save a bug report: {}", e)
+                }
+            };
+            self.toasts.push(message);
+            return;
+        }
+
+        if let Some((_, typing)) = &mut self.naming {
+            match key.code {
+                KeyCode::Enter if typing.trim().chars().count() >= leaderboard::MIN_NAME_LENGTH => {
+                    self.finish_naming()
+                }
+                // Keeping the name offered
+                KeyCode::Esc => {
+                    typing.clear();
+                    self.finish_naming();
+                }
+                code => {
+                    form::edit(typing, code, leaderboard::MAX_NAME_LENGTH);
+                }
+            }
+            return;
+        }
+
+        if self.break_due() {
+            // Any key dismisses the reminder, rather than doing what it would
+            self.session.dismiss();
+            self.session.save(&self.data_file(session::SESSION_FILE));
+            return;
+        }
+
+        let new_state = match &mut self.state {
+            GameState::ReadyToStart => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => Some(GameState::Exit),
+                KeyCode::Up | KeyCode::Char('w') => {
+                    self.mode = self.mode.cycle(-1);
+                    self.scores = Self::load_high_scores(&self.data_dir, self.mode);
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('s') => {
+                    self.mode = self.mode.cycle(1);
+                    self.scores = Self::load_high_scores(&self.data_dir, self.mode);
+                    None
+                }
+                KeyCode::Left | KeyCode::Char('a') => {
+                    self.target = VictoryTarget::cycle(self.target, -1);
+                    None
+                }
+                KeyCode::Right | KeyCode::Char('d') => {
+                    self.target = VictoryTarget::cycle(self.target, 1);
+                    None
+                }
+                KeyCode::Char('c') => Some(GameState::Skins {
+                    selected: self.skin,
+                }),
+                KeyCode::Char('m') => Some(GameState::Modifiers { selected: 0 }),
+                KeyCode::Char('y') => {
+                    self.copy_run_code();
+                    None
+                }
+                KeyCode::Char('p') => {
+                    self.paste_run_code();
+                    None
+                }
+                KeyCode::Char('t') => Some(GameState::TournamentSetup {
+                    names: Vec::new(),
+                    typing: String::new(),
+                    rounds: tournament::DEFAULT_ROUNDS,
+                }),
+                KeyCode::Char('b') => {
+                    self.settings.background = self.settings.background.cycle(1);
+                    while self.setting_goal(Field::Background).is_some() {
+                        self.settings.background = self.settings.background.cycle(1);
+                    }
+                    None
+                }
+                KeyCode::Tab => {
+                    // Pick up any edits made to the level files since launch
+                    self.levels = level::load_dir(Path::new(level::LEVELS_DIR), 3);
+                    Some(GameState::LevelSelect {
+                        selected: self.level.map_or(0, |index| index + 1),
+                    })
+                }
+                KeyCode::Char('r') => Some(GameState::ArenaPrompt {
+                    selected: self.arena_preset.map_or(0, |preset| {
+                        ArenaPreset::ALL
+                            .iter()
+                            .position(|&p| p == preset)
+                            .unwrap_or(0)
+                    }),
+                    starting: false,
+                }),
+                KeyCode::Char('o') => Some(GameState::Settings {
+                    selected: 0,
+                    then: Box::new(GameState::ReadyToStart),
+                }),
+                KeyCode::Char('h') => Some(GameState::HighScores {
+                    then: Box::new(GameState::ReadyToStart),
+                }),
+                KeyCode::Char('v') => Some(self.spectate()),
+                KeyCode::Char('l') => Some(GameState::ReplaySelect {
+                    runs: replay::saved_runs(Path::new(replay::REPLAYS_DIR)),
+                    selected: 0,
+                }),
+                KeyCode::Char(' ') => self.begin(),
+                _ => None,
+            },
+            GameState::TournamentSetup {
+                names,
+                typing,
+                rounds,
+            } => match key.code {
+                KeyCode::Esc => Some(GameState::ReadyToStart),
+                KeyCode::Left => {
+                    *rounds = (*rounds - 1).max(1);
+                    None
+                }
+                KeyCode::Right => {
+                    *rounds = (*rounds + 1).min(tournament::MAX_ROUNDS);
+                    None
+                }
+                KeyCode::Backspace => {
+                    if typing.pop().is_none() {
+                        // Take back the last player added
+                        names.pop();
+                    }
+                    None
+                }
+                KeyCode::Enter if !typing.trim().is_empty() => {
+                    if names.len() < tournament::MAX_PLAYERS {
+                        names.push(typing.trim().to_string());
+                    }
+                    typing.clear();
+                    None
+                }
+                KeyCode::Enter => {
+                    match Tournament::new(names.clone(), *rounds, rand::random()) {
+                        Ok(tournament) => {
+                            self.tournament = Some(tournament);
+                            Some(GameState::Standings)
+                        }
+                        // Not enough players yet
+                        Err(_) => None,
+                    }
+                }
+                KeyCode::Char(c)
+                    if !c.is_control() && typing.chars().count() < tournament::MAX_NAME_LENGTH =>
+                {
+                    typing.push(c);
+                    None
+                }
+                _ => None,
+            },
+            GameState::ArenaPrompt { selected, starting } => match key.code {
+                KeyCode::Esc => Some(GameState::ReadyToStart),
+                KeyCode::Up | KeyCode::Char('w') => {
+                    *selected = form::step(*selected, -1, ArenaPreset::ALL.len());
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('s') => {
+                    *selected = form::step(*selected, 1, ArenaPreset::ALL.len());
+                    None
+                }
+                KeyCode::Char(' ') | KeyCode::Enter => {
+                    self.arena_preset = Some(ArenaPreset::ALL[*selected]);
+                    if *starting {
+                        self.begin()
+                    } else {
+                        Some(GameState::ReadyToStart)
+                    }
+                }
+                _ => None,
+            },
+            GameState::Standings => match key.code {
+                KeyCode::Esc => {
+                    self.tournament = None;
+                    Some(GameState::ReadyToStart)
+                }
+                KeyCode::Char(' ') | KeyCode::Enter => {
+                    if self.tournament_player().is_some() {
+                        self.begin()
+                    } else {
+                        self.tournament = None;
+                        Some(GameState::ReadyToStart)
+                    }
+                }
+                _ => None,
+            },
+            GameState::Modifiers { selected } => match key.code {
+                KeyCode::Esc | KeyCode::Char(' ') | KeyCode::Enter => Some(GameState::ReadyToStart),
+                KeyCode::Up | KeyCode::Char('w') => {
+                    *selected = form::step(*selected, -1, Mutator::ALL.len());
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('s') => {
+                    *selected = form::step(*selected, 1, Mutator::ALL.len());
+                    None
+                }
+                // Locked modifiers stay off
+                _ if !self
+                    .unlocks
+                    .is_unlocked(Item::Mutator(Mutator::ALL[*selected])) =>
+                {
+                    None
+                }
+                KeyCode::Left | KeyCode::Char('a') => {
+                    self.mutators.cycle(Mutator::ALL[*selected], -1);
+                    None
+                }
+                KeyCode::Right | KeyCode::Char('d') => {
+                    self.mutators.cycle(Mutator::ALL[*selected], 1);
+                    None
+                }
+                _ => None,
+            },
+            GameState::Wizard { selected, .. } => match key.code {
+                KeyCode::Esc | KeyCode::Char(' ') | KeyCode::Enter => {
+                    self.save_settings();
+                    Some(GameState::ReadyToStart)
+                }
+                KeyCode::Up | KeyCode::Char('w') => {
+                    *selected = form::step(*selected, -1, Field::ALL.len());
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('s') => {
+                    *selected = form::step(*selected, 1, Field::ALL.len());
+                    None
+                }
+                KeyCode::Left | KeyCode::Char('a') => {
+                    let field = Field::ALL[*selected];
+                    self.cycle_setting(field, -1);
+                    None
+                }
+                KeyCode::Right | KeyCode::Char('d') => {
+                    let field = Field::ALL[*selected];
+                    self.cycle_setting(field, 1);
+                    None
+                }
+                _ => None,
+            },
+            GameState::Skins { selected } => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => Some(GameState::ReadyToStart),
+                KeyCode::Up | KeyCode::Char('w') => {
+                    *selected = form::step(*selected, -1, self.skins.len());
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('s') => {
+                    *selected = form::step(*selected, 1, self.skins.len());
+                    None
+                }
+                // Locked skins can be looked at, but not worn
+                KeyCode::Char(' ') | KeyCode::Enter
+                    if self
+                        .unlocks
+                        .skin_goal(&self.skins[*selected].name)
+                        .is_none() =>
+                {
+                    self.skin = *selected;
+                    Some(GameState::ReadyToStart)
+                }
+                _ => None,
+            },
+            GameState::HighScores { then } => match key.code {
+                KeyCode::Esc
+                | KeyCode::Char(' ')
+                | KeyCode::Enter
+                | KeyCode::Char('q')
+                | KeyCode::Char('h') => Some(std::mem::replace(then.as_mut(), GameState::Exit)),
+                _ => None,
+            },
+            GameState::Settings { selected, then } => match key.code {
+                KeyCode::Esc | KeyCode::Char(' ') | KeyCode::Enter => {
+                    let then = std::mem::replace(then.as_mut(), GameState::Exit);
+                    self.save_settings();
+                    Some(then)
+                }
+                KeyCode::Up | KeyCode::Char('w') => {
+                    *selected = form::step(*selected, -1, Field::ALL.len());
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('s') => {
+                    *selected = form::step(*selected, 1, Field::ALL.len());
+                    None
+                }
+                KeyCode::Left | KeyCode::Char('a') => {
+                    let field = Field::ALL[*selected];
+                    self.cycle_setting(field, -1);
+                    None
+                }
+                KeyCode::Right | KeyCode::Char('d') => {
+                    let field = Field::ALL[*selected];
+                    self.cycle_setting(field, 1);
+                    None
+                }
+                _ => None,
+            },
+            GameState::StageSelect { selected } => match key.code {
+                KeyCode::Esc => Some(GameState::ReadyToStart),
+                KeyCode::Up | KeyCode::Char('w') => {
+                    *selected = selected.saturating_sub(1);
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('s') => {
+                    *selected = (*selected + 1).min(STAGES.len() - 1);
+                    None
+                }
+                KeyCode::Char(' ') | KeyCode::Enter if self.campaign.is_unlocked(*selected) => {
+                    // Problems starting the stage are shown on the start screen
+                    let index = *selected;
+                    self.start_stage(index).or(Some(GameState::ReadyToStart))
+                }
+                _ => None,
+            },
+            GameState::PuzzleSelect { selected } => match key.code {
+                KeyCode::Esc => Some(GameState::ReadyToStart),
+                KeyCode::Up | KeyCode::Char('w') => {
+                    *selected = selected.saturating_sub(1);
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('s') => {
+                    *selected = (*selected + 1).min(self.puzzles.len() - 1);
+                    None
+                }
+                KeyCode::Char(' ') | KeyCode::Enter if self.puzzles[*selected].1.is_ok() => {
+                    // Problems starting the puzzle are shown on the start screen
+                    let index = *selected;
+                    self.start_puzzle(index).or(Some(GameState::ReadyToStart))
+                }
+                _ => None,
+            },
+            GameState::ReplaySelect { runs, selected } => match key.code {
+                KeyCode::Esc => Some(GameState::ReadyToStart),
+                KeyCode::Up | KeyCode::Char('w') => {
+                    *selected = selected.saturating_sub(1);
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('s') => {
+                    *selected = (*selected + 1).min(runs.len().saturating_sub(1));
+                    None
+                }
+                KeyCode::Char(' ') | KeyCode::Enter | KeyCode::Char('v') if !runs.is_empty() => {
+                    let path = runs[*selected].clone();
+                    match self.load_run(&path) {
+                        // Spectating plays the run through once, with no scrubbing
+                        Ok(recording) if key.code == KeyCode::Char('v') => {
+                            Some(GameState::Spectating {
+                                feed: Box::new(recording.playback(0, recording.ticks())),
+                                live: true,
+                            })
+                        }
+                        Ok(recording) => {
+                            let then = std::mem::replace(&mut self.state, GameState::Exit);
+                            Some(GameState::Replay {
+                                viewer: Box::new(Viewer::new(&recording)),
+                                then: Box::new(then),
+                            })
+                        }
+                        Err(e) => {
+                            let message = format!("Couldn't load {}: {}", path.display(), e);
+                            error!("{}", message);
+                            self.toasts.push(message);
+                            None
+                        }
+                    }
+                }
+                _ => None,
+            },
+            GameState::LevelSelect { selected } => match key.code {
+                KeyCode::Esc => Some(GameState::ReadyToStart),
+                KeyCode::Up | KeyCode::Char('w') => {
+                    *selected = selected.saturating_sub(1);
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('s') => {
+                    *selected = (*selected + 1).min(self.levels.len());
+                    None
+                }
+                KeyCode::Char('y') => {
+                    let selected = *selected;
+                    self.copy_level(selected);
+                    None
+                }
+                KeyCode::Char('p') => self
+                    .paste_level()
+                    .map(|selected| GameState::LevelSelect { selected }),
+                KeyCode::Char(' ') | KeyCode::Enter => match selected.checked_sub(1) {
+                    None => {
+                        self.level = None;
+                        Some(GameState::ReadyToStart)
+                    }
+                    Some(index) if self.levels[index].1.is_ok() => {
+                        self.level = Some(index);
+                        self.start_error = None;
+                        Some(GameState::ReadyToStart)
+                    }
+                    // Broken levels can't be picked
+                    Some(_) => None,
+                },
+                _ => None,
+            },
+            GameState::Playing(haus, round) => match key.code {
+                KeyCode::Char('q') => {
+                    let haus = std::mem::take(haus);
+                    Some(self.quit(haus))
+                }
+                KeyCode::Esc => Some(GameState::Exit),
+                KeyCode::Char(' ') if self.mode == GameMode::Sandbox => Some(GameState::Sandbox {
+                    haus: std::mem::take(haus),
+                    round: *round,
+                    brush: Brush::default(),
+                    value: 1,
+                }),
+                KeyCode::Char(' ') if !haus.rules.no_pause => {
+                    self.pause_selected = 0;
+                    Some(GameState::Paused(std::mem::take(haus), *round))
+                }
+                KeyCode::Char('b') => {
+                    self.boost_pressed = Some(Instant::now());
+                    None
+                }
+                KeyCode::Char('i') => {
+                    self.settings.stats = !self.settings.stats;
+                    None
+                }
+                KeyCode::Char('z') if haus.rewinds > 0 => {
+                    if let Some(rewound) = self.history.rewind() {
+                        info!("Rewound to tick {}", rewound.tick);
+                        let rewinds = haus.rewinds - 1;
+                        *haus = rewound.haus;
+                        haus.rewinds = rewinds;
+                        *round = rewound.round;
+                        self.rng = rewound.rng;
+                        self.recording.rewind(rewound.tick);
+                    }
+                    None
+                }
+                KeyCode::Char('u') if haus.rules.moves.is_some() => {
+                    if let Some(undone) = self.undo.pop() {
+                        info!("Undid a move, back to move {}", undone.tick);
+                        *haus = undone.haus;
+                        *round = undone.round;
+                        self.rng = undone.rng;
+                        self.recording.rewind(undone.tick);
+                        self.step = false;
+                    }
+                    None
+                }
+                code => {
+                    match self.settings.controls.direction(code) {
+                        // One move at a time in a puzzle, and never back on itself
+                        Some(direction)
+                            if haus.rules.moves.is_some()
+                                && (self.step || direction == haus.snek.direction.opposite()) => {}
+                        Some(direction) => {
+                            if haus.rules.moves.is_some() {
+                                self.undo.push(Rewound {
+                                    tick: self.recording.ticks(),
+                                    haus: haus.clone(),
+                                    round: *round,
+                                    rng: self.rng.clone(),
+                                });
+                                self.step = true;
+                            }
+                            haus.steer(direction);
+                            self.recording.input(Input::Steer(direction));
+                        }
+                        None => {}
+                    }
+                    None
+                }
+            },
+            GameState::Paused(haus, round) => match key.code {
+                KeyCode::Char('q') => {
+                    let haus = std::mem::take(haus);
+                    Some(self.quit(haus))
+                }
+                KeyCode::Esc => Some(GameState::Exit),
+                KeyCode::Up | KeyCode::Char('w') => {
+                    self.pause_selected =
+                        form::step(self.pause_selected, -1, PauseAction::ALL.len());
+                    None
+                }
+                KeyCode::Down | KeyCode::Char('s') => {
+                    self.pause_selected =
+                        form::step(self.pause_selected, 1, PauseAction::ALL.len());
+                    None
+                }
+                KeyCode::Enter => match PauseAction::ALL[self.pause_selected] {
+                    PauseAction::Resume => {
+                        self.countdown = Some(Instant::now() + COUNTDOWN);
+                        Some(GameState::Playing(std::mem::take(haus), *round))
+                    }
+                    // Another go at the same turn would be unfair on the other players
+                    PauseAction::Restart if self.tournament.is_some() => None,
+                    PauseAction::Restart => {
+                        let haus = std::mem::take(haus);
+                        self.record_session(&haus);
+                        self.restart().or(Some(GameState::ReadyToStart))
+                    }
+                    PauseAction::Settings => Some(GameState::Settings {
+                        selected: 0,
+                        then: Box::new(GameState::Paused(std::mem::take(haus), *round)),
+                    }),
+                    PauseAction::Quit => {
+                        let haus = std::mem::take(haus);
+                        self.record_turn(haus.score);
+                        self.record_session(&haus);
+                        Some(self.menu())
+                    }
+                },
+                KeyCode::Char(' ') => {
+                    self.countdown = Some(Instant::now() + COUNTDOWN);
+                    Some(GameState::Playing(std::mem::take(haus), *round))
+                }
+                KeyCode::Char('o') => Some(GameState::Settings {
+                    selected: 0,
+                    then: Box::new(GameState::Paused(std::mem::take(haus), *round)),
+                }),
+                KeyCode::Char('i') => {
+                    self.settings.stats = !self.settings.stats;
+                    None
+                }
+                _ => None,
+            },
+            GameState::Sandbox {
+                haus,
+                round,
+                brush,
+                value,
+            } => match key.code {
+                KeyCode::Char('q') => {
+                    let haus = std::mem::take(haus);
+                    Some(self.quit(haus))
+                }
+                KeyCode::Esc => Some(GameState::Exit),
+                KeyCode::Char(' ') => {
+                    // Painting isn't recorded, so replays start from the scene as it's left
+                    self.recording = Recording::start(haus, *round, &self.rng);
+                    self.history = History::default();
+                    self.history.push(0, haus, *round, &self.rng);
+                    Some(GameState::Playing(std::mem::take(haus), *round))
+                }
+                KeyCode::Tab => {
+                    *brush = cycle(&Brush::ALL, *brush, 1);
+                    None
+                }
+                KeyCode::Char(digit @ '1'..='5') => {
+                    *value = digit as u16 - '0' as u16;
+                    None
+                }
+                code => {
+                    if let Some(direction) = self.settings.controls.direction(code) {
+                        sandbox::turn(haus, direction);
+                    }
+                    None
+                }
+            },
+            GameState::Highlight { then, .. } => match key.code {
+                KeyCode::Esc | KeyCode::Char(' ') | KeyCode::Enter => {
+                    Some(std::mem::replace(then.as_mut(), GameState::Exit))
+                }
+                _ => None,
+            },
+            GameState::Replay { viewer, then } => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => {
+                    Some(std::mem::replace(then.as_mut(), GameState::Exit))
+                }
+                KeyCode::Char(' ') => {
+                    viewer.paused = !viewer.paused;
+                    None
+                }
+                KeyCode::Char('s') | KeyCode::Char('.') => {
+                    viewer.step();
+                    None
+                }
+                KeyCode::Char('f') => {
+                    viewer.cycle_speed();
+                    None
+                }
+                KeyCode::Left => {
+                    viewer.scrub(-SCRUB_TICKS);
+                    None
+                }
+                KeyCode::Right => {
+                    viewer.scrub(SCRUB_TICKS);
+                    None
+                }
+                KeyCode::Home => {
+                    viewer.seek(0);
+                    None
+                }
+                KeyCode::Char('a') => {
+                    let trail = Trail::from_recording(viewer.recording());
+                    let title = format!("snekrs replay, {} ticks", viewer.ticks());
+                    let then = std::mem::replace(&mut self.state, GameState::Exit);
+                    Some(GameState::TrailArt {
+                        trail,
+                        title,
+                        then: Box::new(then),
+                    })
+                }
+                _ => None,
+            },
+            GameState::TrailArt { trail, title, then } => match key.code {
+                KeyCode::Esc | KeyCode::Char(' ') | KeyCode::Enter => {
+                    Some(std::mem::replace(then.as_mut(), GameState::Exit))
+                }
+                KeyCode::Char('s') if self.kiosk.is_some() => {
+                    self.toasts.push("Saving is switched off on this kiosk");
+                    None
+                }
+                KeyCode::Char('s') => {
+                    let message = match trail.save(Path::new(trail::TRAIL_DIR), title) {
+                        Ok(path) => format!("Trail art saved to {}", path.display()),
+                        Err(e) => {
+                            error!("Error saving trail art: {}", e);
+                            format!("Couldn't save the trail art: {}", e)
+                        }
+                    };
+                    self.toasts.push(message);
+                    None
+                }
+                _ => None,
+            },
+            GameState::GameOver { .. } | GameState::Victory { .. } => match key.code {
+                KeyCode::Esc => Some(GameState::Exit),
+                KeyCode::Char('y') => {
+                    self.copy_run_code();
+                    None
+                }
+                KeyCode::Char('r') => self.recording.highlight().map(|highlight| {
+                    let then = std::mem::replace(&mut self.state, GameState::Exit);
+                    GameState::Highlight {
+                        playback: Box::new(self.recording.playback(highlight.start, highlight.end)),
+                        title: highlight.describe(),
+                        then: Box::new(then),
+                    }
+                }),
+                KeyCode::Char('w') => {
+                    let then = std::mem::replace(&mut self.state, GameState::Exit);
+                    Some(GameState::Replay {
+                        viewer: Box::new(Viewer::new(&self.recording)),
+                        then: Box::new(then),
+                    })
+                }
+                KeyCode::Char('a') => {
+                    let then = std::mem::replace(&mut self.state, GameState::Exit);
+                    Some(GameState::TrailArt {
+                        trail: Trail::from_recording(&self.recording),
+                        title: format!(
+                            "snekrs {}, {} ticks",
+                            self.mode.name(),
+                            self.recording.ticks()
+                        ),
+                        then: Box::new(then),
+                    })
+                }
+                KeyCode::Char('e') => {
+                    self.save_run();
+                    None
+                }
+                KeyCode::Char('h') => {
+                    let then = std::mem::replace(&mut self.state, GameState::Exit);
+                    Some(GameState::HighScores {
+                        then: Box::new(then),
+                    })
+                }
+                KeyCode::Char(' ') => Some(self.menu()),
+                KeyCode::Char('q') if self.tournament.is_some() => Some(GameState::Standings),
+                KeyCode::Char('q') => Some(GameState::ReadyToStart),
+                _ => None,
+            },
+            GameState::Attract { .. } => Some(GameState::ReadyToStart),
+            // Watching is read only, so leaving is all there is to do
+            GameState::Spectating { .. } => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => Some(GameState::ReadyToStart),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match new_state {
+            // Quitting a kiosk only goes as far as the bot playing
+            Some(GameState::Exit) if self.kiosk.is_some() => self.attract(),
+            Some(new_state) => {
+                self.state = new_state;
+                self.clipboard = None;
+            }
+            None => {}
+        }
+        // Keep the preview, or the paused game, in step with the settings,
+        // so the changes show straight away
+        if matches!(
+            self.state,
+            GameState::Wizard { .. } | GameState::Settings { .. }
+        ) {
+            let mut state = std::mem::replace(&mut self.state, GameState::Exit);
+            match &mut state {
+                GameState::Wizard { preview, .. } => self.dress(preview.haus_mut()),
+                GameState::Settings { then, .. } => {
+                    if let GameState::Paused(haus, _) = then.as_mut() {
+                        self.dress(haus);
+                    }
+                }
+                _ => {}
+            }
+            self.state = state;
+        }
+    }
+
+    fn update(&mut self) {
+        if self.kiosk.as_ref().is_some_and(Kiosk::idle)
+            && !matches!(
+                self.state,
+                GameState::Playing(..)
+                    | GameState::Attract { .. }
+                    | GameState::Spectating { .. }
+                    | GameState::Highlight { .. }
+                    | GameState::Replay { .. }
+            )
+        {
+            self.attract();
+        }
+        if let GameState::ReadyToStart = self.state {
+            self.title.step();
+            return;
+        }
+        if let GameState::Spectating { feed, live } = &mut self.state {
+            if *live {
+                *live = feed.advance();
+            }
+            return;
+        }
+        if let GameState::Attract { demo } = &mut self.state {
+            if !demo.advance() {
+                let size = self.arena_size.unwrap_or(PREVIEW_ARENA);
+                self.state = GameState::Attract {
+                    demo: self.new_bot(size),
+                };
+            }
+            return;
+        }
+        if let GameState::Wizard { selected, preview } = &mut self.state {
+            if !preview.advance() {
+                let selected = *selected;
+                self.state = GameState::Wizard {
+                    selected,
+                    preview: self.new_bot(PREVIEW_ARENA),
+                };
+            }
+            return;
+        }
+        if let GameState::Replay { viewer, .. } = &mut self.state {
+            viewer.advance();
+            return;
+        }
+        if let GameState::Highlight { playback, then, .. } = &mut self.state {
+            if !playback.step() {
+                self.state = std::mem::replace(then.as_mut(), GameState::Exit);
+            }
+            return;
+        }
+        let held = self.boost_held();
+        let GameState::Playing(haus, round) = &mut self.state else {
+            return;
+        };
+        // A puzzle's snek waits for the player to move
+        if haus.rules.moves.is_some() && !std::mem::take(&mut self.step) {
+            return;
+        }
+
+        haus.set_boost(held);
+        if held {
+            self.recording.input(Input::Boost);
+        }
+        let escalated = haus.escalated;
+        let rewinds = haus.rewinds;
+        let speed = haus.rules.speed.tick_rate(haus.score);
+        let result = haus.tick(round, &mut self.rng);
+        self.recording.tick(haus, *round, &self.rng);
+        let tick = self.recording.ticks();
+        self.run.record(tick, haus.snek.head);
+        haus.ghost_head = self
+            .best
+            .as_ref()
+            .and_then(|best| best.head(tick, haus.size));
+        // No going back to before a rewind was picked up, to pick it up again
+        if haus.rewinds > rewinds {
+            self.history.clear();
+        }
+        self.history
+            .push(self.recording.ticks(), haus, *round, &self.rng);
+        if haus.escalated > escalated {
+            let step = haus.escalation[haus.escalated - 1];
+            self.toasts.push(step.escalation.describe());
+        }
+        if haus.rules.speed.tick_rate(haus.score) < speed {
+            self.toasts.push("Speed up!");
+        }
+        let best = self.scores.best();
+        if !self.paced && best > 0 && haus.score > best {
+            self.paced = true;
+            self.toasts.push("New high score pace!");
+        }
+        match result {
+            StepResult::Nommed(_) => self.recording.event(replay::Event::Nom {
+                combo: haus.combo.max(1),
+            }),
+            StepResult::Ongoing if haus.lethal_neighbours() >= 2 => {
+                self.recording.event(replay::Event::NearMiss {
+                    tight: haus.lethal_neighbours(),
+                })
+            }
+            _ => {}
+        }
+
+        match result {
+            StepResult::Died(cause) => {
+                info!("{} with a score of {}", cause.describe(), haus.score);
+                // Game over - save the haus and score
+                let final_score = haus.score;
+                let haus = std::mem::take(haus);
+                let rank = self.update_high_score(&haus);
+                self.record_unlocks(&haus);
+                self.record_turn(final_score);
+                self.record_session(&haus);
+                self.hooks.run(HookEvent::GameOver, &self.hook_env(&haus));
+                self.state = GameState::GameOver { haus, final_score };
+                self.ask_name(rank);
+            }
+            StepResult::Victory => {
+                let final_score = haus.score;
+                let haus = std::mem::take(haus);
+                info!("Victory with a score of {}", final_score);
+                let rank = self.update_high_score(&haus);
+                self.record_unlocks(&haus);
+                self.record_session(&haus);
+                if let Some(stage) = self.stage {
+                    self.campaign.clear(stage);
+                    if self.kiosk.is_none() {
+                        self.campaign.save(&self.data_file(campaign::PROGRESS_FILE));
+                    }
+                }
+                if let Some(Ok(puzzle)) = self.puzzle.map(|index| &self.puzzles[index].1) {
+                    self.solved.solve(&puzzle.level.name);
+                    if self.kiosk.is_none() {
+                        self.solved.save(&self.data_file(puzzle::SOLVED_FILE));
+                    }
+                }
+                self.record_turn(final_score);
+                self.hooks.run(HookEvent::Victory, &self.hook_env(&haus));
+                self.state = GameState::Victory { haus, final_score };
+                self.ask_name(rank);
+            }
+            StepResult::Nommed(score) => {
+                info!("Nommed a morsel worth {}", score);
+            }
+            StepResult::LostLife => {
+                info!("Lost a life, {} remaining", haus.lives);
+            }
+            StepResult::Ongoing => {
+                // Normal movement, nothing special to do
+            }
+        }
+    }
+}
+
+impl SnekHaus {
+    /// The buffer cell for `pos`, if it's on the floor being shown.
+    fn floor_cell<'b>(
+        &self,
+        buf: &'b mut Buffer,
+        area: Rect,
+        pos: Pos,
+    ) -> Option<&'b mut buffer::Cell> {
+        if pos.floor != self.snek.head.floor {
+            return None;
+        }
+        buf.cell_mut((pos.x + area.x, pos.y + area.y))
+    }
+}
+
+impl Widget for &SnekHaus {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let floor = self.snek.head.floor;
+
+        // Background pattern first, for everything else to be drawn over. The
+        // shading stays out from under the sneks and morsels, whose symbols
+        // would clash with it
+        if self.background != Background::None {
+            let occupied: HashSet<Pos> = self
+                .player_sneks()
+                .flat_map(|snek| snek.body.iter().chain([&snek.head]))
+                .copied()
+                .chain(self.moresels.iter().map(|morsel| morsel.pos))
+                .collect();
+            for y in 0..self.size.height {
+                for x in 0..self.size.width {
+                    let pos = Pos { x, y, floor };
+                    let Some(cell) = self.floor_cell(buf, area, pos) else {
+                        continue;
+                    };
+                    match self.background {
+                        Background::Checkerboard
+                            if (x + y) % 2 == 0 && !occupied.contains(&pos) =>
+                        {
+                            cell.set_bg(self.skin.background);
+                        }
+                        Background::Dots
+                            if x.is_multiple_of(GRID_SPACING.0)
+                                && y.is_multiple_of(GRID_SPACING.1) =>
+                        {
+                            cell.set_symbol(self.glyphs.dot)
+                                .set_fg(self.skin.background);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        // Lethal cells outside the safe zone
+        if self.shrunk_by > 0 {
+            for y in 0..self.size.height {
+                for x in 0..self.size.width {
+                    let pos = Pos { x, y, floor };
+                    if !self.in_safe_zone(pos) {
+                        if let Some(cell) = self.floor_cell(buf, area, pos) {
+                            cell.set_bg(Color::Red);
+                        }
                     }
-                    StepResult::Ongoing => {
-                        // Normal movement, nothing special to do
+                }
+            }
+        }
+
+        // The best run's head, under everything else so it never hides anything
+        if let Some(cell) = self
+            .ghost_head
+            .and_then(|pos| self.floor_cell(buf, area, pos))
+        {
+            cell.set_symbol(self.glyphs.ghost).set_fg(Color::DarkGray);
+        }
+
+        // The ways into trouble next tick, tinted under whatever's there
+        if self.danger_cells {
+            for pos in self.lethal_moves() {
+                if let Some(cell) = self.floor_cell(buf, area, pos) {
+                    cell.set_bg(DANGER_TINT);
+                }
+            }
+        }
+
+        // The body runs from the tail to just behind the head
+        for snek in self.player_sneks().filter(|_| self.body_visible()) {
+            let length = snek.body.len();
+            for (index, &pos) in snek.body.iter().rev().enumerate() {
+                let towards_head = match index {
+                    0 => Some(snek.head),
+                    _ => snek.body.get(length - index).copied(),
+                };
+                let towards_tail = (index + 1 < length).then(|| snek.body[length - index - 2]);
+                let segment = skin::Segment {
+                    index,
+                    length,
+                    pos,
+                    links: [towards_head, towards_tail]
+                        .map(|other| other.and_then(|other| pos.direction_to(other, self.size))),
+                };
+                if let Some(cell) = self.floor_cell(buf, area, pos) {
+                    self.skin.draw(cell, segment, self.ticks, &self.glyphs);
+                }
+            }
+        }
+
+        for &pos in &self.ice {
+            if let Some(cell) = self.floor_cell(buf, area, pos) {
+                cell.set_symbol(self.glyphs.ice).set_fg(Color::LightBlue);
+            }
+        }
+
+        for &pos in &self.walls {
+            if let Some(cell) = self.floor_cell(buf, area, pos) {
+                cell.set_symbol(self.glyphs.wall).set_fg(Color::Gray);
+            }
+        }
+
+        for &pos in &self.hazards {
+            if let Some(cell) = self.floor_cell(buf, area, pos) {
+                cell.set_symbol(self.glyphs.hazard).set_fg(Color::Green);
+            }
+        }
+
+        for &pos in &self.weeds {
+            if let Some(cell) = self.floor_cell(buf, area, pos) {
+                cell.set_symbol(self.glyphs.weed).set_fg(Color::LightGreen);
+            }
+        }
+
+        // Add portals, each pair in its own colour
+        for (pair, colour) in self.portals.iter().zip(PORTAL_COLOURS.iter().cycle()) {
+            for pos in [pair.a, pair.b] {
+                if let Some(cell) = self.floor_cell(buf, area, pos) {
+                    cell.set_symbol(self.glyphs.portal).set_fg(*colour);
+                }
+            }
+        }
+
+        for wormhole in &self.wormholes {
+            if let Some(cell) = self.floor_cell(buf, area, wormhole.pos) {
+                cell.set_symbol(self.glyphs.wormhole)
+                    .set_fg(Color::LightMagenta);
+            }
+        }
+
+        // Add tunnels, pointing along the way they wrap
+        for &pos in &self.tunnels {
+            let symbol = if pos.x == 0 || pos.x + 1 == self.size.width {
+                self.glyphs.tunnels[0]
+            } else {
+                self.glyphs.tunnels[1]
+            };
+            if let Some(cell) = self.floor_cell(buf, area, pos) {
+                cell.set_symbol(symbol).set_fg(Color::LightMagenta);
+            }
+        }
+
+        // Add speed pads, pointing the way they push
+        for (&pos, &direction) in &self.pads {
+            let symbol = match direction {
+                Direction::North => self.glyphs.pads[0],
+                Direction::East => self.glyphs.pads[1],
+                Direction::South => self.glyphs.pads[2],
+                _ => self.glyphs.pads[3],
+            };
+            if let Some(cell) = self.floor_cell(buf, area, pos) {
+                cell.set_symbol(symbol).set_fg(Color::LightYellow);
+            }
+        }
+
+        // Add stairs, pointing the way they lead
+        for pair in &self.stairs {
+            for (pos, other) in [(pair.a, pair.b), (pair.b, pair.a)] {
+                let symbol = if other.floor > pos.floor {
+                    self.glyphs.stairs[0]
+                } else {
+                    self.glyphs.stairs[1]
+                };
+                if let Some(cell) = self.floor_cell(buf, area, pos) {
+                    cell.set_symbol(symbol).set_fg(Color::LightCyan);
+                }
+            }
+        }
+
+        for rival in self.live_rivals() {
+            for &pos in &rival.snek.body {
+                if let Some(cell) = self.floor_cell(buf, area, pos) {
+                    cell.set_symbol(" ").set_bg(Color::Blue);
+                }
+            }
+            if let Some(cell) = self.floor_cell(buf, area, rival.snek.head) {
+                cell.set_symbol(self.glyphs.rival).set_fg(Color::LightBlue);
+            }
+        }
+
+        for obstacle in &self.obstacles {
+            if let Some(cell) = self.floor_cell(buf, area, obstacle.pos()) {
+                cell.set_symbol(self.glyphs.obstacle)
+                    .set_fg(Color::LightRed);
+            }
+        }
+
+        for &pos in &self.predators {
+            if let Some(cell) = self.floor_cell(buf, area, pos) {
+                cell.set_symbol(self.glyphs.predator).set_fg(Color::Red);
+            }
+        }
+
+        // The twin's head, without the danger warning, which only looks
+        // ahead of the snek
+        if let Some(twin) = &self.twin {
+            if let Some(cell) = self.floor_cell(buf, area, twin.head) {
+                self.draw_head(cell, twin);
+            }
+        }
+
+        // Add snake head (different symbol/color)
+        if let Some(cell) = self.floor_cell(buf, area, self.snek.head) {
+            self.draw_head(cell, &self.snek);
+            if self.danger_warning {
+                let tint = match self.danger_ahead(2) {
+                    Some(1) => Some(Color::Red),
+                    Some(_) => Some(Color::Yellow),
+                    None => None,
+                };
+                if let Some(tint) = tint {
+                    cell.set_fg(Color::Black).set_bg(tint);
+                }
+            }
+        }
+
+        // Add morsels
+        for morsel in &self.moresels {
+            if let Some(cell) = self.floor_cell(buf, area, morsel.pos) {
+                let (symbol, colour) = match morsel.kind {
+                    MorselKind::Normal | MorselKind::Bonus => (
+                        self.morsel_symbol(&self.glyphs.morsels, morsel.growth_value),
+                        self.theme.morsel,
+                    ),
+                    MorselKind::Brittle => (
+                        self.morsel_symbol(&self.glyphs.morsels, morsel.growth_value),
+                        self.theme.brittle,
+                    ),
+                    MorselKind::Poison => (self.glyphs.poison, Color::Magenta),
+                    MorselKind::Scythe => (self.glyphs.scythe, Color::Yellow),
+                    MorselKind::Rewind => (self.glyphs.rewind, Color::LightGreen),
+                    MorselKind::Shield => (self.glyphs.shield, Color::LightCyan),
+                    MorselKind::Phase => (self.glyphs.phase, Color::White),
+                    MorselKind::Trap => (
+                        self.morsel_symbol(&self.glyphs.traps, morsel.growth_value),
+                        self.theme.morsel,
+                    ),
+                };
+                cell.set_symbol(symbol).set_fg(colour);
+            }
+        }
+
+        // Fog hides everything out of sight of the heads
+        if let Some(radius) = self.fog {
+            for y in 0..self.size.height {
+                for x in 0..self.size.width {
+                    let pos = Pos { x, y, floor };
+                    if self
+                        .player_sneks()
+                        .all(|snek| pos.wrapped_distance(snek.head, self.size) > radius)
+                    {
+                        if let Some(cell) = self.floor_cell(buf, area, pos) {
+                            cell.reset();
+                        }
                     }
                 }
             }
-            _ => {}
         }
+
+        // A wide head covers the cell to its right, whatever's there
+        glyphs::clear_spill(
+            buf,
+            Rect {
+                width: self.size.width,
+                height: self.size.height,
+                ..area
+            }
+            .intersection(area),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A game keeping its files in a temporary directory of its own, so tests
+    /// never touch the player's. The directory isn't created, so nothing is
+    /// saved unless a test creates it.
+    fn test_game(settings: Settings) -> Game {
+        static GAMES: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "snekrs_game_{}_{}",
+            std::process::id(),
+            GAMES.fetch_add(1, Ordering::Relaxed)
+        ));
+        Game::in_dir(settings, dir)
+    }
+
+    #[test]
+    fn test_opposite_directions() {
+        // Test direct opposites
+        assert_eq!(Direction::North.opposite(), Direction::South, "North should be opposite of South");
+        assert_eq!(Direction::South.opposite(), Direction::North, "South should be opposite of North");
+        assert_eq!(Direction::East.opposite(), Direction::West, "East should be opposite of West");
+        assert_eq!(Direction::West.opposite(), Direction::East, "West should be opposite of East");
+
+        // Test double opposite returns to original for all directions
+        assert_eq!(Direction::North.opposite().opposite(), Direction::North, "Double opposite of North should return to North");
+        assert_eq!(Direction::South.opposite().opposite(), Direction::South, "Double opposite of South should return to South");
+        assert_eq!(Direction::East.opposite().opposite(), Direction::East, "Double opposite of East should return to East");
+        assert_eq!(Direction::West.opposite().opposite(), Direction::West, "Double opposite of West should return to West");
+    }
+
+    #[test]
+    fn test_zero_delta() {
+        let arena_size = Size {
+            width: 10,
+            height: 10,
+        };
+
+        let pos = Pos { x: 5, y: 5, floor: 0 };
+        let delta = PosDelta { x: 0, y: 0 };
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, pos, "Zero delta should not change position");
+
+        // Test at boundaries too
+        let pos = Pos { x: 0, y: 0, floor: 0 };
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, pos, "Zero delta should not change position at minimum bounds");
+
+        let pos = Pos { x: 9, y: 9, floor: 0 };
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, pos, "Zero delta should not change position at maximum bounds");
+    }
+
+    #[test]
+    fn test_wrapped_distance() {
+        let size = Size {
+            width: 10,
+            height: 10,
+        };
+        assert_eq!(Pos { x: 1, y: 1, floor: 0 }.wrapped_distance(Pos { x: 4, y: 3, floor: 0 }, size), 5, "Plain distance should be manhattan");
+        assert_eq!(Pos { x: 0, y: 0, floor: 0 }.wrapped_distance(Pos { x: 9, y: 9, floor: 0 }, size), 2, "Distance should take the short way round");
+    }
+
+    #[test]
+    fn test_arena_size_deltas() {
+        let arena_size = Size {
+            width: 10,
+            height: 10,
+        };
+        let non_square_arena_size = Size {
+            width: 8,
+            height: 6,
+        };
+        let pos = Pos { x: 5, y: 5, floor: 0 };
+
+        // Moving exactly one arena width/height should return to the same position
+        let delta = PosDelta { x: 10, y: 10 };
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, pos, "Moving by exact arena size should return to same position");
+
+        // Moving exactly negative arena width/height should also return to same position
+        let delta = PosDelta { x: -10, y: -10 };
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, pos, "Moving by negative arena size should return to same position");
+
+        // Test with different x and y arena dimensions
+        let pos = Pos { x: 3, y: 3, floor: 0 };
+        let delta = PosDelta { x: 8, y: 6 };
+        let new_pos = pos.wrapped_add(delta, non_square_arena_size);
+        assert_eq!(new_pos, pos, "Moving by non-square arena dimensions should wrap correctly");
+    }
+
+    #[test]
+    fn test_large_deltas() {
+        let arena_size = Size {
+            width: 10,
+            height: 10,
+        };
+
+        let pos = Pos { x: 5, y: 5, floor: 0 };
+        let delta = PosDelta { x: 3, y: -3 };
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, Pos { x: 8, y: 2, floor: 0 }, "Large delta should wrap correctly within arena bounds");
+
+        // Test wrapping with large positive delta
+        let delta = PosDelta { x: 8, y: 12 };
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, Pos { x: 3, y: 7, floor: 0 }, "Large positive delta should wrap: (5+8)%10=3, (5+12)%10=7");
+
+        // Test wrapping with large negative delta
+        let delta = PosDelta { x: -12, y: -8 };
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, Pos { x: 3, y: 7, floor: 0 }, "Large negative delta should wrap: (5-12)%10=3, (5-8)%10=7");
+    }
+
+    #[test]
+    fn test_very_large_deltas() {
+        let arena_size = Size {
+            width: 10,
+            height: 10,
+        };
+
+        let pos = Pos { x: 5, y: 5, floor: 0 };
+        let delta = PosDelta { x: 25, y: -15 };
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, Pos { x: 0, y: 0, floor: 0 }, "Very large deltas should wrap multiple times: (5+25)%10=0, (5-15)%10=0");
+    }
+
+    #[test]
+    fn test_basic_movement() {
+        let arena_size = Size {
+            width: 10,
+            height: 10,
+        };
+
+        let pos = Pos { x: 5, y: 5, floor: 0 };
+        let delta: PosDelta = Direction::North.into();
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, Pos { x: 5, y: 4, floor: 0 }, "Moving North should decrease y coordinate by 1");
+    }
+
+    #[test]
+    fn test_wrap_underflow() {
+        let arena_size = Size {
+            width: 10,
+            height: 10,
+        };
+
+        let pos = Pos { x: 0, y: 0, floor: 0 };
+        let delta: PosDelta = Direction::North.into();
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, Pos { x: 0, y: 9, floor: 0 }, "Moving North from top should wrap to bottom");
+
+        let pos = Pos { x: 0, y: 5, floor: 0 };
+        let delta: PosDelta = Direction::West.into();
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, Pos { x: 9, y: 5, floor: 0 }, "Moving West from left edge should wrap to right edge");
+    }
+
+    #[test]
+    fn test_wrap_overflow() {
+        let arena_size = Size {
+            width: 10,
+            height: 10,
+        };
+
+        let pos = Pos { x: 9, y: 9, floor: 0 };
+        let delta: PosDelta = Direction::South.into();
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, Pos { x: 9, y: 0, floor: 0 }, "Moving South from bottom should wrap to top");
+
+        let pos = Pos { x: 9, y: 5, floor: 0 };
+        let delta: PosDelta = Direction::East.into();
+        let new_pos = pos.wrapped_add(delta, arena_size);
+        assert_eq!(new_pos, Pos { x: 0, y: 5, floor: 0 }, "Moving East from right edge should wrap to left edge");
+    }
+
+    #[test]
+    fn test_all_directions() {
+        let arena_size = Size {
+            width: 10,
+            height: 10,
+        };
+
+        let pos = Pos { x: 5, y: 5, floor: 0 };
+
+        assert_eq!(
+            pos.wrapped_add(Direction::North.into(), arena_size),
+            Pos { x: 5, y: 4, floor: 0 },
+            "Moving North should decrease y coordinate"
+        );
+        assert_eq!(
+            pos.wrapped_add(Direction::South.into(), arena_size),
+            Pos { x: 5, y: 6, floor: 0 },
+            "Moving South should increase y coordinate"
+        );
+        assert_eq!(
+            pos.wrapped_add(Direction::East.into(), arena_size),
+            Pos { x: 6, y: 5, floor: 0 },
+            "Moving East should increase x coordinate"
+        );
+        assert_eq!(
+            pos.wrapped_add(Direction::West.into(), arena_size),
+            Pos { x: 4, y: 5, floor: 0 },
+            "Moving West should decrease x coordinate"
+        );
+    }
+
+    #[test]
+    fn test_collision_detection() {
+        let snek = Snek {
+            head: Pos { x: 5, y: 5, floor: 0 },
+            body: VecDeque::from([Pos { x: 5, y: 6, floor: 0 }, Pos { x: 5, y: 7, floor: 0 }, Pos { x: 6, y: 7, floor: 0 }]),
+            direction: Direction::North,
+            pending_growth: 0,
+        };
+
+        assert!(snek.would_collide_with_body(Pos { x: 5, y: 6, floor: 0 }), "Should detect collision with first body segment");
+        assert!(snek.would_collide_with_body(Pos { x: 6, y: 7, floor: 0 }), "Should detect collision with last body segment");
+        assert!(!snek.would_collide_with_body(Pos { x: 5, y: 5, floor: 0 }), "Head position should not count as body collision");
+        assert!(!snek.would_collide_with_body(Pos { x: 4, y: 6, floor: 0 }), "Adjacent position should not count as collision");
+    }
+
+    #[test]
+    fn test_snacking() {
+        let mut snek = Snek {
+            head: Pos { x: 5, y: 5, floor: 0 },
+            body: VecDeque::new(),
+            direction: Direction::North,
+            pending_growth: 0,
+        };
+
+        let morsel = Morsel {
+            pos: Pos { x: 5, y: 4, floor: 0 },
+            growth_value: 3,
+            kind: MorselKind::Normal,
+            age: 0,
+        };
+
+        snek.snack(morsel, 1);
+        assert_eq!(snek.pending_growth, 3, "Snacking should set pending_growth to morsel's growth value");
+
+        snek.snack(Morsel {
+            pos: Pos { x: 0, y: 0, floor: 0 },
+            growth_value: 2,
+            kind: MorselKind::Normal,
+            age: 0,
+        }, 1);
+        assert_eq!(snek.pending_growth, 5, "Multiple snacks should accumulate pending_growth");
+
+        snek.snack(morsel, 2);
+        assert_eq!(snek.pending_growth, 11, "Faster growth should grow more segments per point");
+    }
+
+    #[test]
+    fn test_new_odd_snek() {
+        let size = Size {
+            width: 10,
+            height: 10,
+        };
+
+        let snek = Snek::new(size, 3);
+        println!("Odd length snek:");
+        println!("  head: {:?}", snek.head);
+        println!("  body: {:?}", snek.body);
+
+        let body: Vec<_> = snek.body.iter().collect();
+        println!("  body segments:");
+        for (i, pos) in body.iter().enumerate() {
+            println!("    segment {}: {:?}", i, pos);
+        }
+
+        assert_eq!(snek.head, Pos { x: 6, y: 5, floor: 0 }, "Head should be positioned correctly for odd length snake");
+        assert_eq!(snek.body.len(), 3, "Body should have correct number of segments");
+        assert_eq!(snek.body[0], Pos { x: 3, y: 5, floor: 0 }, "Leftmost segment should be positioned correctly");
+        assert_eq!(snek.body[1], Pos { x: 4, y: 5, floor: 0 }, "Middle segment should be positioned correctly");
+        assert_eq!(snek.body[2], Pos { x: 5, y: 5, floor: 0 }, "Rightmost segment should be positioned correctly");
+    }
+
+    #[test]
+    fn test_new_snek_even() {
+        let size = Size {
+            width: 10,
+            height: 10,
+        };
+        let snek = Snek::new(size, 4);
+
+        println!("Even length snek:");
+        println!("  head: {:?}", snek.head);
+        println!("  body: {:?}", snek.body);
+        println!("  body segments:");
+        for (i, pos) in snek.body.iter().enumerate() {
+            println!("    segment {}: {:?}", i, pos);
+        }
+
+        assert_eq!(snek.head, Pos { x: 7, y: 5, floor: 0 }, "Head should be positioned correctly for even length snake");
+        assert_eq!(snek.body.len(), 4, "Body should have correct number of segments");
+        assert_eq!(snek.body[0], Pos { x: 3, y: 5, floor: 0 }, "Leftmost segment should be positioned correctly");
+        assert_eq!(snek.body[1], Pos { x: 4, y: 5, floor: 0 }, "Second segment should be positioned correctly");
+        assert_eq!(snek.body[2], Pos { x: 5, y: 5, floor: 0 }, "Third segment should be positioned correctly");
+        assert_eq!(snek.body[3], Pos { x: 6, y: 5, floor: 0 }, "Rightmost segment should be positioned correctly");
+        assert_eq!(snek.direction, Direction::East, "Snake should start facing East");
+        assert_eq!(snek.pending_growth, 0, "Snake should start with no pending growth");
+    }
+
+    #[test]
+    fn test_snek_movement() {
+        let size = Size {
+            width: 10,
+            height: 10,
+        };
+        let mut snek = Snek::new(size, 3);
+        println!("Starting snek: {:?}", snek);
+
+        let initial_head = snek.head;
+        let initial_body: Vec<Pos> = snek.body.iter().cloned().collect();
+
+        snek.slither(size);
+
+        println!("Moved snek: {:?}", snek);
+
+        assert_eq!(
+            snek.head,
+            Pos {
+                x: initial_head.x + 1,
+                y: initial_head.y,
+                floor: 0
+            },
+            "Head position should be one step east of old head"
+        );
+
+        assert_eq!(
+            snek.body.back(),
+            Some(&initial_head),
+            "Old head is now the highest body segment"
+        );
+
+        assert_eq!(
+            initial_body[2], snek.body[1],
+            "Middle segments moved up 2 to 1"
+        );
+        assert_eq!(
+            initial_body[1], snek.body[0],
+            "Middle segments moved up 1 to 0"
+        );
+
+        assert_eq!(snek.body.len(), 3, "Length remained the same");
+        assert!(!snek.body.contains(&initial_body[0]), "Tail was removed");
+    }
+
+    #[test]
+    fn test_snek_self_collision() {
+        let size = Size {
+            width: 10,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+
+        assert!(!haus.check_snek_hit_itself(), "New snake should not be in self-collision state");
+
+        haus.snek.body.push_back(haus.snek.head);
+        assert!(haus.check_snek_hit_itself(), "Snake should detect collision when head overlaps with body");
+    }
+
+    #[test]
+    fn test_shield() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        let head = haus.snek.head;
+        haus.moresels.clear();
+        haus.moresels.push(Morsel { pos: Pos { x: head.x + 1, ..head }, growth_value: 1, kind: MorselKind::Shield, age: 0 });
+        haus.slither_on();
+        assert!(haus.shield, "Eating the pickup should raise the shield");
+
+        let head = haus.snek.head;
+        haus.walls.insert(Pos { x: head.x + 1, ..head });
+        assert!(matches!(haus.slither_on(), StepResult::Ongoing), "The shield should take the crash");
+        assert_eq!(haus.snek.head, head, "The snek should stop short of the wall");
+        assert!(!haus.shield, "The shield should be used up");
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::HitWall)), "The next crash should be deadly");
+
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.shield = true;
+        haus.moresels.clear();
+        haus.moresels.push(Morsel { pos: Pos { x: haus.snek.head.x + 1, ..haus.snek.head }, growth_value: 1, kind: MorselKind::Poison, age: 0 });
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::Poisoned)), "A shield shouldn't stop poison");
+    }
+
+    #[test]
+    fn test_phasing() {
+        let size = Size { width: 20, height: 10 };
+        let mut haus = SnekHaus::new(size, 3, Rules { edges: EdgePolicy::Walled, ..Rules::default() });
+        let head = haus.snek.head;
+        haus.moresels.clear();
+        haus.moresels.push(Morsel { pos: Pos { x: head.x + 1, ..head }, growth_value: 1, kind: MorselKind::Phase, age: 0 });
+        haus.slither_on();
+        assert_eq!(haus.phasing, PHASE_TICKS, "Eating the pickup should start phasing");
+
+        let head = haus.snek.head;
+        haus.walls.extend([Pos { x: head.x + 1, ..head }, Pos { x: head.x + 2, ..head }]);
+        haus.phasing = 1;
+        assert!(matches!(haus.slither_on(), StepResult::Ongoing), "A phasing snek should pass into a wall");
+        assert_eq!(haus.phasing, 1, "Phasing shouldn't run out inside a wall");
+        assert!(matches!(haus.slither_on(), StepResult::Ongoing), "A phasing snek should pass through the wall");
+        assert!(matches!(haus.slither_on(), StepResult::Ongoing), "A phasing snek should come out the other side");
+        assert_eq!(haus.phasing, 0, "Phasing should run out once clear of the wall");
+        haus.walls.insert(Pos { x: haus.snek.head.x + 1, ..haus.snek.head });
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::HitWall)), "Walls should be deadly again");
+
+        let mut haus = SnekHaus::new(size, 3, Rules { edges: EdgePolicy::Solid, ..Rules::default() });
+        haus.snek.head = Pos { x: size.width - 2, ..haus.snek.head };
+        haus.phasing = PHASE_TICKS;
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::HitWall)), "Solid edges should stop a phasing snek");
+    }
+
+    #[test]
+    fn test_lives_respawn() {
+        let size = Size {
+            width: 10,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, GameMode::Lives.rules());
+        haus.score = 7;
+
+        for remaining in [2, 1] {
+            haus.snek.body.push_back(Pos { x: haus.snek.head.x + 1, y: haus.snek.head.y, floor: 0 });
+            assert!(matches!(haus.slither_on(), StepResult::LostLife), "Collision should cost a life while lives remain");
+            assert_eq!(haus.lives, remaining, "A life should have been used up");
+            assert_eq!(haus.snek.head, Pos { x: 6, y: 5, floor: 0 }, "Snake should respawn at the centre");
+            assert_eq!(haus.snek.body.len(), 3, "Snake should respawn at its starting length");
+            assert_eq!(haus.score, 7, "Score should be kept across respawns");
+        }
+
+        haus.snek.body.push_back(Pos { x: haus.snek.head.x + 1, y: haus.snek.head.y, floor: 0 });
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::BitSelf)), "Last life lost should end the game");
+    }
+
+    #[test]
+    fn test_shrinking_arena() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, GameMode::Shrinking.rules());
+        haus.moresels.push(Morsel {
+            pos: Pos { x: 0, y: 0, floor: 0 },
+            growth_value: 1,
+            kind: MorselKind::Normal,
+            age: 0,
+        });
+
+        haus.shrink_arena();
+        assert_eq!(haus.shrunk_by, 1, "Arena should shrink by one ring");
+        assert!(!haus.in_safe_zone(Pos { x: 0, y: 5, floor: 0 }), "Outer ring should be lethal");
+        assert!(!haus.in_safe_zone(Pos { x: 19, y: 5, floor: 0 }), "Outer ring should be lethal on the far side");
+        assert!(haus.in_safe_zone(Pos { x: 1, y: 1, floor: 0 }), "Inner cells should remain safe");
+        assert!(haus.moresels.is_empty(), "Morsels in the lethal ring should be removed");
+
+        haus.shrink_arena();
+        haus.shrink_arena();
+        assert_eq!(haus.shrunk_by, 2, "Arena should never shrink below the minimum safe zone");
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let morsel = haus.new_morsel(&mut rng).unwrap();
+            assert!(
+                haus.in_safe_zone(morsel.pos),
+                "Morsels should only spawn in the safe zone"
+            );
+        }
+    }
+
+    #[test]
+    fn test_portal_teleports_head() {
+        let size = Size {
+            width: 10,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.portals.push(PortalPair {
+            a: Pos { x: 7, y: 5, floor: 0 },
+            b: Pos { x: 2, y: 1, floor: 0 },
+        });
+
+        assert_eq!(haus.next_head(&haus.snek, Direction::East), Pos { x: 3, y: 1, floor: 0 }, "Lookahead should follow the portal");
+        haus.slither_on();
+        assert_eq!(haus.snek.head, Pos { x: 3, y: 1, floor: 0 }, "Head should exit beyond the partner portal");
+        assert_eq!(haus.snek.direction, Direction::East, "Direction should be preserved through the portal");
+        assert_eq!(haus.snek.body.back(), Some(&Pos { x: 6, y: 5, floor: 0 }), "Body should follow from before the portal");
+    }
+
+    #[test]
+    fn test_setup_places_portals() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, GameMode::Portals.rules());
+        haus.setup(&mut rand::thread_rng());
+
+        assert_eq!(haus.portals.len(), 2, "Portal mode should place two pairs");
+        for pair in &haus.portals {
+            assert_ne!(pair.a, pair.b, "Portal ends should be distinct");
+            assert_ne!(pair.a.y, haus.snek.head.y, "Portals should be kept off the starting row");
+        }
+        for morsel in &haus.moresels {
+            assert!(!haus.is_portal(morsel.pos), "Morsels should not be placed on portals");
+        }
+    }
+
+    #[test]
+    fn test_crowded_arena_gives_up_on_portals() {
+        let size = Size {
+            width: 10,
+            height: 5,
+        };
+        let mut haus = SnekHaus::new(size, 3, GameMode::Portals.rules());
+        // Wall off everything but the snek's own row
+        for y in 0..size.height {
+            for x in 0..size.width {
+                let pos = Pos { x, y, floor: 0 };
+                if y != haus.snek.head.y {
+                    haus.walls.insert(pos);
+                }
+            }
+        }
+        let mut rng = rand::thread_rng();
+        haus.setup(&mut rng);
+
+        assert!(haus.portals.is_empty(), "No room should mean no portals");
+        assert_eq!(haus.random_portal_cell(&mut rng), None);
+        let free = haus.random_empty_cell(&mut rng).unwrap();
+        assert_eq!(free.y, haus.snek.head.y, "Only the snek's row is free");
+    }
+
+    #[test]
+    fn test_victory_targets() {
+        let size = Size {
+            width: 10,
+            height: 10,
+        };
+        let rules = Rules {
+            target: Some(VictoryTarget::Score(3)),
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        haus.moresels.push(Morsel {
+            pos: Pos { x: 7, y: 5, floor: 0 },
+            growth_value: 2,
+            kind: MorselKind::Normal,
+            age: 0,
+        });
+        haus.moresels.push(Morsel {
+            pos: Pos { x: 8, y: 5, floor: 0 },
+            growth_value: 2,
+            kind: MorselKind::Normal,
+            age: 0,
+        });
+
+        assert!(matches!(haus.slither_on(), StepResult::Nommed(2)), "Short of the target the game should go on");
+        assert_eq!(VictoryTarget::Score(3).progress(&haus), (2, 3), "Progress should track the score");
+        assert!(matches!(haus.slither_on(), StepResult::Victory), "Reaching the target score should win");
+
+        let length = VictoryTarget::Length(6);
+        assert_eq!(length.progress(&haus), (5, 6), "Length should include the head");
+        haus.slither_on();
+        assert!(length.reached(&haus), "Length target should be reached once the snek grows");
+
+        let mut haus = SnekHaus::new(size, 3, Rules { target: Some(VictoryTarget::Survive(2)), ..Rules::default() });
+        haus.clock = Duration::from_millis(1900);
+        assert_eq!(VictoryTarget::Survive(2).progress(&haus), (1, 2), "Progress should count whole seconds survived");
+        assert!(matches!(haus.slither_on(), StepResult::Victory), "Surviving long enough should win");
+    }
+
+    #[test]
+    fn test_walls_are_lethal() {
+        let level = Level::parse("#######\n#.....#\n#...S##\n#######", "walled", 3).expect("Level should parse");
+        let mut haus = SnekHaus::from_level(&level, 3, Rules::default());
+
+        assert_eq!(haus.snek.head, Pos { x: 4, y: 2, floor: 0 }, "Snake should start at the level spawn");
+        assert_eq!(haus.snek.body, VecDeque::from([Pos { x: 1, y: 2, floor: 0 }, Pos { x: 2, y: 2, floor: 0 }, Pos { x: 3, y: 2, floor: 0 }]), "Body should trail west of the spawn");
+        assert!(haus.is_lethal(Pos { x: 5, y: 2, floor: 0 }), "Walls should be lethal");
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::HitWall)), "Running into a wall should end the game");
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            assert!(
+                !haus.walls.contains(&haus.new_morsel(&mut rng).unwrap().pos),
+                "Morsels should never spawn in walls"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rival_competes_for_morsels() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.rivals.push(Rival {
+            snek: Snek::spawn_at(Pos { x: 10, y: 1, floor: 0 }, 3),
+            score: 0,
+            alive: true,
+        });
+        haus.moresels.push(Morsel {
+            pos: Pos { x: 11, y: 1, floor: 0 },
+            growth_value: 4,
+            kind: MorselKind::Normal,
+            age: 0,
+        });
+
+        haus.slither_on();
+        assert_eq!(haus.rivals[0].score, 4, "Rival should score for morsels it eats");
+        assert_eq!(haus.rivals[0].snek.pending_growth, 4, "Rival should grow from morsels it eats");
+        assert!(haus.moresels.is_empty(), "Eaten morsel should be removed");
+        assert_eq!(haus.score, 0, "Player shouldn't score for the rival's morsel");
+    }
+
+    #[test]
+    fn test_hitting_rival_ends_game() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        // Rival body lies across the player's path
+        haus.rivals.push(Rival {
+            snek: Snek {
+                head: Pos { x: 12, y: 7, floor: 0 },
+                body: VecDeque::from([Pos { x: 12, y: 4, floor: 0 }, Pos { x: 12, y: 5, floor: 0 }, Pos { x: 12, y: 6, floor: 0 }]),
+                direction: Direction::South,
+                pending_growth: 0,
+            },
+            score: 0,
+            alive: true,
+        });
+        haus.snek.head = Pos { x: 11, y: 5, floor: 0 };
+
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::HitRival)), "Running into the rival's body should end the run");
+    }
+
+    #[test]
+    fn test_rival_crashing_into_player() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        // Rival boxed in against the player's body with nowhere safe to go
+        haus.snek.body = VecDeque::from([Pos { x: 0, y: 3, floor: 0 }, Pos { x: 1, y: 3, floor: 0 }, Pos { x: 2, y: 3, floor: 0 }]);
+        haus.snek.head = Pos { x: 3, y: 3, floor: 0 };
+        haus.walls.extend([Pos { x: 0, y: 1, floor: 0 }, Pos { x: 1, y: 1, floor: 0 }, Pos { x: 2, y: 2, floor: 0 }]);
+        haus.rivals.push(Rival {
+            snek: Snek {
+                head: Pos { x: 1, y: 2, floor: 0 },
+                body: VecDeque::from([Pos { x: 0, y: 2, floor: 0 }]),
+                direction: Direction::East,
+                pending_growth: 0,
+            },
+            score: 0,
+            alive: true,
+        });
+
+        assert!(matches!(haus.slither_on(), StepResult::Ongoing), "Player should carry on");
+        assert!(!haus.rivals[0].alive, "Rival should crash when it has nowhere to go");
+    }
+
+    #[test]
+    fn test_morsel_weights() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let rules = Rules {
+            morsel_weights: Some(MorselWeights::new(&[0, 0, 1])),
+            ..Rules::default()
+        };
+        assert!(
+            (0..20).all(|_| rules.roll_morsel_value(&mut rng) == 3),
+            "Only values with weight should be picked"
+        );
+        assert_eq!(
+            rules.max_morsel_value(),
+            3,
+            "The weights should set the most a morsel is worth"
+        );
+        assert_eq!(
+            Rules::default().mean_morsel_value(),
+            3.0,
+            "Values from 1 to 5 should average 3"
+        );
+        let rules = Rules {
+            morsel_weights: Some(MorselWeights::new(&[3, 1])),
+            ..Rules::default()
+        };
+        assert_eq!(
+            rules.mean_morsel_value(),
+            1.25,
+            "The average should follow the weights"
+        );
+        let big = (0..1000)
+            .filter(|_| rules.roll_morsel_value(&mut rng) == 2)
+            .count();
+        assert!(
+            (150..350).contains(&big),
+            "Values should be picked in proportion to their weights, got {} of 1000",
+            big
+        );
+        assert!(
+            GameMode::TimeAttack.rules().mean_morsel_value() > Rules::default().mean_morsel_value(),
+            "Time Attack should favour big morsels"
+        );
+
+        let mut game = test_game(Settings {
+            morsel_weights: Some(MorselWeights::new(&[4, 2, 1])),
+            ..Settings::default()
+        });
+        game.arena_size = Some(Size {
+            width: 30,
+            height: 15,
+        });
+        game.mode = GameMode::TimeAttack;
+        let Some(GameState::Playing(haus, _)) = game.start_game() else {
+            panic!("Game should start");
+        };
+        assert_eq!(
+            haus.rules.morsel_weights(),
+            &[4, 2, 1],
+            "The setting should take over from the mode's weights"
+        );
+
+        let mut haus = SnekHaus::new(
+            Size {
+                width: 10,
+                height: 5,
+            },
+            3,
+            Rules {
+                morsel_weights: Some(MorselWeights::new(&[1, 1, 1])),
+                ..Rules::default()
+            },
+        );
+        assert_eq!(
+            haus.morsel_symbol(&glyphs::UNICODE.morsels, 3),
+            "★",
+            "The most a morsel is worth should get the last symbol"
+        );
+        assert_eq!(
+            haus.morsel_symbol(&glyphs::UNICODE.morsels, 2),
+            "♥",
+            "Symbols should spread over the table"
+        );
+        assert_eq!(
+            haus.morsel_symbol(&glyphs::UNICODE.morsels, 5),
+            "★",
+            "Values over the most should still have a symbol"
+        );
+        haus.rules = Rules::default();
+        assert_eq!(haus.morsel_symbol(&glyphs::UNICODE.traps, 2), "♢", "The usual values should keep their symbols");
+    }
+
+    #[test]
+    fn test_battle_royale() {
+        let size = Size { width: 40, height: 20 };
+        let mut haus = SnekHaus::new(size, 3, GameMode::Royale.rules());
+        haus.setup(&mut StdRng::seed_from_u64(7));
+        assert!(ROYALE_RIVALS.contains(&(haus.rivals.len() as u8)), "A battle royale should have 5 to 10 rivals, not {}", haus.rivals.len());
+
+        let mut haus = SnekHaus::new(Size { width: 20, height: 10 }, 3, GameMode::Royale.rules());
+        haus.snek.body = VecDeque::from([Pos { x: 0, y: 3, floor: 0 }, Pos { x: 1, y: 3, floor: 0 }, Pos { x: 2, y: 3, floor: 0 }]);
+        haus.snek.head = Pos { x: 3, y: 3, floor: 0 };
+        haus.walls.extend([Pos { x: 0, y: 1, floor: 0 }, Pos { x: 1, y: 1, floor: 0 }, Pos { x: 2, y: 2, floor: 0 }]);
+        haus.rivals.push(Rival {
+            snek: Snek { head: Pos { x: 1, y: 2, floor: 0 }, body: VecDeque::from([Pos { x: 0, y: 2, floor: 0 }]), direction: Direction::East, pending_growth: 1 },
+            score: 0,
+            alive: true,
+        });
+        assert!(matches!(haus.slither_on(), StepResult::Victory), "The last snek standing should win");
+        let remains: Vec<Pos> = haus.moresels.iter().map(|m| m.pos).collect();
+        assert_eq!(remains, vec![Pos { x: 0, y: 2, floor: 0 }, Pos { x: 1, y: 2, floor: 0 }], "A crashed rival's body should turn into morsels");
+    }
+
+    #[test]
+    fn test_victory_screen() {
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        let size = Size {
+            width: 60,
+            height: 16,
+        };
+        let mut screen = |game: &mut Game, rules| {
+            game.state = GameState::Victory {
+                final_score: 5,
+                haus: SnekHaus::new(size, 3, rules),
+            };
+            bugreport::frame_text(
+                terminal
+                    .draw(|f| game.render(f))
+                    .expect("Victory should draw")
+                    .buffer,
+            )
+        };
+
+        let royale = screen(&mut game, GameMode::Royale.rules());
+        assert!(
+            royale.contains("Last snek standing!"),
+            "Winning a battle royale should say so"
+        );
+        assert!(
+            !royale.contains("Target"),
+            "A battle royale has no target to reach"
+        );
+        let target = Rules {
+            target: Some(VictoryTarget::Score(5)),
+            ..Rules::default()
+        };
+        assert!(
+            screen(&mut game, target).contains("Target Score 5 reached"),
+            "Reaching a target should say which"
+        );
+        let puzzle = Puzzle::parse("moves: 5\n#...S*.#", "x").expect("Puzzle should parse");
+        let solved = screen(&mut game, puzzle.rules());
+        assert!(
+            solved.contains("Puzzle solved in 0 moves"),
+            "Solving a puzzle should count the moves"
+        );
+        assert!(
+            !solved.contains("Target"),
+            "A puzzle has no target to reach"
+        );
+        fs::remove_dir_all(&game.data_dir).ok();
+    }
+
+    #[test]
+    fn test_trap_morsel() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, GameMode::Classic.rules());
+        haus.score = 3;
+        haus.combo = 2;
+        haus.combo_timer = 5;
+        let length = haus.snek.length();
+        haus.moresels.push(Morsel { pos: Pos { x: 12, y: 5, floor: 0 }, growth_value: 2, kind: MorselKind::Trap, age: 0 });
+        haus.moresels.push(Morsel { pos: Pos { x: 13, y: 5, floor: 0 }, growth_value: 4, kind: MorselKind::Trap, age: 0 });
+
+        assert!(matches!(haus.slither_on(), StepResult::Nommed(0)), "Traps should be eaten for nothing");
+        assert_eq!(haus.score, 1, "Traps should take their value off the score");
+        assert_eq!(haus.combo, 0, "Traps should break the combo");
+        haus.slither_on();
+        assert_eq!(haus.score, 0, "Traps shouldn't take the score below zero");
+        haus.slither_on();
+        assert_eq!(haus.snek.length(), length, "Traps shouldn't make the snek grow");
+    }
+
+    #[test]
+    fn test_run_codes() {
+        let mutators = Mutators {
+            mirror: Mirror::All,
+            rotting: true,
+            traps: true,
+            edges: Some(EdgePolicy::Solid),
+            ..Mutators::default()
+        };
+        assert_eq!(
+            Mutators::from_bits(mutators.bits()),
+            Some(mutators),
+            "Modifiers should pack and unpack the same"
+        );
+        assert_eq!(Mutators::from_bits(0), Some(Mutators::default()));
+        assert_eq!(
+            Mutators::from_bits(0xffff),
+            None,
+            "Unknown modifiers should be refused"
+        );
+
+        let mut game = test_game(Settings::default());
+        let size = Size {
+            width: 30,
+            height: 12,
+        };
+        let code = RunCode {
+            mode: "weeds".to_string(),
+            size,
+            speed: "normal".to_string(),
+            start_length: game.settings.start_length,
+            growth: game.settings.growth,
+            morsel_weights: "mode".to_string(),
+            mutators: mutators.bits(),
+            seed: 0xc0ffee,
+        };
+        assert!(game.use_run_code(code.clone()).is_ok());
+        assert_eq!(game.mode, GameMode::Weeds);
+        assert_eq!(
+            game.mutators, mutators,
+            "Modifiers should come with the code"
+        );
+        assert_eq!(game.next_arena, Some(size), "So should the arena");
+        assert_eq!(game.next_seed, Some(0xc0ffee));
+
+        game.next_seed = None;
+        let fast = RunCode {
+            speed: "fast".to_string(),
+            ..code
+        };
+        assert!(
+            game.use_run_code(fast).is_err(),
+            "A run at another speed shouldn't be replayed at this one"
+        );
+        assert_eq!(game.next_seed, None, "A refused code should change nothing");
+    }
+
+    #[test]
+    fn test_traps_are_a_modifier() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let count_traps = |rules: Rules| {
+            let haus = SnekHaus::new(size, 3, rules);
+            let mut rng = StdRng::seed_from_u64(7);
+            (0..1000)
+                .filter_map(|_| haus.new_morsel(&mut rng))
+                .filter(|m| m.kind == MorselKind::Trap)
+                .count()
+        };
+        assert_eq!(
+            count_traps(GameMode::Classic.rules()),
+            0,
+            "Traps should only turn up with the modifier"
+        );
+        let rules = Rules {
+            mutators: Mutators {
+                traps: true,
+                ..Mutators::default()
+            },
+            ..GameMode::Classic.rules()
+        };
+        assert!(count_traps(rules) > 0, "The modifier should hide traps");
+    }
+
+    #[test]
+    fn test_famine() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = Rules {
+            famine: Some(2),
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 2, rules);
+        haus.moresels.push(Morsel { pos: Pos { x: 12, y: 5, floor: 0 }, growth_value: 1, kind: MorselKind::Normal, age: 0 });
+        haus.slither_on();
+        assert_eq!(haus.famine, 2, "Eating should hold off the famine");
+        haus.slither_on();
+        haus.slither_on();
+        assert_eq!(haus.snek.length(), 3, "Growth still to come should go first");
+        haus.slither_on();
+        haus.slither_on();
+        assert_eq!(haus.snek.length(), 2, "A hungry snek should lose a segment every interval");
+        haus.slither_on();
+        haus.slither_on();
+        assert_eq!(haus.snek.length(), 1, "The snek should waste away to its head");
+        haus.slither_on();
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::WastedAway)), "Losing the head should be fatal");
+    }
+
+    #[test]
+    fn test_chaos_mutations() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, GameMode::Chaos.rules());
+        let mut rng = StdRng::seed_from_u64(1);
+        haus.mutate(&mut rng);
+        assert_eq!(haus.mutation, None, "Nothing should change before the first interval");
+        haus.clock = CHAOS_INTERVAL;
+        haus.mutate(&mut rng);
+        assert_eq!(haus.mutations, 1, "A rule should change every interval");
+        assert!(haus.mutation.is_some(), "The change should be announced");
+
+        haus.apply_mutation(Mutation::Edges(EdgePolicy::Walled));
+        assert!(haus.walls.contains(&Pos { x: 0, y: 0, floor: 0 }), "Walled edges should be walls");
+        assert!(!haus.walls.contains(&Pos { x: 0, y: 5, floor: 0 }), "Walled edges should keep their tunnels");
+        haus.apply_mutation(Mutation::Edges(EdgePolicy::Wrap));
+        assert!(haus.walls.is_empty(), "Wrapping again should take the walls down");
+
+        haus.rules.speed.base_millis = *CHAOS_SPEEDS.start();
+        haus.apply_mutation(Mutation::Speed { faster: true });
+        assert_eq!(haus.rules.speed.base_millis, *CHAOS_SPEEDS.start(), "Speeding up should stop at the fastest");
+
+        haus.apply_mutation(Mutation::DoubleValue(true));
+        haus.moresels.push(Morsel { pos: Pos { x: 12, y: 5, floor: 0 }, growth_value: 2, kind: MorselKind::Normal, age: 0 });
+        haus.slither_on();
+        assert_eq!(haus.score, 4, "Morsels should score double");
+    }
+
+    #[test]
+    fn test_boost_meter() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = Rules {
+            boost: true,
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        haus.set_boost(true);
+        assert!(!haus.boosting, "An empty meter can't boost");
+        haus.moresels.push(Morsel { pos: Pos { x: 12, y: 5, floor: 0 }, growth_value: 1, kind: MorselKind::Normal, age: 0 });
+        haus.moresels.push(Morsel { pos: Pos { x: 13, y: 5, floor: 0 }, growth_value: 1, kind: MorselKind::Normal, age: 0 });
+        haus.slither_on();
+        assert_eq!(haus.boost, BOOST_PER_POINT, "Eating should fill the meter");
+
+        let normal = haus.tick_rate();
+        haus.set_boost(true);
+        assert_eq!(haus.tick_rate(), normal / 2, "Boosting should play at double speed");
+        haus.slither_on();
+        assert_eq!(haus.score, 3, "Noms while boosting should score double");
+        assert_eq!(haus.boost, 2 * BOOST_PER_POINT - BOOST_DRAIN, "Boosting should drain the meter");
+        haus.set_boost(false);
+        assert_eq!(haus.tick_rate(), normal, "Letting go should go back to normal speed");
+    }
+
+    #[test]
+    fn test_starvation() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = Rules {
+            hunger: Some(3),
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        haus.moresels.push(Morsel {
+            pos: Pos { x: 13, y: 5, floor: 0 },
+            growth_value: 1,
+            kind: MorselKind::Normal,
+            age: 0,
+        });
+
+        assert!(matches!(haus.slither_on(), StepResult::Ongoing), "Hungry snek should keep going");
+        assert_eq!(haus.hunger, 2, "Hunger should deplete each tick");
+        assert!(matches!(haus.slither_on(), StepResult::Nommed(1)), "Snek should eat");
+        assert_eq!(haus.hunger, 3, "Eating should refill hunger");
+
+        haus.slither_on();
+        haus.slither_on();
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::Starved)), "Running out of food should end the run");
+        assert_eq!(haus.death, Some(DeathCause::Starved), "Death cause should be recorded");
+    }
+
+    #[test]
+    fn test_walled_edges_with_tunnels() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = Rules {
+            edges: EdgePolicy::Walled,
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        assert!(haus.walls.contains(&Pos { x: 0, y: 0, floor: 0 }), "Corners should be walled");
+        assert!(!haus.walls.contains(&Pos { x: 19, y: 5, floor: 0 }), "Tunnels should be left open");
+
+        for _ in 0..9 {
+            assert!(matches!(haus.slither_on(), StepResult::Ongoing), "Snek should slither through the tunnel");
+        }
+        assert_eq!(haus.snek.head, Pos { x: 0, y: 5, floor: 0 }, "Tunnel should wrap to the opposite edge");
+
+        haus.snek.head = Pos { x: 5, y: 1, floor: 0 };
+        haus.change_direction(Direction::North);
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::HitWall)), "Edges away from tunnels should be walls");
+    }
+
+    #[test]
+    fn test_cylinder_edges() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = Rules {
+            edges: EdgePolicy::WrapAcross,
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        haus.moresels.clear();
+        assert!(haus.walls.contains(&Pos { x: 5, y: 0, floor: 0 }), "The top edge should be walled");
+        assert!(!haus.walls.contains(&Pos { x: 19, y: 5, floor: 0 }), "The side edges should be open");
+        for _ in 0..9 {
+            assert!(matches!(haus.slither_on(), StepResult::Ongoing), "Snek should slither off the side");
+        }
+        assert_eq!(haus.snek.head, Pos { x: 0, y: 5, floor: 0 }, "The sides should wrap");
+        haus.change_direction(Direction::North);
+        let died = (0..5).any(|_| matches!(haus.slither_on(), StepResult::Died(DeathCause::HitWall)));
+        assert!(died, "The top should be a wall");
+
+        let rules = Rules {
+            edges: EdgePolicy::WrapDown,
+            ..Rules::default()
+        };
+        let haus = SnekHaus::new(size, 3, rules);
+        assert!(haus.walls.contains(&Pos { x: 19, y: 5, floor: 0 }), "The side edges should be walled");
+        assert!(!haus.walls.contains(&Pos { x: 5, y: 0, floor: 0 }), "The top edge should be open");
+    }
+
+    #[test]
+    fn test_hardcore() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = GameMode::Hardcore.rules();
+        let mut haus = SnekHaus::new(size, 3, rules);
+        let mut rng = rand::thread_rng();
+        assert!(haus.tunnels.is_empty(), "Hardcore edges should have no tunnels");
+        assert!(haus.walls.contains(&Pos { x: 19, y: 5, floor: 0 }), "Every edge cell should be a wall");
+        assert_eq!(rules.speed.tick_rate(0) * 2, SpeedCurve::default().tick_rate(0), "Hardcore should run at double speed");
+        assert!(rules.no_pause, "Hardcore shouldn't allow pausing");
+        assert_ne!(GameMode::Hardcore.high_score_file(), HIGH_SCORE_FILE, "Hardcore should keep its own high score");
+
+        haus.setup(&mut rng);
+        assert!(haus.moresels.iter().all(|m| m.growth_value == 1), "Hardcore morsels should only be worth 1");
+    }
+
+    #[test]
+    fn test_survival() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = GameMode::Survival.rules();
+        let mut haus = SnekHaus::new(size, 3, rules);
+        let mut rng = rand::thread_rng();
+        haus.moresels.push(Morsel {
+            pos: Pos { x: 12, y: 5, floor: 0 },
+            growth_value: 5,
+            kind: MorselKind::Normal,
+            age: 0,
+        });
+
+        haus.slither_on();
+        assert_eq!(haus.score, 0, "Morsels shouldn't score in survival");
+        haus.clock = Duration::from_secs(95);
+        haus.slither_on();
+        assert_eq!(haus.score, 95, "Score should be the seconds survived");
+
+        haus.escalate(&mut rng);
+        assert_eq!(haus.escalations, 3, "A hazard should be added for every interval passed");
+        assert!(haus.moresels.iter().any(|m| m.kind == MorselKind::Poison), "Poison should be among the hazards");
+        assert_eq!(haus.obstacles.len(), 1, "A moving obstacle should be among the hazards");
+        haus.escalate(&mut rng);
+        assert_eq!(haus.escalations, 3, "Hazards shouldn't be added until the next interval");
+    }
+
+    #[test]
+    fn test_poison_is_fatal() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.moresels.push(Morsel {
+            pos: Pos { x: 12, y: 5, floor: 0 },
+            growth_value: 1,
+            kind: MorselKind::Poison,
+            age: 0,
+        });
+
+        haus.top_up_morsels(&mut rand::thread_rng());
+        assert_eq!(haus.moresels.len(), 2, "Poison shouldn't count as something to eat");
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::Poisoned)), "Eating poison should be fatal");
+    }
+
+    #[test]
+    fn test_time_attack() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = Rules {
+            lives: Some(3),
+            time_limit: Some(SpeedCurve::default().tick_rate(0) * 3),
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+
+        haus.slither_on();
+        haus.slither_on();
+        assert_eq!(
+            haus.time_left(),
+            Some(SpeedCurve::default().tick_rate(0)),
+            "Clock should count down each tick"
+        );
+        assert!(
+            matches!(haus.slither_on(), StepResult::Died(DeathCause::TimeUp)),
+            "Game should end when the clock runs out"
+        );
+        assert_eq!(
+            haus.death,
+            Some(DeathCause::TimeUp),
+            "Time up should be recorded, whatever the lives left"
+        );
+    }
+
+    #[test]
+    fn test_margin_shrinks_arena() {
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        game.settings.margin = 0;
+        terminal.draw(|f| game.render(f)).expect("Start screen should draw");
+        let full = game.arena_size.expect("Arena size should be set");
+        game.settings.margin = 2;
+        terminal.draw(|f| game.render(f)).expect("Start screen should draw");
+        let inset = game.arena_size.expect("Arena size should be set");
+        assert_eq!((inset.width, inset.height), (full.width - 4, full.height - 4), "Margin should inset every side");
+    }
+
+    #[test]
+    fn test_mirrored_controls() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = Rules {
+            mutators: Mutators { mirror: Mirror::LeftRight, ..Mutators::default() },
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        let mut rng = rand::thread_rng();
+
+        haus.mirror_timer = 0;
+        haus.flip_mirror(&mut rng);
+        assert!(haus.mirrored, "Controls should flip when the timer runs out");
+        assert!(MIRROR_TICKS.contains(&haus.mirror_timer), "Timer should restart at random");
+
+        haus.steer(Direction::North);
+        assert_eq!(haus.snek.direction, Direction::North, "Up and down should still work");
+        haus.slither_on();
+        haus.steer(Direction::West);
+        assert_eq!(haus.snek.direction, Direction::East, "Left should steer right while mirrored");
+        assert_eq!(Direction::South.mirrored(Mirror::All), Direction::North, "Mirroring everything should swap up and down too");
+    }
+
+    #[test]
+    fn test_turn_queue() {
+        let size = Size { width: 20, height: 10 };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        let start = haus.snek.head;
+        haus.steer(Direction::North);
+        haus.steer(Direction::West);
+        assert_eq!(haus.snek.direction, Direction::North, "Only one turn should be made a tick");
+        haus.slither_on();
+        assert_eq!(haus.snek.head, Pos { y: start.y - 1, ..start }, "The first turn should be made straight away");
+        haus.slither_on();
+        assert_eq!(haus.snek.head, Pos { x: start.x - 1, y: start.y - 1, ..start }, "The second turn should wait for the next tick, not double the snek back");
+        for _ in 0..10 {
+            haus.steer(Direction::South);
+        }
+        assert_eq!(haus.turns.len(), MAX_QUEUED_TURNS, "Only a few turns should be kept waiting");
+    }
+
+    #[test]
+    fn test_dizzy_snek() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = Rules {
+            mutators: Mutators { dizzy: true, ..Mutators::default() },
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        let mut rng = rand::thread_rng();
+
+        haus.dizzy_timer = 0;
+        haus.wobble(&mut rng);
+        assert!(DIZZY_TICKS.contains(&haus.dizzy_timer), "Timer should restart at random");
+        haus.slither_on();
+        assert!(matches!(haus.snek.direction, Direction::North | Direction::South), "Dizzy snek should turn on its own");
+
+        haus.dizzy_timer = 0;
+        haus.wobble(&mut rng);
+        haus.steer(Direction::East);
+        haus.slither_on();
+        assert_eq!(haus.snek.direction, Direction::East, "Steering should overrule the dizzy turn");
+        assert_eq!(haus.dizzy_turn, None, "Overruled turns shouldn't linger");
+    }
+
+    #[test]
+    fn test_ice() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.moresels.clear();
+        let start = haus.snek.head;
+        haus.ice.insert(start);
+        haus.ice.insert(Pos { x: start.x + 1, ..start });
+
+        haus.steer(Direction::North);
+        haus.slither_on();
+        assert_eq!(haus.snek.direction, Direction::East, "The snek shouldn't turn on ice");
+        haus.steer(Direction::South);
+        haus.slither_on();
+        assert_eq!(haus.snek.head, Pos { x: start.x + 2, ..start }, "The snek should slide straight across the ice");
+        haus.steer(Direction::North);
+        haus.slither_on();
+        assert_eq!(haus.snek.direction, Direction::North, "The snek should turn again once it's off the ice");
+    }
+
+    #[test]
+    fn test_speed_pads() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.moresels.clear();
+        let start = haus.snek.head;
+        haus.pads.insert(Pos { x: start.x + 1, ..start }, Direction::North);
+        assert!(matches!(haus.slither_on(), StepResult::Ongoing), "Crossing a pad should be safe");
+        assert_eq!(haus.snek.head, Pos { x: start.x + 1, y: start.y - 1, ..start }, "The pad should push the snek a cell further");
+        assert_eq!(haus.snek.direction, Direction::North, "The snek should carry on the way the pad points");
+
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.moresels.clear();
+        haus.pads.insert(Pos { x: start.x + 1, ..start }, Direction::West);
+        haus.slither_on();
+        assert_eq!(haus.snek.head, Pos { x: start.x + 1, ..start }, "A pad pointing back the way the snek came shouldn't push it");
+
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.moresels.clear();
+        haus.pads.insert(Pos { x: start.x + 1, ..start }, Direction::East);
+        haus.walls.insert(Pos { x: start.x + 2, ..start });
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::HitWall)), "The cell a pad pushes into should be checked too");
+    }
+
+    #[test]
+    fn test_score_escalation() {
+        let size = Size {
+            width: 30,
+            height: 15,
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.escalation = escalation::parse("50: obstacles 2\n100: predator\n150: fog 6").expect("Table should parse");
+        haus.escalate_by_score(&mut rng);
+        assert_eq!(haus.escalated, 0, "Nothing should happen before the first threshold");
+        haus.score = 120;
+        haus.escalate_by_score(&mut rng);
+        assert_eq!(haus.escalated, 2, "Every threshold passed should be applied");
+        assert_eq!(haus.obstacles.len(), 2, "Obstacles should appear at 50");
+        assert_eq!(haus.predators.len(), 1, "A predator should appear at 100");
+        assert!(haus.predators[0].wrapped_distance(haus.snek.head, size) >= 10, "The predator should start well away");
+        assert_eq!(haus.fog, None, "Fog shouldn't roll in before 150");
+        haus.score = 150;
+        haus.escalate_by_score(&mut rng);
+        assert_eq!(haus.fog, Some(6), "Fog should roll in at 150");
+
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.moresels.clear();
+        let head = haus.snek.head;
+        haus.predators.push(Pos { x: head.x + 4, ..head });
+        let mut caught = None;
+        for _ in 0..4 {
+            if let StepResult::Died(cause) = haus.slither_on() {
+                caught = Some(cause);
+                break;
+            }
+        }
+        assert_eq!(caught, Some(DeathCause::Caught), "A predator should hunt down the head");
     }
-}
 
-impl Widget for &SnekHaus {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        for pos in &self.snek.body {
-            buf[(pos.x + area.x, pos.y + area.y)]
-                .set_symbol(" ")
-                .set_bg(Color::Green);
+    #[test]
+    fn test_tron_trail() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        let rules = GameMode::Tron.rules();
+        assert!(!rules.bonus_rounds, "Bonus rounds would let the snek through its trail");
+        let mut haus = SnekHaus::new(size, 3, rules);
+        haus.setup(&mut rng);
+        assert!(haus.moresels.is_empty(), "There should be nothing to eat");
+        let start = haus.snek.length();
+        for _ in 0..4 {
+            haus.tick(&mut Round::default(), &mut rng);
         }
+        assert_eq!(haus.snek.length(), start + 4, "The snek should never shrink");
+        assert_eq!(haus.score, start + 4, "The score should count the cells covered");
 
-        // Add snake head (different symbol/color)
-        buf[(self.snek.head.x + area.x, self.snek.head.y + area.y)]
-            .set_symbol("😀")
-            .set_fg(Color::Yellow);
+        haus.change_direction(Direction::North);
+        haus.slither_on();
+        haus.change_direction(Direction::West);
+        haus.slither_on();
+        haus.change_direction(Direction::South);
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::BitSelf)), "The trail should stay lethal");
+        assert_ne!(GameMode::Tron.high_score_file(), GameMode::Classic.high_score_file(), "Tron should keep its own high score");
+    }
 
-        // Add morsels
-        for morsel in &self.moresels {
-            buf[(morsel.pos.x + area.x, morsel.pos.y + area.y)]
-                .set_symbol(MORSEL_SYMBOLS[morsel.growth_value as usize - 1])
-                .set_fg(Color::LightRed);
+    #[test]
+    fn test_twin_sneks() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut haus = SnekHaus::new(size, 3, GameMode::Twin.rules());
+        haus.setup(&mut rng);
+        haus.moresels.clear();
+        let twin = haus.twin.clone().expect("Twin mode should have a twin");
+        assert_eq!(twin.head, Pos { x: 19 - haus.snek.head.x, ..haus.snek.head }, "The twin should start as a mirror image");
+        assert_eq!(twin.direction, Direction::West, "The twin should head the other way");
+
+        haus.change_direction(Direction::North);
+        assert_eq!(haus.twin.as_ref().unwrap().direction, Direction::North, "Up and down shouldn't be mirrored");
+        haus.change_direction(Direction::East);
+        assert_eq!(haus.twin.as_ref().unwrap().direction, Direction::West, "Left and right should be mirrored");
+
+        // Heading for each other, they meet in the middle
+        let mut result = StepResult::Ongoing;
+        for _ in 0..4 {
+            result = haus.slither_on();
         }
+        assert!(matches!(result, StepResult::Died(DeathCause::HitTwin)), "The sneks should crash into each other, got {:?}", result);
+
+        let mut haus = SnekHaus::new(size, 3, GameMode::Twin.rules());
+        haus.setup(&mut rng);
+        haus.moresels.clear();
+        let twin = haus.twin.as_ref().unwrap().head;
+        haus.walls.insert(Pos { x: twin.x - 1, ..twin });
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::HitWall)), "The twin crashing should end the game");
+        assert_ne!(GameMode::Twin.high_score_file(), GameMode::Classic.high_score_file(), "Twin Sneks should keep its own high score");
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_rotting_morsels() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = Rules {
+            mutators: Mutators { rotting: true, ..Mutators::default() },
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        haus.moresels.clear();
+        haus.moresels.push(Morsel { pos: Pos { x: 12, y: 2, floor: 0 }, growth_value: 5, kind: MorselKind::Normal, age: 0 });
+        haus.moresels.push(Morsel { pos: Pos { x: 14, y: 2, floor: 0 }, growth_value: 3, kind: MorselKind::Trap, age: 0 });
+
+        for _ in 0..ROT_TICKS - 1 {
+            haus.rot();
+        }
+        assert_eq!(haus.moresels[0].growth_value, 5, "Fresh morsels should keep their value for a while");
+        haus.rot();
+        assert_eq!(haus.moresels[0].growth_value, 4, "Morsels should lose a point as they rot");
+        for _ in 0..ROT_TICKS * 10 {
+            haus.rot();
+        }
+        assert_eq!(haus.moresels[0].growth_value, 1, "Rotten morsels should still be worth a point");
+        assert_eq!(haus.moresels[1].growth_value, 3, "Traps shouldn't rot");
+
+        haus.rules.mutators.rotting = false;
+        haus.moresels[0].growth_value = 5;
+        for _ in 0..ROT_TICKS {
+            haus.rot();
+        }
+        assert_eq!(haus.moresels[0].growth_value, 5, "Morsels shouldn't rot without the modifier");
+    }
 
     #[test]
-    fn test_opposite_directions() {
-        // Test direct opposites
-        assert_eq!(Direction::North.opposite(), Direction::South, "North should be opposite of South");
-        assert_eq!(Direction::South.opposite(), Direction::North, "South should be opposite of North");
-        assert_eq!(Direction::East.opposite(), Direction::West, "East should be opposite of West");
-        assert_eq!(Direction::West.opposite(), Direction::East, "West should be opposite of East");
+    fn test_smooth_motion() {
+        let size = Size { width: 10, height: 10 };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        let at = |x| Pos { x, y: 5, floor: 0 };
+        let head = |haus: &SnekHaus| {
+            let mut buffer = Buffer::empty(Rect::from(size));
+            haus.render(Rect::from(size), &mut buffer);
+            let cell = &buffer[(5, 5)];
+            (cell.symbol().to_string(), cell.modifier.contains(Modifier::REVERSED))
+        };
+        haus.snek = Snek {
+            head: at(5),
+            body: VecDeque::from([at(3), at(4)]),
+            direction: Direction::East,
+            pending_growth: 0,
+        };
+        assert_eq!(
+            head(&haus),
+            ("▸".to_string(), false),
+            "The head should be drawn as usual without smooth motion"
+        );
+        haus.motion = Some(0.0);
+        assert_eq!(head(&haus), ("▏".to_string(), false), "The head should start sliding in from behind");
+        haus.motion = Some(0.5);
+        assert_eq!(head(&haus), ("▌".to_string(), false), "The head should be half way in half way through the tick");
+        haus.motion = Some(1.0);
+        assert_eq!(head(&haus), ("█".to_string(), false), "The head should fill its cell by the next tick");
+
+        haus.snek = Snek { head: at(5), body: VecDeque::from([at(7), at(6)]), direction: Direction::West, pending_growth: 0 };
+        haus.motion = Some(0.0);
+        assert_eq!(head(&haus), ("▉".to_string(), true), "Sliding in from the right should draw the empty part reversed");
+        haus.glyphs = glyphs::ASCII;
+        assert_eq!(
+            head(&haus),
+            ("{".to_string(), false),
+            "ASCII has nothing to slide with"
+        );
+    }
 
-        // Test double opposite returns to original for all directions
-        assert_eq!(Direction::North.opposite().opposite(), Direction::North, "Double opposite of North should return to North");
-        assert_eq!(Direction::South.opposite().opposite(), Direction::South, "Double opposite of South should return to South");
-        assert_eq!(Direction::East.opposite().opposite(), Direction::East, "Double opposite of East should return to East");
-        assert_eq!(Direction::West.opposite().opposite(), Direction::West, "Double opposite of West should return to West");
+    #[test]
+    fn test_invisible_body() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let rules = Rules {
+            mutators: Mutators { invisible: true, ..Mutators::default() },
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        haus.moresels.clear();
+        assert!(!haus.body_visible(), "The body should start hidden");
+        haus.moresels.push(Morsel { pos: Pos { x: haus.snek.head.x + 1, ..haus.snek.head }, growth_value: 1, kind: MorselKind::Normal, age: 0 });
+        haus.slither_on();
+        assert!(haus.body_visible(), "A nom should flash the body up");
+        for _ in 0..FLASH_TICKS {
+            haus.slither_on();
+        }
+        assert!(!haus.body_visible(), "The flash should fade");
+        assert!(SnekHaus::new(size, 3, Rules::default()).body_visible(), "The body should show without the modifier");
     }
 
     #[test]
-    fn test_zero_delta() {
-        let arena_size = Size {
-            width: 10,
+    fn test_wormholes() {
+        let size = Size {
+            width: 20,
             height: 10,
         };
+        let mut rng = StdRng::seed_from_u64(3);
+        let rules = Rules {
+            mutators: Mutators { wormholes: true, ..Mutators::default() },
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        haus.setup(&mut rng);
+        assert_eq!(haus.wormholes.len(), WORMHOLES, "The modifier should put out wormholes");
+        assert!(haus.wormholes.iter().all(|wormhole| !haus.moresels.iter().any(|m| m.pos == wormhole.pos)), "Morsels shouldn't land on wormholes");
+
+        let ahead = Pos { x: haus.snek.head.x + 1, ..haus.snek.head };
+        haus.moresels.retain(|m| m.pos != ahead);
+        haus.wormholes = vec![Wormhole { pos: ahead, exit: None }];
+        assert!(!matches!(haus.tick(&mut Round::default(), &mut rng), StepResult::Died(_)), "A wormhole should be safe to enter");
+        let exit = haus.wormholes[0].exit.expect("There should be somewhere safe to go");
+        assert_eq!(haus.snek.head, exit, "The head should come out of the wormhole's exit");
+        assert!(!haus.is_lethal(exit.wrapped_add(haus.snek.direction.into(), size)), "The way ahead should be clear");
+    }
 
-        let pos = Pos { x: 5, y: 5 };
-        let delta = PosDelta { x: 0, y: 0 };
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, pos, "Zero delta should not change position");
+    #[test]
+    fn test_diagonal_moves() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.steer(Direction::NorthEast);
+        assert_eq!(haus.snek.direction, Direction::East, "Diagonals should be ignored without the mutator");
 
-        // Test at boundaries too
-        let pos = Pos { x: 0, y: 0 };
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, pos, "Zero delta should not change position at minimum bounds");
+        let rules = Rules {
+            mutators: Mutators { diagonal: true, ..Mutators::default() },
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        let start = haus.snek.head;
+        haus.steer(Direction::NorthEast);
+        haus.slither_on();
+        assert_eq!((haus.snek.head.x, haus.snek.head.y), (start.x + 1, start.y - 1), "The snek should move diagonally");
+        haus.steer(Direction::SouthWest);
+        assert_eq!(haus.snek.direction, Direction::NorthEast, "The snek still can't double back diagonally");
+        assert_eq!(Direction::NorthEast.mirrored(Mirror::LeftRight), Direction::NorthWest, "Mirroring left and right should keep the vertical part");
+        assert_eq!(Direction::ALL.iter().filter(|d| d.opposite().opposite() == **d).count(), 8, "Every direction should have an opposite");
+    }
 
-        let pos = Pos { x: 9, y: 9 };
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, pos, "Zero delta should not change position at maximum bounds");
+    #[test]
+    fn test_bug_report() {
+        let dir = std::env::temp_dir().join(format!("snekrs_game_bugs_{}", std::process::id()));
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+
+        let path = game
+            .save_bug_report(&dir)
+            .expect("Bug report should be saved");
+        let report = fs::read_to_string(&path).expect("Bug report should be readable");
+        assert!(
+            report.contains("Press SPACE to start"),
+            "Report should include the screen"
+        );
+        assert!(
+            report.contains(&format!("== Seed ==\n{}", game.seed)),
+            "Report should include the seed"
+        );
+        assert!(
+            report.contains("ReadyToStart"),
+            "Report should include the game state"
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_break_reminder() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings {
+            break_reminder: Some(20),
+            ..Settings::default()
+        });
+        fs::create_dir_all(&game.data_dir).expect("Data directory should be created");
+        game.session
+            .record_game(Duration::from_secs(25 * 60), session::now());
+        assert!(
+            !game.break_due(),
+            "Breaks shouldn't be suggested in the middle of things"
+        );
+
+        game.state = GameState::GameOver { haus: SnekHaus::default(), final_score: 0 };
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Game over should draw").buffer);
+        assert!(screen.contains("Time for a break?"), "A break should be suggested between games");
+        game.handle_input(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        assert!(matches!(game.state, GameState::GameOver { .. }), "The key dismissing the reminder shouldn't do anything else");
+        assert!(!game.break_due(), "The reminder should be dismissed");
+        assert_eq!(
+            Session::load(&game.data_file(session::SESSION_FILE), session::now()),
+            game.session,
+            "Dismissing the reminder should be saved"
+        );
+        fs::remove_dir_all(&game.data_dir).ok();
+    }
+
+    #[test]
+    fn test_rewind_pickup() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut game = test_game(Settings::default());
+        game.arena_size = Some(Size {
+            width: 30,
+            height: 15,
+        });
+        game.handle_input(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        let GameState::Playing(haus, _) = &mut game.state else { panic!("Space should start a game") };
+        haus.moresels.clear();
+        haus.moresels.push(Morsel { pos: haus.snek.head.wrapped_add(haus.snek.direction.into(), haus.size), growth_value: 1, kind: MorselKind::Rewind, age: 0 });
+        game.update();
+        let GameState::Playing(haus, _) = &game.state else { panic!("The game should go on") };
+        assert_eq!(haus.rewinds, 1, "Eating the pickup should give a rewind charge");
+        let head = haus.snek.head;
+        for _ in 0..3 {
+            game.update();
+        }
+        game.handle_input(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE));
+        let GameState::Playing(haus, _) = &game.state else { panic!("The game should go on") };
+        assert_eq!(haus.snek.head, head, "Rewinding shouldn't go back before the pickup");
+        assert_eq!(haus.rewinds, 0, "Rewinding should use up the charge");
+        assert_eq!(game.recording.ticks(), 1, "The recording should rewind too");
+    }
+
+    #[test]
+    fn test_puzzle() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut game = test_game(Settings::default());
+        game.arena_size = Some(Size {
+            width: 30,
+            height: 15,
+        });
+        fs::create_dir_all(&game.data_dir).expect("Data directory should be created");
+        game.mode = GameMode::Puzzle;
+        let press = |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        press(&mut game, KeyCode::Char(' '));
+        assert!(matches!(game.state, GameState::PuzzleSelect { selected: 0 }), "Puzzles should start with a choice of puzzle");
+        press(&mut game, KeyCode::Char(' '));
+        let GameState::Playing(haus, _) = &game.state else { panic!("Space should start the puzzle") };
+        let (start, morsels) = (haus.snek.head, haus.moresels.len());
+        assert_eq!(morsels, 2, "The puzzle's morsels should be on the board");
+        game.update();
+        let GameState::Playing(haus, _) = &game.state else { panic!("The puzzle should go on") };
+        assert_eq!(haus.snek.head, start, "The snek shouldn't move until the player does");
+
+        press(&mut game, KeyCode::Down);
+        game.update();
+        press(&mut game, KeyCode::Char('u'));
+        let GameState::Playing(haus, _) = &game.state else { panic!("The puzzle should go on") };
+        assert_eq!((haus.snek.head, haus.ticks), (start, 0), "Undo should take the move back");
+
+        press(&mut game, KeyCode::Left);
+        game.update();
+        let GameState::Playing(haus, _) = &game.state else { panic!("The puzzle should go on") };
+        assert_eq!(haus.ticks, 0, "Turning back on itself shouldn't use a move");
+        for code in [KeyCode::Right, KeyCode::Right, KeyCode::Right, KeyCode::Down, KeyCode::Down] {
+            press(&mut game, code);
+            game.update();
+        }
+        assert!(
+            game.naming.is_some(),
+            "The first puzzle score should make the table"
+        );
+        press(&mut game, KeyCode::Esc);
+        assert!(
+            matches!(game.state, GameState::Victory { .. }),
+            "Eating everything in time should solve the puzzle"
+        );
+        assert!(
+            game.solved.is_solved("First Bite"),
+            "The puzzle should be marked solved"
+        );
+        assert!(
+            Solved::load(&game.data_file(puzzle::SOLVED_FILE)).is_solved("First Bite"),
+            "Solving it should be saved"
+        );
+        press(&mut game, KeyCode::Char(' '));
+        assert!(
+            matches!(game.state, GameState::PuzzleSelect { selected: 1 }),
+            "Space should go on to the next puzzle"
+        );
+        fs::remove_dir_all(&game.data_dir).ok();
+
+        let mut haus = SnekHaus::from_level(&Puzzle::parse("moves: 1\n#...S..*#", "x").unwrap().level, 1, Rules { moves: Some(1), ..Rules::default() });
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::OutOfMoves)), "Running out of moves should end the puzzle");
+    }
+
+    #[test]
+    fn test_ghost_race() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut game = test_game(Settings::default());
+        fs::create_dir_all(&game.data_dir).expect("Data directory should be created");
+        game.arena_size = Some(Size {
+            width: 30,
+            height: 15,
+        });
+        game.handle_input(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        let GameState::Playing(haus, _) = &game.state else { panic!("Space should start a game") };
+        let mut best = Ghost::new(haus.size);
+        let path = [Pos { x: 3, y: 3, floor: 0 }, Pos { x: 4, y: 3, floor: 0 }];
+        for (tick, &pos) in path.iter().enumerate() {
+            best.record(tick as u32, pos);
+        }
+        game.best = Some(best);
+        game.update();
+        let GameState::Playing(haus, _) = &game.state else { panic!("The game should go on") };
+        assert_eq!(haus.ghost_head, Some(path[1]), "The ghost should follow the best run");
+        assert_eq!(game.run.head(1, haus.size), Some(haus.snek.head), "The run should be recorded as it goes");
+        game.update();
+        let GameState::Playing(haus, _) = &game.state else {
+            panic!("The game should go on")
+        };
+        assert_eq!(
+            haus.ghost_head, None,
+            "The ghost should vanish once the best run is over"
+        );
+
+        let GameState::Playing(haus, _) = &game.state else {
+            panic!("The game should go on")
+        };
+        let mut haus = haus.clone();
+        haus.score = 1;
+        game.update_high_score(&haus);
+        assert!(
+            Ghost::load(&game.data_file(&GameMode::Classic.ghost_file())).is_some(),
+            "A new best should keep its ghost"
+        );
+        fs::remove_dir_all(&game.data_dir).ok();
+    }
+
+    #[test]
+    fn test_locked_items() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut game = test_game(Settings::default());
+        game.unlocks = Unlocks::default();
+        let press = |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        for _ in 0..Background::ALL.len() {
+            press(&mut game, KeyCode::Char('b'));
+            assert_ne!(game.settings.background, Background::Dots, "B should skip locked backgrounds");
+        }
+
+        game.settings.background = Background::Dots;
+        assert!(game.describe_setting(Field::Background).contains("locked: score 50 in Classic"), "Menus should say how to unlock");
+        let mut haus = SnekHaus::default();
+        game.dress(&mut haus);
+        assert_eq!(haus.background, Background::None, "Locked backgrounds shouldn't be played with");
+
+        press(&mut game, KeyCode::Char('m'));
+        let wormholes = Mutator::ALL.iter().position(|&m| m == Mutator::Wormholes).expect("Wormholes should be a modifier");
+        for _ in 0..wormholes {
+            press(&mut game, KeyCode::Down);
+        }
+        press(&mut game, KeyCode::Right);
+        assert!(!game.mutators.wormholes, "Locked modifiers shouldn't switch on");
+
+        game.unlocks.record(GameMode::Portals, 50, 3);
+        press(&mut game, KeyCode::Right);
+        assert!(game.mutators.wormholes, "Unlocked modifiers should switch on");
+    }
+
+    #[test]
+    fn test_kiosk() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        game.start_kiosk();
+        game.update();
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Attract mode should draw").buffer);
+        assert!(screen.contains("Press any key to play"), "The kiosk should open with the bot playing");
+
+        let press = |game: &mut Game, code, modifiers| game.handle_input(KeyEvent::new(code, modifiers));
+        press(&mut game, KeyCode::Char('x'), KeyModifiers::NONE);
+        assert!(matches!(game.state, GameState::ReadyToStart), "Any key should bring up the start screen");
+        press(&mut game, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut game, KeyCode::Char('q'), KeyModifiers::NONE);
+        assert!(matches!(game.state, GameState::Attract { .. }), "Quitting should go back to the bot, not the shell");
+        assert_eq!(game.mode, GameMode::default(), "The next player should start afresh");
+
+        press(&mut game, KeyCode::Char(' '), KeyModifiers::NONE);
+        press(&mut game, KeyCode::Down, KeyModifiers::NONE);
+        game.kiosk.as_mut().unwrap().last_input -= kiosk::IDLE;
+        game.update();
+        assert!(matches!(game.state, GameState::Attract { .. }), "An idle kiosk should go back to the bot");
+        assert_eq!(game.mode, GameMode::default(), "Choices should be reset while idle");
+
+        press(&mut game, KeyCode::Char('q'), KeyModifiers::CONTROL | KeyModifiers::ALT);
+        assert!(matches!(game.state, GameState::Exit), "The unlock combination should quit");
+    }
+
+    #[test]
+    fn test_wizard_preview() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        game.welcome();
+        let symbols = Field::ALL.iter().position(|&f| f == Field::Symbols).expect("Symbols should be a field");
+        for _ in 0..symbols {
+            game.handle_input(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        }
+        game.handle_input(KeyEvent::new(KeyCode::Left, KeyModifiers::NONE));
+        assert_eq!(game.settings.symbols, settings::Symbols::Ascii, "Left should change the selected setting");
+
+        game.update();
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Wizard should draw").buffer);
+        assert!(screen.contains("Symbols: < ASCII only >"), "Wizard should show the chosen setting");
+        assert!(!glyphs::UNICODE.heads.iter().chain(&glyphs::UNICODE.morsels).any(|glyph| screen.contains(glyph)), "Preview should be drawn in ASCII");
+    }
+
+    #[test]
+    fn test_settings_from_pause() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+        let press =
+            |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        press(&mut game, KeyCode::Char(' '));
+        press(&mut game, KeyCode::Char(' '));
+        assert!(matches!(game.state, GameState::Paused(..)), "Space should pause the game");
+        press(&mut game, KeyCode::Char('o'));
+        let symbols = Field::ALL.iter().position(|&f| f == Field::Symbols).expect("Symbols should be a field");
+        for _ in 0..symbols {
+            press(&mut game, KeyCode::Down);
+        }
+        press(&mut game, KeyCode::Left);
+        let GameState::Settings { then, .. } = &game.state else { panic!("O should open the settings") };
+        let GameState::Paused(haus, _) = then.as_ref() else { panic!("Settings should go back to the paused game") };
+        assert_eq!(haus.glyphs, glyphs::ASCII, "The paused game should be redrawn with the new symbols");
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Settings should draw").buffer);
+        assert!(screen.contains("Symbols: < ASCII only >"), "Settings should show the chosen value");
+
+        let edges = Field::ALL.iter().position(|&f| f == Field::Edges).expect("Edges should be a field");
+        for _ in symbols..edges {
+            press(&mut game, KeyCode::Down);
+        }
+        press(&mut game, KeyCode::Right);
+        assert_eq!(game.mutators.edges, Some(EdgePolicy::Wrap), "Edges should carry over to the modifiers");
+    }
+
+    #[test]
+    fn test_skins_menu() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings {
+            symbols: settings::Symbols::Unicode,
+            ..Settings::default()
+        });
+        let press =
+            |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        let pick = |game: &mut Game, name: &str| {
+            let index = game.skins.iter().position(|skin| skin.name == name).expect("Skin should be bundled");
+            press(game, KeyCode::Char('c'));
+            for _ in 0..index {
+                press(game, KeyCode::Down);
+            }
+            press(game, KeyCode::Char(' '));
+        };
+        game.unlocks = Unlocks::default();
+        pick(&mut game, "Fire");
+        assert!(matches!(game.state, GameState::Skins { .. }), "A locked skin shouldn't be worn");
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Skins should draw").buffer);
+        assert!(screen.contains("> Fire (locked: "), "The locked skin should say how to unlock it");
+        assert!(screen.contains("Pipes") && screen.contains("────"), "Skins should be shown on a snek");
+
+        press(&mut game, KeyCode::Esc);
+        press(&mut game, KeyCode::Char('c'));
+        for _ in 1..game.skins.len() {
+            press(&mut game, KeyCode::Down);
+        }
+        let last = game.skins[game.skins.len() - 1].name.clone();
+        let mut short =
+            Terminal::new(backend::TestBackend::new(80, 12)).expect("Test terminal should start");
+        let screen = bugreport::frame_text(
+            short
+                .draw(|f| game.render(f))
+                .expect("Skins should draw")
+                .buffer,
+        );
+        assert!(
+            screen.contains(&format!("> {}", last)),
+            "The list should scroll to keep the selected skin in view"
+        );
+
+        press(&mut game, KeyCode::Esc);
+        pick(&mut game, "Pipes");
+        assert!(matches!(game.state, GameState::ReadyToStart), "Picking a skin should go back to the start screen");
+        assert_eq!(game.skins[game.skin].name, "Pipes", "The picked skin should be worn");
+    }
+
+    #[test]
+    fn test_pause_menu() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        let press =
+            |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+        press(&mut game, KeyCode::Char(' '));
+        press(&mut game, KeyCode::Char(' '));
+        assert!(matches!(game.state, GameState::Paused(..)), "Space should pause the game");
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Pause menu should draw").buffer);
+        assert!(screen.contains("> Resume <") && screen.contains("Quit to menu"), "The pause menu should list its actions");
+
+        press(&mut game, KeyCode::Down);
+        press(&mut game, KeyCode::Enter);
+        assert!(matches!(game.state, GameState::Playing(..)), "Restart should start a new game");
+
+        press(&mut game, KeyCode::Char(' '));
+        for _ in 0..PauseAction::ALL.len() {
+            press(&mut game, KeyCode::Down);
+        }
+        press(&mut game, KeyCode::Enter);
+        assert!(matches!(game.state, GameState::ReadyToStart), "Quit to menu should go back to the start screen");
+    }
+
+    #[test]
+    fn test_replay_screen() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        let press =
+            |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+        press(&mut game, KeyCode::Char(' '));
+        for _ in 0..30 {
+            game.update();
+        }
+        let GameState::Playing(haus, _) = &mut game.state else { panic!("Space should start a game") };
+        let haus = std::mem::take(haus);
+        game.state = GameState::GameOver { haus, final_score: 0 };
+
+        press(&mut game, KeyCode::Char('w'));
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Replay should draw").buffer);
+        assert!(screen.contains("Tick 0/30  1x"), "The replay should start at the beginning, with its progress shown");
+        press(&mut game, KeyCode::Char('f'));
+        press(&mut game, KeyCode::Right);
+        game.update();
+        let GameState::Replay { viewer, .. } = &game.state else { panic!("W should watch the replay") };
+        assert_eq!(viewer.tick(), 22, "Scrubbing and playing at double speed should move the replay on");
+        press(&mut game, KeyCode::Esc);
+        assert!(matches!(game.state, GameState::GameOver { .. }), "Esc should go back to the game over screen");
+    }
+
+    #[test]
+    fn test_spectating() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        let press =
+            |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+        press(&mut game, KeyCode::Char('v'));
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Spectating should draw").buffer);
+        let header = screen.lines().nth(1).expect("The header should have a line");
+        assert!(header.contains("SPECTATING Computer snek") && header.contains("Score: 0"), "The header should say what's being watched");
+        let GameState::Spectating { feed, .. } = &game.state else { panic!("V should spectate") };
+        let start = feed.haus().snek.head;
+
+        press(&mut game, KeyCode::Char(' '));
+        press(&mut game, KeyCode::Left);
+        game.update();
+        let GameState::Spectating { feed, live } = &game.state else { panic!("Spectating should ignore play keys") };
+        assert!(*live && feed.haus().snek.head != start, "The feed should move on each tick");
+        press(&mut game, KeyCode::Esc);
+        assert!(matches!(game.state, GameState::ReadyToStart), "Esc should leave");
+    }
+
+    #[test]
+    fn test_speed_gauge() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+        game.handle_input(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Game should draw").buffer);
+        let header = screen.lines().nth(1).expect("The header should have a line");
+        assert!(header.contains("Score: 0") && header.contains("Speed 6.7/s"), "The header should show the speed beside the score");
+        let GameState::Playing(haus, _) = &mut game.state else { panic!("Space should start a game") };
+        haus.rules.speed = SpeedCurve { base_millis: 25, min_millis: 25, ..SpeedCurve::default() };
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Game should draw").buffer);
+        assert!(screen.contains("Speed 40.0/s"), "The gauge should keep up with the speed");
+    }
+
+    #[test]
+    fn test_high_score_pace() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        game.scores = Leaderboard::default();
+        game.scores.record(Entry { score: 1, mode: game.mode.slug().to_string(), date: String::new(), name: "Ada".to_string() });
+        terminal.draw(|f| game.render(f)).expect("Start screen should draw");
+        game.handle_input(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        for _ in 0..2 {
+            let GameState::Playing(haus, _) = &mut game.state else { panic!("Space should start a game") };
+            haus.moresels.clear();
+            haus.moresels.push(Morsel { pos: haus.snek.head.wrapped_add(haus.snek.direction.into(), haus.size), growth_value: 2, kind: MorselKind::Normal, age: 0 });
+            game.update();
+        }
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Game should draw").buffer);
+        assert!(screen.contains("New high score pace!"), "Beating the best score should be toasted");
+        assert_eq!(game.toasts.waiting(), 0, "Beating it again in the same game shouldn't be");
+    }
+
+    #[test]
+    fn test_title_screen() {
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 60)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        let screen = bugreport::frame_text(
+            terminal
+                .draw(|f| game.render(f))
+                .expect("Start screen should draw")
+                .buffer,
+        );
+        assert!(
+            screen.contains(r"|____/|_| \_|") && screen.contains("Press SPACE to start"),
+            "A tall start screen should show the logo over the menu"
+        );
+        game.update();
+        let moved = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Start screen should draw").buffer);
+        assert_ne!(screen, moved, "The title's snek should slither on with the game's ticks");
+
+        let mut terminal = Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Start screen should draw").buffer);
+        assert!(!screen.contains(r"|____/|_| \_|") && screen.contains("Press SPACE to start"), "A short start screen should leave the logo out for the menu");
+    }
+
+    #[test]
+    fn test_start_screen_fits() {
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        game.mode = GameMode::TimeAttack;
+        game.start_error = Some("Couldn't start: the level is broken".to_string());
+        let screen = bugreport::frame_text(
+            terminal
+                .draw(|f| game.render(f))
+                .expect("Start screen should draw")
+                .buffer,
+        );
+        assert!(
+            screen.contains("Mode: < Time Attack >"),
+            "The chosen mode should be shown"
+        );
+        assert!(
+            screen.contains("Couldn't start: the level is broken"),
+            "Start errors should fit on a standard terminal"
+        );
+        fs::remove_dir_all(&game.data_dir).ok();
+    }
+
+    #[test]
+    fn test_fixed_arena() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let fixed = Size {
+            width: 30,
+            height: 15,
+        };
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(100, 40)).expect("Test terminal should start");
+        let mut game = test_game(Settings {
+            arena_size: Some(fixed),
+            ..Settings::default()
+        });
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+        game.handle_input(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        let GameState::Playing(haus, _) = &game.state else { panic!("Space should start a game") };
+        assert_eq!(haus.size, fixed, "The arena should be the size in the settings, not the terminal's");
+
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(30, 15)).expect("Test terminal should start");
+        let mut game = test_game(Settings {
+            arena_size: Some(fixed),
+            ..Settings::default()
+        });
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+        game.handle_input(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        assert!(matches!(game.state, GameState::ReadyToStart), "A terminal too small for the arena shouldn't start a game");
+        assert!(game.start_error.as_ref().is_some_and(|e| e.contains("30x15 arena")), "The start screen should say the arena doesn't fit");
+    }
+
+    #[test]
+    fn test_run_stats() {
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        let mut haus = SnekHaus::new(
+            Size {
+                width: 40,
+                height: 15,
+            },
+            3,
+            Rules::default(),
+        );
+        for growth_value in [2, 2, 1] {
+            haus.moresels.clear();
+            haus.moresels.push(Morsel { pos: Pos { x: haus.snek.head.x + 1, ..haus.snek.head }, growth_value, kind: MorselKind::Normal, age: 0 });
+            haus.slither_on();
+        }
+        assert_eq!(haus.noms, BTreeMap::from([(1, 1), (2, 2)]), "Morsels eaten should be counted by what they were worth");
+        for _ in 0..5 {
+            haus.slither_on();
+        }
+        assert_eq!(haus.longest, haus.snek.length(), "The longest should keep up as the snek grows");
+        assert!(haus.longest > 3, "The snek should have grown from its noms");
+
+        game.personal_best = true;
+        game.state = GameState::GameOver {
+            haus,
+            final_score: 5,
+        };
+        let screen = bugreport::frame_text(
+            terminal
+                .draw(|f| game.render(f))
+                .expect("Game over should draw")
+                .buffer,
+        );
+        assert!(
+            screen.contains("Morsels eaten: 3") && screen.contains("worth 2: 2"),
+            "The stats should show the morsels eaten"
+        );
+        assert!(
+            screen.contains("Average speed: ") && screen.contains("New personal best!"),
+            "The stats should show the speed and the best"
+        );
+
+        game.mode = GameMode::Sandbox;
+        let screen = bugreport::frame_text(
+            terminal
+                .draw(|f| game.render(f))
+                .expect("Game over should draw")
+                .buffer,
+        );
+        assert!(
+            !screen.contains("personal best") && !screen.contains("Personal best"),
+            "Practice shouldn't be measured against a best"
+        );
+        game.mode = GameMode::Classic;
+        game.arena_size = Some(Size {
+            width: 30,
+            height: 15,
+        });
+        game.state = game.start_game().expect("Game should start");
+        assert!(
+            !game.personal_best,
+            "A new game shouldn't carry over the last one's best"
+        );
+    }
+
+    #[test]
+    fn test_high_score_table() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        let press =
+            |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        game.mode = GameMode::Sandbox;
+        game.player_name = "Ann".to_string();
+        let haus = SnekHaus {
+            score: 7,
+            ..SnekHaus::new(
+                Size {
+                    width: 40,
+                    height: 20,
+                },
+                3,
+                Rules::default(),
+            )
+        };
+        assert_eq!(
+            game.update_high_score(&haus),
+            None,
+            "Practice scores shouldn't go in the table"
+        );
+        assert!(game.scores.entries().is_empty() && !game.personal_best);
+
+        game.mode = GameMode::Classic;
+        fs::create_dir_all(&game.data_dir).expect("Data directory should be made");
+        let rank = game.update_high_score(&haus);
+        assert_eq!(rank, Some(0), "A first score should top the table");
+        game.state = GameState::GameOver { haus, final_score: 7 };
+        game.ask_name(rank);
+        assert_eq!(game.naming, Some((0, "Ann".to_string())), "The last name used should be offered");
+
+        for code in [KeyCode::Backspace, KeyCode::Backspace, KeyCode::Backspace, KeyCode::Char('B'), KeyCode::Char('o')] {
+            press(&mut game, code);
+        }
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Name entry should draw").buffer);
+        assert!(screen.contains("Bo_") && screen.contains("GAME OVER"), "The name should be typed over the game over screen");
+        press(&mut game, KeyCode::Enter);
+        assert!(game.naming.is_some(), "A name should be at least three characters");
+        press(&mut game, KeyCode::Char('b'));
+        press(&mut game, KeyCode::Enter);
+        assert!(
+            game.naming.is_none() && matches!(game.state, GameState::GameOver { .. }),
+            "Enter should save the name"
+        );
+        assert_eq!(
+            (
+                game.scores.entries()[0].name.as_str(),
+                game.player_name.as_str()
+            ),
+            ("Bob", "Bob"),
+            "The name should go in the table and be offered next time"
+        );
+        assert_eq!(
+            Game::load_high_scores(&game.data_dir, GameMode::Classic).entries()[0].name,
+            "Bob",
+            "The table should be saved with the name"
+        );
+
+        press(&mut game, KeyCode::Char('h'));
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("High scores should draw").buffer);
+        assert!(screen.contains("1.  Bo"), "H should show the table after a game");
+        press(&mut game, KeyCode::Char(' '));
+        assert!(
+            matches!(game.state, GameState::GameOver { .. }),
+            "Space should go back to the game over screen"
+        );
+        fs::remove_dir_all(&game.data_dir).ok();
     }
 
     #[test]
-    fn test_arena_size_deltas() {
-        let arena_size = Size {
-            width: 10,
-            height: 10,
-        };
-        let non_square_arena_size = Size {
-            width: 8,
-            height: 6,
-        };
-        let pos = Pos { x: 5, y: 5 };
+    fn test_stats_sidebar() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings {
+            stats: true,
+            ..Settings::default()
+        });
+        let press =
+            |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+        press(&mut game, KeyCode::Char(' '));
+        let GameState::Playing(haus, _) = &game.state else { panic!("Space should start a game") };
+        assert_eq!(haus.size.width, 78 - STATS_WIDTH, "The arena should leave room for the sidebar");
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Game should draw").buffer);
+        assert!(screen.contains("Length: ") && screen.contains("Eaten: 0"), "The sidebar should show the run's stats");
+
+        press(&mut game, KeyCode::Char('i'));
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Game should draw").buffer);
+        assert!(!screen.contains("Eaten:"), "I should hide the sidebar");
+    }
 
-        // Moving exactly one arena width/height should return to the same position
-        let delta = PosDelta { x: 10, y: 10 };
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, pos, "Moving by exact arena size should return to same position");
+    #[test]
+    fn test_nom_flash() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings {
+            symbols: settings::Symbols::Unicode,
+            ..Settings::default()
+        });
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+        game.handle_input(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        let GameState::Playing(haus, _) = &mut game.state else { panic!("Space should start a game") };
+        haus.moresels.clear();
+        haus.moresels.push(Morsel { pos: Pos { x: haus.snek.head.x + 1, ..haus.snek.head }, growth_value: 1, kind: MorselKind::Normal, age: 0 });
+        haus.slither_on();
+        assert_eq!(haus.flash, FLASH_TICKS, "A nom should start the flash");
+        let head = glyphs::UNICODE.head(haus.snek.direction);
+        let theme = game.theme();
+        let buffer = terminal.draw(|f| game.render(f)).expect("Game should draw").buffer.clone();
+        let screen = bugreport::frame_text(&buffer);
+        let score = screen.lines().find_map(|line| line.find("    Score: ")).expect("The header should show the score") as u16 + 5;
+        assert_eq!(buffer[(score, 1)].bg, theme.morsel, "The score should pulse after a nom");
+        let GameState::Playing(haus, _) = &mut game.state else { panic!("The game should go on") };
+        let drawn = buffer.content.iter().find(|cell| cell.symbol() == head).expect("The head should be drawn");
+        assert_eq!(drawn.bg, theme.head, "The head should light up after a nom");
+
+        for _ in 0..FLASH_TICKS {
+            haus.slither_on();
+        }
+        assert_eq!(haus.flash, 0, "The flash should fade");
+    }
 
-        // Moving exactly negative arena width/height should also return to same position
-        let delta = PosDelta { x: -10, y: -10 };
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, pos, "Moving by negative arena size should return to same position");
+    #[test]
+    fn test_countdown() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        let press =
+            |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+        press(&mut game, KeyCode::Char(' '));
+        assert!(game.counting_down(), "A new game should count down before the snek moves");
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Game should draw").buffer);
+        assert!(screen.contains("Get ready... 3"), "The countdown should be shown over the arena");
+
+        game.countdown = Some(Instant::now());
+        assert!(!game.counting_down(), "The countdown should run out");
+        press(&mut game, KeyCode::Char(' '));
+        assert!(!game.counting_down(), "There's nothing to count down while paused");
+        press(&mut game, KeyCode::Char(' '));
+        assert!(game.counting_down(), "Resuming should count down again");
+    }
 
-        // Test with different x and y arena dimensions
-        let pos = Pos { x: 3, y: 3 };
-        let delta = PosDelta { x: 8, y: 6 };
-        let new_pos = pos.wrapped_add(delta, non_square_arena_size);
-        assert_eq!(new_pos, pos, "Moving by non-square arena dimensions should wrap correctly");
+    #[test]
+    fn test_tournament_turns() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut terminal =
+            Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let mut game = test_game(Settings::default());
+        terminal
+            .draw(|f| game.render(f))
+            .expect("Start screen should draw");
+        let press =
+            |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        press(&mut game, KeyCode::Char('t'));
+        for name in ["Ann", "Bo"] {
+            name.chars().for_each(|c| press(&mut game, KeyCode::Char(c)));
+            press(&mut game, KeyCode::Enter);
+        }
+        press(&mut game, KeyCode::Enter);
+        assert!(matches!(game.state, GameState::Standings), "ENTER with no name should start the tournament");
+
+        press(&mut game, KeyCode::Char(' '));
+        assert!(matches!(game.state, GameState::Playing(..)), "SPACE should start the first turn");
+        let seed = game.seed;
+        press(&mut game, KeyCode::Char('q'));
+        press(&mut game, KeyCode::Char(' '));
+        assert!(matches!(game.state, GameState::Standings), "Game over should go back to the standings");
+        assert_eq!(game.tournament.as_ref().unwrap().players[0].scores, vec![0], "The turn should be scored");
+        let screen = bugreport::frame_text(terminal.draw(|f| game.render(f)).expect("Standings should draw").buffer);
+        assert!(screen.contains("Next up: Bo"), "The standings should say who's next");
+
+        press(&mut game, KeyCode::Char(' '));
+        assert_eq!(
+            game.seed, seed,
+            "Both players should get the same seed in a round"
+        );
+        press(&mut game, KeyCode::Char(' '));
+        press(&mut game, KeyCode::Char('q'));
+        assert_eq!(
+            game.tournament.as_ref().unwrap().players[1].scores,
+            vec![0],
+            "Quitting from the pause menu should score the turn too"
+        );
     }
 
     #[test]
-    fn test_large_deltas() {
-        let arena_size = Size {
-            width: 10,
+    fn test_background_patterns() {
+        let size = Size {
+            width: 20,
             height: 10,
         };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        let area = Rect::new(0, 0, size.width, size.height);
+        let draw = |haus: &SnekHaus| {
+            let mut buffer = Buffer::empty(area);
+            haus.render(area, &mut buffer);
+            buffer
+        };
 
-        let pos = Pos { x: 5, y: 5 };
-        let delta = PosDelta { x: 3, y: -3 };
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, Pos { x: 8, y: 2 }, "Large delta should wrap correctly within arena bounds");
-
-        // Test wrapping with large positive delta
-        let delta = PosDelta { x: 8, y: 12 };
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, Pos { x: 3, y: 7 }, "Large positive delta should wrap: (5+8)%10=3, (5+12)%10=7");
-
-        // Test wrapping with large negative delta
-        let delta = PosDelta { x: -12, y: -8 };
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, Pos { x: 3, y: 7 }, "Large negative delta should wrap: (5-12)%10=3, (5-8)%10=7");
+        haus.background = Background::Checkerboard;
+        haus.moresels = vec![Morsel { pos: Pos { x: 2, y: 0, floor: 0 }, growth_value: 1, kind: MorselKind::Normal, age: 0 }];
+        let buffer = draw(&haus);
+        assert_eq!(buffer[(0, 0)].bg, haus.skin.background, "Checkerboard should shade every other cell");
+        assert_eq!(buffer[(1, 0)].bg, Color::Reset, "Checkerboard should leave the cells between alone");
+        assert_eq!(buffer[(2, 0)].bg, Color::Reset, "Checkerboard should stay out from under morsels");
+        haus.skin.style = skin::Style::Segments;
+        let neck = haus.snek.body[haus.snek.body.len() - 1];
+        let neck = if (neck.x + neck.y).is_multiple_of(2) { neck } else { haus.snek.body[haus.snek.body.len() - 2] };
+        assert_eq!(draw(&haus)[(neck.x, neck.y)].bg, Color::Reset, "Checkerboard should stay out from under a body drawn in segments");
+
+        haus.background = Background::Dots;
+        let buffer = draw(&haus);
+        assert_eq!(buffer[(GRID_SPACING.0, GRID_SPACING.1)].symbol(), "·", "Dots should sit on the grid");
+        assert_eq!(buffer[(1, 0)].symbol(), " ", "Dots should only be on the grid");
+        let head = haus.snek.head;
+        assert_eq!(buffer[(head.x, head.y)].symbol(), glyphs::UNICODE.head(haus.snek.direction), "The snek should be drawn over the background");
     }
 
     #[test]
-    fn test_very_large_deltas() {
-        let arena_size = Size {
-            width: 10,
+    fn test_danger_warning() {
+        let size = Size {
+            width: 20,
             height: 10,
         };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.danger_warning = true;
+        let head = haus.snek.head;
+        assert_eq!(haus.look_ahead(2), vec![Pos { x: head.x + 1, ..head }, Pos { x: head.x + 2, ..head }], "Look ahead should follow the snek's direction");
+        assert_eq!(haus.danger_ahead(2), None, "An open arena should be safe");
+
+        let area = Rect::new(0, 0, size.width, size.height);
+        let head_bg = |haus: &SnekHaus| {
+            let mut buffer = Buffer::empty(area);
+            haus.render(area, &mut buffer);
+            buffer[(head.x, head.y)].bg
+        };
+        assert_eq!(head_bg(&haus), Color::Reset, "Head shouldn't be tinted when it's safe");
+        haus.walls.insert(Pos { x: head.x + 2, ..head });
+        assert_eq!(haus.danger_ahead(2), Some(2), "A wall two ahead should be spotted");
+        assert_eq!(head_bg(&haus), Color::Yellow, "Danger two ahead should tint the head yellow");
+        haus.walls.insert(Pos { x: head.x + 1, ..head });
+        assert_eq!(head_bg(&haus), Color::Red, "Danger right ahead should tint the head red");
+        haus.danger_warning = false;
+        assert_eq!(head_bg(&haus), Color::Reset, "The warning should be optional");
+    }
 
-        let pos = Pos { x: 5, y: 5 };
-        let delta = PosDelta { x: 25, y: -15 };
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, Pos { x: 0, y: 0 }, "Very large deltas should wrap multiple times: (5+25)%10=0, (5-15)%10=0");
+    #[test]
+    fn test_danger_cells() {
+        let size = Size { width: 20, height: 10 };
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        let head = haus.snek.head;
+        let ahead = Pos { x: head.x + 1, ..head };
+        let above = Pos { y: head.y - 1, ..head };
+        let below = Pos { y: head.y + 1, ..head };
+        assert!(haus.lethal_moves().is_empty(), "An open arena should have no lethal moves");
+        haus.walls.extend([ahead, above]);
+        assert_eq!(HashSet::<Pos>::from_iter(haus.lethal_moves()), HashSet::from([ahead, above]), "Walls beside the head should be lethal moves");
+
+        let area = Rect::new(0, 0, size.width, size.height);
+        let tints = |haus: &SnekHaus| {
+            let mut buffer = Buffer::empty(area);
+            haus.render(area, &mut buffer);
+            [ahead, above, below].map(|pos| buffer[(pos.x, pos.y)].bg == DANGER_TINT)
+        };
+        assert_eq!(tints(&haus), [false; 3], "The assist should be off unless asked for");
+        haus.danger_cells = true;
+        assert_eq!(tints(&haus), [true, true, false], "Only the lethal cells should be tinted");
     }
 
     #[test]
-    fn test_basic_movement() {
-        let arena_size = Size {
-            width: 10,
+    fn test_wide_head() {
+        let size = Size {
+            width: 20,
             height: 10,
         };
+        let mut game = test_game(Settings::default());
+        game.skins = vec![Skin {
+            head: Some("😀".to_string()),
+            ..Skin::default()
+        }];
+        game.skin = 0;
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        game.dress(&mut haus);
+        haus.glyphs = glyphs::UNICODE;
+        let head = haus.snek.head;
+        haus.moresels.push(Morsel { pos: Pos { x: head.x + 1, ..head }, growth_value: 1, kind: MorselKind::Normal, age: 0 });
+        let area = Rect::new(0, 0, size.width, size.height);
+        let draw = |haus: &SnekHaus, at: Pos| {
+            let mut buffer = Buffer::empty(area);
+            haus.render(area, &mut buffer);
+            [buffer[(at.x, at.y)].symbol().to_string(), buffer[(at.x + 1, at.y)].symbol().to_string()]
+        };
+        assert_eq!(draw(&haus, head), ["😀", " "], "The cell a wide head spills into should be cleared");
 
-        let pos = Pos { x: 5, y: 5 };
-        let delta: PosDelta = Direction::North.into();
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, Pos { x: 5, y: 4 }, "Moving North should decrease y coordinate by 1");
+        haus.snek.head.x = size.width - 1;
+        let mut buffer = Buffer::empty(area);
+        haus.render(area, &mut buffer);
+        assert_eq!(
+            buffer[(size.width - 1, head.y)].symbol(),
+            "▸",
+            "A wide head with no room to spill should fall back to an arrow"
+        );
+
+        game.settings.head = Head::At;
+        game.dress(&mut haus);
+        haus.snek.head = head;
+        assert_eq!(draw(&haus, head)[0], "@", "The head setting should swap a wide head for a narrow one");
     }
 
     #[test]
-    fn test_wrap_underflow() {
-        let arena_size = Size {
+    fn test_colour_themes() {
+        let size = Size {
             width: 10,
-            height: 10,
+            height: 5,
         };
+        let mut game = test_game(Settings {
+            theme: theme::NEON,
+            backdrop: settings::Backdrop::Dark,
+            ..Settings::default()
+        });
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        game.dress(&mut haus);
+        assert_eq!(haus.skin.colours, vec![theme::NEON.snek], "The built in skin should take the theme's colour");
+        let head = haus.snek.head;
+        haus.moresels.push(Morsel { pos: Pos { x: head.x + 2, ..head }, growth_value: 1, kind: MorselKind::Normal, age: 0 });
+
+        let area = Rect::new(0, 0, size.width, size.height);
+        let mut buffer = Buffer::empty(area);
+        haus.render(area, &mut buffer);
+        assert_eq!(buffer[(head.x, head.y)].fg, theme::NEON.head, "The head should be in the theme's colour");
+        assert_eq!(buffer[(head.x + 2, head.y)].fg, theme::NEON.morsel, "Morsels should be in the theme's colour");
+
+        let mut terminal = Terminal::new(backend::TestBackend::new(80, 24)).expect("Test terminal should start");
+        let buffer = terminal.draw(|f| game.render(f)).expect("Start screen should draw").buffer.clone();
+        assert_eq!(buffer[(1, 0)].fg, theme::NEON.border, "Borders should be in the theme's colour");
+        assert_eq!(buffer[(1, 1)].fg, theme::NEON.text, "Text should be in the theme's colour");
+        assert_eq!(buffer[(0, 0)].symbol(), "╔", "Borders should be drawn in the theme's lines");
+    }
 
-        let pos = Pos { x: 0, y: 0 };
-        let delta: PosDelta = Direction::North.into();
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, Pos { x: 0, y: 9 }, "Moving North from top should wrap to bottom");
+    #[test]
+    fn test_recording_replays_the_game() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut game = test_game(Settings::default());
+        game.arena_size = Some(Size {
+            width: 30,
+            height: 15,
+        });
+        game.mode = GameMode::Survival;
+        game.state = game.start_game().expect("Game should start");
+        for tick in 0..60 {
+            if tick % 7 == 3 {
+                let key = [KeyCode::Up, KeyCode::Left, KeyCode::Down, KeyCode::Right][tick / 7 % 4];
+                game.handle_input(KeyEvent::new(key, KeyModifiers::NONE));
+            }
+            game.update();
+        }
 
-        let pos = Pos { x: 0, y: 5 };
-        let delta: PosDelta = Direction::West.into();
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, Pos { x: 9, y: 5 }, "Moving West from left edge should wrap to right edge");
+        let mut playback = game.recording.playback(0, game.recording.ticks());
+        while playback.step() {}
+        let (GameState::Playing(haus, _) | GameState::GameOver { haus, .. }) = &game.state else {
+            panic!("Game should still be on, or just over");
+        };
+        assert_eq!((playback.haus.snek.head, playback.haus.score), (haus.snek.head, haus.score), "Playing the recording back should end where the game did");
+        assert_eq!(playback.haus.obstacles, haus.obstacles, "Random escalations should happen again on playback");
     }
 
     #[test]
-    fn test_wrap_overflow() {
-        let arena_size = Size {
-            width: 10,
-            height: 10,
+    fn test_saved_runs_play_back() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let mut game = test_game(Settings {
+            start_length: 5,
+            growth: 2,
+            ..Settings::default()
+        });
+        let press =
+            |game: &mut Game, code| game.handle_input(KeyEvent::new(code, KeyModifiers::NONE));
+        game.arena_size = Some(Size {
+            width: 30,
+            height: 15,
+        });
+        game.mode = GameMode::Survival;
+        game.state = game.start_game().expect("Game should start");
+        for tick in 0..60 {
+            if tick % 7 == 3 {
+                let key = [KeyCode::Up, KeyCode::Left, KeyCode::Down, KeyCode::Right][tick / 7 % 4];
+                press(&mut game, key);
+            }
+            game.update();
+        }
+        assert!(game.run_saveable(), "A game with a run code can be saved");
+
+        let dir = std::env::temp_dir().join(format!("snekrs_replays_{}", std::process::id()));
+        let run = game
+            .last_run
+            .clone()
+            .expect("The game should have a run code");
+        let path = game
+            .recording
+            .saved(run)
+            .save(&dir)
+            .expect("The run should save");
+        // Settings don't matter to a saved run, it brings its own
+        game.settings = Settings::default();
+        let recording = game.load_run(&path).expect("The run should load");
+        assert_eq!(
+            recording.ticks(),
+            game.recording.ticks(),
+            "Every tick of the run should load"
+        );
+        let mut playback = recording.playback(0, recording.ticks());
+        while playback.step() {}
+        let (GameState::Playing(haus, _) | GameState::GameOver { haus, .. }) = &game.state else {
+            panic!("Game should still be on, or just over");
         };
+        assert_eq!(
+            (playback.haus.snek.head, playback.haus.score),
+            (haus.snek.head, haus.score),
+            "A saved run should play out as the game did"
+        );
+        assert_eq!(
+            playback.haus.obstacles, haus.obstacles,
+            "Random escalations should happen again in a saved run"
+        );
 
-        let pos = Pos { x: 9, y: 9 };
-        let delta: PosDelta = Direction::South.into();
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, Pos { x: 9, y: 0 }, "Moving South from bottom should wrap to top");
+        game.state = GameState::ReplaySelect {
+            runs: vec![path],
+            selected: 0,
+        };
+        press(&mut game, KeyCode::Enter);
+        let GameState::Replay { viewer, .. } = &game.state else {
+            panic!("Picking a saved run should watch it")
+        };
+        assert!(viewer.ticks() > 0, "The run should have something to watch");
+        press(&mut game, KeyCode::Char('a'));
+        assert!(
+            matches!(game.state, GameState::TrailArt { .. }),
+            "A saved run should have trail art too"
+        );
+        press(&mut game, KeyCode::Esc);
+        press(&mut game, KeyCode::Esc);
+        assert!(
+            matches!(game.state, GameState::ReplaySelect { .. }),
+            "Leaving the replay should go back to the saved runs"
+        );
 
-        let pos = Pos { x: 9, y: 5 };
-        let delta: PosDelta = Direction::East.into();
-        let new_pos = pos.wrapped_add(delta, arena_size);
-        assert_eq!(new_pos, Pos { x: 0, y: 5 }, "Moving East from right edge should wrap to left edge");
+        press(&mut game, KeyCode::Char('v'));
+        let GameState::Spectating { feed, .. } = &game.state else {
+            panic!("A saved run should be there to spectate")
+        };
+        assert_eq!(feed.name(), "Replay", "The header should say it's a replay");
+        let start = feed.haus().snek.head;
+        game.update();
+        let GameState::Spectating { feed, live } = &game.state else {
+            panic!("Spectating should carry on while the run does")
+        };
+        assert!(*live, "The saved run should still be playing");
+        assert_ne!(
+            feed.haus().snek.head,
+            start,
+            "The saved run should play out in the spectator"
+        );
+        fs::remove_dir_all(&dir).ok();
     }
 
     #[test]
-    fn test_all_directions() {
-        let arena_size = Size {
-            width: 10,
+    fn test_weeds() {
+        let size = Size {
+            width: 20,
             height: 10,
         };
+        let rules = Rules {
+            weed_interval: Some(2),
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        let mut rng = rand::thread_rng();
+        haus.setup(&mut rng);
+        assert_eq!(haus.weeds.len(), WEED_SEEDS, "Weeds should be planted at the start");
+        assert!(haus.weeds.iter().all(|w| w.y != haus.snek.head.y), "Weeds shouldn't be planted in the snek's path");
+
+        haus.ticks = 2;
+        haus.grow_weeds(&mut rng);
+        assert_eq!(haus.weeds.len(), WEED_SEEDS + 1, "Weeds should spread every interval");
+        haus.ticks = 3;
+        haus.grow_weeds(&mut rng);
+        assert_eq!(haus.weeds.len(), WEED_SEEDS + 1, "Weeds shouldn't spread between intervals");
+
+        // Every free neighbour of this weed is within reach of the head
+        let head = haus.snek.head;
+        let near = head.wrapped_add(Direction::South.into(), size);
+        haus.weeds = HashSet::from([near]);
+        haus.moresels.clear();
+        haus.ticks = 4;
+        haus.grow_weeds(&mut rng);
+        assert_eq!(
+            haus.weeds,
+            HashSet::from([near]),
+            "Weeds shouldn't spread next to the snek's head"
+        );
 
-        let pos = Pos { x: 5, y: 5 };
+        let ahead = Pos {
+            x: 12,
+            y: 5,
+            floor: 0,
+        };
+        haus.moresels.clear();
+        haus.weeds = HashSet::from([ahead, Pos { x: 15, y: 5, floor: 0 }, Pos { x: 0, y: 0, floor: 0 }]);
+        haus.moresels.push(Morsel { pos: ahead, growth_value: 1, kind: MorselKind::Scythe, age: 0 });
+        haus.weeds.remove(&ahead);
+        haus.slither_on();
+        assert_eq!(haus.weeds, HashSet::from([Pos { x: 0, y: 0, floor: 0 }]), "A scythe should clear the weeds nearby");
+
+        haus.weeds.insert(Pos { x: 13, y: 5, floor: 0 });
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::HitWeed)), "Running into a weed should be fatal");
+    }
 
-        assert_eq!(
-            pos.wrapped_add(Direction::North.into(), arena_size),
-            Pos { x: 5, y: 4 },
-            "Moving North should decrease y coordinate"
+    #[test]
+    fn test_extras_left_out_of_strict_modes() {
+        let classic = GameMode::Classic.rules();
+        assert!(
+            classic.combo_window.is_some()
+                && classic.bonus_rounds
+                && classic.boost
+                && classic.rewind_odds.is_some()
+                && classic.shield_odds.is_some(),
+            "Classic should get the extras"
         );
+        for mode in [
+            GameMode::Hardcore,
+            GameMode::Daily,
+            GameMode::Puzzle,
+            GameMode::Sandbox,
+        ] {
+            let rules = mode.rules();
+            assert!(
+                rules.combo_window.is_none()
+                    && !rules.bonus_rounds
+                    && !rules.boost
+                    && rules.rewind_odds.is_none()
+                    && rules.shield_odds.is_none(),
+                "{} should be played without the extras",
+                mode.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_separate_high_scores() {
         assert_eq!(
-            pos.wrapped_add(Direction::South.into(), arena_size),
-            Pos { x: 5, y: 6 },
-            "Moving South should increase y coordinate"
+            GameMode::Classic.high_score_file(),
+            HIGH_SCORE_FILE,
+            "Most modes should share the high score"
         );
-        assert_eq!(
-            pos.wrapped_add(Direction::East.into(), arena_size),
-            Pos { x: 6, y: 5 },
-            "Moving East should increase x coordinate"
+        assert_ne!(
+            GameMode::TimeAttack.high_score_file(),
+            HIGH_SCORE_FILE,
+            "Time attack should keep its own high score"
         );
-        assert_eq!(
-            pos.wrapped_add(Direction::West.into(), arena_size),
-            Pos { x: 4, y: 5 },
-            "Moving West should decrease x coordinate"
+        assert!(
+            GameMode::Daily
+                .high_score_file()
+                .contains(&daily::date(daily::today())),
+            "Daily best should be kept per date"
         );
     }
 
     #[test]
-    fn test_collision_detection() {
-        let snek = Snek {
-            head: Pos { x: 5, y: 5 },
-            body: VecDeque::from([Pos { x: 5, y: 6 }, Pos { x: 5, y: 7 }, Pos { x: 6, y: 7 }]),
-            direction: Direction::North,
-            pending_growth: 0,
+    fn test_daily_layout_is_shared() {
+        let setup = || {
+            let mut haus = SnekHaus::new(daily::ARENA, 3, GameMode::Daily.rules());
+            let mut rng = StdRng::seed_from_u64(daily::today());
+            haus.setup(&mut rng);
+            for _ in 0..3 {
+                haus.moresels.clear();
+                haus.top_up_morsels(&mut rng);
+            }
+            haus
         };
-
-        assert!(snek.would_collide_with_body(Pos { x: 5, y: 6 }), "Should detect collision with first body segment");
-        assert!(snek.would_collide_with_body(Pos { x: 6, y: 7 }), "Should detect collision with last body segment");
-        assert!(!snek.would_collide_with_body(Pos { x: 5, y: 5 }), "Head position should not count as body collision");
-        assert!(!snek.would_collide_with_body(Pos { x: 4, y: 6 }), "Adjacent position should not count as collision");
+        let (mine, yours) = (setup(), setup());
+        assert_eq!(mine.portals, yours.portals, "Everyone should get the same portals");
+        assert_eq!(mine.obstacles, yours.obstacles, "Everyone should get the same obstacles");
+        assert_eq!(mine.moresels, yours.moresels, "Everyone should get the same morsels");
     }
 
     #[test]
-    fn test_snacking() {
-        let mut snek = Snek {
-            head: Pos { x: 5, y: 5 },
-            body: VecDeque::new(),
-            direction: Direction::North,
-            pending_growth: 0,
+    fn test_bonus_round() {
+        let size = Size {
+            width: 20,
+            height: 10,
+        };
+        let mut haus = SnekHaus::new(size, 5, Rules::default());
+        let mut rng = rand::thread_rng();
+        let mut round = Round::Normal {
+            until_bonus: Duration::from_secs(1),
         };
 
-        let morsel = Morsel {
-            pos: Pos { x: 5, y: 4 },
-            growth_value: 3,
+        round.advance(Duration::from_millis(600), &mut haus, &mut rng);
+        assert!(matches!(round, Round::Normal { .. }), "Bonus round shouldn't start early");
+        round.advance(Duration::from_millis(600), &mut haus, &mut rng);
+        assert_eq!(round, Round::Bonus { remaining: BONUS_ROUND_LENGTH }, "Bonus round should start on time");
+        assert_eq!(haus.moresels.len(), 200 / 8, "Small arena should get fewer bonus morsels");
+        assert!(haus.moresels.iter().all(|m| m.growth_value == 1), "Bonus morsels should be low value");
+
+        let shield = Morsel {
+            pos: Pos {
+                x: 0,
+                y: 0,
+                floor: 0,
+            },
+            growth_value: 1,
+            kind: MorselKind::Shield,
+            age: 0,
         };
+        haus.moresels.push(shield);
+        haus.snek.head = haus.snek.body[1];
+        assert!(matches!(haus.slither_on(), StepResult::Ongoing), "Collisions should be off in a bonus round");
 
-        snek.snack(morsel);
-        assert_eq!(snek.pending_growth, 3, "Snacking should set pending_growth to morsel's growth value");
+        // Still inside its own body when the round ends
+        haus.snek.head = haus.snek.body[1];
+        round.advance(BONUS_ROUND_LENGTH, &mut haus, &mut rng);
+        assert_eq!(
+            round,
+            Round::default(),
+            "Play should go back to normal after the bonus round"
+        );
+        assert_eq!(
+            haus.moresels,
+            vec![shield],
+            "Only the bonus morsels should be cleared away"
+        );
+        assert!(haus.ghost, "The snek should stay a ghost until it's clear");
 
-        snek.snack(Morsel {
-            pos: Pos { x: 0, y: 0 },
-            growth_value: 2,
-        });
-        assert_eq!(snek.pending_growth, 5, "Multiple snacks should accumulate pending_growth");
+        haus.snek = Snek::new(size, 5);
+        round.advance(Duration::from_millis(100), &mut haus, &mut rng);
+        assert!(!haus.ghost, "Collisions should be back on");
     }
 
     #[test]
-    fn test_new_odd_snek() {
+    fn test_brittle_morsel_drops_tail() {
         let size = Size {
-            width: 10,
+            width: 20,
             height: 10,
         };
+        let mut haus = SnekHaus::new(size, 5, Rules::default());
+        haus.moresels.push(Morsel {
+            pos: Pos { x: 13, y: 5, floor: 0 },
+            growth_value: 1,
+            kind: MorselKind::Brittle,
+            age: 0,
+        });
 
-        let snek = Snek::new(size, 3);
-        println!("Odd length snek:");
-        println!("  head: {:?}", snek.head);
-        println!("  body: {:?}", snek.body);
-
-        let body: Vec<_> = snek.body.iter().collect();
-        println!("  body segments:");
-        for (i, pos) in body.iter().enumerate() {
-            println!("    segment {}: {:?}", i, pos);
-        }
+        assert!(matches!(haus.slither_on(), StepResult::Nommed(1)), "Brittle morsels should still be eaten");
+        let tail: HashSet<Pos> = (8..11).map(|x| Pos { x, y: 5, floor: 0 }).collect();
+        assert_eq!(haus.hazards, tail, "Last tail segments should be left behind");
+        assert_eq!(haus.snek.body.len(), 2, "Dropped segments should leave the body");
+        assert!(haus.is_lethal(Pos { x: 9, y: 5, floor: 0 }), "Dropped tail should be lethal");
 
-        assert_eq!(snek.head, Pos { x: 6, y: 5 }, "Head should be positioned correctly for odd length snake");
-        assert_eq!(snek.body.len(), 3, "Body should have correct number of segments");
-        assert_eq!(snek.body[0], Pos { x: 3, y: 5 }, "Leftmost segment should be positioned correctly");
-        assert_eq!(snek.body[1], Pos { x: 4, y: 5 }, "Middle segment should be positioned correctly");
-        assert_eq!(snek.body[2], Pos { x: 5, y: 5 }, "Rightmost segment should be positioned correctly");
+        haus.snek.head = Pos { x: 7, y: 5, floor: 0 };
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::HitHazard)), "Running into the dropped tail should be fatal");
     }
 
     #[test]
-    fn test_new_snek_even() {
+    fn test_shed_tail_keeps_a_segment() {
         let size = Size {
-            width: 10,
+            width: 20,
             height: 10,
         };
-        let snek = Snek::new(size, 4);
+        let mut snek = Snek::new(size, 2);
+        assert_eq!(snek.shed_tail(TAIL_DROP).len(), 1, "Shedding should leave at least one segment");
+        assert_eq!(snek.body.len(), 1, "Snek should keep its last segment");
+    }
 
-        println!("Even length snek:");
-        println!("  head: {:?}", snek.head);
-        println!("  body: {:?}", snek.body);
-        println!("  body segments:");
-        for (i, pos) in snek.body.iter().enumerate() {
-            println!("    segment {}: {:?}", i, pos);
-        }
+    #[test]
+    fn test_obstacle_patrols() {
+        let route: Vec<Pos> = (0..3).map(|x| Pos { x, y: 0, floor: 0 }).collect();
+        let walk = |mut obstacle: Obstacle| {
+            (0..6)
+                .map(|_| {
+                    let x = obstacle.pos().x;
+                    obstacle.advance();
+                    x
+                })
+                .collect::<Vec<_>>()
+        };
 
-        assert_eq!(snek.head, Pos { x: 7, y: 5 }, "Head should be positioned correctly for even length snake");
-        assert_eq!(snek.body.len(), 4, "Body should have correct number of segments");
-        assert_eq!(snek.body[0], Pos { x: 3, y: 5 }, "Leftmost segment should be positioned correctly");
-        assert_eq!(snek.body[1], Pos { x: 4, y: 5 }, "Second segment should be positioned correctly");
-        assert_eq!(snek.body[2], Pos { x: 5, y: 5 }, "Third segment should be positioned correctly");
-        assert_eq!(snek.body[3], Pos { x: 6, y: 5 }, "Rightmost segment should be positioned correctly");
-        assert_eq!(snek.direction, Direction::East, "Snake should start facing East");
-        assert_eq!(snek.pending_growth, 0, "Snake should start with no pending growth");
+        assert_eq!(walk(Obstacle::new(route.clone(), Patrol::BackAndForth)), vec![0, 1, 2, 1, 0, 1], "Patrol should turn back at each end");
+        assert_eq!(walk(Obstacle::new(route, Patrol::Loop)), vec![0, 1, 2, 0, 1, 2], "Loop should go back to the start");
     }
 
     #[test]
-    fn test_snek_movement() {
+    fn test_obstacles() {
         let size = Size {
-            width: 10,
+            width: 20,
             height: 10,
         };
-        let mut snek = Snek::new(size, 3);
-        println!("Starting snek: {:?}", snek);
-
-        let initial_head = snek.head;
-        let initial_body: Vec<Pos> = snek.body.iter().cloned().collect();
-
-        snek.slither(size);
-
-        println!("Moved snek: {:?}", snek);
-
-        assert_eq!(
-            snek.head,
-            Pos {
-                x: initial_head.x + 1,
-                y: initial_head.y
-            },
-            "Head position should be one step east of old head"
-        );
-
-        assert_eq!(
-            snek.body.back(),
-            Some(&initial_head),
-            "Old head is now the highest body segment"
-        );
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        let route = vec![Pos { x: 12, y: 4, floor: 0 }, Pos { x: 12, y: 5, floor: 0 }];
+        haus.obstacles.push(Obstacle::new(route, Patrol::BackAndForth));
+
+        assert!(matches!(haus.slither_on(), StepResult::Ongoing), "Snek should pass in front of the obstacle");
+        assert_eq!(haus.obstacles[0].pos(), Pos { x: 12, y: 4, floor: 0 }, "Obstacle should wait rather than land on the head");
+        haus.slither_on();
+        assert_eq!(haus.obstacles[0].pos(), Pos { x: 12, y: 5, floor: 0 }, "Obstacle should move on once the head has gone");
+
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
+        haus.obstacles.push(Obstacle::new(vec![Pos { x: 13, y: 5, floor: 0 }], Patrol::Loop));
+        haus.slither_on();
+        assert!(matches!(haus.slither_on(), StepResult::Died(DeathCause::HitObstacle)), "Running into an obstacle should be fatal");
+    }
 
-        assert_eq!(
-            initial_body[2], snek.body[1],
-            "Middle segments moved up 2 to 1"
-        );
-        assert_eq!(
-            initial_body[1], snek.body[0],
-            "Middle segments moved up 1 to 0"
-        );
+    #[test]
+    fn test_setup_places_obstacles() {
+        let size = Size {
+            width: 40,
+            height: 20,
+        };
+        let rules = GameMode::Obstacles.rules();
+        let mut haus = SnekHaus::new(size, 3, rules);
+        haus.setup(&mut rand::thread_rng());
 
-        assert_eq!(snek.body.len(), 3, "Length remained the same");
-        assert!(!snek.body.contains(&initial_body[0]), "Tail was removed");
+        assert_eq!(haus.obstacles.len(), 3, "Every obstacle should be placed");
+        for obstacle in &haus.obstacles {
+            assert!(obstacle.route.iter().all(|pos| pos.y != haus.snek.head.y), "Routes should keep off the snek's row");
+        }
     }
 
     #[test]
-    fn test_snek_self_collision() {
+    fn test_combo_scoring() {
         let size = Size {
-            width: 10,
+            width: 20,
             height: 10,
         };
-        let mut haus = SnekHaus::new(size, 3);
+        let rules = Rules {
+            combo_window: Some(3),
+            ..Rules::default()
+        };
+        let mut haus = SnekHaus::new(size, 3, rules);
+        for x in [12, 13, 15, 19] {
+            haus.moresels.push(Morsel {
+                pos: Pos { x, y: 5, floor: 0 },
+                growth_value: 1,
+                kind: MorselKind::Normal,
+                age: 0,
+            });
+        }
 
-        assert!(!haus.check_snek_hit_itself(), "New snake should not be in self-collision state");
+        assert!(matches!(haus.slither_on(), StepResult::Nommed(1)), "First nom should score no bonus");
+        assert!(matches!(haus.slither_on(), StepResult::Nommed(2)), "Quick second nom should score a bonus");
+        haus.slither_on();
+        assert!(matches!(haus.slither_on(), StepResult::Nommed(3)), "Bonus should grow along the chain");
+        assert_eq!(haus.combo, 3, "Combo should count the chain");
+        assert_eq!(haus.score, 6, "Score should include the bonuses");
 
-        haus.snek.body.push_back(haus.snek.head);
-        assert!(haus.check_snek_hit_itself(), "Snake should detect collision when head overlaps with body");
+        for _ in 0..3 {
+            haus.slither_on();
+        }
+        assert_eq!(haus.announcement.map(|a| a.text()), Some("Tasty!"), "Reaching a milestone should be announced");
+        assert_eq!(haus.combo, 0, "Combo should reset after a long gap");
+        assert!(matches!(haus.slither_on(), StepResult::Nommed(1)), "Nom after a gap should start a new chain");
+
+        for _ in 0..ANNOUNCEMENT_TICKS {
+            haus.slither_on();
+        }
+        assert_eq!(haus.announcement, None, "Announcements should fade away");
+    }
+
+    #[test]
+    fn test_stairs_change_floor() {
+        let text = "########\n#..S.^.#\n########\n---\n########\n#..#.v.#\n########";
+        let level = Level::parse(text, "stairs", 2).expect("Level should parse");
+        let mut haus = SnekHaus::from_level(&level, 2, Rules::default());
+        assert_eq!(haus.floors, 2, "Level should have two floors");
+
+        haus.slither_on();
+        assert_eq!(haus.snek.head, Pos { x: 4, y: 1, floor: 0 }, "Snake should still be on the ground floor");
+        haus.slither_on();
+        assert_eq!(haus.snek.head, Pos { x: 6, y: 1, floor: 1 }, "Stairs should lead up a floor, exiting beyond them");
+        assert_eq!(haus.snek.body.back(), Some(&Pos { x: 4, y: 1, floor: 0 }), "Body should stay behind on the floor below");
+        assert!(haus.is_lethal(Pos { x: 3, y: 1, floor: 1 }), "Walls on the upper floor should be lethal");
+        assert!(!haus.is_lethal(Pos { x: 2, y: 1, floor: 1 }), "Floor below's body shouldn't block the floor above");
     }
 
     #[test]
@@ -903,11 +9872,13 @@ mod tests {
             width: 10,
             height: 10,
         };
-        let mut haus = SnekHaus::new(size, 3);
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
 
         let morsel = Morsel {
             pos: haus.snek.head,
             growth_value: 2,
+            kind: MorselKind::Normal,
+            age: 0,
         };
         haus.moresels.push(morsel);
 
@@ -926,15 +9897,19 @@ mod tests {
             width: 10,
             height: 10,
         };
-        let mut haus = SnekHaus::new(size, 3);
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
 
         let head_morsel = Morsel {
             pos: haus.snek.head,
             growth_value: 2,
+            kind: MorselKind::Normal,
+            age: 0,
         };
         let other_morsel = Morsel {
-            pos: Pos { x: 0, y: 0 },
+            pos: Pos { x: 0, y: 0, floor: 0 },
             growth_value: 3,
+            kind: MorselKind::Normal,
+            age: 0,
         };
 
         haus.moresels.push(head_morsel);
@@ -951,7 +9926,7 @@ mod tests {
             width: 10,
             height: 10,
         };
-        let mut haus = SnekHaus::new(size, 3);
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
 
         haus.change_direction(Direction::East);
         assert_eq!(haus.snek.direction, Direction::East, "Snake should change to valid new direction");
@@ -966,11 +9941,13 @@ mod tests {
             width: 10,
             height: 10,
         };
-        let mut haus = SnekHaus::new(size, 3);
+        let mut haus = SnekHaus::new(size, 3, Rules::default());
 
         let valid_morsel = Morsel {
-            pos: Pos { x: 0, y: 0 },
+            pos: Pos { x: 0, y: 0, floor: 0 },
             growth_value: 1,
+            kind: MorselKind::Normal,
+            age: 0,
         };
         haus.place_morsel(valid_morsel);
         assert_eq!(haus.moresels.len(), 1, "Valid morsel should be added to arena");
@@ -978,6 +9955,8 @@ mod tests {
         let invalid_morsel = Morsel {
             pos: haus.snek.head,
             growth_value: 1,
+            kind: MorselKind::Normal,
+            age: 0,
         };
         let result = std::panic::catch_unwind(move || {
             haus.place_morsel(invalid_morsel);
@@ -0,0 +1,209 @@
+//! Puzzles: hand-made boards where every morsel has to be eaten within a
+//! limited number of moves. The snek only moves when the player does, and
+//! moves can be undone.
+//!
+//! A puzzle file is a level file with a move limit:
+//!
+//! ```text
+//! ; Lines starting with ';' are comments
+//! name: First Bite
+//! moves: 5
+//! ############
+//! #...S..*...#
+//! #..........#
+//! #......*...#
+//! ############
+//! ```
+//!
+//! Every tile works as it does in a level, and `*` is a morsel worth one point.
+
+use crate::level::Level;
+use crate::{GameMode, Pos, Rules, SpeedCurve};
+use log::error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const PUZZLES_DIR: &str = "puzzles";
+/// Where the names of the solved puzzles are kept between sessions.
+pub const SOLVED_FILE: &str = ".snekrs_puzzles.txt";
+/// Every puzzle starts with a snek this long, so it plays as it was made
+pub const LENGTH: u16 = 3;
+/// Tick length for puzzles, only long enough to show each move
+const TICK_MILLIS: u64 = 40;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Puzzle {
+    pub level: Level,
+    /// Moves allowed to eat every morsel
+    pub moves: u16,
+}
+
+impl Puzzle {
+    /// Parse a puzzle, using `default_name` if the file doesn't name itself.
+    pub fn parse(text: &str, default_name: &str) -> Result<Puzzle, String> {
+        let mut moves = None;
+        let mut board = String::new();
+        for (index, line) in text.lines().enumerate() {
+            match line.strip_prefix("moves:") {
+                Some(value) => {
+                    let value = value.trim();
+                    moves = Some(value.parse().map_err(|_| {
+                        format!("line {}: '{}' isn't a number of moves", index + 1, value)
+                    })?);
+                    // Keep the line numbers in level errors right
+                    board.push('\n');
+                }
+                None => {
+                    board.push_str(line);
+                    board.push('\n');
+                }
+            }
+        }
+        let moves = moves.ok_or("no move limit 'moves:'")?;
+        let level = Level::parse(&board, default_name, LENGTH).map_err(|e| e.to_string())?;
+        if level.morsels.is_empty() {
+            return Err("no morsels '*' to eat".to_string());
+        }
+        let mut body = (1..=LENGTH).map(|i| Pos {
+            x: level.spawn.x - i,
+            ..level.spawn
+        });
+        if body.any(|pos| level.morsels.contains(&pos)) {
+            return Err("a morsel is under the snek's starting body".to_string());
+        }
+        Ok(Puzzle { level, moves })
+    }
+
+    pub fn load(path: &Path) -> Result<Puzzle, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let default_name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Puzzle::parse(&text, &default_name)
+    }
+
+    pub fn rules(&self) -> Rules {
+        let mut rules = GameMode::Puzzle.rules();
+        rules.moves = Some(self.moves);
+        rules.speed = SpeedCurve {
+            base_millis: TICK_MILLIS,
+            min_millis: TICK_MILLIS,
+            ..SpeedCurve::default()
+        };
+        rules.bonus_rounds = false;
+        rules.boost = false;
+        rules
+    }
+}
+
+/// Load every puzzle in `dir`, sorted by file name. Puzzles that fail to load
+/// are kept alongside the error so it can be shown to the player.
+pub fn load_dir(dir: &Path) -> Vec<(PathBuf, Result<Puzzle, String>)> {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file())
+            .collect(),
+        // No puzzles directory just means no puzzles
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| {
+            let puzzle = Puzzle::load(&path);
+            (path, puzzle)
+        })
+        .collect()
+}
+
+/// The puzzles solved so far, by name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Solved {
+    names: Vec<String>,
+}
+
+impl Solved {
+    /// Load the solved puzzles from `path`, with none solved if there isn't a
+    /// file yet.
+    pub fn load(path: &Path) -> Solved {
+        match fs::read_to_string(path) {
+            Ok(text) => Solved {
+                names: text
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Solved::default(),
+            Err(e) => {
+                error!("Error loading solved puzzles: {}", e);
+                Solved::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        if let Err(e) = fs::write(path, self.names.join("\n")) {
+            error!("Error saving solved puzzles: {}", e);
+        }
+    }
+
+    pub fn is_solved(&self, name: &str) -> bool {
+        self.names.iter().any(|solved| solved == name)
+    }
+
+    pub fn solve(&mut self, name: &str) {
+        if !self.is_solved(name) {
+            self.names.push(name.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_puzzle() {
+        let puzzle = Puzzle::parse("name: Tiny\nmoves: 4\n#......#\n#...S*.#\n", "x").expect("Puzzle should parse");
+        assert_eq!(puzzle.moves, 4, "The move limit should come from the file");
+        assert_eq!(puzzle.level.name, "Tiny", "The name should come from the file");
+        assert_eq!(puzzle.level.morsels.len(), 1, "Morsels should come from the board");
+        assert_eq!(puzzle.rules().moves, Some(4), "The rules should hold the move limit");
+
+        assert!(Puzzle::parse("#...S*.#", "x").is_err(), "A puzzle needs a move limit");
+        assert!(Puzzle::parse("moves: lots\n#...S*.#", "x").is_err(), "The move limit should be a number");
+        assert!(Puzzle::parse("moves: 3\n#...S..#", "x").is_err(), "A puzzle needs something to eat");
+        assert!(Puzzle::parse("moves: 3\n#.*.S..#", "x").is_err(), "Morsels can't start under the snek");
+        let error = Puzzle::parse("moves: 3\n#...S*.#\n#..", "x").expect_err("Ragged rows should be refused");
+        assert!(error.contains("line 3"), "Level errors should point at the right line, got {}", error);
+    }
+
+    #[test]
+    fn test_bundled_puzzles() {
+        let puzzles = load_dir(Path::new(PUZZLES_DIR));
+        assert!(!puzzles.is_empty(), "There should be puzzles to play");
+        for (path, puzzle) in puzzles {
+            assert!(puzzle.is_ok(), "{} should load: {:?}", path.display(), puzzle);
+        }
+    }
+
+    #[test]
+    fn test_solved_persists() {
+        let path = std::env::temp_dir().join(format!("snekrs_puzzles_{}.txt", std::process::id()));
+        let mut solved = Solved::default();
+        solved.solve("First Bite");
+        solved.solve("First Bite");
+        solved.save(&path);
+        let loaded = Solved::load(&path);
+        assert!(loaded.is_solved("First Bite"), "Solved puzzles should load back");
+        assert!(!loaded.is_solved("Detour"), "Unsolved puzzles should stay unsolved");
+        assert_eq!(loaded, solved, "Solving twice should only be recorded once");
+        fs::remove_file(&path).ok();
+        assert_eq!(Solved::load(&path), Solved::default(), "A missing file should solve nothing");
+    }
+}
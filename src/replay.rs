@@ -0,0 +1,664 @@
+//! Recording games so any part of them can be played back.
+//!
+//! Everything random in a game comes from its seeded rng, so a game plays out
+//! the same again given the same input on the same ticks. A recording keeps
+//! the input for every tick, with a keyframe of the whole game every
+//! `KEYFRAME_TICKS` ticks to seek from, and a stream of notable events to pick
+//! highlights out of.
+//!
+//! The last few ticks are also kept whole in a `History`, for the player to
+//! rewind to.
+//!
+//! A `Viewer` plays a whole recording back at the viewer's pace, apart from
+//! anything the live game does with input.
+//!
+//! A run can be saved to a file to watch again later. Keyframes are too big
+//! to keep, so the file holds the run code the game was set up from, then the
+//! input for each tick, `-` for none, and loading plays it all out again:
+//!
+//! ```text
+//! run: SNEK-classic-40x20-normal-3-1-mode-0000-00000000c0ffee00
+//! -
+//! n
+//! e b
+//! ```
+//!
+//! Directions are written `n`, `ne`, `e` and so on round to `nw`, and `b` is
+//! the boost held.
+
+use crate::bugreport;
+use crate::clipboard::RunCode;
+use crate::{Direction, Round, SnekHaus, StepResult};
+use rand::rngs::StdRng;
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const REPLAYS_DIR: &str = "replays";
+/// Ticks between keyframes. Seeking replays at most this many ticks.
+const KEYFRAME_TICKS: u32 = 20;
+/// Ticks shown before a highlight, to set the scene
+const LEAD_IN: u32 = 10;
+/// Ticks shown after a highlight
+const FOLLOW_THROUGH: u32 = 5;
+/// Highlights are cut down to their last this many ticks
+const MAX_HIGHLIGHT_TICKS: u32 = 150;
+/// How far back a rewind goes
+const REWIND_TICKS: u32 = 10;
+/// Speeds a viewer plays at, in recorded ticks for each tick of the game
+pub const SPEEDS: [u32; 3] = [1, 2, 4];
+
+/// Everything needed to carry on a game from the start of a tick.
+#[derive(Clone, Debug)]
+struct Keyframe {
+    tick: u32,
+    haus: SnekHaus,
+    round: Round,
+    rng: StdRng,
+}
+
+/// What the player did ahead of a tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Input {
+    Steer(Direction),
+    /// Held the boost key
+    Boost,
+}
+
+impl Input {
+    /// Every input, with how it's written in a saved run.
+    const WORDS: [(Input, &'static str); 9] = [
+        (Input::Steer(Direction::North), "n"),
+        (Input::Steer(Direction::NorthEast), "ne"),
+        (Input::Steer(Direction::East), "e"),
+        (Input::Steer(Direction::SouthEast), "se"),
+        (Input::Steer(Direction::South), "s"),
+        (Input::Steer(Direction::SouthWest), "sw"),
+        (Input::Steer(Direction::West), "w"),
+        (Input::Steer(Direction::NorthWest), "nw"),
+        (Input::Boost, "b"),
+    ];
+
+    fn word(self) -> &'static str {
+        Input::WORDS
+            .iter()
+            .find(|&&(input, _)| input == self)
+            .map_or("", |&(_, word)| word)
+    }
+
+    fn parse(word: &str) -> Option<Input> {
+        Input::WORDS
+            .iter()
+            .find(|&&(_, known)| known == word)
+            .map(|&(input, _)| input)
+    }
+}
+
+/// Something worth seeing again.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    /// A morsel eaten, this far into a combo
+    Nom { combo: u16 },
+    /// Squeezing past this many lethal cells around the head
+    NearMiss { tight: u8 },
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Recording {
+    keyframes: Vec<Keyframe>,
+    /// Input for each tick, in the order it came
+    inputs: Vec<Vec<Input>>,
+    /// Input since the last tick
+    pending: Vec<Input>,
+    /// Events, with the tick they happened on
+    events: Vec<(u32, Event)>,
+}
+
+impl Recording {
+    /// Start recording a game that's about to play its first tick.
+    pub fn start(haus: &SnekHaus, round: Round, rng: &StdRng) -> Recording {
+        Recording {
+            keyframes: vec![Keyframe {
+                tick: 0,
+                haus: haus.clone(),
+                round,
+                rng: rng.clone(),
+            }],
+            ..Recording::default()
+        }
+    }
+
+    /// Ticks recorded so far.
+    pub fn ticks(&self) -> u32 {
+        self.inputs.len() as u32
+    }
+
+    /// Note input ahead of the next tick.
+    pub fn input(&mut self, input: Input) {
+        self.pending.push(input);
+    }
+
+    /// Note that a tick was played, leaving the game as it is now.
+    pub fn tick(&mut self, haus: &SnekHaus, round: Round, rng: &StdRng) {
+        self.inputs.push(std::mem::take(&mut self.pending));
+        if self.ticks().is_multiple_of(KEYFRAME_TICKS) {
+            self.keyframes.push(Keyframe {
+                tick: self.ticks(),
+                haus: haus.clone(),
+                round,
+                rng: rng.clone(),
+            });
+        }
+    }
+
+    /// Forget everything after the first `ticks` ticks, for a game rewound to
+    /// the end of them.
+    pub fn rewind(&mut self, ticks: u32) {
+        self.inputs.truncate(ticks as usize);
+        self.pending.clear();
+        self.keyframes.retain(|keyframe| keyframe.tick <= ticks);
+        self.events.retain(|&(tick, _)| tick < ticks);
+    }
+
+    /// Note something worth seeing again on the last tick played.
+    pub fn event(&mut self, event: Event) {
+        self.events.push((self.ticks().saturating_sub(1), event));
+    }
+
+    /// The run's best moment: the longest combo, or failing that the tightest
+    /// squeeze, with the latest one winning ties.
+    pub fn highlight(&self) -> Option<Highlight> {
+        let combo = self
+            .events
+            .iter()
+            .filter_map(|&(tick, event)| match event {
+                Event::Nom { combo } if combo > 1 => Some((combo, tick)),
+                _ => None,
+            })
+            .max();
+        let (event, start, end) = if let Some((combo, end)) = combo {
+            // Back to the nom that started the chain
+            let start = self
+                .events
+                .iter()
+                .rev()
+                .find(|&&(tick, event)| tick <= end && event == Event::Nom { combo: 1 })
+                .map_or(end, |&(tick, _)| tick);
+            (Event::Nom { combo }, start, end)
+        } else {
+            let (tight, tick) = self
+                .events
+                .iter()
+                .filter_map(|&(tick, event)| match event {
+                    Event::NearMiss { tight } => Some((tight, tick)),
+                    _ => None,
+                })
+                .max()?;
+            (Event::NearMiss { tight }, tick, tick)
+        };
+
+        let end = (end + FOLLOW_THROUGH + 1).min(self.ticks());
+        let start = start
+            .saturating_sub(LEAD_IN)
+            .max(end.saturating_sub(MAX_HIGHLIGHT_TICKS));
+        Some(Highlight { event, start, end })
+    }
+
+    /// Record a saved run all over again, from `haus` as it opened and `rng`
+    /// as it was left by setting it up.
+    pub fn replay(saved: &SavedRun, haus: SnekHaus, rng: StdRng) -> Recording {
+        let round = Round::default();
+        let mut recording = Recording::start(&haus, round, &rng);
+        let mut playback = Playback {
+            haus,
+            round,
+            rng,
+            tick: 0,
+            end: saved.inputs.len() as u32,
+            inputs: saved.inputs.clone(),
+        };
+        for inputs in &saved.inputs {
+            for &input in inputs {
+                recording.input(input);
+            }
+            let going = playback.step();
+            recording.tick(&playback.haus, playback.round, &playback.rng);
+            if !going {
+                break;
+            }
+        }
+        recording
+    }
+
+    /// The run recorded so far, to save, set up again from `run`.
+    pub fn saved(&self, run: RunCode) -> SavedRun {
+        SavedRun {
+            run,
+            inputs: self.inputs.clone(),
+        }
+    }
+
+    /// Play the recording back from the start of tick `from`, up to the start of `to`.
+    pub fn playback(&self, from: u32, to: u32) -> Playback {
+        let keyframe = self
+            .keyframes
+            .iter()
+            .rev()
+            .find(|keyframe| keyframe.tick <= from)
+            .expect("Recordings start with a keyframe");
+        let mut playback = Playback {
+            haus: keyframe.haus.clone(),
+            round: keyframe.round,
+            rng: keyframe.rng.clone(),
+            tick: keyframe.tick,
+            end: to.min(self.ticks()),
+            inputs: self.inputs.clone(),
+        };
+        while playback.tick < from && playback.step() {}
+        playback
+    }
+}
+
+/// A run kept in a file: the run code it was set up from, and the input for
+/// each tick.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedRun {
+    pub run: RunCode,
+    inputs: Vec<Vec<Input>>,
+}
+
+impl SavedRun {
+    fn parse(text: &str) -> Result<SavedRun, String> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with(';'));
+        let run = lines
+            .next()
+            .and_then(|line| line.strip_prefix("run:"))
+            .ok_or("expected the run code first, like 'run: SNEK-...'")?;
+        let run = RunCode::parse(run)?;
+        let inputs = lines
+            .enumerate()
+            .map(|(tick, line)| {
+                line.split_whitespace()
+                    .filter(|&word| word != "-")
+                    .map(|word| {
+                        Input::parse(word)
+                            .ok_or_else(|| format!("tick {}: '{}' isn't an input", tick, word))
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(SavedRun { run, inputs })
+    }
+
+    pub fn load(path: &Path) -> Result<SavedRun, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        SavedRun::parse(&text)
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!("run: {}\n", self.run);
+        for inputs in &self.inputs {
+            let words: Vec<&str> = inputs.iter().map(|input| input.word()).collect();
+            if words.is_empty() {
+                text.push_str("-\n");
+            } else {
+                text.push_str(&words.join(" "));
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// Save the run to a new file in `dir`, returning where it went.
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = bugreport::unique_path(dir, "snekrs-replay");
+        fs::write(&path, self.to_text())?;
+        Ok(path)
+    }
+}
+
+/// Every saved run in `dir`, sorted by file name, which sorts them by when
+/// they were saved.
+pub fn saved_runs(dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file())
+            .collect(),
+        // No replays directory just means nothing saved yet
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    paths
+}
+
+/// The game as it was after each of the last `REWIND_TICKS` ticks, and now.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    keyframes: VecDeque<Keyframe>,
+}
+
+/// A game as it was, to carry on from.
+pub struct Rewound {
+    /// Ticks played up to this point
+    pub tick: u32,
+    pub haus: SnekHaus,
+    pub round: Round,
+    pub rng: StdRng,
+}
+
+impl History {
+    /// Note the game as it is after `tick` ticks.
+    pub fn push(&mut self, tick: u32, haus: &SnekHaus, round: Round, rng: &StdRng) {
+        self.keyframes.push_back(Keyframe {
+            tick,
+            haus: haus.clone(),
+            round,
+            rng: rng.clone(),
+        });
+        if self.keyframes.len() > REWIND_TICKS as usize + 1 {
+            self.keyframes.pop_front();
+        }
+    }
+
+    /// Forget every earlier tick, so nothing before now can be rewound to.
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+    }
+
+    /// Go back as far as there's history for, up to `REWIND_TICKS`, taking
+    /// the ticks since off the history. `None` if there's nothing to go back to.
+    pub fn rewind(&mut self) -> Option<Rewound> {
+        if self.keyframes.len() < 2 {
+            return None;
+        }
+        let keyframe = self.keyframes.pop_front()?;
+        self.keyframes.clear();
+        self.keyframes.push_back(keyframe.clone());
+        Some(Rewound {
+            tick: keyframe.tick,
+            haus: keyframe.haus,
+            round: keyframe.round,
+            rng: keyframe.rng,
+        })
+    }
+}
+
+/// Part of a game worth seeing again, from the start of tick `start` up to `end`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Highlight {
+    pub event: Event,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Highlight {
+    pub fn describe(&self) -> String {
+        match self.event {
+            Event::Nom { combo } => format!("{} nom combo", combo),
+            Event::NearMiss { .. } => "Near miss".to_string(),
+        }
+    }
+}
+
+/// A recorded game being played again.
+#[derive(Clone, Debug)]
+pub struct Playback {
+    pub haus: SnekHaus,
+    round: Round,
+    rng: StdRng,
+    /// The next tick to play
+    tick: u32,
+    end: u32,
+    inputs: Vec<Vec<Input>>,
+}
+
+impl Playback {
+    /// Play the next tick, returning false once there are none left.
+    pub fn step(&mut self) -> bool {
+        if self.tick >= self.end {
+            return false;
+        }
+        let inputs = &self.inputs[self.tick as usize];
+        for &input in inputs {
+            if let Input::Steer(pressed) = input {
+                self.haus.steer(pressed);
+            }
+        }
+        self.haus.set_boost(inputs.contains(&Input::Boost));
+        let result = self.haus.tick(&mut self.round, &mut self.rng);
+        self.tick += 1;
+        !matches!(result, StepResult::Died(_) | StepResult::Victory)
+    }
+}
+
+/// A whole recording played back at the viewer's pace: paused, stepped a
+/// tick at a time, sped up, or scrubbed back and forth.
+#[derive(Clone, Debug)]
+pub struct Viewer {
+    recording: Recording,
+    pub playback: Playback,
+    pub paused: bool,
+    /// Ticks played for each tick of the game, one of `SPEEDS`
+    pub speed: u32,
+}
+
+impl Viewer {
+    /// Watch `recording` from the start.
+    pub fn new(recording: &Recording) -> Viewer {
+        Viewer {
+            recording: recording.clone(),
+            playback: recording.playback(0, recording.ticks()),
+            paused: false,
+            speed: SPEEDS[0],
+        }
+    }
+
+    /// The next tick to play.
+    pub fn tick(&self) -> u32 {
+        self.playback.tick
+    }
+
+    /// Ticks in the whole recording.
+    pub fn ticks(&self) -> u32 {
+        self.recording.ticks()
+    }
+
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// Play on for a tick of the game, unless paused.
+    pub fn advance(&mut self) {
+        if self.paused {
+            return;
+        }
+        for _ in 0..self.speed {
+            if !self.playback.step() {
+                // Nothing more to see, so stop on the last tick
+                self.paused = true;
+                break;
+            }
+        }
+    }
+
+    /// Pause, and play a single tick.
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.playback.step();
+    }
+
+    /// Go to the start of `tick`, or the end if there's not that many.
+    pub fn seek(&mut self, tick: u32) {
+        let ticks = self.ticks();
+        self.playback = self.recording.playback(tick.min(ticks), ticks);
+    }
+
+    /// Go `ticks` ticks forwards, or backwards if negative.
+    pub fn scrub(&mut self, ticks: i32) {
+        self.seek(self.tick().saturating_add_signed(ticks));
+    }
+
+    /// Step through `SPEEDS`, wrapping round.
+    pub fn cycle_speed(&mut self) {
+        self.speed = crate::cycle(&SPEEDS, self.speed, 1);
+    }
+
+    /// How far through the recording, from 0 to 1.
+    pub fn progress(&self) -> f64 {
+        self.tick() as f64 / self.ticks().max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameMode, Size};
+    use rand::SeedableRng;
+
+    /// Play `ticks` ticks of classic, turning now and then, recording as it goes.
+    fn record(ticks: u32) -> (Recording, SnekHaus) {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut haus = SnekHaus::new(Size { width: 30, height: 15 }, 3, GameMode::Classic.rules());
+        haus.setup(&mut rng);
+        let mut round = Round::default();
+        let mut recording = Recording::start(&haus, round, &rng);
+        for tick in 0..ticks {
+            if tick % 9 == 4 {
+                let turn = if tick % 18 == 4 { Direction::North } else { Direction::East };
+                haus.steer(turn);
+                recording.input(Input::Steer(turn));
+            }
+            // Boost for a stretch, once there's some saved up
+            let boost = (30..40).contains(&tick);
+            haus.set_boost(boost);
+            if boost {
+                recording.input(Input::Boost);
+            }
+            haus.tick(&mut round, &mut rng);
+            recording.tick(&haus, round, &rng);
+        }
+        (recording, haus)
+    }
+
+    #[test]
+    fn test_playback_matches_the_game() {
+        let (recording, haus) = record(50);
+        let mut playback = recording.playback(0, 50);
+        while playback.step() {}
+        let place = |haus: &SnekHaus| (haus.snek.head, haus.snek.body.clone());
+        assert_eq!(place(&playback.haus), place(&haus), "Playing back from the start should end up where the game did");
+        assert_eq!(playback.haus.moresels, haus.moresels, "Morsels should be placed the same way again");
+
+        let mut seeked = recording.playback(33, 50);
+        assert_eq!(seeked.tick, 33, "Seeking should stop at the tick asked for");
+        while seeked.step() {}
+        assert_eq!(place(&seeked.haus), place(&haus), "Seeking from a keyframe should end up in the same place");
+    }
+
+    #[test]
+    fn test_viewer() {
+        let (recording, haus) = record(50);
+        let mut viewer = Viewer::new(&recording);
+        viewer.cycle_speed();
+        viewer.advance();
+        assert_eq!((viewer.speed, viewer.tick()), (2, 2), "A viewer at double speed should play two ticks at a time");
+        viewer.step();
+        assert!(viewer.paused && viewer.tick() == 3, "Stepping should pause and play a single tick");
+        viewer.advance();
+        assert_eq!(viewer.tick(), 3, "A paused viewer shouldn't play on");
+
+        viewer.scrub(40);
+        assert_eq!(viewer.tick(), 43, "Scrubbing should jump forwards");
+        viewer.scrub(-100);
+        assert_eq!(viewer.tick(), 0, "Scrubbing back should stop at the start");
+        viewer.seek(999);
+        assert_eq!(viewer.progress(), 1.0, "Seeking past the end should stop at the end");
+        assert_eq!(viewer.playback.haus.snek.head, haus.snek.head, "The end should be where the game ended");
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut haus = SnekHaus::new(Size { width: 30, height: 15 }, 3, GameMode::Classic.rules());
+        haus.setup(&mut rng);
+        let mut round = Round::default();
+        let mut recording = Recording::start(&haus, round, &rng);
+        let mut history = History::default();
+        history.push(0, &haus, round, &rng);
+        assert!(history.clone().rewind().is_none(), "There's nothing to rewind to before the first tick");
+        for _ in 0..25 {
+            haus.tick(&mut round, &mut rng);
+            recording.tick(&haus, round, &rng);
+            history.push(recording.ticks(), &haus, round, &rng);
+        }
+        let rewound = history.rewind().expect("There should be history to rewind to");
+        assert_eq!(rewound.tick, 25 - REWIND_TICKS, "A rewind should go back ten ticks");
+        recording.rewind(rewound.tick);
+        assert_eq!(recording.ticks(), rewound.tick, "The recording should be cut back to match");
+        let mut playback = recording.playback(0, rewound.tick);
+        while playback.step() {}
+        assert_eq!(playback.haus.snek.head, rewound.haus.snek.head, "The recording should agree with the rewound game");
+        assert!(history.rewind().is_none(), "Rewinding again needs fresh history");
+    }
+
+    #[test]
+    fn test_saved_run() {
+        let (recording, haus) = record(50);
+        let run = RunCode::parse("SNEK-classic-30x15-normal-3-1-mode-0000-7")
+            .expect("Run code should parse");
+        let saved = recording.saved(run);
+        assert_eq!(
+            SavedRun::parse(&saved.to_text()),
+            Ok(saved.clone()),
+            "A saved run should load back the same"
+        );
+        assert!(
+            saved.to_text().contains("\n-\n") && saved.to_text().contains("\ne b\n"),
+            "Ticks should be written with their input, or '-' for none"
+        );
+
+        let opening = &recording.keyframes[0];
+        let replayed = Recording::replay(&saved, opening.haus.clone(), opening.rng.clone());
+        assert_eq!(
+            replayed.ticks(),
+            recording.ticks(),
+            "Every tick should be recorded again"
+        );
+        let mut playback = replayed.playback(0, replayed.ticks());
+        while playback.step() {}
+        assert_eq!(
+            (playback.haus.snek.head, playback.haus.score),
+            (haus.snek.head, haus.score),
+            "A saved run should play out the same as the game did"
+        );
+
+        assert!(
+            SavedRun::parse("n\ne\n").is_err(),
+            "A saved run needs its run code"
+        );
+        assert!(
+            SavedRun::parse("run: SNEK-classic-30x15-normal-3-1-mode-0000-7\nup\n").is_err(),
+            "Unknown input should be refused"
+        );
+    }
+
+    #[test]
+    fn test_highlight() {
+        let (mut recording, _) = record(200);
+        assert_eq!(recording.highlight(), None, "A run with nothing notable has no highlight");
+
+        recording.events.push((60, Event::NearMiss { tight: 2 }));
+        assert_eq!(recording.highlight(), Some(Highlight { event: Event::NearMiss { tight: 2 }, start: 50, end: 66 }), "A near miss should be shown with some lead in");
+
+        recording.events.extend([(80, Event::Nom { combo: 1 }), (85, Event::Nom { combo: 2 }), (90, Event::Nom { combo: 3 }), (120, Event::Nom { combo: 1 }), (125, Event::Nom { combo: 2 })]);
+        let best = recording.highlight().expect("Combos should be highlighted");
+        assert_eq!(best.event, Event::Nom { combo: 3 }, "The longest combo should win over near misses");
+        assert_eq!((best.start, best.end), (70, 96), "The whole chain should be shown");
+    }
+}
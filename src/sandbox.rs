@@ -0,0 +1,150 @@
+//! The practice sandbox: with time stopped, paint morsels and walls into the
+//! arena with the mouse and drag the snek about, then carry on playing to try
+//! out a tricky spot as often as it takes.
+
+use crate::{Direction, Morsel, MorselKind, Pos, SnekHaus};
+
+/// What a left click paints in the sandbox. A right click always clears.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Brush {
+    #[default]
+    Morsel,
+    Wall,
+    /// Moves the snek's head to the cell, its body following in the same shape
+    Snek,
+}
+
+impl Brush {
+    pub const ALL: [Brush; 3] = [Brush::Morsel, Brush::Wall, Brush::Snek];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Brush::Morsel => "Morsel",
+            Brush::Wall => "Wall",
+            Brush::Snek => "Move snek",
+        }
+    }
+}
+
+/// Paint `pos` with `brush`, returning whether anything changed. Cells that
+/// are already taken are left alone.
+pub fn paint(haus: &mut SnekHaus, pos: Pos, brush: Brush, value: u16) -> bool {
+    match brush {
+        Brush::Morsel if haus.is_empty(pos) => {
+            haus.place_morsel(Morsel {
+                pos,
+                growth_value: value,
+                kind: MorselKind::Normal,
+                age: 0,
+            });
+            true
+        }
+        Brush::Wall if haus.is_empty(pos) => haus.walls.insert(pos),
+        Brush::Snek => move_snek(haus, pos),
+        _ => false,
+    }
+}
+
+/// Clear any morsel or wall from `pos`, returning whether there was one.
+pub fn clear(haus: &mut SnekHaus, pos: Pos) -> bool {
+    let morsels = haus.moresels.len();
+    haus.moresels.retain(|morsel| morsel.pos != pos);
+    haus.walls.remove(&pos) || haus.moresels.len() != morsels
+}
+
+/// Point the snek `direction`, unless that would run it straight back into
+/// its own neck. Returns whether it turned.
+pub fn turn(haus: &mut SnekHaus, direction: Direction) -> bool {
+    if !haus.directions().contains(&direction)
+        || haus.snek.body.back() == Some(&haus.next_head(&haus.snek, direction))
+    {
+        return false;
+    }
+    haus.snek.direction = direction;
+    true
+}
+
+/// Move the snek so its head is on `pos`, keeping its shape and heading, if
+/// all of it still fits in the arena clear of walls and morsels.
+fn move_snek(haus: &mut SnekHaus, pos: Pos) -> bool {
+    let snek = &haus.snek;
+    let dx = pos.x as i32 - snek.head.x as i32;
+    let dy = pos.y as i32 - snek.head.y as i32;
+    let shift = |cell: Pos| {
+        let x = u16::try_from(cell.x as i32 + dx).ok()?;
+        let y = u16::try_from(cell.y as i32 + dy).ok()?;
+        let moved = Pos { x, y, ..cell };
+        (x < haus.size.width
+            && y < haus.size.height
+            && !haus.walls.contains(&moved)
+            && !haus.moresels.iter().any(|morsel| morsel.pos == moved))
+        .then_some(moved)
+    };
+    let Some(body) = snek
+        .body
+        .iter()
+        .map(|&cell| shift(cell))
+        .collect::<Option<_>>()
+    else {
+        return false;
+    };
+    let Some(head) = shift(snek.head) else {
+        return false;
+    };
+    haus.snek.body = body;
+    haus.snek.head = head;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rules, Size};
+
+    fn haus() -> SnekHaus {
+        let mut haus = SnekHaus::new(Size { width: 20, height: 10 }, 3, Rules::default());
+        haus.moresels.clear();
+        haus
+    }
+
+    #[test]
+    fn test_paint_and_clear() {
+        let mut haus = haus();
+        let pos = Pos { x: 2, y: 2, floor: 0 };
+        assert!(paint(&mut haus, pos, Brush::Morsel, 3), "A morsel should paint onto an empty cell");
+        assert_eq!(haus.moresels[0].growth_value, 3, "The morsel should be worth what was picked");
+        assert!(!paint(&mut haus, pos, Brush::Wall, 1), "Walls shouldn't paint over morsels");
+        assert!(clear(&mut haus, pos), "Clearing should take the morsel away");
+        assert!(paint(&mut haus, pos, Brush::Wall, 1), "A wall should paint onto the cleared cell");
+        let head = haus.snek.head;
+        assert!(!paint(&mut haus, head, Brush::Wall, 1), "Walls shouldn't paint over the snek");
+        assert!(clear(&mut haus, pos) && !haus.walls.contains(&pos), "Clearing should take the wall away");
+        assert!(!clear(&mut haus, pos), "Clearing an empty cell should do nothing");
+    }
+
+    #[test]
+    fn test_move_snek() {
+        let mut haus = haus();
+        let length = haus.snek.length();
+        let target = Pos { x: 10, y: 7, floor: 0 };
+        assert!(paint(&mut haus, target, Brush::Snek, 1), "The snek should move to an open spot");
+        assert_eq!(haus.snek.head, target, "The head should be where it was put");
+        assert_eq!(haus.snek.length(), length, "The snek should keep its length");
+        assert!(haus.snek.body.iter().all(|cell| cell.y == 7 && cell.x < 10), "The body should keep its shape behind the head");
+
+        assert!(!paint(&mut haus, Pos { x: 1, y: 7, floor: 0 }, Brush::Snek, 1), "The body can't be pushed off the edge");
+        haus.walls.insert(Pos { x: 6, y: 3, floor: 0 });
+        assert!(!paint(&mut haus, Pos { x: 8, y: 3, floor: 0 }, Brush::Snek, 1), "The body can't be put on a wall");
+        assert_eq!(haus.snek.head, target, "A refused move should leave the snek be");
+    }
+
+    #[test]
+    fn test_turn() {
+        let mut haus = haus();
+        assert!(turn(&mut haus, Direction::North), "The snek should turn to face anywhere open");
+        assert!(turn(&mut haus, Direction::East), "The snek should turn back the way it was going");
+        assert!(!turn(&mut haus, Direction::West), "The snek can't face its own neck");
+        assert!(!turn(&mut haus, Direction::NorthWest), "Diagonals need the diagonal modifier");
+        assert_eq!(haus.snek.direction, Direction::East, "Refused turns should leave the snek be");
+    }
+}
@@ -0,0 +1,76 @@
+//! Normalized scores, for comparing runs played at different speeds and under
+//! different rules. Anything ranking scores across modes, such as the summary
+//! screens and leaderboards, should go through here.
+
+use crate::{EdgePolicy, Rules, Scoring};
+
+/// Tick length that normalized scores are measured against, that of Classic.
+pub const REFERENCE_TICK_MILLIS: u64 = 150;
+/// What a morsel is worth on average, when all values from 1 to 5 are as likely.
+const MEAN_MORSEL_VALUE: f64 = 3.0;
+
+/// How much harder `rules` make scoring, apart from the speed. Normalized
+/// scores already take the speed into account.
+pub fn multiplier(rules: &Rules) -> f64 {
+    let mut multiplier = match rules.edges {
+        EdgePolicy::Wrap => 1.0,
+        EdgePolicy::WrapAcross | EdgePolicy::WrapDown => 1.05,
+        EdgePolicy::Walled => 1.1,
+        EdgePolicy::Solid => 1.25,
+    };
+    if let Some(lives) = rules.lives {
+        multiplier /= lives.max(1) as f64;
+    }
+    multiplier *= MEAN_MORSEL_VALUE / rules.mean_morsel_value().max(1.0);
+    if rules.hunger.is_some() || rules.famine.is_some() {
+        multiplier *= 1.2;
+    }
+    if rules.chaos_interval.is_some() {
+        multiplier *= 1.2;
+    }
+    // A faster growing snek fills the arena sooner
+    if let Some(growth) = rules.growth {
+        multiplier *= 1.0 + 0.1 * growth.saturating_sub(1) as f64;
+    }
+    multiplier * (1.0 + 0.05 * rules.obstacles as f64)
+}
+
+/// Points per minute, with every tick counted as a reference tick however fast
+/// it was played, then scaled by the difficulty multiplier. `None` under
+/// survival and coverage scoring, where the score only counts time.
+pub fn normalized(score: u16, ticks: u32, rules: &Rules) -> Option<f64> {
+    if rules.scoring != Scoring::Morsels {
+        return None;
+    }
+    let minutes = (ticks as u64 * REFERENCE_TICK_MILLIS) as f64 / 60_000.0;
+    if minutes == 0.0 {
+        return Some(0.0);
+    }
+    Some(score as f64 / minutes * multiplier(rules))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameMode;
+
+    #[test]
+    fn test_normalized_ignores_speed() {
+        let classic = GameMode::Classic.rules();
+        let mut fast = classic;
+        fast.speed.base_millis = 50;
+        // 400 ticks is a minute at the reference speed
+        assert_eq!(normalized(30, 400, &classic), Some(30.0), "A minute of classic should score its points");
+        assert_eq!(normalized(30, 400, &fast), normalized(30, 400, &classic), "Speed shouldn't change the normalized score");
+        assert_eq!(normalized(0, 0, &classic), Some(0.0), "A run with no ticks should score nothing");
+        assert_eq!(normalized(30, 400, &GameMode::Survival.rules()), None, "Survival scores are already times");
+        assert_eq!(normalized(30, 400, &GameMode::Tron.rules()), None, "Tron scores only count moves");
+    }
+
+    #[test]
+    fn test_multiplier() {
+        assert_eq!(multiplier(&GameMode::Classic.rules()), 1.0, "Classic should be the baseline");
+        assert!(multiplier(&GameMode::Hardcore.rules()) > 1.0, "Hardcore should be worth more");
+        assert!(multiplier(&GameMode::Lives.rules()) < 1.0, "Extra lives should be worth less");
+    }
+}
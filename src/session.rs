@@ -0,0 +1,133 @@
+//! Break reminders: how long the player has been playing without a proper
+//! break, kept between quick restarts so quitting and relaunching doesn't
+//! reset it.
+
+use log::error;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Where the session is kept between restarts.
+pub const SESSION_FILE: &str = ".snekrs_session.txt";
+/// Time away from the game that counts as a break, starting a fresh session
+pub const BREAK: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Session {
+    /// Play time since the last break
+    pub played: Duration,
+    /// Play time when the player was last reminded to take a break
+    reminded: Duration,
+    /// When the last game ended, in seconds since the Unix epoch
+    last_played: u64,
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+impl Session {
+    fn parse(text: &str) -> Option<Session> {
+        let mut numbers = text.split_whitespace().map(|word| word.parse().ok());
+        let mut next = || numbers.next().flatten();
+        Some(Session {
+            played: Duration::from_secs(next()?),
+            reminded: Duration::from_secs(next()?),
+            last_played: next()?,
+        })
+    }
+
+    /// Load the session from `path` as of `now`, starting a fresh one if
+    /// there isn't one or it ended longer ago than a break.
+    pub fn load(path: &Path, now: u64) -> Session {
+        let session = match fs::read_to_string(path) {
+            Ok(text) => Session::parse(&text).unwrap_or_else(|| {
+                error!("Error in {}: expected three numbers", path.display());
+                Session::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Session::default(),
+            Err(e) => {
+                error!("Error loading session: {}", e);
+                Session::default()
+            }
+        };
+        session.resume(now)
+    }
+
+    pub fn save(&self, path: &Path) {
+        let text = format!(
+            "{} {} {}",
+            self.played.as_secs(),
+            self.reminded.as_secs(),
+            self.last_played
+        );
+        if let Err(e) = fs::write(path, text) {
+            error!("Error saving session: {}", e);
+        }
+    }
+
+    /// The session as of `now`, or a fresh one if there's been a break.
+    fn resume(self, now: u64) -> Session {
+        if now.saturating_sub(self.last_played) >= BREAK.as_secs() {
+            Session {
+                last_played: now,
+                ..Session::default()
+            }
+        } else {
+            self
+        }
+    }
+
+    /// Count a game of `length` that ended at `now`.
+    pub fn record_game(&mut self, length: Duration, now: u64) {
+        *self = self.resume(now.saturating_sub(length.as_secs()));
+        self.played += length;
+        self.last_played = now;
+    }
+
+    /// Whether it's time to suggest a break, `every` so much play time.
+    pub fn break_due(&self, every: Duration) -> bool {
+        self.played.saturating_sub(self.reminded) >= every
+    }
+
+    /// Put off the next reminder until another `every` has been played.
+    pub fn dismiss(&mut self) {
+        self.reminded = self.played;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_break_reminders() {
+        let every = Duration::from_secs(30 * 60);
+        let mut session = Session::default().resume(1000);
+        session.record_game(Duration::from_secs(20 * 60), 2200);
+        assert!(!session.break_due(every), "A break shouldn't be suggested too soon");
+        session.record_game(Duration::from_secs(15 * 60), 3200);
+        assert!(session.break_due(every), "A break should be suggested after long enough");
+        session.dismiss();
+        assert!(!session.break_due(every), "Dismissing should put off the reminder");
+
+        session.record_game(Duration::from_secs(60), 3200 + BREAK.as_secs() + 60);
+        assert_eq!(session.played, Duration::from_secs(60), "Time away should start a fresh session");
+    }
+
+    #[test]
+    fn test_session_persists() {
+        let path = std::env::temp_dir().join(format!("snekrs_session_{}.txt", std::process::id()));
+        let mut session = Session::default().resume(5000);
+        session.record_game(Duration::from_secs(300), 5300);
+        session.save(&path);
+        assert_eq!(Session::load(&path, 5400), session, "A quick restart should carry on the session");
+        assert_eq!(Session::load(&path, 5300 + BREAK.as_secs()).played, Duration::ZERO, "A restart after a break should start afresh");
+        fs::remove_file(&path).ok();
+        assert_eq!(Session::load(&path, 100).played, Duration::ZERO, "A missing session should start afresh");
+    }
+}
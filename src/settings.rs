@@ -0,0 +1,1116 @@
+//! Player settings, kept in a plain text file of `setting: value` lines:
+//!
+//! ```text
+//! ; Lines starting with ';' are comments
+//! margin: 1
+//! ```
+//!
+//! - `margin` leaves that many blank cells around the edge of the terminal, for
+//!   multiplexers and fonts that clip the outermost cells
+//! - `multiplexer` is `auto`, `on` or `off`. When on, the game sticks to what
+//!   tmux and screen pass through reliably: 256 colours, and no mouse capture
+//!   or terminal queries. `auto` switches it on when running inside either
+//! - `controls` is `all`, `arrows`, `wasd` or `hjkl`, the keys that steer the
+//!   snek. `all` takes any of them, and `both` is still read as `all`
+//! - `skin` is the name of the skin to start with
+//! - `head` is `skin`, `arrows`, `diamond`, `bullseye` or `at`, what to draw
+//!   the head with. `skin` takes the skin's own head if it has one, which
+//!   may be an emoji two cells wide, and the rest are one cell wide
+//! - `theme` is `classic`, `neon`, `pastel` or `monochrome`, the colours of
+//!   the snek, morsels, borders and text
+//! - `backdrop` is `auto`, `dark` or `light`, the terminal's own background.
+//!   On a light one the theme's colours are darkened to stay readable.
+//!   `auto` goes by `COLORFGBG`, and takes it as dark when that isn't set
+//! - `tail_fade` is `on` or `off`, darkening the body from the head to the
+//!   tail so the tail end stands out
+//! - `speed` is `relaxed`, `normal` or `fast`, scaling the tick in every mode
+//!   but the daily challenge
+//! - `symbols` is `auto`, `unicode` or `ascii`, for fonts missing the arena's
+//!   symbols. `auto` picks Unicode unless the locale is set to something
+//!   other than UTF-8
+//! - `smooth_motion` is `on` or `off`, sliding the head between cells by
+//!   redrawing between ticks. It needs Unicode symbols
+//! - `background` is `none`, `checkerboard` or `dots`, a faint pattern behind
+//!   the arena to help judge distances, shaded by the skin
+//! - `crt` is `on` or `off`, a retro look with dimmed scanlines and a green
+//!   tint over the game
+//! - `danger_warning` is `on` or `off`, tinting the snek's head when it's
+//!   about to run into something
+//! - `danger_cells` is `on` or `off`, faintly tinting the cells beside the
+//!   head that it would crash into if it turned there next
+//! - `start_length` is how many segments the snek starts with, from 1 to 20
+//! - `growth` is how many segments the snek grows for each point a morsel is
+//!   worth, from 1 to 4
+//! - `morsel_weights` is `mode`, or how likely a morsel is to be worth 1, 2, 3
+//!   and so on, up to five weights separated by commas. `4,2,1` makes most
+//!   morsels small and none worth more than 3. `mode` leaves it to each mode
+//! - `break_reminder` is `off`, or how many minutes of play without a break,
+//!   from 5 to 240, before suggesting one between games
+//! - `stats` is `on` or `off`, a sidebar beside the arena with the snek's
+//!   length, speed, time, morsels eaten and growth still to come
+//! - `arena` is `ask`, `classic`, `marathon`, `dual` or `stretch`, how to use
+//!   a very wide terminal. `ask` asks before the first game
+//! - `arena_size` is `fill`, or a fixed size like `40x20` centred in the
+//!   terminal, from 10x5 to 400x200, so scores compare between machines.
+//!   Levels and the daily challenge keep their own sizes
+//! - `edges` is `mode`, `wrap`, `wrap_across`, `wrap_down`, `walled` or
+//!   `solid`, what happens at the arena's edges. `mode` leaves it to each mode
+//! - `kiosk_unlock` is the key combination that quits kiosk mode, like
+//!   `ctrl+alt+q`
+//!
+//! The daily challenge ignores the speed, starting length, growth and morsel
+//! weights, so it's the same for everyone.
+//!
+//! The first time the game runs without a settings file, the welcome wizard
+//! asks about the ones in `Field` and writes the file.
+
+use crate::glyphs::{self, Glyphs};
+use crate::kiosk::Unlock;
+use crate::split::ArenaPreset;
+use crate::theme::{self, Theme};
+use crate::{Direction, EdgePolicy, MorselWeights, Size};
+use crossterm::event::KeyCode;
+use log::error;
+use std::env;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+pub const SETTINGS_FILE: &str = ".snekrs_settings.txt";
+pub const START_LENGTHS: RangeInclusive<u16> = 1..=20;
+pub const GROWTHS: RangeInclusive<u16> = 1..=4;
+pub const BREAK_REMINDERS: RangeInclusive<u16> = 5..=240;
+pub const ARENA_WIDTHS: RangeInclusive<u16> = 10..=400;
+pub const ARENA_HEIGHTS: RangeInclusive<u16> = 5..=200;
+/// Break reminders offered in the menu, in minutes
+const BREAK_REMINDER_CHOICES: [Option<u16>; 5] = [None, Some(20), Some(30), Some(45), Some(60)];
+/// Arena presets offered in the menu, `None` asking on wide terminals
+const ARENA_CHOICES: [Option<ArenaPreset>; 5] = [
+    None,
+    Some(ArenaPreset::Classic),
+    Some(ArenaPreset::Marathon),
+    Some(ArenaPreset::Dual),
+    Some(ArenaPreset::Stretch),
+];
+/// Fixed arena sizes offered in the menu, `None` filling the terminal
+const ARENA_SIZE_CHOICES: [Option<Size>; 5] = [
+    None,
+    Some(Size {
+        width: 30,
+        height: 15,
+    }),
+    Some(Size {
+        width: 40,
+        height: 20,
+    }),
+    Some(Size {
+        width: 60,
+        height: 20,
+    }),
+    Some(Size {
+        width: 80,
+        height: 24,
+    }),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    /// Cells left blank on each side of the terminal
+    pub margin: u16,
+    /// Whether to play safe for terminal multiplexers
+    pub multiplexer: Toggle,
+    pub controls: Controls,
+    /// Name of the player's skin, or `None` for the first one found
+    pub skin: Option<String>,
+    pub head: Head,
+    pub theme: Theme,
+    pub backdrop: Backdrop,
+    /// Darken the body towards the tail
+    pub tail_fade: bool,
+    pub speed: Speed,
+    pub symbols: Symbols,
+    /// Slide the head between cells, drawing between ticks
+    pub smooth_motion: bool,
+    pub background: Background,
+    /// Draw the game like an old CRT, with scanlines and a tint
+    pub crt: bool,
+    pub danger_warning: bool,
+    pub danger_cells: bool,
+    /// Segments the snek starts with
+    pub start_length: u16,
+    /// Segments grown for each point a morsel is worth
+    pub growth: u16,
+    /// Morsel weights for every mode, or `None` for each mode's own
+    pub morsel_weights: Option<MorselWeights>,
+    /// Minutes of play before suggesting a break, if at all
+    pub break_reminder: Option<u16>,
+    /// Show the stats sidebar during play
+    pub stats: bool,
+    /// How to use a very wide terminal, or `None` to ask
+    pub arena: Option<ArenaPreset>,
+    /// A fixed size for the open arena, or `None` to fill the terminal
+    pub arena_size: Option<Size>,
+    /// Edges for every mode, or `None` for each mode's own
+    pub edges: Option<EdgePolicy>,
+    pub kiosk_unlock: Unlock,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            margin: 0,
+            multiplexer: Toggle::default(),
+            controls: Controls::default(),
+            skin: None,
+            head: Head::default(),
+            theme: Theme::default(),
+            backdrop: Backdrop::default(),
+            tail_fade: true,
+            speed: Speed::default(),
+            symbols: Symbols::default(),
+            smooth_motion: false,
+            background: Background::default(),
+            crt: false,
+            danger_warning: false,
+            danger_cells: false,
+            start_length: 3,
+            growth: 1,
+            morsel_weights: None,
+            break_reminder: None,
+            stats: false,
+            arena: None,
+            arena_size: None,
+            edges: None,
+            kiosk_unlock: Unlock::default(),
+        }
+    }
+}
+
+/// `value` parsed as a number in `range`.
+fn parse_number(value: &str, name: &str, range: RangeInclusive<u16>) -> Result<u16, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|n| range.contains(n))
+        .ok_or_else(|| {
+            format!(
+                "{} should be from {} to {}, not '{}'",
+                name,
+                range.start(),
+                range.end(),
+                value
+            )
+        })
+}
+
+/// `value` parsed as an arena size, like `40x20`.
+fn parse_arena_size(value: &str) -> Result<Size, String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| format!("expected 'fill' or a size like 40x20, not '{}'", value))?;
+    Ok(Size {
+        width: parse_number(width, "arena_size width", ARENA_WIDTHS)?,
+        height: parse_number(height, "arena_size height", ARENA_HEIGHTS)?,
+    })
+}
+
+/// Morsel weights like `4,2,1`, or `mode` for each mode's own.
+pub fn parse_morsel_weights(value: &str) -> Result<Option<MorselWeights>, String> {
+    match value {
+        "mode" => Ok(None),
+        _ => MorselWeights::parse(value)
+            .map(Some)
+            .map_err(|e| format!("expected 'mode' or weights like 4,2,1: {}", e)),
+    }
+}
+
+/// Morsel weights as they're written in a settings file or run code.
+pub fn morsel_weights_slug(weights: Option<MorselWeights>) -> String {
+    weights.map_or_else(|| "mode".to_string(), |weights| weights.to_string())
+}
+
+/// `current` moved `step` through `range`, wrapping round.
+fn cycle_number(current: u16, step: isize, range: RangeInclusive<u16>) -> u16 {
+    let count = (range.end() - range.start() + 1) as isize;
+    let index = (current.saturating_sub(*range.start()) as isize + step).rem_euclid(count);
+    range.start() + index as u16
+}
+
+/// A setting with a fixed set of values.
+pub trait Choice: Copy + PartialEq + 'static {
+    const ALL: &'static [Self];
+
+    /// How the value is written in the settings file
+    fn slug(&self) -> &'static str;
+
+    /// How the value is shown in menus
+    fn name(&self) -> &'static str;
+
+    fn parse(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|c| c.slug() == s)
+            .ok_or_else(|| {
+                let slugs: Vec<_> = Self::ALL.iter().map(|c| c.slug()).collect();
+                format!("expected one of {}, not '{}'", slugs.join(", "), s)
+            })
+    }
+
+    fn cycle(&self, step: isize) -> Self {
+        crate::cycle(Self::ALL, *self, step)
+    }
+}
+
+/// A setting that can be forced on or off, or left to work itself out.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Toggle {
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+impl Choice for Toggle {
+    const ALL: &'static [Self] = &[Toggle::Auto, Toggle::On, Toggle::Off];
+
+    fn slug(&self) -> &'static str {
+        match self {
+            Toggle::Auto => "auto",
+            Toggle::On => "on",
+            Toggle::Off => "off",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Toggle::Auto => "Auto",
+            Toggle::On => "On",
+            Toggle::Off => "Off",
+        }
+    }
+}
+
+impl Choice for bool {
+    const ALL: &'static [Self] = &[false, true];
+
+    fn slug(&self) -> &'static str {
+        if *self {
+            "on"
+        } else {
+            "off"
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        if *self {
+            "On"
+        } else {
+            "Off"
+        }
+    }
+}
+
+/// Which keys steer the snek. Menus take arrows and WASD either way.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Controls {
+    #[default]
+    All,
+    Arrows,
+    Wasd,
+    /// The vi keys: h left, j down, k up and l right
+    Vi,
+}
+
+impl Choice for Controls {
+    const ALL: &'static [Self] = &[
+        Controls::All,
+        Controls::Arrows,
+        Controls::Wasd,
+        Controls::Vi,
+    ];
+
+    fn slug(&self) -> &'static str {
+        match self {
+            Controls::All => "all",
+            Controls::Arrows => "arrows",
+            Controls::Wasd => "wasd",
+            Controls::Vi => "hjkl",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Controls::All => "Arrows, WASD and hjkl",
+            Controls::Arrows => "Arrow keys",
+            Controls::Wasd => "WASD",
+            Controls::Vi => "hjkl (vi keys)",
+        }
+    }
+}
+
+impl Controls {
+    /// The way `code` steers the snek, if it's one of these controls. The
+    /// numpad's corner keys go diagonally, for games that allow it.
+    pub fn direction(&self, code: KeyCode) -> Option<Direction> {
+        let numpad = match code {
+            KeyCode::Char('7') | KeyCode::Home => Some(Direction::NorthWest),
+            KeyCode::Char('9') | KeyCode::PageUp => Some(Direction::NorthEast),
+            KeyCode::Char('1') | KeyCode::End => Some(Direction::SouthWest),
+            KeyCode::Char('3') | KeyCode::PageDown => Some(Direction::SouthEast),
+            _ => None,
+        };
+        if numpad.is_some() {
+            return numpad;
+        }
+        let arrows = match code {
+            KeyCode::Up => Some(Direction::North),
+            KeyCode::Down => Some(Direction::South),
+            KeyCode::Left => Some(Direction::West),
+            KeyCode::Right => Some(Direction::East),
+            _ => None,
+        };
+        let wasd = match code {
+            KeyCode::Char('w') => Some(Direction::North),
+            KeyCode::Char('s') => Some(Direction::South),
+            KeyCode::Char('a') => Some(Direction::West),
+            KeyCode::Char('d') => Some(Direction::East),
+            _ => None,
+        };
+        let vi = match code {
+            KeyCode::Char('k') => Some(Direction::North),
+            KeyCode::Char('j') => Some(Direction::South),
+            KeyCode::Char('h') => Some(Direction::West),
+            KeyCode::Char('l') => Some(Direction::East),
+            _ => None,
+        };
+        match self {
+            Controls::All => arrows.or(wasd).or(vi),
+            Controls::Arrows => arrows,
+            Controls::Wasd => wasd,
+            Controls::Vi => vi,
+        }
+    }
+}
+
+/// How fast every mode plays, relative to its own speed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Speed {
+    Relaxed,
+    #[default]
+    Normal,
+    Fast,
+}
+
+impl Speed {
+    /// `millis` stretched or shrunk to this speed.
+    pub fn scale(&self, millis: u64) -> u64 {
+        match self {
+            Speed::Relaxed => millis * 4 / 3,
+            Speed::Normal => millis,
+            Speed::Fast => millis * 2 / 3,
+        }
+    }
+}
+
+impl Choice for Speed {
+    const ALL: &'static [Self] = &[Speed::Relaxed, Speed::Normal, Speed::Fast];
+
+    fn slug(&self) -> &'static str {
+        match self {
+            Speed::Relaxed => "relaxed",
+            Speed::Normal => "normal",
+            Speed::Fast => "fast",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Speed::Relaxed => "Relaxed",
+            Speed::Normal => "Normal",
+            Speed::Fast => "Fast",
+        }
+    }
+}
+
+/// Which symbols the arena is drawn with.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Symbols {
+    /// ASCII if the locale is set to something other than UTF-8, otherwise
+    /// Unicode
+    #[default]
+    Auto,
+    Unicode,
+    /// Plain ASCII, for fonts without the card suits and emoji
+    Ascii,
+}
+
+impl Symbols {
+    /// The table of symbols to draw the arena with.
+    pub fn glyphs(self) -> Glyphs {
+        match self {
+            Symbols::Auto if glyphs::locale_is_utf8() => glyphs::UNICODE,
+            Symbols::Unicode => glyphs::UNICODE,
+            Symbols::Auto | Symbols::Ascii => glyphs::ASCII,
+        }
+    }
+}
+
+impl Choice for Symbols {
+    const ALL: &'static [Self] = &[Symbols::Auto, Symbols::Unicode, Symbols::Ascii];
+
+    fn slug(&self) -> &'static str {
+        match self {
+            Symbols::Auto => "auto",
+            Symbols::Unicode => "unicode",
+            Symbols::Ascii => "ascii",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Symbols::Auto => "From the locale",
+            Symbols::Unicode => "Unicode",
+            Symbols::Ascii => "ASCII only",
+        }
+    }
+}
+
+/// What to draw the snek's head with.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Head {
+    /// The skin's own head, or arrows if it hasn't one
+    #[default]
+    Skin,
+    Arrows,
+    Diamond,
+    Bullseye,
+    At,
+}
+
+impl Head {
+    /// The symbol to draw every head with, if not the skin's or the arrows.
+    pub fn symbol(self) -> Option<&'static str> {
+        match self {
+            Head::Skin | Head::Arrows => None,
+            Head::Diamond => Some("◆"),
+            Head::Bullseye => Some("◉"),
+            Head::At => Some("@"),
+        }
+    }
+}
+
+impl Choice for Head {
+    const ALL: &'static [Self] = &[
+        Head::Skin,
+        Head::Arrows,
+        Head::Diamond,
+        Head::Bullseye,
+        Head::At,
+    ];
+
+    fn slug(&self) -> &'static str {
+        match self {
+            Head::Skin => "skin",
+            Head::Arrows => "arrows",
+            Head::Diamond => "diamond",
+            Head::Bullseye => "bullseye",
+            Head::At => "at",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Head::Skin => "Skin's own",
+            Head::Arrows => "Arrows",
+            Head::Diamond => "Diamond ◆",
+            Head::Bullseye => "Bullseye ◉",
+            Head::At => "At sign @",
+        }
+    }
+}
+
+/// The colour of the terminal's own background.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Backdrop {
+    /// Whatever the terminal says, or dark if it doesn't
+    #[default]
+    Auto,
+    Dark,
+    Light,
+}
+
+impl Backdrop {
+    pub fn is_light(self) -> bool {
+        match self {
+            Backdrop::Auto => theme::terminal_is_light(),
+            Backdrop::Dark => false,
+            Backdrop::Light => true,
+        }
+    }
+
+    /// `theme` made readable on this background.
+    pub fn adapt(self, theme: Theme) -> Theme {
+        if self.is_light() {
+            theme.on_light()
+        } else {
+            theme
+        }
+    }
+}
+
+impl Choice for Backdrop {
+    const ALL: &'static [Self] = &[Backdrop::Auto, Backdrop::Dark, Backdrop::Light];
+
+    fn slug(&self) -> &'static str {
+        match self {
+            Backdrop::Auto => "auto",
+            Backdrop::Dark => "dark",
+            Backdrop::Light => "light",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Backdrop::Auto => "From the terminal",
+            Backdrop::Dark => "Dark",
+            Backdrop::Light => "Light",
+        }
+    }
+}
+
+/// A pattern drawn behind everything in the arena.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Background {
+    #[default]
+    None,
+    /// Every other cell shaded
+    Checkerboard,
+    /// A dot at every grid point
+    Dots,
+}
+
+impl Choice for Background {
+    const ALL: &'static [Self] = &[Background::None, Background::Checkerboard, Background::Dots];
+
+    fn slug(&self) -> &'static str {
+        match self {
+            Background::None => "none",
+            Background::Checkerboard => "checkerboard",
+            Background::Dots => "dots",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Background::None => "None",
+            Background::Checkerboard => "Checkerboard",
+            Background::Dots => "Grid dots",
+        }
+    }
+}
+
+impl Choice for Theme {
+    const ALL: &'static [Self] = &theme::THEMES;
+
+    fn slug(&self) -> &'static str {
+        self.slug
+    }
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl Choice for ArenaPreset {
+    const ALL: &'static [Self] = &ArenaPreset::ALL;
+
+    fn slug(&self) -> &'static str {
+        match self {
+            ArenaPreset::Classic => "classic",
+            ArenaPreset::Marathon => "marathon",
+            ArenaPreset::Dual => "dual",
+            ArenaPreset::Stretch => "stretch",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        ArenaPreset::name(self)
+    }
+}
+
+impl Choice for EdgePolicy {
+    const ALL: &'static [Self] = &[
+        EdgePolicy::Wrap,
+        EdgePolicy::WrapAcross,
+        EdgePolicy::WrapDown,
+        EdgePolicy::Walled,
+        EdgePolicy::Solid,
+    ];
+
+    fn slug(&self) -> &'static str {
+        match self {
+            EdgePolicy::Wrap => "wrap",
+            EdgePolicy::WrapAcross => "wrap_across",
+            EdgePolicy::WrapDown => "wrap_down",
+            EdgePolicy::Walled => "walled",
+            EdgePolicy::Solid => "solid",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        EdgePolicy::name(self)
+    }
+}
+
+/// The settings that can be picked from a menu, rather than only in the file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Field {
+    Controls,
+    Skin,
+    Head,
+    Theme,
+    Backdrop,
+    TailFade,
+    Speed,
+    Symbols,
+    SmoothMotion,
+    Background,
+    Crt,
+    DangerWarning,
+    DangerCells,
+    StartLength,
+    Growth,
+    BreakReminder,
+    Stats,
+    Arena,
+    ArenaSize,
+    Edges,
+}
+
+impl Field {
+    pub const ALL: [Field; 20] = [
+        Field::Controls,
+        Field::Skin,
+        Field::Head,
+        Field::Theme,
+        Field::Backdrop,
+        Field::TailFade,
+        Field::Speed,
+        Field::Symbols,
+        Field::SmoothMotion,
+        Field::Background,
+        Field::Crt,
+        Field::DangerWarning,
+        Field::DangerCells,
+        Field::StartLength,
+        Field::Growth,
+        Field::BreakReminder,
+        Field::Stats,
+        Field::Arena,
+        Field::ArenaSize,
+        Field::Edges,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Field::Controls => "Controls",
+            Field::Skin => "Skin",
+            Field::Head => "Head symbol",
+            Field::Theme => "Colour theme",
+            Field::Backdrop => "Terminal background",
+            Field::TailFade => "Fade to the tail",
+            Field::Speed => "Speed",
+            Field::Symbols => "Symbols",
+            Field::SmoothMotion => "Smooth motion",
+            Field::Background => "Background",
+            Field::Crt => "Retro CRT",
+            Field::DangerWarning => "Danger warning",
+            Field::DangerCells => "Danger cells",
+            Field::StartLength => "Starting length",
+            Field::Growth => "Growth per point",
+            Field::BreakReminder => "Break reminder",
+            Field::Stats => "Stats sidebar",
+            Field::Arena => "Wide arenas",
+            Field::ArenaSize => "Arena size",
+            Field::Edges => "Edges",
+        }
+    }
+}
+
+/// Whether the game is running inside tmux or screen.
+pub fn in_multiplexer() -> bool {
+    env::var_os("TMUX").is_some() || env::var_os("STY").is_some()
+}
+
+impl Settings {
+    pub fn parse(text: &str) -> Result<Settings, String> {
+        let mut settings = Settings::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with(';') || line.is_empty() {
+                continue;
+            }
+            let at_line = |e: String| format!("line {}: {}", index + 1, e);
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| at_line("expected 'setting: value'".to_string()))?;
+            let value = value.trim();
+            match key.trim() {
+                "margin" => {
+                    settings.margin = value
+                        .parse()
+                        .map_err(|_| at_line(format!("invalid margin '{}'", value)))?
+                }
+                "multiplexer" => settings.multiplexer = Toggle::parse(value).map_err(at_line)?,
+                // From before the vi keys, when there were only two sets
+                "controls" if value == "both" => settings.controls = Controls::All,
+                "controls" => settings.controls = Controls::parse(value).map_err(at_line)?,
+                "skin" => settings.skin = Some(value.to_string()),
+                "head" => settings.head = Head::parse(value).map_err(at_line)?,
+                "theme" => settings.theme = Theme::parse(value).map_err(at_line)?,
+                "backdrop" => settings.backdrop = Backdrop::parse(value).map_err(at_line)?,
+                "speed" => settings.speed = Speed::parse(value).map_err(at_line)?,
+                "symbols" => settings.symbols = Symbols::parse(value).map_err(at_line)?,
+                "background" => settings.background = Background::parse(value).map_err(at_line)?,
+                "crt" => settings.crt = bool::parse(value).map_err(at_line)?,
+                "smooth_motion" => settings.smooth_motion = bool::parse(value).map_err(at_line)?,
+                "tail_fade" => settings.tail_fade = bool::parse(value).map_err(at_line)?,
+                "danger_warning" => {
+                    settings.danger_warning = bool::parse(value).map_err(at_line)?
+                }
+                "danger_cells" => settings.danger_cells = bool::parse(value).map_err(at_line)?,
+                "start_length" => {
+                    settings.start_length =
+                        parse_number(value, "start_length", START_LENGTHS).map_err(at_line)?
+                }
+                "growth" => {
+                    settings.growth = parse_number(value, "growth", GROWTHS).map_err(at_line)?
+                }
+                "morsel_weights" => {
+                    settings.morsel_weights = parse_morsel_weights(value).map_err(at_line)?
+                }
+                "break_reminder" if value == "off" => settings.break_reminder = None,
+                "break_reminder" => {
+                    let minutes =
+                        parse_number(value, "break_reminder", BREAK_REMINDERS).map_err(at_line)?;
+                    settings.break_reminder = Some(minutes)
+                }
+                "stats" => settings.stats = bool::parse(value).map_err(at_line)?,
+                "arena" if value == "ask" => settings.arena = None,
+                "arena" => settings.arena = Some(ArenaPreset::parse(value).map_err(at_line)?),
+                "arena_size" if value == "fill" => settings.arena_size = None,
+                "arena_size" => {
+                    settings.arena_size = Some(parse_arena_size(value).map_err(at_line)?)
+                }
+                "edges" if value == "mode" => settings.edges = None,
+                "edges" => settings.edges = Some(EdgePolicy::parse(value).map_err(at_line)?),
+                "kiosk_unlock" => settings.kiosk_unlock = Unlock::parse(value).map_err(at_line)?,
+                other => return Err(at_line(format!("unknown setting '{}'", other))),
+            }
+        }
+        Ok(settings)
+    }
+
+    /// The settings as the text of a settings file, which parses back to them.
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "margin: {}\nmultiplexer: {}\ncontrols: {}\n",
+            self.margin,
+            self.multiplexer.slug(),
+            self.controls.slug()
+        );
+        if let Some(skin) = &self.skin {
+            text.push_str(&format!("skin: {}\n", skin));
+        }
+        text.push_str(&format!(
+            "head: {}\ntheme: {}\nbackdrop: {}\ntail_fade: {}\nspeed: {}\nsymbols: {}\nsmooth_motion: {}\nbackground: {}\ncrt: {}\ndanger_warning: {}\ndanger_cells: {}\nstart_length: {}\ngrowth: {}\n",
+            self.head.slug(),
+            self.theme.slug(),
+            self.backdrop.slug(),
+            self.tail_fade.slug(),
+            self.speed.slug(),
+            self.symbols.slug(),
+            self.smooth_motion.slug(),
+            self.background.slug(),
+            self.crt.slug(),
+            self.danger_warning.slug(),
+            self.danger_cells.slug(),
+            self.start_length,
+            self.growth
+        ));
+        text.push_str(&format!(
+            "morsel_weights: {}\n",
+            morsel_weights_slug(self.morsel_weights)
+        ));
+        match self.break_reminder {
+            Some(minutes) => text.push_str(&format!("break_reminder: {}\n", minutes)),
+            None => text.push_str("break_reminder: off\n"),
+        }
+        text.push_str(&format!(
+            "stats: {}\narena: {}\n",
+            self.stats.slug(),
+            self.arena.map_or("ask", |arena| arena.slug())
+        ));
+        match self.arena_size {
+            Some(size) => text.push_str(&format!("arena_size: {}x{}\n", size.width, size.height)),
+            None => text.push_str("arena_size: fill\n"),
+        }
+        text.push_str(&format!(
+            "edges: {}\n",
+            self.edges.map_or("mode", |edges| edges.slug())
+        ));
+        if self.kiosk_unlock != Unlock::default() {
+            text.push_str(&format!("kiosk_unlock: {}\n", self.kiosk_unlock));
+        }
+        text
+    }
+
+    /// The menu's name for the value of `field`, other than the skin, which
+    /// only the game knows the names of.
+    pub fn describe(&self, field: Field) -> String {
+        match field {
+            Field::Controls => self.controls.name().to_string(),
+            Field::Skin => String::new(),
+            Field::Head => self.head.name().to_string(),
+            Field::Theme => self.theme.name().to_string(),
+            Field::Backdrop => self.backdrop.name().to_string(),
+            Field::TailFade => self.tail_fade.name().to_string(),
+            Field::Speed => self.speed.name().to_string(),
+            Field::Symbols => self.symbols.name().to_string(),
+            Field::SmoothMotion => self.smooth_motion.name().to_string(),
+            Field::Background => self.background.name().to_string(),
+            Field::Crt => self.crt.name().to_string(),
+            Field::DangerWarning => self.danger_warning.name().to_string(),
+            Field::DangerCells => self.danger_cells.name().to_string(),
+            Field::StartLength => self.start_length.to_string(),
+            Field::Growth if self.growth == 1 => "1 segment".to_string(),
+            Field::Growth => format!("{} segments", self.growth),
+            Field::BreakReminder => match self.break_reminder {
+                Some(minutes) => format!("Every {} minutes", minutes),
+                None => "Off".to_string(),
+            },
+            Field::Stats => self.stats.name().to_string(),
+            Field::Arena => match self.arena {
+                Some(arena) => arena.name().to_string(),
+                None => "Ask".to_string(),
+            },
+            Field::ArenaSize => match self.arena_size {
+                Some(size) => format!("{}x{}", size.width, size.height),
+                None => "Fill the terminal".to_string(),
+            },
+            Field::Edges => match self.edges {
+                Some(edges) => edges.name().to_string(),
+                None => "Mode's own".to_string(),
+            },
+        }
+    }
+
+    /// Step `field` through its values, apart from the skin.
+    pub fn cycle(&mut self, field: Field, step: isize) {
+        match field {
+            Field::Controls => self.controls = self.controls.cycle(step),
+            Field::Skin => {}
+            Field::Head => self.head = self.head.cycle(step),
+            Field::Theme => self.theme = self.theme.cycle(step),
+            Field::Backdrop => self.backdrop = self.backdrop.cycle(step),
+            Field::TailFade => self.tail_fade = self.tail_fade.cycle(step),
+            Field::Speed => self.speed = self.speed.cycle(step),
+            Field::Symbols => self.symbols = self.symbols.cycle(step),
+            Field::SmoothMotion => self.smooth_motion = self.smooth_motion.cycle(step),
+            Field::Background => self.background = self.background.cycle(step),
+            Field::Crt => self.crt = self.crt.cycle(step),
+            Field::DangerWarning => self.danger_warning = self.danger_warning.cycle(step),
+            Field::DangerCells => self.danger_cells = self.danger_cells.cycle(step),
+            Field::StartLength => {
+                self.start_length = cycle_number(self.start_length, step, START_LENGTHS)
+            }
+            Field::Growth => self.growth = cycle_number(self.growth, step, GROWTHS),
+            Field::BreakReminder => {
+                self.break_reminder =
+                    crate::cycle(&BREAK_REMINDER_CHOICES, self.break_reminder, step)
+            }
+            Field::Stats => self.stats = self.stats.cycle(step),
+            Field::Arena => self.arena = crate::cycle(&ARENA_CHOICES, self.arena, step),
+            Field::ArenaSize => {
+                self.arena_size = crate::cycle(&ARENA_SIZE_CHOICES, self.arena_size, step)
+            }
+            Field::Edges => self.edges = crate::cycle(&EdgePolicy::CHOICES, self.edges, step),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /// Whether to hold back on colours and terminal features for tmux and screen.
+    pub fn multiplexer_compat(&self) -> bool {
+        match self.multiplexer {
+            Toggle::Auto => in_multiplexer(),
+            Toggle::On => true,
+            Toggle::Off => false,
+        }
+    }
+
+    /// Load settings from `path`. A missing file means the defaults, and a
+    /// broken one is logged and ignored rather than stopping the game.
+    pub fn load(path: &Path) -> Settings {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Settings::default(),
+            Err(e) => {
+                error!("Error reading settings: {}", e);
+                return Settings::default();
+            }
+        };
+        Settings::parse(&text).unwrap_or_else(|e| {
+            error!("Error in {}: {}", path.display(), e);
+            Settings::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_settings() {
+        let settings = Settings::parse("; clipped by tmux\nmargin: 2\n").expect("Settings should parse");
+        assert_eq!(settings.margin, 2, "Margin should come from the file");
+        assert_eq!(settings.multiplexer, Toggle::Auto, "Multiplexer compatibility should default to auto");
+
+        let forced = Settings::parse("multiplexer: on").expect("Settings should parse");
+        assert!(forced.multiplexer_compat(), "Forcing compatibility on should switch it on");
+        assert!(Settings::parse("multiplexer: maybe").is_err(), "Unknown toggles should be rejected");
+        assert_eq!(Settings::parse("").expect("Empty settings should parse"), Settings::default(), "Missing settings should be defaults");
+
+        assert!(Settings::parse("margin: wide").is_err(), "Non-numeric margins should be rejected");
+        assert!(Settings::parse("volume: 11").is_err(), "Unknown settings should be rejected");
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let settings = Settings { margin: 1, controls: Controls::Wasd, skin: Some("Tiger Stripes".to_string()), theme: theme::PASTEL, backdrop: Backdrop::Light, head: Head::Bullseye, speed: Speed::Fast, symbols: Symbols::Ascii, background: Background::Dots, danger_warning: true, danger_cells: true, start_length: 8, growth: 2, stats: true, tail_fade: false, smooth_motion: true, crt: true, ..Settings::default() };
+        assert_eq!(Settings::parse(&settings.to_text()), Ok(settings), "Saved settings should load back the same");
+        assert_eq!(Settings::parse(&Settings::default().to_text()), Ok(Settings::default()), "Default settings should load back the same");
+    }
+
+    #[test]
+    fn test_arena_size() {
+        let settings = Settings::parse("arena_size: 50x25").expect("Settings should parse");
+        assert_eq!(settings.arena_size, Some(Size { width: 50, height: 25 }), "Arenas should be any size in range");
+        assert_eq!(Settings::parse(&settings.to_text()), Ok(settings), "A fixed arena should load back the same");
+        assert!(Settings::parse("arena_size: 5x5").is_err() && Settings::parse("arena_size: big").is_err(), "Arenas too small or without a size shouldn't load");
+        let mut settings = Settings::default();
+        settings.cycle(Field::ArenaSize, 2);
+        assert_eq!(settings.describe(Field::ArenaSize), "40x20", "The menu should offer the classic arena");
+        settings.cycle(Field::ArenaSize, -2);
+        assert_eq!(settings.describe(Field::ArenaSize), "Fill the terminal", "The menu should go back to filling the terminal");
+    }
+
+    #[test]
+    fn test_cycle_fields() {
+        let mut settings = Settings::default();
+        settings.cycle(Field::Speed, 1);
+        assert_eq!(settings.speed, Speed::Fast, "Speed should step up from normal");
+        settings.cycle(Field::Symbols, -1);
+        assert_eq!(settings.describe(Field::Symbols), "ASCII only", "Symbols should wrap round");
+        settings.cycle(Field::Theme, -1);
+        assert_eq!(settings.theme, theme::MONOCHROME, "Themes should wrap round");
+        assert!(Speed::Relaxed.scale(150) > 150 && Speed::Fast.scale(150) < 150, "Speeds should stretch and shrink the tick");
+    }
+
+    #[test]
+    fn test_length_and_growth() {
+        let mut settings = Settings::default();
+        assert_eq!((settings.start_length, settings.growth), (3, 1), "Snek should start as it always has");
+        settings.cycle(Field::StartLength, -3);
+        assert_eq!(settings.start_length, 20, "Starting length should wrap round below 1");
+        settings.cycle(Field::Growth, 1);
+        assert_eq!(settings.describe(Field::Growth), "2 segments", "Growth should step up");
+        assert!(Settings::parse("start_length: 0").is_err(), "A snek needs at least one segment");
+        assert!(Settings::parse("growth: 5").is_err(), "Growth should be limited");
+        assert!(Settings::parse("growth: fast").is_err(), "Growth should be a number");
+    }
+
+    #[test]
+    fn test_morsel_weights() {
+        let settings = Settings::parse("morsel_weights: 4, 2,1").expect("Settings should parse");
+        assert_eq!(
+            settings.morsel_weights.map(|weights| weights.to_string()),
+            Some("4,2,1".to_string()),
+            "Weights should be read in order"
+        );
+        assert_eq!(
+            Settings::parse(&settings.to_text()),
+            Ok(settings),
+            "Weights should save and load back"
+        );
+        assert_eq!(
+            Settings::parse("morsel_weights: mode").map(|s| s.morsel_weights),
+            Ok(None),
+            "'mode' should leave the weights to each mode"
+        );
+        assert!(
+            Settings::parse("morsel_weights: 1,1,1,1,1,1").is_err(),
+            "Morsels can't be worth more than there are symbols for"
+        );
+        assert!(
+            Settings::parse("morsel_weights: 0,0").is_err()
+                && Settings::parse("morsel_weights: big").is_err(),
+            "Weights should be numbers, and not all nothing"
+        );
+    }
+
+    #[test]
+    fn test_break_reminder() {
+        let settings = Settings::parse("break_reminder: 40").expect("Settings should parse");
+        assert_eq!(settings.break_reminder, Some(40), "Reminders should be any number of minutes");
+        assert_eq!(Settings::parse(&settings.to_text()), Ok(settings), "Reminders should save and load back");
+        assert!(Settings::parse("break_reminder: 1").is_err(), "Reminders shouldn't nag too often");
+        let mut settings = Settings::default();
+        settings.cycle(Field::BreakReminder, 1);
+        assert_eq!(settings.describe(Field::BreakReminder), "Every 20 minutes", "The menu should offer reminders");
+        settings.cycle(Field::BreakReminder, -1);
+        assert_eq!(settings.break_reminder, None, "The menu should switch reminders off");
+    }
+
+    #[test]
+    fn test_arena_and_edges() {
+        let settings = Settings::parse("arena: marathon\nedges: wrap_across").expect("Settings should parse");
+        assert_eq!(settings.arena, Some(ArenaPreset::Marathon), "The arena preset should come from the file");
+        assert_eq!(settings.edges, Some(EdgePolicy::WrapAcross), "The edges should come from the file");
+        assert_eq!(Settings::parse(&settings.to_text()), Ok(settings), "Arena and edges should save and load back");
+        assert!(Settings::parse("edges: bouncy").is_err(), "Unknown edges should be rejected");
+        let mut settings = Settings::default();
+        assert_eq!(settings.describe(Field::Edges), "Mode's own", "Edges should be left to the mode by default");
+        settings.cycle(Field::Arena, -1);
+        assert_eq!(settings.arena, Some(ArenaPreset::Stretch), "Arena presets should wrap round");
+        settings.cycle(Field::Arena, 1);
+        assert_eq!(settings.describe(Field::Arena), "Ask", "The menu should go back to asking");
+    }
+
+    #[test]
+    fn test_kiosk_unlock() {
+        let settings = Settings::parse("kiosk_unlock: ctrl+k").expect("Settings should parse");
+        assert_eq!(settings.kiosk_unlock, Unlock::parse("ctrl+k").unwrap(), "The unlock combination should come from the file");
+        assert_eq!(Settings::parse(&settings.to_text()), Ok(settings), "The unlock combination should save and load back");
+        assert!(Settings::parse("kiosk_unlock: k").is_err(), "A lone key shouldn't unlock");
+    }
+
+    #[test]
+    fn test_controls() {
+        assert_eq!(Controls::All.direction(KeyCode::Char('a')), Some(Direction::West), "All the schemes should take WASD");
+        assert_eq!(Controls::All.direction(KeyCode::Up), Some(Direction::North), "All the schemes should take arrows");
+        assert_eq!(Controls::All.direction(KeyCode::Char('j')), Some(Direction::South), "All the schemes should take the vi keys");
+        assert_eq!(Controls::Vi.direction(KeyCode::Char('l')), Some(Direction::East), "The vi keys should steer");
+        assert_eq!(Controls::Vi.direction(KeyCode::Char('d')), None, "Vi controls should leave WASD alone");
+        assert_eq!(Settings::parse("controls: both").map(|s| s.controls), Ok(Controls::All), "Older settings files should still load");
+        assert_eq!(Controls::Arrows.direction(KeyCode::Char('w')), None, "Arrow controls should leave WASD alone");
+        assert_eq!(Controls::Wasd.direction(KeyCode::Down), None, "WASD controls should leave arrows alone");
+        assert_eq!(Controls::Wasd.direction(KeyCode::Char('9')), Some(Direction::NorthEast), "The numpad should go diagonally with any controls");
+        assert_eq!(Controls::Arrows.direction(KeyCode::End), Some(Direction::SouthWest), "The numpad should work without num lock");
+    }
+}
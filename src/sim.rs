@@ -0,0 +1,346 @@
+use crate::bot::{Greedy, Policy};
+use crate::scoring;
+use crate::{Direction, GameMode, Round, Rules, Size, Snek, SnekHaus, StepResult};
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::prelude::*;
+use std::collections::VecDeque;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// Arena used for headless runs, so results don't depend on the terminal.
+const SIM_ARENA: Size = Size {
+    width: 40,
+    height: 20,
+};
+/// Runs that survive this long are cut short rather than looping forever.
+const MAX_TICKS: u32 = 10_000;
+const DEFAULT_RUNS: u32 = 100;
+
+/// The outcome of a single headless run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RunStats {
+    pub score: u16,
+    pub ticks: u32,
+    /// Wall-clock time the run would have taken at the rules' speed
+    pub elapsed: Duration,
+    /// Decisions that ran over the time limit and were replaced by going straight on
+    pub violations: u32,
+    /// See `scoring::normalized`
+    pub normalized: Option<f64>,
+}
+
+/// Handicaps that keep bots on an even footing in a tournament.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Fairness {
+    /// Longest a bot may think about a move. Runs with a limit depend on how
+    /// busy the machine is, so aren't reproducible.
+    pub decision_limit: Option<Duration>,
+    /// Ticks behind the game that the bot sees, like a person's reaction time
+    pub observation_delay: u32,
+}
+
+/// Ask `policy` where `snek` should go, based on what it can see in `seen`.
+/// Returns the direction and whether the bot went over the time limit, in
+/// which case the snek carries straight on.
+fn fair_decision(
+    policy: &mut dyn Policy,
+    seen: &SnekHaus,
+    snek: &Snek,
+    limit: Option<Duration>,
+) -> (Direction, bool) {
+    let started = Instant::now();
+    let direction = policy.decide(seen, &seen.snek);
+    match limit {
+        Some(limit) if started.elapsed() > limit => (snek.direction, true),
+        _ => (direction, false),
+    }
+}
+
+/// Play one game to completion with `policy` steering and no rendering. Each
+/// tick is played just as it is on screen, bonus rounds, hazards and all.
+pub fn simulate(
+    rules: Rules,
+    size: Size,
+    policy: &mut dyn Policy,
+    seed: u64,
+    fairness: Fairness,
+) -> RunStats {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut haus = SnekHaus::new(size, 3, rules);
+    haus.setup(&mut rng);
+
+    // The oldest entry is what the bot gets to see
+    let mut history = VecDeque::new();
+    let mut round = Round::default();
+    let mut ticks = 0;
+    let mut violations = 0;
+    while ticks < MAX_TICKS {
+        let (direction, violated) = if fairness.observation_delay == 0 {
+            fair_decision(policy, &haus, &haus.snek, fairness.decision_limit)
+        } else {
+            history.push_back(haus.clone());
+            if history.len() > fairness.observation_delay as usize + 1 {
+                history.pop_front();
+            }
+            fair_decision(policy, &history[0], &haus.snek, fairness.decision_limit)
+        };
+        violations += violated as u32;
+        haus.change_direction(direction);
+        ticks += 1;
+
+        if let StepResult::Died(_) | StepResult::Victory = haus.tick(&mut round, &mut rng) {
+            break;
+        }
+    }
+
+    RunStats {
+        score: haus.score,
+        ticks,
+        elapsed: haus.clock,
+        violations,
+        normalized: scoring::normalized(haus.score, ticks, &rules),
+    }
+}
+
+/// The seed for run number `run` of a batch started from `master_seed`. Each
+/// run's seed depends on nothing else, and neighbouring runs get unrelated
+/// streams of random numbers.
+pub fn run_seed(master_seed: u64, run: u32) -> u64 {
+    // SplitMix64
+    let mut z = master_seed.wrapping_add((run as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Play `runs` independent games spread across every core, each steered by
+/// its own policy from `new_policy`. Stats come back in run order, and are the
+/// same however the runs were scheduled.
+pub fn simulate_many<P: Policy>(
+    rules: Rules,
+    size: Size,
+    new_policy: impl Fn() -> P + Sync,
+    master_seed: u64,
+    runs: u32,
+    fairness: Fairness,
+) -> Vec<RunStats> {
+    (0..runs)
+        .into_par_iter()
+        .map(|run| {
+            let seed = run_seed(master_seed, run);
+            simulate(rules, size, &mut new_policy(), seed, fairness)
+        })
+        .collect()
+}
+
+/// Averages over a batch of runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub runs: u32,
+    pub mean_score: f64,
+    pub mean_ticks: f64,
+    pub mean_seconds: f64,
+    pub best_score: u16,
+    pub violations: u32,
+    /// Zero when the runs have no normalized score
+    pub mean_normalized: f64,
+}
+
+impl Summary {
+    pub fn from_runs(runs: &[RunStats]) -> Summary {
+        if runs.is_empty() {
+            return Summary::default();
+        }
+        let n = runs.len() as f64;
+        Summary {
+            runs: runs.len() as u32,
+            mean_score: runs.iter().map(|r| r.score as f64).sum::<f64>() / n,
+            mean_ticks: runs.iter().map(|r| r.ticks as f64).sum::<f64>() / n,
+            mean_seconds: runs.iter().map(|r| r.elapsed.as_secs_f64()).sum::<f64>() / n,
+            best_score: runs.iter().map(|r| r.score).max().unwrap_or(0),
+            violations: runs.iter().map(|r| r.violations).sum(),
+            mean_normalized: runs.iter().filter_map(|r| r.normalized).sum::<f64>() / n,
+        }
+    }
+}
+
+/// Parse a configuration of the form `mode[:base_ms[:step_ms]]`, e.g. `classic:120:5`.
+fn parse_config(spec: &str) -> Result<Rules, String> {
+    let mut parts = spec.split(':');
+    let mode_name = parts.next().unwrap_or_default();
+    let mode =
+        GameMode::from_slug(mode_name).ok_or_else(|| format!("Unknown mode: {}", mode_name))?;
+    let mut rules = mode.rules();
+
+    let mut millis = |name: &str| -> Result<Option<u64>, String> {
+        parts
+            .next()
+            .map(|p| {
+                p.parse()
+                    .map_err(|_| format!("Invalid {} in {}: {}", name, spec, p))
+            })
+            .transpose()
+    };
+    if let Some(base) = millis("base tick")? {
+        rules.speed.base_millis = base;
+    }
+    if let Some(step) = millis("speed step")? {
+        rules.speed.step_millis = step;
+    }
+    Ok(rules)
+}
+
+fn run_batch(rules: Rules, runs: u32, seed: u64, fairness: Fairness) -> Summary {
+    // Every configuration sees the same seeds, so only the rules differ
+    let stats = simulate_many(rules, SIM_ARENA, Greedy::default, seed, runs, fairness);
+    Summary::from_runs(&stats)
+}
+
+/// `snekrs compare <config-a> <config-b> [--runs N] [--seed S] [--time-limit MS] [--delay TICKS]`
+///
+/// Plays the greedy bot under two configurations and prints their stats side by side.
+/// `--time-limit` and `--delay` apply the tournament fairness rules, with any
+/// decisions over the limit reported as timeouts.
+pub fn compare_command(args: &[String]) -> io::Result<()> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+
+    let mut configs = Vec::new();
+    let mut runs = DEFAULT_RUNS;
+    let mut seed = 0;
+    let mut fairness = Fairness::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" | "--seed" | "--time-limit" | "--delay" => {
+                let value = args
+                    .next()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .ok_or_else(|| invalid(format!("{} needs a number", arg)))?;
+                match arg.as_str() {
+                    "--runs" => runs = value as u32,
+                    "--seed" => seed = value,
+                    "--time-limit" => fairness.decision_limit = Some(Duration::from_millis(value)),
+                    _ => fairness.observation_delay = value as u32,
+                }
+            }
+            spec => configs.push((spec, parse_config(spec).map_err(invalid)?)),
+        }
+    }
+    let [(name_a, rules_a), (name_b, rules_b)] = configs[..] else {
+        return Err(invalid(
+            "Usage: snekrs compare <mode[:base_ms[:step_ms]]> <mode[:base_ms[:step_ms]]> [--runs N] [--seed S] [--time-limit MS] [--delay TICKS]".into(),
+        ));
+    };
+
+    let a = run_batch(rules_a, runs, seed, fairness);
+    let b = run_batch(rules_b, runs, seed, fairness);
+
+    println!("{} runs of the {} bot, seed {}", runs, Greedy.name(), seed);
+    if let Some(limit) = fairness.decision_limit {
+        println!("Decisions limited to {}ms", limit.as_millis());
+    }
+    if fairness.observation_delay > 0 {
+        println!(
+            "Bot sees the arena {} ticks late",
+            fairness.observation_delay
+        );
+    }
+    println!("{:<16}{:>16}{:>16}{:>12}", "", name_a, name_b, "B - A");
+    let rows = [
+        ("mean score", a.mean_score, b.mean_score),
+        ("mean ticks", a.mean_ticks, b.mean_ticks),
+        ("mean seconds", a.mean_seconds, b.mean_seconds),
+        ("best score", a.best_score as f64, b.best_score as f64),
+        ("normalized", a.mean_normalized, b.mean_normalized),
+        ("timeouts", a.violations as f64, b.violations as f64),
+    ];
+    for (label, a, b) in rows {
+        println!("{:<16}{:>16.1}{:>16.1}{:>+12.1}", label, a, b, b - a);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let rules = parse_config("classic:120:5").expect("Config should parse");
+        assert_eq!(rules.speed.base_millis, 120, "Base tick should be read from the config");
+        assert_eq!(rules.speed.step_millis, 5, "Speed step should be read from the config");
+
+        let rules = parse_config("lives").expect("Mode on its own should parse");
+        assert_eq!(rules, GameMode::Lives.rules(), "Bare mode should use the mode's rules");
+
+        assert!(parse_config("nonsense").is_err(), "Unknown modes should be rejected");
+        assert!(parse_config("classic:fast").is_err(), "Non-numeric speeds should be rejected");
+    }
+
+    #[test]
+    fn test_simulation_is_reproducible() {
+        let rules = Rules::default();
+        let first = simulate(rules, SIM_ARENA, &mut Greedy, 42, Fairness::default());
+        let second = simulate(rules, SIM_ARENA, &mut Greedy, 42, Fairness::default());
+        assert_eq!(first, second, "Same seed and rules should give the same run");
+        assert!(first.ticks > 0, "Run should last at least one tick");
+    }
+
+    #[test]
+    fn test_parallel_runs_are_reproducible() {
+        let rules = Rules::default();
+        let parallel = simulate_many(rules, SIM_ARENA, Greedy::default, 5, 8, Fairness::default());
+        let serial: Vec<_> = (0..8).map(|run| simulate(rules, SIM_ARENA, &mut Greedy, run_seed(5, run), Fairness::default())).collect();
+        assert_eq!(parallel, serial, "Parallel runs should match the same runs played one at a time");
+        assert_ne!(run_seed(5, 0), run_seed(5, 1), "Each run should get its own seed");
+        assert_ne!(run_seed(5, 1), run_seed(6, 0), "Master seeds shouldn't overlap each other's runs");
+    }
+
+    #[test]
+    fn test_speed_changes_elapsed_time() {
+        let mut fast = Rules::default();
+        fast.speed.base_millis = 75;
+        let slow = simulate(Rules::default(), SIM_ARENA, &mut Greedy, 7, Fairness::default());
+        let fast = simulate(fast, SIM_ARENA, &mut Greedy, 7, Fairness::default());
+        assert_eq!(slow.ticks, fast.ticks, "Tick speed shouldn't change what the bot does");
+        assert_eq!(slow.elapsed, fast.elapsed * 2, "Halving the tick should halve the run time");
+    }
+
+    /// Turns north, after thinking for a while, and remembers the ticks it was shown.
+    #[derive(Default)]
+    struct Ponderer {
+        thinking: Duration,
+        seen: Vec<u32>,
+    }
+
+    impl Policy for Ponderer {
+        fn name(&self) -> &str {
+            "ponderer"
+        }
+
+        fn decide(&mut self, haus: &SnekHaus, _snek: &Snek) -> Direction {
+            std::thread::sleep(self.thinking);
+            self.seen.push(haus.ticks);
+            Direction::North
+        }
+    }
+
+    #[test]
+    fn test_slow_decisions_go_straight() {
+        let haus = SnekHaus::new(SIM_ARENA, 3, Rules::default());
+        let mut bot = Ponderer { thinking: Duration::from_millis(20), ..Ponderer::default() };
+        let limit = Some(Duration::from_millis(1));
+        assert_eq!(fair_decision(&mut bot, &haus, &haus.snek, limit), (Direction::East, true), "Timed out bot should carry straight on");
+        assert_eq!(fair_decision(&mut bot, &haus, &haus.snek, None), (Direction::North, false), "Without a limit the bot can take its time");
+    }
+
+    #[test]
+    fn test_observation_delay() {
+        let fairness = Fairness { observation_delay: 2, ..Fairness::default() };
+        let mut bot = Ponderer::default();
+        let stats = simulate(Rules::default(), SIM_ARENA, &mut bot, 3, fairness);
+        assert!(stats.ticks > 5, "Run should last a few ticks");
+        assert_eq!(bot.seen[..5], [0, 0, 0, 1, 2], "Bot should see the arena two ticks late");
+        assert_eq!(stats.violations, 0, "No limit means no timeouts");
+    }
+}
@@ -0,0 +1,571 @@
+//! How the snek's body is drawn, loaded from plain text files.
+//!
+//! A skin file names a pattern and the colours it works through:
+//!
+//! ```text
+//! ; Lines starting with ';' are comments
+//! name: Bumblebee
+//! pattern: striped
+//! colours: yellow, black
+//! background: #202020
+//! ```
+//!
+//! - `solid` paints the whole body in the first colour
+//! - `striped` changes colour every couple of segments, counting back from the head
+//! - `checker` alternates colours across the arena, so the body looks like it
+//!   slides over a chequerboard
+//! - `rainbow` is striped one segment at a time, and shifts along every tick
+//! - `gradient` fades through the colours from the head to the tail
+//!
+//! Colours are names like `lightgreen` or hex like `#ff8800`. The optional
+//! `background` colour shades the arena's background pattern, when there is one.
+//!
+//! The optional `style` is `blocks`, solid cells of colour, or `segments`,
+//! box-drawing lines joining each segment to the next. The optional `head`
+//! is a symbol to draw the head with in place of the usual one.
+
+use crate::glyphs::{self, Glyphs};
+use crate::{Direction, Pos};
+use log::error;
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+pub const SKINS_DIR: &str = "skins";
+/// Segments in each band of a striped skin
+const STRIPE_WIDTH: usize = 2;
+/// A grey dark enough to stay in the background on a dark terminal
+const DEFAULT_BACKGROUND: Color = Color::Indexed(236);
+/// How much darker the tail is than the head, when the body fades
+const TAIL_FADE: f32 = 0.6;
+/// The 16 standard colours, roughly as most terminals show them
+const STANDARD: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (128, 0, 0),
+    (0, 128, 0),
+    (128, 128, 0),
+    (0, 0, 128),
+    (128, 0, 128),
+    (0, 128, 128),
+    (192, 192, 192),
+    (128, 128, 128),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (0, 0, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+const RAINBOW: [Color; 6] = [
+    Color::Red,
+    Color::LightRed,
+    Color::Yellow,
+    Color::Green,
+    Color::Blue,
+    Color::Magenta,
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Pattern {
+    #[default]
+    Solid,
+    Striped,
+    Checker,
+    Rainbow,
+    Gradient,
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "solid" => Ok(Pattern::Solid),
+            "striped" => Ok(Pattern::Striped),
+            "checker" => Ok(Pattern::Checker),
+            "rainbow" => Ok(Pattern::Rainbow),
+            "gradient" => Ok(Pattern::Gradient),
+            _ => Err(format!("unknown pattern '{}'", s)),
+        }
+    }
+}
+
+/// What each body segment is drawn as.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Style {
+    /// A cell filled with the segment's colour
+    #[default]
+    Blocks,
+    /// A line in the segment's colour, joined up with the segments either side
+    Segments,
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blocks" => Ok(Style::Blocks),
+            "segments" => Ok(Style::Segments),
+            _ => Err(format!("unknown style '{}'", s)),
+        }
+    }
+}
+
+/// One segment of the body, for a skin to draw.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    /// Segments back from the head
+    pub index: usize,
+    /// Segments in the whole body
+    pub length: usize,
+    pub pos: Pos,
+    /// The way to the segment on the head's side, then the tail's, if
+    /// they're next to this one
+    pub links: [Option<Direction>; 2],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Skin {
+    pub name: String,
+    pub pattern: Pattern,
+    pub colours: Vec<Color>,
+    /// Colour of the arena's background pattern
+    pub background: Color,
+    pub style: Style,
+    /// Drawn in place of the symbol table's head
+    pub head: Option<String>,
+    /// Darken the body towards the tail, as the settings ask rather than
+    /// the skin's file
+    pub tail_fade: bool,
+}
+
+impl Default for Skin {
+    fn default() -> Self {
+        Skin {
+            name: "Classic".to_string(),
+            pattern: Pattern::Solid,
+            colours: vec![Color::Green],
+            background: DEFAULT_BACKGROUND,
+            style: Style::Blocks,
+            head: None,
+            tail_fade: false,
+        }
+    }
+}
+
+impl Skin {
+    pub fn parse(text: &str, default_name: &str) -> Result<Skin, String> {
+        let mut skin = Skin {
+            name: default_name.to_string(),
+            ..Skin::default()
+        };
+        let mut colours = None;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with(';') || line.is_empty() {
+                continue;
+            }
+            let at_line = |e: String| format!("line {}: {}", index + 1, e);
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| at_line("expected 'key: value'".to_string()))?;
+            let value = value.trim();
+            match key.trim() {
+                "name" => skin.name = value.to_string(),
+                "pattern" => skin.pattern = value.parse().map_err(at_line)?,
+                "colours" | "colors" => {
+                    let parsed = value
+                        .split(',')
+                        .map(|c| {
+                            c.trim()
+                                .parse()
+                                .map_err(|_| format!("unknown colour '{}'", c.trim()))
+                        })
+                        .collect::<Result<Vec<Color>, _>>()
+                        .map_err(at_line)?;
+                    colours = Some(parsed);
+                }
+                "background" => {
+                    skin.background = value
+                        .parse()
+                        .map_err(|_| at_line(format!("unknown colour '{}'", value)))?
+                }
+                "style" => skin.style = value.parse().map_err(at_line)?,
+                "head" if value.is_empty() => return Err(at_line("no head given".to_string())),
+                "head" => skin.head = Some(value.to_string()),
+                other => return Err(at_line(format!("unknown setting '{}'", other))),
+            }
+        }
+
+        skin.colours = match (colours, skin.pattern) {
+            (Some(colours), _) if !colours.is_empty() => colours,
+            (_, Pattern::Rainbow) => RAINBOW.to_vec(),
+            _ => return Err("no colours given".to_string()),
+        };
+        Ok(skin)
+    }
+
+    /// The same skin, with any true colours swapped for the nearest of the
+    /// standard 256.
+    pub fn limited_to_256(self) -> Skin {
+        Skin {
+            colours: self.colours.into_iter().map(indexed).collect(),
+            background: indexed(self.background),
+            ..self
+        }
+    }
+
+    /// The colour of `segment` on the given game `tick`.
+    pub fn colour(&self, segment: Segment, tick: u32) -> Color {
+        let n = self.colours.len();
+        let pick = match self.pattern {
+            Pattern::Solid => 0,
+            Pattern::Striped => segment.index / STRIPE_WIDTH,
+            Pattern::Checker => (segment.pos.x + segment.pos.y) as usize,
+            Pattern::Rainbow => segment.index + tick as usize,
+            Pattern::Gradient => return self.fade(self.gradient(segment), segment),
+        };
+        self.fade(self.colours[pick % n], segment)
+    }
+
+    /// `colour` darkened the further `segment` is from the head, if the body
+    /// fades. True colours fade through true colours, and the rest through
+    /// the standard 256, so a skin limited to those stays that way.
+    fn fade(&self, colour: Color, segment: Segment) -> Color {
+        let Some((r, g, b)) = rgb(colour) else {
+            return colour;
+        };
+        if !self.tail_fade || segment.index == 0 || segment.length < 2 {
+            return colour;
+        }
+        let along = segment.index.min(segment.length - 1) as f32 / (segment.length - 1) as f32;
+        let dim = |v: u8| (v as f32 * (1.0 - TAIL_FADE * along)).round() as u8;
+        let faded = Color::Rgb(dim(r), dim(g), dim(b));
+        match colour {
+            Color::Rgb(..) => faded,
+            _ => indexed(faded),
+        }
+    }
+
+    fn gradient(&self, segment: Segment) -> Color {
+        let last = self.colours.len() - 1;
+        if last == 0 || segment.length < 2 {
+            return self.colours[0];
+        }
+        // How far along the colours this segment is, in steps of one colour
+        let along = segment.index.min(segment.length - 1) as f32 * last as f32
+            / (segment.length - 1) as f32;
+        let from = (along as usize).min(last - 1);
+        let mix = along - from as f32;
+        match (self.colours[from], self.colours[from + 1]) {
+            (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+                let blend = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * mix).round() as u8;
+                Color::Rgb(blend(r1, r2), blend(g1, g2), blend(b1, b2))
+            }
+            // Named colours can't be blended, so take the nearer
+            (near, _) if mix < 0.5 => near,
+            (_, far) => far,
+        }
+    }
+
+    /// Draw `segment` into `cell`, on the given game `tick`, with symbols
+    /// from `glyphs`.
+    pub fn draw(&self, cell: &mut Cell, segment: Segment, tick: u32, glyphs: &Glyphs) {
+        let colour = self.colour(segment, tick);
+        match (self.style, joint(segment.links, glyphs)) {
+            (Style::Segments, Some(symbol)) => {
+                cell.set_symbol(symbol).set_fg(colour);
+            }
+            // Diagonal links have no box-drawing line, so fill the cell
+            _ => {
+                cell.set_symbol(glyphs.body).set_bg(colour);
+            }
+        }
+    }
+
+    /// Draw a snek heading east along the top row of `area`, to show the skin
+    /// off, with its head in `head_colour`.
+    pub fn draw_sample(&self, area: Rect, buf: &mut Buffer, glyphs: &Glyphs, head_colour: Color) {
+        let head = self.head(glyphs, Direction::East);
+        // A wide head spills into the last cell
+        let right = area.right() - (glyphs::is_wide(head) as u16).min(area.width);
+        let length = (right - area.left()).saturating_sub(1) as usize;
+        for (index, x) in (area.left()..right).rev().enumerate() {
+            let Some(cell) = buf.cell_mut((x, area.top())) else {
+                continue;
+            };
+            if index == 0 {
+                cell.set_symbol(head).set_fg(head_colour);
+                continue;
+            }
+            let segment = Segment {
+                index: index - 1,
+                length,
+                pos: Pos { x, y: 0, floor: 0 },
+                links: [
+                    Some(Direction::East),
+                    (index < length).then_some(Direction::West),
+                ],
+            };
+            self.draw(cell, segment, 0, glyphs);
+        }
+    }
+
+    /// The symbol to draw the head with, going in `direction`. A head of the
+    /// skin's own is left out when drawing in plain ASCII, unless it's ASCII
+    /// too.
+    pub fn head<'a>(&'a self, glyphs: &Glyphs, direction: Direction) -> &'a str {
+        match &self.head {
+            Some(head) if *glyphs != glyphs::ASCII || head.is_ascii() => head,
+            _ => glyphs.head(direction),
+        }
+    }
+}
+
+/// The line joining a segment to its neighbours, which runs straight on
+/// from a segment at either end of the body.
+fn joint(links: [Option<Direction>; 2], glyphs: &Glyphs) -> Option<&'static str> {
+    let [across, down, north_east, south_east, south_west, north_west] = glyphs.joints;
+    let link = |direction| links.contains(&Some(direction));
+    let straight = |direction| match direction {
+        Direction::North | Direction::South => Some(down),
+        Direction::East | Direction::West => Some(across),
+        _ => None,
+    };
+    match links {
+        [Some(a), Some(b)] if a == b.opposite() => straight(a),
+        [Some(_), Some(_)] if link(Direction::North) && link(Direction::East) => Some(north_east),
+        [Some(_), Some(_)] if link(Direction::South) && link(Direction::East) => Some(south_east),
+        [Some(_), Some(_)] if link(Direction::South) && link(Direction::West) => Some(south_west),
+        [Some(_), Some(_)] if link(Direction::North) && link(Direction::West) => Some(north_west),
+        [Some(_), Some(_)] => None,
+        [Some(direction), None] | [None, Some(direction)] => straight(direction),
+        [None, None] => None,
+    }
+}
+
+/// The red, green and blue of `colour`, or `None` for the terminal's own
+/// colours.
+pub fn rgb(colour: Color) -> Option<(u8, u8, u8)> {
+    let standard = |index: usize| Some(STANDARD[index]);
+    match colour {
+        Color::Reset => None,
+        Color::Black => standard(0),
+        Color::Red => standard(1),
+        Color::Green => standard(2),
+        Color::Yellow => standard(3),
+        Color::Blue => standard(4),
+        Color::Magenta => standard(5),
+        Color::Cyan => standard(6),
+        Color::Gray => standard(7),
+        Color::DarkGray => standard(8),
+        Color::LightRed => standard(9),
+        Color::LightGreen => standard(10),
+        Color::LightYellow => standard(11),
+        Color::LightBlue => standard(12),
+        Color::LightMagenta => standard(13),
+        Color::LightCyan => standard(14),
+        Color::White => standard(15),
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(index @ 0..=15) => standard(index as usize),
+        Color::Indexed(index @ 16..=231) => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + 40 * v };
+            let index = index - 16;
+            Some((level(index / 36), level(index / 6 % 6), level(index % 6)))
+        }
+        Color::Indexed(index) => {
+            let grey = 8 + 10 * (index - 232);
+            Some((grey, grey, grey))
+        }
+    }
+}
+
+/// The nearest colour in the 256 colour palette, for terminals that can't show true colour.
+pub fn indexed(colour: Color) -> Color {
+    let Color::Rgb(r, g, b) = colour else {
+        return colour;
+    };
+    if r == g && g == b {
+        // The greyscale ramp runs from 8 to 238 in steps of 10
+        return match r {
+            0..=7 => Color::Indexed(16),
+            249..=255 => Color::Indexed(231),
+            _ => Color::Indexed(232 + ((r - 8) / 10).min(23)),
+        };
+    }
+    // Otherwise the 6x6x6 cube, with levels 0, 95, 135, 175, 215 and 255
+    let level = |v: u8| match v {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (v - 35) / 40,
+    };
+    Color::Indexed(16 + 36 * level(r) + 6 * level(g) + level(b))
+}
+
+/// The built in skin followed by every skin in `dir` that loads, sorted by
+/// file name. Broken skins are logged and left out.
+pub fn load_dir(dir: &Path) -> Vec<Skin> {
+    let mut paths: Vec<_> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+
+    let loaded = paths.into_iter().filter_map(|path| {
+        let default_name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let skin = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Skin::parse(&text, &default_name));
+        skin.map_err(|e| error!("Error loading skin {}: {}", path.display(), e))
+            .ok()
+    });
+    std::iter::once(Skin::default()).chain(loaded).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: Pos = Pos { x: 0, y: 0, floor: 0 };
+
+    fn segment(index: usize, pos: Pos) -> Segment {
+        Segment { index, length: 4, pos, links: [None, None] }
+    }
+
+    #[test]
+    fn test_parse_skin() {
+        let text = "; stripes\nname: Bee\npattern: striped\ncolours: yellow, #000000\n";
+        let skin = Skin::parse(text, "fallback").expect("Skin should parse");
+        assert_eq!(skin.name, "Bee", "Name should come from the file");
+        assert_eq!(skin.pattern, Pattern::Striped, "Pattern should come from the file");
+        assert_eq!(skin.colours, vec![Color::Yellow, Color::Rgb(0, 0, 0)], "Colours should accept names and hex");
+
+        let rainbow = Skin::parse("pattern: rainbow", "fallback").expect("Rainbow should parse");
+        assert_eq!(rainbow.colours.len(), RAINBOW.len(), "Rainbow should have default colours");
+
+        assert!(Skin::parse("pattern: spotty\ncolours: red", "x").is_err(), "Unknown patterns should be rejected");
+        assert!(Skin::parse("colours: red, plaid", "x").is_err(), "Unknown colours should be rejected");
+        assert!(Skin::parse("pattern: checker", "x").is_err(), "Patterns other than rainbow need colours");
+        assert_eq!(Skin::parse("colours: red\nbackground: #102010", "x").map(|s| s.background), Ok(Color::Rgb(16, 32, 16)), "Background should come from the file");
+        assert_eq!(rainbow.background, DEFAULT_BACKGROUND, "Background should default to dark grey");
+        let pipes = Skin::parse("colours: red\nstyle: segments\nhead: >", "x").expect("Styled skin should parse");
+        assert_eq!((pipes.style, pipes.head.as_deref()), (Style::Segments, Some(">")), "Style and head should come from the file");
+        assert!(Skin::parse("colours: red\nstyle: wobbly", "x").is_err(), "Unknown styles should be rejected");
+    }
+
+    #[test]
+    fn test_patterns() {
+        let skin = |pattern| Skin {
+            name: String::new(),
+            pattern,
+            colours: vec![Color::Red, Color::Blue],
+            ..Skin::default()
+        };
+        let striped: Vec<_> = (0..4).map(|i| skin(Pattern::Striped).colour(segment(i, ORIGIN), 0)).collect();
+        assert_eq!(striped, vec![Color::Red, Color::Red, Color::Blue, Color::Blue], "Stripes should be bands of segments");
+
+        let beside = Pos { x: 1, ..ORIGIN };
+        assert_ne!(skin(Pattern::Checker).colour(segment(0, ORIGIN), 0), skin(Pattern::Checker).colour(segment(0, beside), 0), "Neighbouring cells should differ");
+
+        assert_ne!(skin(Pattern::Rainbow).colour(segment(0, ORIGIN), 0), skin(Pattern::Rainbow).colour(segment(0, ORIGIN), 1), "Rainbow should shift each tick");
+        assert_eq!(skin(Pattern::Solid).colour(segment(3, beside), 7), Color::Red, "Solid should always use the first colour");
+
+        let fade = Skin { pattern: Pattern::Gradient, colours: vec![Color::Rgb(0, 0, 0), Color::Rgb(90, 30, 0)], ..Skin::default() };
+        let faded: Vec<_> = (0..4).map(|i| fade.colour(segment(i, ORIGIN), 0)).collect();
+        assert_eq!(faded, vec![Color::Rgb(0, 0, 0), Color::Rgb(30, 10, 0), Color::Rgb(60, 20, 0), Color::Rgb(90, 30, 0)], "Gradients should blend from the head to the tail");
+        assert_eq!(skin(Pattern::Gradient).colour(segment(3, ORIGIN), 0), Color::Blue, "Named colours should step rather than blend");
+    }
+
+    #[test]
+    fn test_tail_fade() {
+        let true_colour = Skin { colours: vec![Color::Rgb(200, 100, 50)], tail_fade: true, ..Skin::default() };
+        assert_eq!(true_colour.colour(segment(0, ORIGIN), 0), Color::Rgb(200, 100, 50), "The head end should keep its colour");
+        assert_eq!(true_colour.colour(segment(3, ORIGIN), 0), Color::Rgb(80, 40, 20), "The tail should be darkest");
+        let named = Skin { tail_fade: true, ..Skin::default() };
+        assert!(matches!(named.colour(segment(3, ORIGIN), 0), Color::Indexed(_)), "Standard colours should fade through the 256");
+        assert_eq!(Skin::default().colour(segment(3, ORIGIN), 0), Color::Green, "Without the fade the tail should match the head");
+        assert_eq!(rgb(Color::Indexed(196)), Some((255, 0, 0)), "The colour cube should map back to its colours");
+    }
+
+    #[test]
+    fn test_segment_style() {
+        let skin = Skin { style: Style::Segments, head: Some("◆".to_string()), ..Skin::default() };
+        let draw = |links, glyphs: &Glyphs| {
+            let mut cell = Cell::default();
+            skin.draw(&mut cell, Segment { links, ..segment(1, ORIGIN) }, 0, glyphs);
+            (cell.symbol().to_string(), cell.fg)
+        };
+        let (north, south, east) = (
+            Some(Direction::North),
+            Some(Direction::South),
+            Some(Direction::East),
+        );
+        assert_eq!(
+            draw([north, south], &glyphs::UNICODE),
+            ("│".to_string(), Color::Green),
+            "A straight run should be a line in the skin's colour"
+        );
+        assert_eq!(
+            draw([south, east], &glyphs::UNICODE).0,
+            "┌",
+            "A turn should be a corner"
+        );
+        assert_eq!(
+            draw([None, east], &glyphs::UNICODE).0,
+            "─",
+            "The tail should run straight on"
+        );
+        assert_eq!(
+            draw([north, east], &glyphs::ASCII).0,
+            "+",
+            "ASCII should have its own joints"
+        );
+        assert_eq!(
+            draw([Some(Direction::NorthEast), south], &glyphs::UNICODE).0,
+            " ",
+            "Diagonals should fall back to a block"
+        );
+        assert_eq!(
+            skin.head(&glyphs::UNICODE, Direction::North),
+            "◆",
+            "The skin's head should replace the usual one"
+        );
+        assert_eq!(
+            skin.head(&glyphs::ASCII, Direction::North),
+            "A",
+            "A head that isn't ASCII should be left out in ASCII"
+        );
+    }
+
+    #[test]
+    fn test_limited_to_256() {
+        let skin = Skin {
+            name: String::new(),
+            pattern: Pattern::Solid,
+            colours: vec![Color::Rgb(255, 0, 0), Color::Rgb(128, 128, 128), Color::Blue],
+            background: Color::Rgb(0, 0, 0),
+            ..Skin::default()
+        };
+        let limited = skin.limited_to_256();
+        assert_eq!(limited.colours, vec![Color::Indexed(196), Color::Indexed(244), Color::Blue], "True colours should map to the nearest of 256");
+        assert_eq!(limited.background, Color::Indexed(16), "Background should be limited too");
+    }
+
+    #[test]
+    fn test_bundled_skins_load() {
+        let skins = load_dir(Path::new(SKINS_DIR));
+        assert!(skins.len() > 1, "Bundled skins should load");
+        assert_eq!(skins[0], Skin::default(), "Built in skin should come first");
+    }
+}
@@ -0,0 +1,100 @@
+//! Watching a game the player has no part in. The spectator screen only
+//! ever reads the arena from a `Feed`, so a feed can be anything that comes
+//! up with a `SnekHaus` every tick: a computer snek playing here, a
+//! recording, or one day another player over the network.
+
+use crate::bot::{Greedy, Policy};
+use crate::replay::Playback;
+use crate::{SnekHaus, StepResult};
+use rand::rngs::StdRng;
+use std::fmt;
+
+/// A game to watch, moved on a tick at a time.
+pub trait Feed: fmt::Debug {
+    /// Who or what is being watched, for the header
+    fn name(&self) -> String;
+
+    /// The arena as it is now.
+    fn haus(&self) -> &SnekHaus;
+
+    /// Move on a tick, returning false once there's nothing more to watch.
+    fn advance(&mut self) -> bool;
+}
+
+/// A computer snek playing a game of its own.
+#[derive(Debug)]
+pub struct Bot {
+    haus: SnekHaus,
+    rng: StdRng,
+}
+
+impl Bot {
+    /// Watch a computer snek play in `haus`, set up with `rng`.
+    pub fn new(haus: SnekHaus, rng: StdRng) -> Bot {
+        Bot { haus, rng }
+    }
+
+    /// The arena, to redress it when the settings change.
+    pub fn haus_mut(&mut self) -> &mut SnekHaus {
+        &mut self.haus
+    }
+}
+
+impl Feed for Bot {
+    fn name(&self) -> String {
+        "Computer snek".to_string()
+    }
+
+    fn haus(&self) -> &SnekHaus {
+        &self.haus
+    }
+
+    fn advance(&mut self) -> bool {
+        let direction = Greedy.decide(&self.haus, &self.haus.snek);
+        self.haus.change_direction(direction);
+        if let StepResult::Died(_) = self.haus.slither_on() {
+            return false;
+        }
+        self.haus.top_up_morsels(&mut self.rng);
+        true
+    }
+}
+
+impl Feed for Playback {
+    fn name(&self) -> String {
+        "Replay".to_string()
+    }
+
+    fn haus(&self) -> &SnekHaus {
+        &self.haus
+    }
+
+    fn advance(&mut self) -> bool {
+        self.step()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameMode, Round, Size};
+    use crate::replay::Recording;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_feeds() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut haus = SnekHaus::new(Size { width: 30, height: 15 }, 3, GameMode::Classic.rules());
+        haus.setup(&mut rng);
+        let mut recording = Recording::start(&haus, Round::default(), &rng);
+        let mut bot = Bot::new(haus, rng.clone());
+        for _ in 0..10 {
+            assert!(bot.advance(), "A computer snek should last a while in an open arena");
+            recording.tick(bot.haus(), Round::default(), &rng);
+        }
+        assert_ne!(bot.haus().snek.head, recording.playback(0, 0).haus.snek.head, "The computer snek should have moved");
+
+        let mut replay: Box<dyn Feed> = Box::new(recording.playback(0, 1));
+        assert!(!replay.advance() || !replay.advance(), "A replay should end with its recording");
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{Size, SnekHaus};
+use ratatui::{prelude::*, widgets::*};
+
+/// How the game area is divided between independent game views.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SplitLayout {
+    Single,
+    Dual,
+    Quad,
+}
+
+impl SplitLayout {
+    /// The smallest layout with room for `views` panes.
+    pub fn for_views(views: usize) -> SplitLayout {
+        match views {
+            0 | 1 => SplitLayout::Single,
+            2 => SplitLayout::Dual,
+            _ => SplitLayout::Quad,
+        }
+    }
+
+    /// Divide `area` into one rectangle per pane, in reading order.
+    pub fn areas(&self, area: Rect) -> Vec<Rect> {
+        let halves = |area: Rect, direction: layout::Direction| {
+            Layout::default()
+                .direction(direction)
+                .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+                .split(area)
+        };
+
+        match self {
+            SplitLayout::Single => vec![area],
+            SplitLayout::Dual => halves(area, layout::Direction::Horizontal).to_vec(),
+            SplitLayout::Quad => halves(area, layout::Direction::Vertical)
+                .iter()
+                .flat_map(|&row| halves(row, layout::Direction::Horizontal).to_vec())
+                .collect(),
+        }
+    }
+}
+
+/// Arenas at least this wide are offered a preset rather than stretching
+/// across the whole terminal, which makes the game far easier than intended.
+pub const WIDE_ARENA: u16 = 160;
+/// The classic arena, the size snekrs was balanced for
+const CLASSIC_ARENA: Size = Size {
+    width: 40,
+    height: 20,
+};
+
+/// How to use the room on a very wide terminal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArenaPreset {
+    /// The classic arena, centred
+    Classic,
+    /// Twice as wide as the classic arena, for long games
+    Marathon,
+    /// The arena fills the left half of the screen, with the run's details
+    /// in the right half
+    Dual,
+    /// The arena fills the whole terminal
+    Stretch,
+}
+
+impl ArenaPreset {
+    pub const ALL: [ArenaPreset; 4] = [
+        ArenaPreset::Classic,
+        ArenaPreset::Marathon,
+        ArenaPreset::Dual,
+        ArenaPreset::Stretch,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArenaPreset::Classic => "Centred classic",
+            ArenaPreset::Marathon => "Double-wide marathon",
+            ArenaPreset::Dual => "Split dual-view",
+            ArenaPreset::Stretch => "Stretch to fit",
+        }
+    }
+
+    /// Whether an arena of `available` is wide enough to ask for a preset.
+    pub fn wanted(available: Size) -> bool {
+        available.width >= WIDE_ARENA
+    }
+
+    /// The arena to play in, given `available` room inside the game pane's
+    /// border.
+    pub fn size(&self, available: Size) -> Size {
+        let capped = |size: Size| Size {
+            width: size.width.min(available.width),
+            height: size.height.min(available.height),
+        };
+        match self {
+            ArenaPreset::Classic => capped(CLASSIC_ARENA),
+            ArenaPreset::Marathon => capped(Size {
+                width: CLASSIC_ARENA.width * 2,
+                ..CLASSIC_ARENA
+            }),
+            ArenaPreset::Dual => {
+                let pane = Rect::new(0, 0, available.width + 2, available.height + 2);
+                let left = SplitLayout::Dual.areas(pane)[0];
+                Size {
+                    width: left.width.saturating_sub(2),
+                    height: left.height.saturating_sub(2),
+                }
+            }
+            ArenaPreset::Stretch => available,
+        }
+    }
+}
+
+/// A single game to draw in its own pane.
+pub struct GameView<'a> {
+    pub title: String,
+    pub haus: &'a SnekHaus,
+}
+
+impl<'a> GameView<'a> {
+    pub fn new(title: impl Into<String>, haus: &'a SnekHaus) -> Self {
+        GameView {
+            title: title.into(),
+            haus,
+        }
+    }
+}
+
+/// Whether an arena of `size` fits inside `inner` without clipping.
+pub fn fits(inner: Rect, size: Size) -> bool {
+    inner.width >= size.width && inner.height >= size.height
+}
+
+/// The area an arena of `size` takes up when centred in `inner`.
+pub fn centred(inner: Rect, size: Size) -> Rect {
+    Rect {
+        x: inner.x + inner.width.saturating_sub(size.width) / 2,
+        y: inner.y + inner.height.saturating_sub(size.height) / 2,
+        width: size.width.min(inner.width),
+        height: size.height.min(inner.height),
+    }
+}
+
+/// Render each view in its own bordered pane and return the areas the arenas
+/// were drawn in, so callers can draw overlays on top.
+pub fn render_views(frame: &mut Frame, area: Rect, views: &[GameView]) -> Vec<Rect> {
+    let layout = SplitLayout::for_views(views.len());
+    let mut arena_areas = Vec::with_capacity(views.len());
+
+    for (view, pane) in views.iter().zip(layout.areas(area)) {
+        let mut block = view.haus.theme.block().title(view.title.as_str());
+        // With several panes the shared header can't show everyone's score
+        if layout != SplitLayout::Single {
+            block = block.title(Line::from(format!("Score: {}", view.haus.score)).right_aligned());
+        }
+        // Only the head's floor is drawn, so say which one it is
+        if view.haus.floors > 1 {
+            block = block.title(
+                Line::from(format!(
+                    "Floor {}/{}",
+                    view.haus.snek.head.floor + 1,
+                    view.haus.floors
+                ))
+                .centered(),
+            );
+        }
+        let inner = block.inner(pane);
+        frame.render_widget(block, pane);
+
+        if fits(inner, view.haus.size) {
+            let arena = centred(inner, view.haus.size);
+            frame.render_widget(view.haus, arena);
+            arena_areas.push(arena);
+        } else {
+            frame.render_widget(
+                Paragraph::new(format!(
+                    "Pane too small\nneed {}x{}",
+                    view.haus.size.width, view.haus.size.height
+                ))
+                .alignment(Alignment::Center),
+                inner,
+            );
+            arena_areas.push(inner);
+        }
+    }
+
+    arena_areas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_for_views() {
+        assert_eq!(SplitLayout::for_views(1), SplitLayout::Single, "One view should use the whole area");
+        assert_eq!(SplitLayout::for_views(2), SplitLayout::Dual, "Two views should split the area");
+        assert_eq!(SplitLayout::for_views(3), SplitLayout::Quad, "Three views should round up to four panes");
+        assert_eq!(SplitLayout::for_views(4), SplitLayout::Quad, "Four views should use four panes");
+    }
+
+    #[test]
+    fn test_pane_areas() {
+        let area = Rect::new(0, 0, 80, 40);
+
+        assert_eq!(SplitLayout::Single.areas(area), vec![area], "Single layout should use the whole area");
+
+        let dual = SplitLayout::Dual.areas(area);
+        assert_eq!(dual, vec![Rect::new(0, 0, 40, 40), Rect::new(40, 0, 40, 40)], "Dual layout should sit side by side");
+
+        let quad = SplitLayout::Quad.areas(area);
+        assert_eq!(
+            quad,
+            vec![
+                Rect::new(0, 0, 40, 20),
+                Rect::new(40, 0, 40, 20),
+                Rect::new(0, 20, 40, 20),
+                Rect::new(40, 20, 40, 20),
+            ],
+            "Quad layout should be a two by two grid in reading order"
+        );
+    }
+
+    #[test]
+    fn test_centred() {
+        let inner = Rect::new(1, 1, 20, 10);
+        let size = Size {
+            width: 10,
+            height: 4,
+        };
+        assert_eq!(centred(inner, size), Rect::new(6, 4, 10, 4), "Smaller arena should sit in the middle");
+        assert_eq!(centred(inner, Size { width: 20, height: 10 }), inner, "Full size arena should fill the pane");
+    }
+
+    #[test]
+    fn test_arena_presets() {
+        let available = Size { width: 238, height: 50 };
+        assert!(ArenaPreset::wanted(available), "A 240 column terminal should be offered presets");
+        assert!(!ArenaPreset::wanted(Size { width: 120, height: 50 }), "Ordinary terminals should just stretch");
+        assert_eq!(ArenaPreset::Classic.size(available), Size { width: 40, height: 20 }, "Classic should be 40x20");
+        assert_eq!(ArenaPreset::Marathon.size(available), Size { width: 80, height: 20 }, "Marathon should be twice as wide");
+        assert_eq!(ArenaPreset::Dual.size(available), Size { width: 118, height: 50 }, "Dual should fill the left pane");
+        assert_eq!(ArenaPreset::Stretch.size(available), available, "Stretch should use everything");
+        let short = Size { width: 200, height: 12 };
+        assert_eq!(ArenaPreset::Classic.size(short), Size { width: 40, height: 12 }, "Presets should shrink to fit");
+    }
+
+    #[test]
+    fn test_fits() {
+        let size = Size {
+            width: 10,
+            height: 5,
+        };
+        assert!(fits(Rect::new(0, 0, 10, 5), size), "Exact fit should be allowed");
+        assert!(!fits(Rect::new(0, 0, 9, 5), size), "Narrow pane should be rejected");
+        assert!(!fits(Rect::new(0, 0, 10, 4), size), "Short pane should be rejected");
+    }
+}
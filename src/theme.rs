@@ -0,0 +1,190 @@
+//! Colour themes for the whole screen: the snek, the morsels, the borders
+//! and the text, along with the lines the borders are drawn in. A skin of its own still colours the snek's body, and the
+//! rest of the arena keeps the colours that tell things apart.
+//!
+//! The themes are made for a dark terminal. On a light one each colour goes
+//! through `Theme::on_light`, which darkens the ones that would wash out.
+
+use crate::skin;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, BorderType, Borders};
+use std::env;
+
+/// The brightest a colour can be and still read on a light background, as a
+/// luminance from 0 to 1
+const LIGHT_MAX_LUMINANCE: f32 = 0.4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    /// How the theme is written in the settings file
+    pub slug: &'static str,
+    pub name: &'static str,
+    /// The body, when the snek is wearing the built in skin
+    pub snek: Color,
+    pub head: Color,
+    /// Morsels, and the traps that copy them
+    pub morsel: Color,
+    pub brittle: Color,
+    /// Around the arena and the header
+    pub border: Color,
+    pub text: Color,
+    /// The lines drawn around the arena and the header
+    pub border_type: BorderType,
+    /// How the titles set into those lines are styled
+    pub title: Modifier,
+}
+
+pub const CLASSIC: Theme = Theme {
+    slug: "classic",
+    name: "Classic green",
+    snek: Color::Green,
+    head: Color::Yellow,
+    morsel: Color::LightRed,
+    brittle: Color::LightCyan,
+    border: Color::Reset,
+    text: Color::Reset,
+    border_type: BorderType::Plain,
+    title: Modifier::empty(),
+};
+
+pub const NEON: Theme = Theme {
+    slug: "neon",
+    name: "Neon",
+    snek: Color::Indexed(46),
+    head: Color::Indexed(226),
+    morsel: Color::Indexed(201),
+    brittle: Color::Indexed(51),
+    border: Color::Indexed(201),
+    text: Color::Indexed(51),
+    border_type: BorderType::Double,
+    title: Modifier::BOLD,
+};
+
+pub const PASTEL: Theme = Theme {
+    slug: "pastel",
+    name: "Pastel",
+    snek: Color::Indexed(151),
+    head: Color::Indexed(229),
+    morsel: Color::Indexed(217),
+    brittle: Color::Indexed(153),
+    border: Color::Indexed(183),
+    text: Color::Indexed(189),
+    border_type: BorderType::Rounded,
+    title: Modifier::ITALIC,
+};
+
+/// Morsels and traps are told apart by shape alone anyway
+pub const MONOCHROME: Theme = Theme {
+    slug: "monochrome",
+    name: "Monochrome",
+    snek: Color::White,
+    head: Color::White,
+    morsel: Color::Gray,
+    brittle: Color::Gray,
+    border: Color::DarkGray,
+    text: Color::White,
+    border_type: BorderType::Thick,
+    title: Modifier::BOLD,
+};
+
+pub const THEMES: [Theme; 4] = [CLASSIC, NEON, PASTEL, MONOCHROME];
+
+impl Default for Theme {
+    fn default() -> Self {
+        CLASSIC
+    }
+}
+
+impl Theme {
+    /// A block bordered all round in the theme's lines, for the arena, the
+    /// header and the screens standing in for the arena.
+    pub fn block(&self) -> Block<'static> {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(self.border_type)
+            .border_style(Style::default().fg(self.border))
+            .title_style(Style::default().add_modifier(self.title))
+    }
+
+    /// The theme made readable on a light background. Named colours are
+    /// swapped for fixed ones too, as light terminal themes tend to remap
+    /// them, and the terminal's own colours are left alone.
+    pub fn on_light(self) -> Theme {
+        Theme {
+            snek: darken(self.snek),
+            head: darken(self.head),
+            morsel: darken(self.morsel),
+            brittle: darken(self.brittle),
+            border: darken(self.border),
+            text: darken(self.text),
+            ..self
+        }
+    }
+}
+
+/// `colour` as a fixed colour no brighter than `LIGHT_MAX_LUMINANCE`.
+fn darken(colour: Color) -> Color {
+    let Some((r, g, b)) = skin::rgb(colour) else {
+        return colour;
+    };
+    let scale = (LIGHT_MAX_LUMINANCE / luminance((r, g, b))).min(1.0);
+    let dim = |v: u8| (v as f32 * scale).round() as u8;
+    let darkened = Color::Rgb(dim(r), dim(g), dim(b));
+    match colour {
+        Color::Rgb(..) => darkened,
+        _ => skin::indexed(darkened),
+    }
+}
+
+/// How bright `rgb` looks, from 0 to 1.
+fn luminance((r, g, b): (u8, u8, u8)) -> f32 {
+    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0
+}
+
+/// Whether the terminal says its background is light, going by the
+/// `COLORFGBG` that rxvt, Konsole and others set.
+pub fn terminal_is_light() -> bool {
+    env::var("COLORFGBG").is_ok_and(|value| colorfgbg_is_light(&value))
+}
+
+/// Whether a `COLORFGBG` of `fg;bg`, or `fg;default;bg`, has a light
+/// background: white, light grey, or one of the bright colours but grey.
+fn colorfgbg_is_light(value: &str) -> bool {
+    value
+        .rsplit(';')
+        .next()
+        .and_then(|bg| bg.trim().parse::<u8>().ok())
+        .is_some_and(|bg| bg == 7 || (9..=15).contains(&bg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_on_light() {
+        let light = CLASSIC.on_light();
+        assert!(!matches!(light.snek, Color::Green | Color::Indexed(0..=15)), "Named colours should be fixed on a light background");
+        for colour in [light.snek, light.head, light.morsel, light.brittle, MONOCHROME.on_light().text] {
+            let rgb = skin::rgb(colour).expect("Theme colours should have a shade");
+            assert!(luminance(rgb) < 0.5, "{:?} should be dark enough to read on white", colour);
+        }
+        assert_eq!(light.text, Color::Reset, "The terminal's own colours should be left alone");
+        assert!(colorfgbg_is_light("0;15") && colorfgbg_is_light("0;default;7"), "White and grey backgrounds are light");
+        assert!(!colorfgbg_is_light("15;0") && !colorfgbg_is_light("7;8") && !colorfgbg_is_light(""), "Black and dark grey backgrounds are dark");
+    }
+
+    #[test]
+    fn test_block() {
+        use ratatui::buffer::Buffer;
+        use ratatui::layout::Rect;
+        use ratatui::widgets::Widget;
+        let area = Rect::new(0, 0, 10, 3);
+        for (theme, corner) in [(CLASSIC, "┌"), (NEON, "╔"), (PASTEL, "╭"), (MONOCHROME, "┏")] {
+            let mut buf = Buffer::empty(area);
+            theme.block().title("Snek").render(area, &mut buf);
+            assert_eq!(buf[(0, 0)].symbol(), corner, "{} should have its own border", theme.name);
+            assert_eq!(buf[(1, 0)].modifier, theme.title, "{} should style its titles", theme.name);
+        }
+    }
+}
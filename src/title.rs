@@ -0,0 +1,171 @@
+//! The start screen's title: the SNEKRS logo in ASCII art, with a snek in
+//! the player's skin slithering along behind it. The title keeps its own
+//! tick, stepped along with the game's, so the snek carries on however long
+//! the start screen is up.
+
+use crate::glyphs::{self, Glyphs};
+use crate::skin::{Segment, Skin};
+use crate::{Direction, Pos, Size};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier};
+use std::f32::consts::TAU;
+
+const LOGO: [&str; 5] = [
+    r" ____  _   _ _____ _  __ ____  ____  ",
+    r"/ ___|| \ | | ____| |/ /|  _ \/ ___| ",
+    r"\___ \|  \| |  _| | ' / | |_) \___ \ ",
+    r" ___) | |\  | |___| . \ |  _ < ___) |",
+    r"|____/|_| \_|_____|_|\_\|_| \_\____/ ",
+];
+/// Rows the title takes up: the logo, with a row above and below for the
+/// snek to weave through
+pub const HEIGHT: u16 = 7;
+/// Columns in one wave of the snek's path
+const WAVE: u16 = 12;
+/// Rows the snek weaves above and below the middle
+const AMPLITUDE: f32 = 3.0;
+/// Segments in the snek, head and all
+const LENGTH: usize = 10;
+
+#[derive(Debug, Default)]
+pub struct Title {
+    tick: u32,
+}
+
+impl Title {
+    /// Move the snek on a cell.
+    pub fn step(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+    }
+
+    /// Columns the title takes up: the logo, with room either side for the
+    /// snek to come and go, rounded up to whole waves.
+    pub fn width() -> u16 {
+        (LOGO[0].len() as u16 + 2 * WAVE).div_ceil(WAVE) * WAVE
+    }
+
+    /// The cells the snek slithers through, one step apart. The last cell
+    /// wraps round to the first, so the snek goes round and round.
+    fn path(width: u16) -> Vec<Pos> {
+        let row = |x: u16| {
+            let wave = (x as f32 * TAU / WAVE as f32).sin();
+            (HEIGHT as f32 / 2.0 - 0.5 + AMPLITUDE * wave).round() as u16
+        };
+        let mut path = Vec::new();
+        let mut y = row(width - 1);
+        for x in 0..width {
+            let to = row(x);
+            while y != to {
+                path.push(Pos { x, y, floor: 0 });
+                y = if to > y { y + 1 } else { y - 1 };
+            }
+            path.push(Pos { x, y, floor: 0 });
+        }
+        path
+    }
+
+    /// Draw the title centred along the top of `area`, the snek wearing
+    /// `skin` with its head in `head_colour`.
+    pub fn draw(
+        &self,
+        area: Rect,
+        buf: &mut Buffer,
+        skin: &Skin,
+        glyphs: &Glyphs,
+        head_colour: Color,
+    ) {
+        let size = Size {
+            width: Self::width(),
+            height: HEIGHT,
+        };
+        let left = area.x + area.width.saturating_sub(size.width) / 2;
+        // Where a cell of the title goes on screen, if it fits
+        let at = |x: u16, y: u16| {
+            (x < size.width.min(area.width) && y < size.height.min(area.height))
+                .then_some((left + x, area.y + y))
+        };
+
+        let path = Self::path(size.width);
+        let head = self.tick as usize % path.len();
+        let snek: Vec<Pos> = (0..LENGTH)
+            .map(|index| path[(head + path.len() - index) % path.len()])
+            .collect();
+        for (index, &pos) in snek.iter().enumerate() {
+            let Some(cell) = at(pos.x, pos.y).and_then(|xy| buf.cell_mut(xy)) else {
+                continue;
+            };
+            if index == 0 {
+                let direction = snek[1].direction_to(pos, size).unwrap_or(Direction::East);
+                let mut head = skin.head(glyphs, direction);
+                if glyphs::is_wide(head) && pos.x + 1 >= size.width {
+                    head = glyphs.head(direction);
+                }
+                cell.set_symbol(head).set_fg(head_colour);
+                continue;
+            }
+            let segment = Segment {
+                index: index - 1,
+                length: LENGTH - 1,
+                pos,
+                links: [
+                    pos.direction_to(snek[index - 1], size),
+                    snek.get(index + 1)
+                        .and_then(|&next| pos.direction_to(next, size)),
+                ],
+            };
+            skin.draw(cell, segment, self.tick, glyphs);
+        }
+
+        // The logo goes on top, so the snek slithers behind it
+        let indent = (size.width - LOGO[0].len() as u16) / 2;
+        for (y, line) in (1..).zip(LOGO) {
+            for (x, letter) in (indent..).zip(line.chars()) {
+                if letter == ' ' {
+                    continue;
+                }
+                if let Some(cell) = at(x, y).and_then(|xy| buf.cell_mut(xy)) {
+                    cell.set_char(letter)
+                        .set_fg(head_colour)
+                        .modifier
+                        .insert(Modifier::BOLD);
+                }
+            }
+        }
+        glyphs::clear_spill(
+            buf,
+            Rect {
+                x: left,
+                width: size.width,
+                height: size.height,
+                ..area
+            }
+            .intersection(area),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_title() {
+        let path = Title::path(Title::width());
+        let size = Size { width: Title::width(), height: HEIGHT };
+        assert!(path.iter().all(|pos| pos.y < HEIGHT), "The snek should stay inside the title");
+        assert!((0..path.len()).all(|i| path[i].direction_to(path[(i + 1) % path.len()], size).is_some_and(|d| (d as usize) < 4)), "Each step should be one cell across or down, wrapping round");
+
+        let mut title = Title::default();
+        let area = Rect::new(0, 0, Title::width(), HEIGHT);
+        let mut buf = Buffer::empty(area);
+        title.draw(area, &mut buf, &Skin::default(), &Glyphs::default(), Color::Yellow);
+        let text = |buf: &Buffer| (0..HEIGHT).map(|y| (0..area.width).map(|x| buf[(x, y)].symbol()).collect::<String>()).collect::<Vec<_>>();
+        let before = text(&buf);
+        assert!(before[1].contains(LOGO[0].trim()), "The logo should be drawn");
+        title.step();
+        let mut after = Buffer::empty(area);
+        title.draw(area, &mut after, &Skin::default(), &Glyphs::default(), Color::Yellow);
+        assert_ne!(before, text(&after), "The snek should move with each step");
+    }
+}
@@ -0,0 +1,84 @@
+//! Short messages along the bottom of the game, like a saved file or a new
+//! unlock. Anything with the game to hand can push one onto the queue, and
+//! they're shown one after another, each for a couple of seconds.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long each toast stays on screen
+pub const DURATION: Duration = Duration::from_millis(2500);
+/// Toasts waiting beyond this many are dropped, oldest first, so the news
+/// never falls far behind the game
+const MAX_WAITING: usize = 4;
+
+#[derive(Debug, Default)]
+pub struct Toasts {
+    waiting: VecDeque<String>,
+    /// The toast on screen, and when it went up
+    shown: Option<(String, Instant)>,
+}
+
+impl Toasts {
+    /// Queue `message` to show once those before it have gone.
+    pub fn push(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        // The same news twice running is no news
+        let latest = self
+            .waiting
+            .back()
+            .or(self.shown.as_ref().map(|(shown, _)| shown));
+        if latest == Some(&message) {
+            return;
+        }
+        if self.waiting.len() == MAX_WAITING {
+            self.waiting.pop_front();
+        }
+        self.waiting.push_back(message);
+    }
+
+    /// The toast to show at `now`, moving on to the next once it's been up
+    /// long enough.
+    pub fn current(&mut self, now: Instant) -> Option<&str> {
+        if self
+            .shown
+            .as_ref()
+            .is_some_and(|(_, at)| now.saturating_duration_since(*at) >= DURATION)
+        {
+            self.shown = None;
+        }
+        if self.shown.is_none() {
+            self.shown = self.waiting.pop_front().map(|message| (message, now));
+        }
+        self.shown.as_ref().map(|(message, _)| message.as_str())
+    }
+
+    /// How many toasts are waiting behind the one on screen.
+    pub fn waiting(&self) -> usize {
+        self.waiting.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toasts() {
+        let mut toasts = Toasts::default();
+        let start = Instant::now();
+        assert_eq!(toasts.current(start), None, "Nothing should show with nothing pushed");
+        toasts.push("Speed up!");
+        toasts.push("Speed up!");
+        toasts.push("Unlocked Fire!");
+        assert_eq!(toasts.current(start), Some("Speed up!"), "Toasts should show in the order they came");
+        assert_eq!(toasts.waiting(), 1, "The same toast twice running should only show once");
+        assert_eq!(toasts.current(start + DURATION / 2), Some("Speed up!"), "A toast should stay up for a while");
+        assert_eq!(toasts.current(start + DURATION), Some("Unlocked Fire!"), "The next toast should follow once the first is done");
+        assert_eq!(toasts.current(start + DURATION * 2), None, "Toasts should go once they're done");
+
+        for n in 0..10 {
+            toasts.push(format!("Toast {}", n));
+        }
+        assert_eq!(toasts.current(start), Some("Toast 6"), "Only the latest few toasts should be kept waiting");
+    }
+}
@@ -0,0 +1,174 @@
+//! Hotseat tournaments: a few players sharing one keyboard take turns at the
+//! same game, round after round, with every player in a round getting the same
+//! seed so nobody gets luckier morsels than anyone else.
+
+use crate::sim;
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 8;
+/// Longest name a player can enter
+pub const MAX_NAME_LENGTH: usize = 12;
+pub const DEFAULT_ROUNDS: usize = 3;
+pub const MAX_ROUNDS: usize = 9;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Player {
+    pub name: String,
+    /// Score for each round played so far
+    pub scores: Vec<u16>,
+}
+
+impl Player {
+    pub fn total(&self) -> u32 {
+        self.scores.iter().map(|&score| score as u32).sum()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Tournament {
+    /// In turn order
+    pub players: Vec<Player>,
+    pub rounds: usize,
+    /// Every round's seed comes from this
+    seed: u64,
+}
+
+impl Tournament {
+    pub fn new(names: Vec<String>, rounds: usize, seed: u64) -> Result<Tournament, String> {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&names.len()) {
+            return Err(format!(
+                "A tournament needs {} to {} players",
+                MIN_PLAYERS, MAX_PLAYERS
+            ));
+        }
+        Ok(Tournament {
+            players: names
+                .into_iter()
+                .map(|name| Player {
+                    name,
+                    scores: Vec::new(),
+                })
+                .collect(),
+            rounds: rounds.clamp(1, MAX_ROUNDS),
+            seed,
+        })
+    }
+
+    /// Index into `players` of who plays next and the round they play, or
+    /// `None` once the tournament is over.
+    pub fn next_turn(&self) -> Option<(usize, usize)> {
+        let round = self.players.iter().map(|p| p.scores.len()).min()?;
+        if round >= self.rounds {
+            return None;
+        }
+        let player = self.players.iter().position(|p| p.scores.len() == round)?;
+        Some((player, round))
+    }
+
+    /// The seed for the next turn, shared by everyone in the same round.
+    pub fn next_seed(&self) -> Option<u64> {
+        self.next_turn()
+            .map(|(_, round)| sim::run_seed(self.seed, round as u32))
+    }
+
+    /// Score the turn just played for whoever played it.
+    pub fn record(&mut self, score: u16) {
+        if let Some((player, _)) = self.next_turn() {
+            self.players[player].scores.push(score);
+        }
+    }
+
+    /// Players from first to last by total score, ties going to whoever
+    /// plays first.
+    pub fn standings(&self) -> Vec<&Player> {
+        let mut standings: Vec<&Player> = self.players.iter().collect();
+        standings.sort_by_key(|player| std::cmp::Reverse(player.total()));
+        standings
+    }
+
+    /// The winner, once every round has been played.
+    pub fn winner(&self) -> Option<&Player> {
+        match self.next_turn() {
+            Some(_) => None,
+            None => self.standings().first().copied(),
+        }
+    }
+
+    /// The standings as a table, one line per player with a header.
+    pub fn table(&self) -> Vec<String> {
+        let mut header = format!("{:<4}{:<width$}", "", "Player", width = MAX_NAME_LENGTH + 2);
+        for round in 1..=self.rounds {
+            header.push_str(&format!("{:>6}", format!("R{}", round)));
+        }
+        header.push_str(&format!("{:>8}", "Total"));
+        let mut lines = vec![header];
+        for (place, player) in self.standings().into_iter().enumerate() {
+            let mut line = format!(
+                "{:<4}{:<width$}",
+                format!("{}.", place + 1),
+                player.name,
+                width = MAX_NAME_LENGTH + 2
+            );
+            for round in 0..self.rounds {
+                let score = player
+                    .scores
+                    .get(round)
+                    .map_or("-".to_string(), |score| score.to_string());
+                line.push_str(&format!("{:>6}", score));
+            }
+            line.push_str(&format!("{:>8}", player.total()));
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(count: usize) -> Vec<String> {
+        ["Ann", "Bo", "Cy", "Di", "Ed", "Flo", "Gus", "Hal", "Ivy"][..count].iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_player_count() {
+        assert!(Tournament::new(names(1), 3, 0).is_err(), "One player isn't a tournament");
+        assert!(Tournament::new(names(9), 3, 0).is_err(), "Nine players are too many");
+        assert!(Tournament::new(names(8), 3, 0).is_ok(), "Eight players should be fine");
+    }
+
+    #[test]
+    fn test_turns() {
+        let mut tournament = Tournament::new(names(3), 2, 42).unwrap();
+        assert_eq!(tournament.next_turn(), Some((0, 0)), "The first player should start");
+        let first_seed = tournament.next_seed();
+        tournament.record(10);
+        assert_eq!(tournament.next_turn(), Some((1, 0)), "Turns should pass down the table");
+        assert_eq!(tournament.next_seed(), first_seed, "Everyone in a round should play the same seed");
+        tournament.record(30);
+        tournament.record(20);
+        assert_eq!(tournament.next_turn(), Some((0, 1)), "The next round should start with the first player");
+        assert_ne!(tournament.next_seed(), first_seed, "Each round should have its own seed");
+        assert_eq!(tournament.winner(), None, "Nobody wins until the last round is done");
+        tournament.record(5);
+        tournament.record(0);
+        tournament.record(25);
+        assert_eq!(tournament.next_turn(), None, "The tournament should end after the last round");
+        tournament.record(99);
+        assert_eq!(tournament.players[0].scores, vec![10, 5], "Scores after the end should be ignored");
+        assert_eq!(tournament.winner().map(|p| p.name.as_str()), Some("Cy"), "The highest total should win");
+    }
+
+    #[test]
+    fn test_table() {
+        let mut tournament = Tournament::new(names(2), 2, 0).unwrap();
+        tournament.record(3);
+        tournament.record(7);
+        tournament.record(2);
+        let table = tournament.table();
+        assert_eq!(table.len(), 3, "A header and a line per player");
+        assert!(table[1].starts_with("1.  Bo") && table[1].trim_end().ends_with('7'), "The leader should be first: {:?}", table[1]);
+        assert!(table[1].contains('-') && !table[2].contains('-'), "Rounds not played yet should show a dash: {:?}", table);
+    }
+}
@@ -0,0 +1,173 @@
+//! Trail art: the whole path a snek took through a game drawn as one picture,
+//! denser wherever it passed more often, to keep as a signature of the run.
+
+use crate::bugreport;
+use crate::replay::Recording;
+use crate::{Pos, Size};
+use ratatui::{prelude::*, widgets::*};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const TRAIL_DIR: &str = "trails";
+/// From cells passed through least to most
+const SHADES: [char; 9] = ['.', ':', '-', '=', '+', '*', '#', '%', '@'];
+/// Colours for the shades in the terminal, from coolest to hottest
+const HEAT: [Color; 5] = [
+    Color::Blue,
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Red,
+];
+const WALL: char = '█';
+
+/// How often the head passed through each cell of the arena, every floor
+/// drawn on top of each other.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trail {
+    size: Size,
+    /// Visits to each cell, row by row
+    visits: Vec<u32>,
+    walls: HashSet<(u16, u16)>,
+}
+
+impl Trail {
+    pub fn new(size: Size) -> Trail {
+        Trail {
+            size,
+            visits: vec![0; size.width as usize * size.height as usize],
+            walls: HashSet::new(),
+        }
+    }
+
+    /// Trace the path of the player's head through the whole of `recording`.
+    pub fn from_recording(recording: &Recording) -> Trail {
+        let mut playback = recording.playback(0, recording.ticks());
+        let haus = &playback.haus;
+        let mut trail = Trail::new(haus.size);
+        trail.walls = haus.walls.iter().map(|pos| (pos.x, pos.y)).collect();
+        for &pos in haus.snek.body.iter().chain([&haus.snek.head]) {
+            trail.visit(pos);
+        }
+        for _ in 0..recording.ticks() {
+            playback.step();
+            trail.visit(playback.haus.snek.head);
+        }
+        trail
+    }
+
+    pub fn visit(&mut self, pos: Pos) {
+        if pos.x < self.size.width && pos.y < self.size.height {
+            self.visits[pos.y as usize * self.size.width as usize + pos.x as usize] += 1;
+        }
+    }
+
+    /// Which of `SHADES` to draw a cell visited `visits` times in, or `None`
+    /// for a cell never visited.
+    fn shade(&self, visits: u32) -> Option<usize> {
+        let most = self.visits.iter().copied().max().unwrap_or(0);
+        (visits > 0)
+            .then(|| ((visits - 1) as usize * SHADES.len() / most as usize).min(SHADES.len() - 1))
+    }
+
+    /// Each row of the picture, top to bottom, with the shade of each cell.
+    fn rows(&self) -> impl Iterator<Item = Vec<(char, Option<usize>)>> + '_ {
+        (0..self.size.height).map(move |y| {
+            (0..self.size.width)
+                .map(|x| {
+                    let visits = self.visits[y as usize * self.size.width as usize + x as usize];
+                    match self.shade(visits) {
+                        Some(shade) => (SHADES[shade], Some(shade)),
+                        None if self.walls.contains(&(x, y)) => (WALL, None),
+                        None => (' ', None),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// The picture as plain text, one line per row.
+    pub fn to_text(&self) -> String {
+        self.rows()
+            .map(|row| {
+                let line: String = row.into_iter().map(|(symbol, _)| symbol).collect();
+                line.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Write the picture under `title` to a new, timestamped file in `dir`,
+    /// returning its path.
+    pub fn save(&self, dir: &Path, title: &str) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = bugreport::unique_path(dir, "snekrs-trail");
+        fs::write(&path, format!("{}\n\n{}\n", title, self.to_text()))?;
+        Ok(path)
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// The picture in colour, for the terminal.
+    pub fn picture(&self) -> Paragraph<'static> {
+        let lines: Vec<Line> = self
+            .rows()
+            .map(|row| {
+                row.into_iter()
+                    .map(|(symbol, shade)| {
+                        let colour = match shade {
+                            Some(shade) => HEAT[shade * HEAT.len() / SHADES.len()],
+                            None => Color::DarkGray,
+                        };
+                        Span::styled(symbol.to_string(), Style::default().fg(colour))
+                    })
+                    .collect::<Line>()
+            })
+            .collect();
+        Paragraph::new(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameMode, Round, SnekHaus};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_shading() {
+        let mut trail = Trail::new(Size { width: 4, height: 2 });
+        trail.walls.insert((3, 1));
+        for _ in 0..9 {
+            trail.visit(Pos { x: 0, y: 0, floor: 0 });
+        }
+        trail.visit(Pos { x: 1, y: 0, floor: 0 });
+        trail.visit(Pos { x: 2, y: 0, floor: 1 });
+        trail.visit(Pos { x: 9, y: 9, floor: 0 });
+        assert_eq!(trail.to_text(), "@..\n   █", "Busy cells should be darker, and every floor drawn together");
+    }
+
+    #[test]
+    fn test_from_recording() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut haus = SnekHaus::new(Size { width: 30, height: 15 }, 3, GameMode::Classic.rules());
+        haus.setup(&mut rng);
+        let mut round = Round::default();
+        let mut recording = Recording::start(&haus, round, &rng);
+        for _ in 0..10 {
+            haus.tick(&mut round, &mut rng);
+            recording.tick(&haus, round, &rng);
+        }
+        let trail = Trail::from_recording(&recording);
+        assert_eq!(trail.size(), haus.size, "The picture should be as big as the arena");
+        let start = recording.playback(0, 0).haus.snek.length() as usize;
+        assert_eq!(trail.visits.iter().filter(|&&visits| visits > 0).count(), start + 10, "The starting body and every move should be traced");
+        let head = haus.snek.head;
+        assert!(trail.visits[head.y as usize * 30 + head.x as usize] > 0, "The trail should end at the head");
+    }
+}
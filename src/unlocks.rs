@@ -0,0 +1,207 @@
+//! Unlockables: skins, backgrounds and modifiers that have to be earned by
+//! reaching a goal before they can be played with. Everything not in
+//! `UNLOCKABLES` is there from the start.
+
+use crate::settings::{Background, Choice};
+use crate::{GameMode, Mutator, Mutators};
+use log::error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Where the unlocked items are kept between sessions.
+pub const UNLOCKS_FILE: &str = ".snekrs_unlocks.txt";
+
+/// Something that can be locked away until it's earned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Item {
+    /// A skin, by name
+    Skin(&'static str),
+    Background(Background),
+    Mutator(Mutator),
+}
+
+impl Item {
+    /// How the item is written in the unlocks file.
+    fn key(&self) -> String {
+        match self {
+            Item::Skin(name) => format!("skin: {}", name),
+            Item::Background(background) => format!("background: {}", background.slug()),
+            Item::Mutator(mutator) => {
+                format!("modifier: {}", Mutators::default().describe(*mutator).0)
+            }
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Item::Skin(name) => format!("the {} skin", name),
+            Item::Background(background) => format!("the {} background", background.name()),
+            Item::Mutator(mutator) => {
+                format!("the {} modifier", Mutators::default().describe(*mutator).0)
+            }
+        }
+    }
+}
+
+/// What it takes to unlock an item.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Goal {
+    /// Finish a game of `mode` with at least `score`
+    Score(GameMode, u16),
+    /// Grow the snek to at least this long in a game of `mode`
+    Length(GameMode, u16),
+}
+
+impl Goal {
+    fn met(&self, mode: GameMode, score: u16, length: u16) -> bool {
+        match *self {
+            Goal::Score(goal_mode, goal) => mode == goal_mode && score >= goal,
+            Goal::Length(goal_mode, goal) => mode == goal_mode && length >= goal,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Goal::Score(mode, score) => format!("score {} in {}", score, mode.name()),
+            Goal::Length(mode, length) => format!("grow to length {} in {}", length, mode.name()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unlockable {
+    pub item: Item,
+    pub goal: Goal,
+}
+
+pub const UNLOCKABLES: [Unlockable; 4] = [
+    Unlockable {
+        item: Item::Skin("Fire"),
+        goal: Goal::Score(GameMode::Hardcore, 100),
+    },
+    Unlockable {
+        item: Item::Background(Background::Dots),
+        goal: Goal::Score(GameMode::Classic, 50),
+    },
+    Unlockable {
+        item: Item::Mutator(Mutator::Invisible),
+        goal: Goal::Length(GameMode::Classic, 40),
+    },
+    Unlockable {
+        item: Item::Mutator(Mutator::Wormholes),
+        goal: Goal::Score(GameMode::Portals, 50),
+    },
+];
+
+/// The items unlocked so far.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Unlocks {
+    /// Keys of the unlocked items, as written in the file
+    unlocked: Vec<String>,
+}
+
+impl Unlocks {
+    /// Load the unlocked items from `path`, with nothing unlocked if there
+    /// isn't a file yet.
+    pub fn load(path: &Path) -> Unlocks {
+        match fs::read_to_string(path) {
+            Ok(text) => Unlocks {
+                unlocked: text
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Unlocks::default(),
+            Err(e) => {
+                error!("Error loading unlocks: {}", e);
+                Unlocks::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        if let Err(e) = fs::write(path, self.unlocked.join("\n")) {
+            error!("Error saving unlocks: {}", e);
+        }
+    }
+
+    /// What still stands between the player and `item`, if anything.
+    pub fn goal(&self, item: Item) -> Option<Goal> {
+        UNLOCKABLES
+            .iter()
+            .find(|unlockable| unlockable.item == item)
+            .filter(|unlockable| !self.unlocked.contains(&unlockable.item.key()))
+            .map(|unlockable| unlockable.goal)
+    }
+
+    /// What still stands between the player and the skin called `name`, if
+    /// anything.
+    pub fn skin_goal(&self, name: &str) -> Option<Goal> {
+        UNLOCKABLES
+            .iter()
+            .find(|unlockable| matches!(unlockable.item, Item::Skin(skin) if skin == name))
+            .and_then(|unlockable| self.goal(unlockable.item))
+    }
+
+    pub fn is_unlocked(&self, item: Item) -> bool {
+        self.goal(item).is_none()
+    }
+
+    /// Unlock everything a game of `mode` that finished with `score` and the
+    /// snek `length` long has earned, returning what's newly unlocked.
+    pub fn record(&mut self, mode: GameMode, score: u16, length: u16) -> Vec<Item> {
+        let earned: Vec<Item> = UNLOCKABLES
+            .iter()
+            .filter(|unlockable| !self.is_unlocked(unlockable.item))
+            .filter(|unlockable| unlockable.goal.met(mode, score, length))
+            .map(|unlockable| unlockable.item)
+            .collect();
+        self.unlocked.extend(earned.iter().map(|item| item.key()));
+        earned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlocking() {
+        let mut unlocks = Unlocks::default();
+        let fire = Item::Skin("Fire");
+        assert_eq!(unlocks.goal(fire), Some(Goal::Score(GameMode::Hardcore, 100)), "The fire skin should start locked");
+        assert!(unlocks.is_unlocked(Item::Skin("Classic")), "Items without a goal should always be unlocked");
+        assert!(unlocks.record(GameMode::Classic, 150, 10).iter().all(|&item| item != fire), "Scoring in the wrong mode shouldn't unlock");
+        assert_eq!(unlocks.record(GameMode::Hardcore, 100, 10), vec![fire], "Reaching the goal should unlock");
+        assert!(unlocks.is_unlocked(fire), "The skin should stay unlocked");
+        assert!(
+            unlocks.record(GameMode::Hardcore, 120, 10).is_empty(),
+            "Nothing should unlock twice"
+        );
+
+        let invisible = Item::Mutator(Mutator::Invisible);
+        unlocks.record(GameMode::Tron, 0, 200);
+        assert!(
+            !unlocks.is_unlocked(invisible),
+            "Growing in a mode that grows on its own shouldn't unlock"
+        );
+        unlocks.record(GameMode::Classic, 0, 40);
+        assert!(unlocks.is_unlocked(invisible), "Growing in Classic should");
+    }
+
+    #[test]
+    fn test_unlocks_persist() {
+        let path = std::env::temp_dir().join(format!("snekrs_unlocks_{}.txt", std::process::id()));
+        let mut unlocks = Unlocks::default();
+        unlocks.record(GameMode::Classic, 10, 45);
+        unlocks.save(&path);
+        let loaded = Unlocks::load(&path);
+        assert!(loaded.is_unlocked(Item::Mutator(Mutator::Invisible)), "Unlocks should load back");
+        assert!(!loaded.is_unlocked(Item::Mutator(Mutator::Wormholes)), "Locked items should stay locked");
+        fs::remove_file(&path).ok();
+        assert_eq!(Unlocks::load(&path), Unlocks::default(), "A missing file should unlock nothing");
+    }
+}